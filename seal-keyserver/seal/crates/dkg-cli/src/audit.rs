@@ -0,0 +1,200 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Audit log of `dkg-cli` invocations, enabled with `--audit-log`. Every invocation appends one
+//! JSON line with its arguments and outcome, so that a ceremony can be reviewed afterwards.
+
+use anyhow::{anyhow, Result};
+use chrono::{SecondsFormat, Utc};
+use serde::Serialize;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use sui_sdk_types::Address;
+
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+
+/// Flags whose values are secrets, replaced by [REDACTED] in the logged arguments.
+const SECRET_FLAGS: [&str; 2] = ["--old-share", "--rpc-auth-header"];
+
+const REDACTED: &str = "<redacted>";
+
+/// Committee and party of an invocation, set by the commands that know them. Shared by the
+/// context of the invocation and `main`, which writes the audit log entry.
+#[derive(Debug, Default)]
+pub(crate) struct AuditContext {
+    committee_id: Mutex<Option<Address>>,
+    party_id: Mutex<Option<u16>>,
+}
+
+impl AuditContext {
+    /// Record the committee of this invocation in the audit log entry.
+    pub(crate) fn record_committee_id(&self, committee_id: Address) {
+        *self.committee_id.lock().unwrap() = Some(committee_id);
+    }
+
+    /// Record my party ID in the audit log entry.
+    pub(crate) fn record_party_id(&self, party_id: u16) {
+        *self.party_id.lock().unwrap() = Some(party_id);
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum Outcome {
+    Success,
+    Failure,
+}
+
+/// One line of the audit log.
+#[derive(Serialize, Debug)]
+pub(crate) struct AuditEntry {
+    /// Time the invocation finished, in RFC 3339 (ISO 8601) format.
+    timestamp: String,
+    command: String,
+    /// All arguments after the program name, with secret values redacted.
+    args: Vec<String>,
+    outcome: Outcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    committee_id: Option<Address>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    party_id: Option<u16>,
+}
+
+impl AuditEntry {
+    /// Entry for an invocation of `command` with the given arguments that finished with `result`,
+    /// with the committee and party recorded in `context`.
+    pub(crate) fn new(
+        command: &str,
+        args: impl IntoIterator<Item = String>,
+        result: &Result<()>,
+        context: &AuditContext,
+    ) -> Self {
+        Self {
+            timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            command: command.to_string(),
+            args: redact_args(args),
+            outcome: if result.is_ok() {
+                Outcome::Success
+            } else {
+                Outcome::Failure
+            },
+            error: result.as_ref().err().map(|e| format!("{e:#}")),
+            committee_id: *context.committee_id.lock().unwrap(),
+            party_id: *context.party_id.lock().unwrap(),
+        }
+    }
+
+    /// Append this entry as a JSON line to `path`. The file is created if needed and written
+    /// synchronously on Unix, so that the entry is on disk when the process exits.
+    pub(crate) fn append(&self, path: &Path) -> Result<()> {
+        let mut options = OpenOptions::new();
+        options.create(true).append(true);
+        #[cfg(unix)]
+        options.custom_flags(libc::O_SYNC);
+        let mut file = options
+            .open(path)
+            .map_err(|e| anyhow!("Failed to open audit log {}: {}", path.display(), e))?;
+        let mut line = serde_json::to_string(self)?;
+        line.push('\n');
+        file.write_all(line.as_bytes())
+            .map_err(|e| anyhow!("Failed to write audit log {}: {}", path.display(), e))?;
+        Ok(())
+    }
+}
+
+/// Replace the values of [SECRET_FLAGS], given as `--flag value` or `--flag=value`.
+fn redact_args(args: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut redacted = Vec::new();
+    let mut redact_next = false;
+    for arg in args {
+        if redact_next {
+            redacted.push(REDACTED.to_string());
+            redact_next = false;
+            continue;
+        }
+        match arg.split_once('=') {
+            Some((flag, _)) if SECRET_FLAGS.contains(&flag) => {
+                redacted.push(format!("{flag}={REDACTED}"));
+            }
+            _ => {
+                redact_next = SECRET_FLAGS.contains(&arg.as_str());
+                redacted.push(arg);
+            }
+        }
+    }
+    redacted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_redact_args() {
+        assert_eq!(
+            redact_args(args(&[
+                "create-message",
+                "--old-share",
+                "0x1234",
+                "--rpc-auth-header=Bearer token",
+                "--keys-file",
+                "./dkg.key",
+            ])),
+            args(&[
+                "create-message",
+                "--old-share",
+                REDACTED,
+                "--rpc-auth-header=<redacted>",
+                "--keys-file",
+                "./dkg.key",
+            ])
+        );
+    }
+
+    #[test]
+    fn test_append_entries() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let context = AuditContext::default();
+        AuditEntry::new("generate-keys", args(&["generate-keys"]), &Ok(()), &context)
+            .append(&path)
+            .unwrap();
+        context.record_committee_id(Address::ZERO);
+        let failed: Result<()> = Err(anyhow!("Committee not found"));
+        AuditEntry::new(
+            "create-message",
+            args(&["create-message"]),
+            &failed,
+            &context,
+        )
+        .append(&path)
+        .unwrap();
+
+        let lines: Vec<serde_json::Value> = fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["command"], "generate-keys");
+        assert_eq!(lines[0]["outcome"], "success");
+        assert!(lines[0].get("error").is_none());
+        assert!(lines[0].get("committee_id").is_none());
+        assert!(lines[0]["timestamp"].as_str().unwrap().ends_with('Z'));
+        assert_eq!(lines[1]["outcome"], "failure");
+        assert_eq!(lines[1]["error"], "Committee not found");
+        assert_eq!(lines[1]["committee_id"], Address::ZERO.to_string());
+        assert!(lines[1].get("party_id").is_none());
+    }
+}
@@ -0,0 +1,582 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The ceremony steps run on a member's machine: creating the message and the local state for
+//! `create-message`, and reading, verifying and processing the messages of all parties for
+//! `process-all`.
+
+use anyhow::{anyhow, Result};
+use dkg_core::types::{format_pk_hex, DkgState, KeysFile, SignedMessage, MAX_MESSAGE_ENVELOPE_LEN};
+use dkg_core::{
+    finalize, prepare_ceremony, process_bundle, process_messages_only, CommitteeRotationVerifier,
+    DkgPhase, ExcludedParty, KeyGroup, OldCommittee,
+};
+use fastcrypto::groups::bls12381::{G2Element, Scalar as G2Scalar};
+use fastcrypto_tbls::dkg_v1::Output;
+use rand::rngs::StdRng;
+use seal_committee::{CommitteeState, RotationPlan, SealCommittee};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::num::NonZeroU16;
+use std::path::{Path, PathBuf};
+use sui_sdk_types::Address;
+
+use crate::context::{status, Context};
+use crate::errors::CliError;
+use crate::progress::Phase;
+use crate::rotation::report_rotation_plan;
+use crate::snapshot::CommitteeSnapshot;
+use crate::storage::DkgStateStorage;
+use crate::MessageOrder;
+
+/// Create the local state for `create-message`, and the message file unless this party is a new
+/// member in a key rotation.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn create_message<G: KeyGroup>(
+    ctx: &Context,
+    committee: &SealCommittee,
+    my_address: &Address,
+    local_keys: &KeysFile,
+    my_old_share: Option<G2Scalar>,
+    old_snapshot: Option<&CommitteeSnapshot>,
+    weights: Option<&HashMap<u16, u16>>,
+    message_dir: &Path,
+    state_dir: &Path,
+    rng: &mut StdRng,
+) -> Result<CreatedMessage> {
+    let old_committee = old_snapshot.map(CommitteeSnapshot::to_committee);
+    let old = match (old_snapshot, &old_committee) {
+        (Some(snapshot), Some(old_committee)) => {
+            let plan =
+                RotationPlan::build(committee, old_committee).map_err(CliError::Validation)?;
+            report_rotation_plan(ctx, &plan)?;
+            Some(OldCommittee {
+                committee: old_committee,
+                partial_pks: snapshot
+                    .parse_partial_pks::<G>()
+                    .map_err(CliError::Validation)?,
+                digest: Some(snapshot.digest.clone()),
+            })
+        }
+        _ => None,
+    };
+
+    let (state, signed_message) = prepare_ceremony(
+        committee,
+        my_address,
+        local_keys,
+        my_old_share,
+        old,
+        weights,
+        rng,
+    )?;
+    status!(
+        ctx,
+        "Registered public keys onchain validated. My party ID: {}",
+        state.config.my_party_id
+    );
+    let message_file = match signed_message {
+        Some(signed_message) => {
+            let message_file = save_message_file(message_dir, &signed_message)?;
+            status!(
+                ctx,
+                "DKG message written to: {}. Share this file with the coordinator.",
+                message_file.display()
+            );
+            Some(message_file)
+        }
+        None => {
+            status!(ctx, "New member in rotation, skipping message creation.");
+            None
+        }
+    };
+
+    state.save(state_dir, ctx)?;
+    status!(
+        ctx,
+        "State saved to {state_dir:?}. Wait for coordinator to announce phase 3."
+    );
+    Ok(CreatedMessage {
+        committee_id: state.config.committee_id,
+        party_id: state.config.my_party_id,
+        message_file,
+        state_dir: state_dir.to_path_buf(),
+    })
+}
+
+/// Result of `create-message` and `rotate-keys`, printed with `--json`.
+#[derive(Serialize)]
+pub(crate) struct CreatedMessage {
+    committee_id: Address,
+    party_id: u16,
+    message_file: Option<PathBuf>,
+    state_dir: PathBuf,
+}
+
+/// Process all messages for `process-all` and print the output.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn process_all<G: KeyGroup>(
+    ctx: &Context,
+    state_dir: &Path,
+    local_keys: &KeysFile,
+    messages_dir: &Path,
+    order: MessageOrder,
+    max_complaints: usize,
+    write_messages_only: bool,
+    weights: Option<&HashMap<u16, u16>>,
+    committee: Option<&SealCommittee>,
+    old_committee: Option<&SealCommittee>,
+    rng: &mut StdRng,
+) -> Result<()> {
+    let mut state = DkgState::<G>::load(state_dir, ctx)?;
+    ctx.audit.record_committee_id(state.config.committee_id);
+    ctx.audit.record_party_id(state.config.my_party_id);
+    if let Some(committee) = committee {
+        check_state_matches_committee(&state, committee).map_err(CliError::Validation)?;
+        status!(ctx, "State matches the committee snapshot.");
+    }
+    if let Some(weights) = weights {
+        state.config.nodes = state
+            .config
+            .nodes_with_weights(weights)
+            .map_err(CliError::Usage)?;
+    }
+    let messages = read_messages_dir(messages_dir, order)?;
+    let (present, missing) = state.messages_from_expected_senders(&messages);
+    status!(
+        ctx,
+        "Messages from expected senders (party IDs): {present:?}"
+    );
+    status!(
+        ctx,
+        "Missing messages from expected senders (party IDs): {missing:?}"
+    );
+    if write_messages_only {
+        return write_processed_messages(ctx, state_dir, &mut state, local_keys, messages, rng);
+    }
+    if state.phase() == DkgPhase::MessagesProcessed {
+        status!(
+            ctx,
+            "Continuing from {} of {} required processed message(s).",
+            state.processed_messages.len(),
+            state.required_processed_messages()
+        );
+        if state.has_sufficient_processed_messages() {
+            status!(ctx, "No further messages are required.");
+        }
+    }
+    let (output, excluded) = process_and_save(
+        ctx,
+        state_dir,
+        &mut state,
+        local_keys,
+        messages,
+        max_complaints,
+        rng,
+    )?;
+    if let (Some(committee), Some(old_committee)) = (committee, old_committee) {
+        let warnings =
+            CommitteeRotationVerifier.verify(committee, old_committee, &state, &output)?;
+        for warning in &warnings {
+            eprintln!("WARNING: {warning}");
+        }
+        status!(
+            ctx,
+            "Verified the rotation from committee {} ({} check(s) skipped).",
+            old_committee.id,
+            warnings.len()
+        );
+    }
+
+    if ctx.json {
+        ctx.print_json_result(&output_summary(&state, &output, &excluded)?)?;
+        print_excluded_parties(&excluded);
+        return Ok(());
+    }
+
+    print_public_keys(ctx, &output, state.config.nodes.num_nodes())?;
+
+    status!(
+        ctx,
+        "============YOUR PARTIAL KEY SHARE, KEEP SECRET====================="
+    );
+    if let Some(shares) = &output.shares {
+        for share in shares {
+            status!(ctx, "MASTER_SHARE={}", format_pk_hex(&share.value)?);
+        }
+    }
+
+    status!(
+        ctx,
+        "============FULL VSS POLYNOMIAL COEFFICIENTS====================="
+    );
+    for i in 0..=output.vss_pk.degree() {
+        let coeff = output.vss_pk.coefficient(i);
+        status!(ctx, "Coefficient {}: {}", i, format_pk_hex(coeff)?);
+    }
+    status!(ctx, "VSS_PK={}", format_pk_hex(&output.vss_pk)?);
+    print_excluded_parties(&excluded);
+    Ok(())
+}
+
+/// Check that the state was initialized for `committee`: the same ID, threshold and members, and
+/// the keys registered by the members while the committee still holds them (Init and PostDKG
+/// state).
+pub(crate) fn check_state_matches_committee<G: KeyGroup>(
+    state: &DkgState<G>,
+    committee: &SealCommittee,
+) -> Result<()> {
+    let config = &state.config;
+    if config.committee_id != committee.id {
+        return Err(anyhow!(
+            "State is for committee {}, the snapshot is of committee {}",
+            config.committee_id,
+            committee.id
+        ));
+    }
+    if config.threshold != committee.threshold {
+        return Err(anyhow!(
+            "State has threshold {}, committee {} has threshold {}",
+            config.threshold,
+            committee.id,
+            committee.threshold
+        ));
+    }
+    if config.nodes.num_nodes() != committee.members.len() {
+        return Err(anyhow!(
+            "State has {} parties, committee {} has {} members",
+            config.nodes.num_nodes(),
+            committee.id,
+            committee.members.len()
+        ));
+    }
+    if matches!(committee.state, CommitteeState::Finalized) {
+        return Ok(());
+    }
+    for (party_id, address) in committee.members.iter().enumerate() {
+        let party_id = party_id as u16;
+        let node = config
+            .nodes
+            .node_id_to_node(party_id)
+            .map_err(|_| anyhow!("State has no party {party_id}"))?;
+        let signing_pk = config
+            .signing_pks
+            .get(&party_id)
+            .ok_or_else(|| anyhow!("State has no signing PK of party {party_id}"))?;
+        committee
+            .verify_member_registration(address, &node.pk, signing_pk)
+            .map_err(|e| anyhow!("Keys of party {party_id} in the state do not match: {e}"))?;
+    }
+    Ok(())
+}
+
+/// As [process_messages], and save the state in `state_dir`. On complaints, the state is saved
+/// with the confirmation for inspection.
+pub(crate) fn process_and_save<G: KeyGroup>(
+    ctx: &Context,
+    state_dir: &Path,
+    state: &mut DkgState<G>,
+    local_keys: &KeysFile,
+    messages: Vec<SignedMessage<G>>,
+    max_complaints: usize,
+    rng: &mut StdRng,
+) -> Result<(Output<G, G2Element>, Vec<ExcludedParty>)> {
+    match process_messages(ctx, state, local_keys, messages, max_complaints, rng) {
+        Ok(result) => {
+            state.save(state_dir, ctx)?;
+            Ok(result)
+        }
+        Err(e) => {
+            if state.confirmation.is_some() {
+                state.save(state_dir, ctx)?;
+            }
+            Err(e)
+        }
+    }
+}
+
+/// The public parts of a DKG output, printed by `process-all --json`. The key share is not
+/// included.
+pub(crate) fn output_summary<G: KeyGroup>(
+    state: &DkgState<G>,
+    output: &Output<G, G2Element>,
+    excluded: &[ExcludedParty],
+) -> Result<serde_json::Value> {
+    Ok(serde_json::json!({
+        "committee_id": state.config.committee_id,
+        "party_id": state.config.my_party_id,
+        "key_server_pk": format_pk_hex(output.vss_pk.c0())?,
+        "partial_pks": partial_pks_hex(output, state.config.nodes.num_nodes())?,
+        "vss_pk": format_pk_hex(&output.vss_pk)?,
+        "excluded_parties": excluded.iter().map(|party| party.party_id).collect::<Vec<_>>(),
+    }))
+}
+
+/// Verify and process the messages for `process-all --write-messages-only`, and store them in the
+/// state without completing the protocol.
+fn write_processed_messages<G: KeyGroup>(
+    ctx: &Context,
+    state_dir: &Path,
+    state: &mut DkgState<G>,
+    local_keys: &KeysFile,
+    messages: Vec<SignedMessage<G>>,
+    rng: &mut StdRng,
+) -> Result<()> {
+    status!(ctx, "Processing {} message(s)...", messages.len());
+    let phase = Phase::start("Verifying and processing messages", 0);
+    let outcome = process_messages_only(state, local_keys, messages, rng)?;
+    phase.finish();
+    state.save(state_dir, ctx)?;
+
+    for sender in &outcome.processed {
+        status!(ctx, "Successfully processed message from party {sender}.");
+    }
+    for party in &outcome.excluded {
+        eprintln!(
+            "WARNING: Message from party {} has a complaint and was not stored: {}",
+            party.party_id, party.reason
+        );
+    }
+    status!(
+        ctx,
+        "State saved to {state_dir:?} with {} of {} required processed message(s).",
+        state.processed_messages.len(),
+        state.required_processed_messages()
+    );
+    if state.has_sufficient_processed_messages() {
+        status!(
+            ctx,
+            "Run process-all without --write-messages-only to complete the protocol."
+        );
+    } else {
+        status!(ctx, "Run process-all again when more messages arrive.");
+    }
+    Ok(())
+}
+
+/// Merge the states in `from` into the state in `state_dir` for `merge-states`.
+pub(crate) fn merge_states<G: KeyGroup>(
+    ctx: &Context,
+    state_dir: &Path,
+    from: &[PathBuf],
+) -> Result<()> {
+    let mut state = DkgState::<G>::load(state_dir, ctx)?;
+    for other_dir in from {
+        let other = DkgState::load(other_dir, ctx).map_err(|e| {
+            CliError::Usage(anyhow!(
+                "Failed to load state from {}: {}",
+                other_dir.display(),
+                e
+            ))
+        })?;
+        state.merge_with(&other).map_err(|e| {
+            CliError::StateConflict(e.context(format!(
+                "Failed to merge state from {}",
+                other_dir.display()
+            )))
+        })?;
+    }
+    state.save(state_dir, ctx)?;
+    println!(
+        "Merged {} state(s) into {state_dir:?}: {} received and {} processed message(s).",
+        from.len(),
+        state.received_messages.len(),
+        state.processed_messages.len()
+    );
+    Ok(())
+}
+
+/// Write a signed message to `message_<party ID>.json` in the given directory.
+pub(crate) fn save_message_file<G: KeyGroup>(
+    dir: &Path,
+    signed_message: &SignedMessage<G>,
+) -> Result<PathBuf> {
+    let message_file = dir.join(format!("message_{}.json", signed_message.message.sender));
+    fs::write(
+        &message_file,
+        serde_json::to_string_pretty(&signed_message.to_message_file()?)?,
+    )?;
+    Ok(message_file)
+}
+
+/// Read a JSON file mapping party IDs to weights, for `--weights-file`.
+pub(crate) fn read_weights_file(path: &Path) -> Result<HashMap<u16, u16>> {
+    let content = fs::read_to_string(path).map_err(|e| {
+        CliError::Usage(anyhow!(
+            "Failed to read weights file {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    let weights: HashMap<u16, u16> = serde_json::from_str(&content)
+        .map_err(|e| CliError::Usage(anyhow!("Invalid weights file {}: {}", path.display(), e)))?;
+    if let Some((party_id, _)) = weights.iter().find(|(_, weight)| **weight == 0) {
+        return Err(CliError::Usage(anyhow!(
+            "Invalid weights file {}: weight of party {party_id} is zero",
+            path.display()
+        ))
+        .into());
+    }
+    Ok(weights)
+}
+
+/// Read all message files from a directory.
+pub(crate) fn read_messages_dir<G: KeyGroup>(
+    messages_dir: &Path,
+    order: MessageOrder,
+) -> Result<Vec<SignedMessage<G>>> {
+    let messages = message_paths(messages_dir, order)?
+        .iter()
+        .map(|path| read_message_file(path))
+        .collect::<Result<Vec<_>>>()?;
+
+    if messages.is_empty() {
+        return Err(
+            CliError::Usage(anyhow!("No files found in directory: {:?}", messages_dir)).into(),
+        );
+    }
+    Ok(messages)
+}
+
+/// Paths of all entries of a messages directory, in the given order so that the messages are
+/// processed the same way on every filesystem.
+pub(crate) fn message_paths(messages_dir: &Path, order: MessageOrder) -> Result<Vec<PathBuf>> {
+    let mut paths = fs::read_dir(messages_dir)
+        .map_err(|e| {
+            CliError::Usage(anyhow!(
+                "Failed to read messages directory {:?}: {}",
+                messages_dir,
+                e
+            ))
+        })?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    match order {
+        MessageOrder::Name => paths.sort(),
+        MessageOrder::PartyId => paths.sort_by_cached_key(|path| {
+            let party_id = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix("message_")?.strip_suffix(".json"))
+                .and_then(|id| id.parse::<u16>().ok());
+            (party_id.is_none(), party_id, path.clone())
+        }),
+    }
+    Ok(paths)
+}
+
+/// Verify and process the messages of all parties, then complete the protocol. Up to
+/// `max_complaints` messages with a complaint are excluded, and returned with the output. The
+/// output is also stored in `state`. On complaints in the merged messages, the confirmation is
+/// stored in `state` before returning an error.
+pub(crate) fn process_messages<G: KeyGroup>(
+    ctx: &Context,
+    state: &mut DkgState<G>,
+    local_keys: &KeysFile,
+    messages: Vec<SignedMessage<G>>,
+    max_complaints: usize,
+    rng: &mut StdRng,
+) -> Result<(Output<G, G2Element>, Vec<ExcludedParty>)> {
+    status!(ctx, "Processing {} message(s)...", messages.len());
+    let phase = Phase::start("Verifying and processing messages", 0);
+    let outcome = process_bundle(state, local_keys, messages, max_complaints, rng)?;
+    phase.finish();
+    if !outcome.skipped.is_empty() {
+        status!(
+            ctx,
+            "Skipped {} already processed message(s).",
+            outcome.skipped.len()
+        );
+    }
+    for sender in &outcome.processed {
+        status!(ctx, "Successfully processed message from party {sender}.");
+    }
+    if !outcome.complaints.is_empty() {
+        return Err(CliError::Verification(anyhow!(
+            "Do NOT propose onchain. Complaint(s) found {:?}.",
+            outcome.complaints,
+        ))
+        .into());
+    }
+
+    let phase = Phase::start("Completing the protocol", 0);
+    let output = finalize(state, local_keys, rng)?;
+    phase.finish();
+    Ok((output, outcome.excluded))
+}
+
+/// Print a warning listing the parties whose messages were excluded because of complaints.
+fn print_excluded_parties(excluded: &[ExcludedParty]) {
+    if excluded.is_empty() {
+        return;
+    }
+    eprintln!("============WARNING: CEREMONY COMPLETED WITH EXCLUDED PARTIES=====================");
+    eprintln!(
+        "WARNING: The messages of {} part(ies) had complaints and were excluded. Share this list with the coordinator before proposing onchain.",
+        excluded.len()
+    );
+    for party in excluded {
+        eprintln!("EXCLUDED_PARTY_{}: {}", party.party_id, party.reason);
+    }
+}
+
+/// Print the key server public key and the partial public keys of all parties.
+pub(crate) fn print_public_keys<G: KeyGroup>(
+    ctx: &Context,
+    output: &Output<G, G2Element>,
+    num_parties: usize,
+) -> Result<()> {
+    status!(
+        ctx,
+        "============KEY SERVER PK AND PARTIAL PKS====================="
+    );
+    status!(ctx, "KEY_SERVER_PK={}", format_pk_hex(&output.vss_pk.c0())?);
+    for (party_id, partial_pk) in partial_pks_hex(output, num_parties)? {
+        status!(ctx, "PARTY_{party_id}_PARTIAL_PK={partial_pk}");
+    }
+    Ok(())
+}
+
+/// Partial public keys (hex-encoded BCS) of all parties in the new committee, by party ID.
+pub(crate) fn partial_pks_hex<G: KeyGroup>(
+    output: &Output<G, G2Element>,
+    num_parties: usize,
+) -> Result<BTreeMap<u16, String>> {
+    (0..num_parties as u16)
+        .map(|party_id| {
+            // party id is 0 index and share index is party id + 1
+            let share_index = NonZeroU16::new(party_id + 1).expect("must be valid");
+            Ok((
+                party_id,
+                format_pk_hex(&output.vss_pk.eval(share_index).value)?,
+            ))
+        })
+        .collect()
+}
+
+/// Read a signed message from a message file created by `create-message`.
+pub(crate) fn read_message_file<G: KeyGroup>(path: &Path) -> Result<SignedMessage<G>> {
+    // Message files come from a shared directory, so check the size before reading.
+    let size = fs::metadata(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?
+        .len();
+    if size > MAX_MESSAGE_ENVELOPE_LEN as u64 {
+        return Err(anyhow!(
+            "Message file {} is too large: {} bytes, at most {} allowed",
+            path.display(),
+            size,
+            MAX_MESSAGE_ENVELOPE_LEN
+        ));
+    }
+    let content = fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+
+    SignedMessage::from_envelope_json(&content).map_err(|e| {
+        anyhow!(
+            "Failed to deserialize message from {}: {}",
+            path.display(),
+            e
+        )
+    })
+}
@@ -0,0 +1,330 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Optional `dkg-cli.toml` config file providing defaults for common flags.
+//!
+//! Values are resolved in order: explicit CLI flag, config file, built-in default.
+
+use crate::context::Context;
+use crate::errors::CliError;
+use anyhow::{anyhow, Result};
+use seal_committee::Network;
+use serde::Deserialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use sui_sdk_types::Address;
+
+/// Config file name, discovered in the state directory if `--config` is not given.
+pub(crate) const CONFIG_FILE_NAME: &str = "dkg-cli.toml";
+pub(crate) const DEFAULT_STATE_DIR: &str = "./dkg-state";
+pub(crate) const DEFAULT_KEYS_FILE: &str = "./dkg-state/dkg.key";
+
+/// Fail for devnet and localnet unless `--allow-non-production-network` is given, so that a
+/// ceremony cannot run against a development network by mistake.
+pub(crate) fn check_network(network: &Network, allow_non_production: bool) -> Result<()> {
+    if !network.is_production() && !allow_non_production {
+        return Err(CliError::Usage(anyhow!(
+            "{network} is not a production network. Pass --allow-non-production-network to use it."
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// Contents of the config file. All fields are optional and unknown keys are rejected.
+#[derive(Deserialize, Default, Debug)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ConfigFile {
+    /// Network (mainnet, testnet, devnet, localnet or custom:<url>).
+    pub network: Option<String>,
+    /// Committee object ID.
+    pub committee_id: Option<Address>,
+    /// Path to the keys file.
+    pub keys_file: Option<PathBuf>,
+    /// State directory.
+    pub state_dir: Option<PathBuf>,
+    /// Timeout in seconds for each RPC request.
+    pub rpc_timeout_secs: Option<u64>,
+}
+
+impl ConfigFile {
+    /// Load and parse a config file.
+    pub(crate) fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read config file {}: {}", path.display(), e))?;
+        toml::from_str(&content)
+            .map_err(|e| anyhow!("Invalid config file {}: {}", path.display(), e))
+    }
+}
+
+/// Values given explicitly on the command line.
+#[derive(Default)]
+pub(crate) struct Flags {
+    pub network: Option<Network>,
+    pub committee_id: Option<Address>,
+    pub keys_file: Option<PathBuf>,
+    pub state_dir: Option<PathBuf>,
+}
+
+/// Where an effective config value came from.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Source {
+    Flag,
+    ConfigFile(PathBuf),
+    Default,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Flag => write!(f, "command line flag"),
+            Source::ConfigFile(path) => write!(f, "config file {}", path.display()),
+            Source::Default => write!(f, "default"),
+        }
+    }
+}
+
+/// Config value along with its source.
+#[derive(Debug)]
+pub(crate) struct Value<T> {
+    pub value: T,
+    pub source: Source,
+}
+
+/// Merged configuration from CLI flags, config file and defaults.
+#[derive(Debug)]
+pub(crate) struct EffectiveConfig {
+    pub config_path: Option<PathBuf>,
+    pub network: Option<Value<Network>>,
+    pub committee_id: Option<Value<Address>>,
+    pub keys_file: Value<PathBuf>,
+    pub state_dir: Value<PathBuf>,
+    pub rpc_timeout: Option<Value<Duration>>,
+    /// Set by `--allow-non-production-network`.
+    allow_non_production_network: bool,
+}
+
+impl EffectiveConfig {
+    /// Merge the flags with the config file given by `--config`, or with `dkg-cli.toml` in the
+    /// state directory given by flag (or the default state directory) if it exists.
+    pub(crate) fn resolve(ctx: &Context, flags: Flags) -> Result<Self> {
+        let config_path = match &ctx.config_path {
+            Some(path) => Some(path.to_path_buf()),
+            None => Some(
+                flags
+                    .state_dir
+                    .as_deref()
+                    .unwrap_or(Path::new(DEFAULT_STATE_DIR))
+                    .join(CONFIG_FILE_NAME),
+            )
+            .filter(|path| path.exists()),
+        };
+        let file = match &config_path {
+            Some(path) => ConfigFile::load(path).map_err(CliError::Usage)?,
+            None => ConfigFile::default(),
+        };
+        let file_source = || Source::ConfigFile(config_path.clone().unwrap_or_default());
+
+        let file_network = file
+            .network
+            .map(|n| Network::from_str(&n))
+            .transpose()
+            .map_err(|e| CliError::Usage(anyhow!(e)))?;
+
+        Ok(Self {
+            network: merge(flags.network, file_network, file_source()),
+            committee_id: merge(flags.committee_id, file.committee_id, file_source()),
+            keys_file: merge(flags.keys_file, file.keys_file, file_source()).unwrap_or(Value {
+                value: PathBuf::from(DEFAULT_KEYS_FILE),
+                source: Source::Default,
+            }),
+            state_dir: merge(flags.state_dir, file.state_dir, file_source()).unwrap_or(Value {
+                value: PathBuf::from(DEFAULT_STATE_DIR),
+                source: Source::Default,
+            }),
+            rpc_timeout: file.rpc_timeout_secs.map(|secs| Value {
+                value: Duration::from_secs(secs),
+                source: file_source(),
+            }),
+            config_path,
+            allow_non_production_network: ctx.allow_non_production_network,
+        })
+    }
+
+    pub(crate) fn network(&self) -> Result<Network> {
+        let network = self
+            .network
+            .as_ref()
+            .map(|v| v.value.clone())
+            .ok_or_else(|| missing("--network", "network"))?;
+        check_network(&network, self.allow_non_production_network)?;
+        Ok(network)
+    }
+
+    pub(crate) fn committee_id(&self) -> Result<Address> {
+        self.committee_id
+            .as_ref()
+            .map(|v| v.value)
+            .ok_or_else(|| missing("--committee-id", "committee_id"))
+    }
+
+    pub(crate) fn keys_file(&self) -> &Path {
+        &self.keys_file.value
+    }
+
+    pub(crate) fn state_dir(&self) -> &Path {
+        &self.state_dir.value
+    }
+
+    pub(crate) fn rpc_timeout(&self) -> Option<Duration> {
+        self.rpc_timeout.as_ref().map(|v| v.value)
+    }
+}
+
+impl fmt::Display for EffectiveConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.config_path {
+            Some(path) => writeln!(f, "# config file: {}", path.display())?,
+            None => writeln!(f, "# config file: none")?,
+        }
+        match &self.network {
+            Some(v) => writeln!(f, "network = \"{}\" ({})", v.value, v.source)?,
+            None => writeln!(f, "network = <unset>")?,
+        }
+        match &self.committee_id {
+            Some(v) => writeln!(f, "committee_id = {} ({})", v.value, v.source)?,
+            None => writeln!(f, "committee_id = <unset>")?,
+        }
+        writeln!(
+            f,
+            "keys_file = {} ({})",
+            self.keys_file.value.display(),
+            self.keys_file.source
+        )?;
+        writeln!(
+            f,
+            "state_dir = {} ({})",
+            self.state_dir.value.display(),
+            self.state_dir.source
+        )?;
+        match &self.rpc_timeout {
+            Some(v) => write!(f, "rpc_timeout_secs = {} ({})", v.value.as_secs(), v.source),
+            None => write!(f, "rpc_timeout_secs = <unset>"),
+        }
+    }
+}
+
+/// Prefer the flag value over the config file value.
+fn merge<T>(flag: Option<T>, file: Option<T>, file_source: Source) -> Option<Value<T>> {
+    match (flag, file) {
+        (Some(value), _) => Some(Value {
+            value,
+            source: Source::Flag,
+        }),
+        (None, Some(value)) => Some(Value {
+            value,
+            source: file_source,
+        }),
+        (None, None) => None,
+    }
+}
+
+fn missing(flag: &str, key: &str) -> anyhow::Error {
+    CliError::Usage(anyhow!(
+        "Missing {flag}. Provide it as a flag or set `{key}` in {CONFIG_FILE_NAME}."
+    ))
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_non_production_network() {
+        for network in [Network::Mainnet, Network::Testnet] {
+            check_network(&network, false).unwrap();
+        }
+        for network in [Network::Devnet, Network::Localnet] {
+            let err = check_network(&network, false).unwrap_err();
+            assert!(
+                err.to_string().contains("--allow-non-production-network"),
+                "{err}"
+            );
+            check_network(&network, true).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_flags_take_precedence() {
+        let dir = TempDir::new().unwrap();
+        let config_path = dir.path().join(CONFIG_FILE_NAME);
+        std::fs::write(
+            &config_path,
+            r#"
+            network = "testnet"
+            committee_id = "0x0000000000000000000000000000000000000000000000000000000000000001"
+            keys_file = "/tmp/file.key"
+            rpc_timeout_secs = 30
+            "#,
+        )
+        .unwrap();
+
+        let ctx = Context {
+            config_path: Some(config_path),
+            ..Context::default()
+        };
+        let config = EffectiveConfig::resolve(
+            &ctx,
+            Flags {
+                network: Some(Network::Mainnet),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(config.network().unwrap(), Network::Mainnet);
+        assert_eq!(config.network.as_ref().unwrap().source, Source::Flag);
+        assert_eq!(
+            config.committee_id().unwrap(),
+            Address::from_str("0x1").unwrap()
+        );
+        assert_eq!(config.keys_file(), Path::new("/tmp/file.key"));
+        assert_eq!(config.state_dir(), Path::new(DEFAULT_STATE_DIR));
+        assert_eq!(config.state_dir.source, Source::Default);
+        assert_eq!(config.rpc_timeout(), Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn test_discover_in_state_dir() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join(CONFIG_FILE_NAME), "network = \"testnet\"").unwrap();
+
+        let config = EffectiveConfig::resolve(
+            &Context::default(),
+            Flags {
+                state_dir: Some(dir.path().to_path_buf()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(config.network().unwrap(), Network::Testnet);
+        assert!(config.committee_id().is_err());
+    }
+
+    #[test]
+    fn test_unknown_key_rejected() {
+        let dir = TempDir::new().unwrap();
+        let config_path = dir.path().join(CONFIG_FILE_NAME);
+        std::fs::write(&config_path, "netwrok = \"testnet\"").unwrap();
+
+        let ctx = Context {
+            config_path: Some(config_path),
+            ..Context::default()
+        };
+        let err = EffectiveConfig::resolve(&ctx, Flags::default()).unwrap_err();
+        assert!(err.to_string().contains("netwrok"), "{err}");
+    }
+}
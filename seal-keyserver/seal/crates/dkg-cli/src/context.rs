@@ -0,0 +1,76 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Options of a single invocation, set from the global flags and the flags of the command.
+
+use crate::audit::AuditContext;
+use crate::permissions::PermissionPolicy;
+use crate::storage::StateFormat;
+use anyhow::Result;
+use seal_committee::GrpcClientConfig;
+use serde::Serialize;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Options of a single invocation. Passed down explicitly rather than kept in globals, so that
+/// invocations in the same process, e.g. in tests or by `rpc-serve`, do not affect each other.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Context {
+    /// Config file given by `--config`.
+    pub config_path: Option<PathBuf>,
+    /// Set by `--allow-non-production-network`.
+    pub allow_non_production_network: bool,
+    /// How to handle files with secrets accessible by others, strict with `--strict-permissions`.
+    pub permissions: PermissionPolicy,
+    /// Set by `--no-lock`, e.g. for NFS or other filesystems without lock support.
+    pub no_lock: bool,
+    /// Set by `--json` of `create-message`, `rotate-keys` and `process-all`, and by `rpc-serve`:
+    /// the result is printed to stdout as a JSON object, and status lines go to stderr.
+    pub json: bool,
+    /// Format states are saved in, BCS with `--bcs` and JSON otherwise.
+    pub state_format: StateFormat,
+    /// Set by `--key-server-version`: the version of the key server data field to read instead
+    /// of the latest version onchain.
+    pub key_server_version: Option<u64>,
+    /// Set by `--rpc-auth-header` and `--rpc-ca-cert`: settings of all gRPC clients.
+    pub grpc_config: GrpcClientConfig,
+    /// Committee and party recorded for the audit log entry of the invocation.
+    pub audit: Arc<AuditContext>,
+}
+
+impl Context {
+    /// Context of tests, failing for files with secrets that are accessible by others.
+    #[cfg(test)]
+    pub(crate) fn for_testing() -> Self {
+        Self {
+            permissions: PermissionPolicy::Strict,
+            ..Self::default()
+        }
+    }
+
+    /// Print a status line to stdout, or to stderr with `--json`. Use the [status] macro.
+    pub(crate) fn status(&self, args: fmt::Arguments) {
+        if self.json {
+            eprintln!("{args}");
+        } else {
+            println!("{args}");
+        }
+    }
+
+    /// Print the result of a command as JSON to stdout if `--json` is set.
+    pub(crate) fn print_json_result<T: Serialize>(&self, result: &T) -> Result<()> {
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(result)?);
+        }
+        Ok(())
+    }
+}
+
+/// Print a status line to stdout, or to stderr with `--json` set in the given [Context].
+macro_rules! status {
+    ($ctx:expr, $($arg:tt)*) => {
+        $ctx.status(format_args!($($arg)*))
+    };
+}
+pub(crate) use status;
@@ -0,0 +1,129 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Error classification and process exit codes for DKG CLI.
+
+use dkg_core::CeremonyError;
+use std::fmt;
+
+/// Process exit codes returned by the CLI. These are stable and safe to match on in scripts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub(crate) enum ExitCode {
+    /// The command completed successfully.
+    Success = 0,
+    /// Any failure that is not classified below.
+    Failure = 1,
+    /// Invalid arguments or flags, including argument parsing errors reported by clap.
+    Usage = 2,
+    /// Network or RPC failure. Usually safe to retry.
+    Network = 3,
+    /// Local data does not match onchain data, e.g. registered public keys or membership.
+    Validation = 4,
+    /// A complaint was found or a cryptographic verification failed. Do not propose onchain.
+    Verification = 5,
+    /// The committee is not in the state required by the command.
+    StateConflict = 6,
+}
+
+impl ExitCode {
+    /// Get the exit code for an error, falling back to [ExitCode::Failure] if unclassified.
+    pub(crate) fn from_error(err: &anyhow::Error) -> Self {
+        if let Some(err) = err.downcast_ref::<CeremonyError>() {
+            return match err {
+                CeremonyError::InvalidInput(_) => ExitCode::Usage,
+                CeremonyError::Validation(_) => ExitCode::Validation,
+                CeremonyError::Verification(_) => ExitCode::Verification,
+                CeremonyError::StateConflict(_) => ExitCode::StateConflict,
+            };
+        }
+        err.downcast_ref::<CliError>()
+            .map_or(ExitCode::Failure, CliError::exit_code)
+    }
+}
+
+impl From<ExitCode> for std::process::ExitCode {
+    fn from(code: ExitCode) -> Self {
+        std::process::ExitCode::from(code as u8)
+    }
+}
+
+/// Classified CLI error. Wrapped in [anyhow::Error] and recovered by downcasting in main.
+#[derive(Debug)]
+pub(crate) enum CliError {
+    Usage(anyhow::Error),
+    Network(anyhow::Error),
+    Validation(anyhow::Error),
+    Verification(anyhow::Error),
+    StateConflict(anyhow::Error),
+}
+
+impl CliError {
+    /// Exit code for this error.
+    pub(crate) fn exit_code(&self) -> ExitCode {
+        match self {
+            CliError::Usage(_) => ExitCode::Usage,
+            CliError::Network(_) => ExitCode::Network,
+            CliError::Validation(_) => ExitCode::Validation,
+            CliError::Verification(_) => ExitCode::Verification,
+            CliError::StateConflict(_) => ExitCode::StateConflict,
+        }
+    }
+
+    fn inner(&self) -> &anyhow::Error {
+        match self {
+            CliError::Usage(e)
+            | CliError::Network(e)
+            | CliError::Validation(e)
+            | CliError::Verification(e)
+            | CliError::StateConflict(e) => e,
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.inner())
+    }
+}
+
+impl std::error::Error for CliError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.inner().source()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn test_exit_code_from_error() {
+        let cases = [
+            (CliError::Usage(anyhow!("usage")), 2),
+            (CliError::Network(anyhow!("network")), 3),
+            (CliError::Validation(anyhow!("validation")), 4),
+            (CliError::Verification(anyhow!("verification")), 5),
+            (CliError::StateConflict(anyhow!("state")), 6),
+        ];
+        for (err, expected) in cases {
+            let err = anyhow::Error::from(err).context("with context");
+            assert_eq!(ExitCode::from_error(&err) as u8, expected);
+        }
+        let cases = [
+            (CeremonyError::InvalidInput(anyhow!("input")), 2),
+            (CeremonyError::Validation(anyhow!("validation")), 4),
+            (CeremonyError::Verification(anyhow!("verification")), 5),
+            (CeremonyError::StateConflict(anyhow!("state")), 6),
+        ];
+        for (err, expected) in cases {
+            let err = anyhow::Error::from(err).context("with context");
+            assert_eq!(ExitCode::from_error(&err) as u8, expected);
+        }
+        assert_eq!(
+            ExitCode::from_error(&anyhow!("unclassified")),
+            ExitCode::Failure
+        );
+    }
+}
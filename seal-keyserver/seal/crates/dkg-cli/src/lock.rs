@@ -0,0 +1,95 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Advisory locks on files with secrets, so that concurrent `dkg-cli` processes cannot corrupt
+//! each other's writes. The lock is taken on a companion `<path>.lock` file so that the file
+//! itself stays clean JSON.
+
+use crate::context::Context;
+use crate::errors::CliError;
+use anyhow::{anyhow, Result};
+use fs2::FileExt;
+use std::ffi::OsString;
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+
+/// A held lock, released when dropped.
+pub(crate) struct FileLock {
+    _file: Option<File>,
+}
+
+/// Take an exclusive lock for writing `path`. Fails if another process holds a lock on it. No
+/// lock is taken with `--no-lock`.
+pub(crate) fn lock_exclusive(path: &Path, ctx: &Context) -> Result<FileLock> {
+    FileLock::acquire(path, true, !ctx.no_lock)
+}
+
+/// Take a shared lock for reading `path`. Fails if another process is writing it. No lock is
+/// taken with `--no-lock`.
+pub(crate) fn lock_shared(path: &Path, ctx: &Context) -> Result<FileLock> {
+    FileLock::acquire(path, false, !ctx.no_lock)
+}
+
+impl FileLock {
+    fn acquire(path: &Path, exclusive: bool, enabled: bool) -> Result<Self> {
+        if !enabled {
+            return Ok(Self { _file: None });
+        }
+        let lock_path = lock_path(path);
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .map_err(|e| anyhow!("Failed to open lock file {}: {}", lock_path.display(), e))?;
+        let result = if exclusive {
+            FileExt::try_lock_exclusive(&file)
+        } else {
+            FileExt::try_lock_shared(&file)
+        };
+        result.map_err(|e| {
+            CliError::StateConflict(anyhow!(
+                "{} is locked by another dkg-cli process ({}). Wait for it to finish, or pass --no-lock if the filesystem does not support locks.",
+                path.display(),
+                e
+            ))
+        })?;
+        Ok(Self { _file: Some(file) })
+    }
+}
+
+/// Path of the lock file of `path`.
+fn lock_path(path: &Path) -> PathBuf {
+    let mut lock_path = OsString::from(path.as_os_str());
+    lock_path.push(".lock");
+    PathBuf::from(lock_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_file_lock() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("dkg.key");
+        assert_eq!(lock_path(&path), dir.path().join("dkg.key.lock"));
+
+        // Readers share the lock.
+        let reader = FileLock::acquire(&path, false, true).unwrap();
+        let other_reader = FileLock::acquire(&path, false, true).unwrap();
+        assert!(FileLock::acquire(&path, true, true).is_err());
+        drop((reader, other_reader));
+
+        // A writer excludes readers and writers until released.
+        let writer = FileLock::acquire(&path, true, true).unwrap();
+        assert!(FileLock::acquire(&path, false, true).is_err());
+        let err = FileLock::acquire(&path, true, true).err().unwrap();
+        assert!(err.to_string().contains("--no-lock"), "{err}");
+        FileLock::acquire(&path, true, false).unwrap();
+        drop(writer);
+        FileLock::acquire(&path, true, true).unwrap();
+        assert!(!path.exists());
+    }
+}
@@ -1,42 +1,145 @@
 // Copyright (c), Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-mod types;
+#[cfg(all(feature = "test-determinism", not(debug_assertions)))]
+compile_error!("The test-determinism feature must never be enabled in release builds.");
+
+mod audit;
+mod ceremony;
+mod config;
+mod context;
+mod errors;
+mod lock;
+mod migrate;
+mod observe;
+mod permissions;
+mod progress;
+mod recover;
+mod rotation;
+mod rpc;
+mod simulate;
+mod snapshot;
+mod storage;
+mod wait;
 
 use anyhow::{anyhow, Result};
-use clap::{Parser, Subcommand};
-use fastcrypto::bls12381::min_sig::BLS12381KeyPair;
+use chrono::{DateTime, SecondsFormat, Utc};
+use clap::builder::{PossibleValue, StringValueParser, TypedValueParser};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum, ValueHint};
+use clap_complete::Shell;
+use dkg_core::schema;
+use dkg_core::types::{
+    format_pk_hex, verify_signature, DkgState, KeysFile, ShareHandoff, LATEST_KEYS_FILE_VERSION,
+};
+use dkg_core::{recover_master_key, KeyGroup, KeyType, ProgressSummary};
 use fastcrypto::encoding::{Base64, Encoding, Hex};
-use fastcrypto::groups::bls12381::{G2Element, Scalar as G2Scalar};
-use fastcrypto::groups::GroupElement;
-use fastcrypto::traits::KeyPair as _;
-use fastcrypto_tbls::dkg_v1::Party;
-use fastcrypto_tbls::ecies_v1::{PrivateKey, PublicKey};
-use fastcrypto_tbls::nodes::{Node, Nodes};
-use fastcrypto_tbls::random_oracle::RandomOracle;
-use rand::thread_rng;
+use fastcrypto::groups::bls12381::{G1Element, G2Element, Scalar as G2Scalar};
+use fastcrypto_tbls::dkg_v1::Output;
+use fastcrypto_tbls::ecies_v1::PublicKey;
+use fastcrypto_tbls::polynomial::PublicPoly;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use schemars::schema::RootSchema;
 use seal_committee::{
-    build_new_to_old_map, create_grpc_client, fetch_committee_data, fetch_partial_key_server_info,
-    Network,
+    create_grpc_client_with_config, export_committee_snapshot, fetch_committee_data,
+    fetch_committee_data_at_version, fetch_key_server_v2, ping_committee_node,
+    verify_partial_pk_consistency, wait_for_object, watch_committee_state, CommitteeFetcher,
+    CommitteeState, GrpcClientConfig, GrpcHealthChecker, HealthStatus, KeyServerV2, Network,
+    ParsedMemberInfo, RotationPlan, SealCommittee, SealCommitteeError, ServerType,
 };
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::num::NonZeroU16;
+use std::io::{IsTerminal, Write};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use sui_rpc::client::v2::Client;
 use sui_sdk_types::Address;
-use types::{DkgState, InitializedConfig, KeysFile};
+
+use crate::audit::{AuditContext, AuditEntry};
+use crate::ceremony::{
+    create_message, merge_states, message_paths, process_all, read_message_file, read_weights_file,
+};
+use crate::config::{EffectiveConfig, Flags};
+use crate::context::{status, Context};
+use crate::errors::{CliError, ExitCode};
+use crate::migrate::{check_independent_key_server, parse_key_share, read_key_share_file};
+use crate::observe::{committee_key_type, observe};
+use crate::permissions::PermissionPolicy;
+use crate::progress::with_spinner;
+use crate::recover::{read_share_file, read_shares_dir, write_share_file};
+use crate::rotation::{fetch_old_committee_snapshot, rotate_keys, validate_rotation};
+use crate::rpc::RpcServer;
+use crate::snapshot::{load_committee, CommitteeSnapshot, InitCommitteeSnapshot};
+use crate::storage::{state_file, state_key_type, DkgStateStorage, KeysFileStorage, StateFormat};
+use crate::wait::{describe_progress, WaitTarget};
 
 #[cfg(unix)]
-use std::os::unix::fs::PermissionsExt;
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
 
-use crate::types::{sign_message, verify_signature, SignedMessage};
+/// Interval between fetches while waiting for the fullnode to index an object.
+const INDEX_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Minimum number of characters to type for `generate-keys --interactive-entropy`.
+const MIN_INTERACTIVE_ENTROPY_CHARS: usize = 64;
 
 #[derive(Parser)]
 #[command(name = "dkg-cli")]
 #[command(about = "DKG and key rotation CLI tool", long_about = None)]
 struct Cli {
+    /// Path to a dkg-cli.toml config file providing defaults for common flags. If not given,
+    /// dkg-cli.toml in the state directory is used if it exists. Flags take precedence.
+    #[arg(long, global = true, value_hint = ValueHint::FilePath)]
+    config: Option<PathBuf>,
+
+    /// Seed for all randomness, for reproducible tests. Only available with the
+    /// `test-determinism` feature which must NEVER be enabled in release builds.
+    #[cfg(feature = "test-determinism")]
+    #[arg(long, global = true)]
+    seed: Option<u64>,
+
+    /// Fail instead of warning when the keys file or the state is accessible by group or others.
+    #[arg(long, global = true)]
+    strict_permissions: bool,
+
+    /// Do not lock the keys file and other files with secrets while reading or writing them, e.g.
+    /// on NFS or other filesystems without lock support.
+    #[arg(long, global = true)]
+    no_lock: bool,
+
+    /// Save the state in BCS to state.bcs instead of JSON to state.json, which is much smaller for
+    /// large committees. States are loaded in either format, and saving replaces the other one.
+    #[arg(long, global = true)]
+    bcs: bool,
+
+    /// Allow devnet and localnet. Without this flag they are rejected, so that a ceremony cannot
+    /// run against a development network by mistake.
+    #[arg(long, global = true)]
+    allow_non_production_network: bool,
+
+    /// Read this version of the key server data instead of the latest version given by the key
+    /// server object of the committee, e.g. to debug an upgrade of the key server.
+    #[arg(long, global = true, value_name = "N")]
+    key_server_version: Option<u64>,
+
+    /// Value of the `authorization` header sent with every request to the fullnode, e.g.
+    /// `Bearer <token>` for RPC providers requiring authentication.
+    #[arg(long, global = true, value_name = "VALUE")]
+    rpc_auth_header: Option<String>,
+
+    /// PEM file with a CA certificate to trust for the fullnode, in addition to the public roots,
+    /// e.g. for RPC providers with a private CA.
+    #[arg(long, global = true, value_hint = ValueHint::FilePath)]
+    rpc_ca_cert: Option<PathBuf>,
+
+    /// Append a JSON line to this file for every invocation, with the timestamp, the subcommand
+    /// and its arguments, the outcome and, when known, the committee and party ID. The value of
+    /// `--old-share` and `--rpc-auth-header` is redacted.
+    #[arg(long, global = true, value_hint = ValueHint::FilePath)]
+    audit_log: Option<PathBuf>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -46,8 +149,68 @@ enum Commands {
     /// Generate ECIES and signing key pairs.
     GenerateKeys {
         /// Path to write the keys file (default: ./dkg-state/dkg.key).
-        #[arg(long, default_value = "./dkg-state/dkg.key")]
-        keys_file: PathBuf,
+        #[arg(long, value_hint = ValueHint::FilePath)]
+        keys_file: Option<PathBuf>,
+
+        /// File whose contents are mixed into the randomness of the keys, in addition to the OS
+        /// RNG, e.g. output of a hardware RNG or dice rolls.
+        #[arg(long, value_hint = ValueHint::FilePath, conflicts_with = "interactive_entropy")]
+        extra_entropy_file: Option<PathBuf>,
+
+        /// Prompt for random keystrokes to mix into the randomness of the keys, in addition to the
+        /// OS RNG.
+        #[arg(long)]
+        interactive_entropy: bool,
+    },
+
+    /// Migrate a keys file to a newer format version in place.
+    MigrateKeys {
+        /// Path to the keys file (default: ./dkg-state/dkg.key).
+        #[arg(long, value_hint = ValueHint::FilePath)]
+        keys_file: Option<PathBuf>,
+
+        /// Current format version of the keys file.
+        #[arg(long)]
+        from_version: u8,
+
+        /// Format version to migrate to.
+        #[arg(long, default_value_t = LATEST_KEYS_FILE_VERSION)]
+        to_version: u8,
+    },
+
+    /// Print the public keys of a keys file and their fingerprint, to confirm them with the other
+    /// members.
+    ShowPublicKeys {
+        /// Path to the keys file (default: ./dkg-state/dkg.key).
+        #[arg(long, value_hint = ValueHint::FilePath)]
+        keys_file: Option<PathBuf>,
+    },
+
+    /// Prepare the key rotation from an independent key server to a committee, keeping its public
+    /// key. The master key is the old share of a 1-of-1 old committee with `--my-address` as its
+    /// member, and a snapshot of that committee is written for `create-message`.
+    MigrateIndependent {
+        /// Path to the file with the master key of the independent key server (hex-encoded BCS).
+        #[arg(long, value_hint = ValueHint::FilePath)]
+        master_key_file: PathBuf,
+
+        /// My address, which must be a member of the new committee.
+        #[arg(long)]
+        my_address: Address,
+
+        /// New committee object ID. Its old committee ID must be the object ID of the independent
+        /// key server. Required unless set in the config file.
+        #[arg(long)]
+        committee_id: Option<Address>,
+
+        /// Network (mainnet, testnet, devnet, localnet or custom:<url>). Required unless set in the
+        /// config file.
+        #[arg(long, value_parser = NetworkValueParser)]
+        network: Option<Network>,
+
+        /// Path to write the snapshot JSON file.
+        #[arg(short, long, value_hint = ValueHint::FilePath)]
+        output: PathBuf,
     },
 
     /// Initialize DKG party state and create DKG message.
@@ -57,511 +220,3976 @@ enum Commands {
         #[arg(long)]
         my_address: Address,
 
-        /// Current committee object ID.
+        /// Current committee object ID. Required unless set in the config file.
         #[arg(long)]
-        committee_id: Address,
+        committee_id: Option<Address>,
 
-        /// Network (mainnet or testnet).
-        #[arg(long, value_parser = parse_network)]
-        network: Network,
+        /// Network (mainnet, testnet, devnet, localnet or custom:<url>). Required unless set in the
+        /// config file.
+        #[arg(long, value_parser = NetworkValueParser)]
+        network: Option<Network>,
 
         /// State directory (default: ./dkg-state).
-        #[arg(long, default_value = "./dkg-state")]
-        state_dir: PathBuf,
+        #[arg(long, value_hint = ValueHint::DirPath)]
+        state_dir: Option<PathBuf>,
 
         /// Path to the keys file (default: ./dkg-state/dkg.key).
-        #[arg(long, default_value = "./dkg-state/dkg.key")]
-        keys_file: PathBuf,
+        #[arg(long, value_hint = ValueHint::FilePath)]
+        keys_file: Option<PathBuf>,
 
         /// Old share for key rotation (hex-encoded BCS, for continuing members only).
         #[arg(long)]
         old_share: Option<String>,
+
+        /// File with the old share for key rotation, instead of `--old-share`. For migrating an
+        /// independent key server, the master key file.
+        #[arg(long, value_hint = ValueHint::FilePath, conflicts_with = "old_share")]
+        old_share_file: Option<PathBuf>,
+
+        /// Snapshot of the old committee from `export-committee`, for key rotation without
+        /// fetching the old committee onchain.
+        #[arg(long, value_hint = ValueHint::FilePath)]
+        old_committee_snapshot: Option<PathBuf>,
+
+        /// Snapshot of the current committee in Init state from `export-committee`, instead of
+        /// fetching it onchain. With `--old-committee-snapshot` or for a fresh DKG, no network
+        /// access is needed.
+        #[arg(long, value_hint = ValueHint::FilePath)]
+        committee_snapshot: Option<PathBuf>,
+
+        /// JSON file mapping party IDs to weights, e.g. `{"0": 2, "3": 2}`. Parties not in the
+        /// file have weight 1. All parties must use the same file.
+        #[arg(long, value_hint = ValueHint::FilePath)]
+        weights_file: Option<PathBuf>,
+
+        /// Print the party ID and message file as JSON to stdout, and status lines to stderr.
+        #[arg(long)]
+        json: bool,
+
+        /// Seconds to wait for the fullnode to index the committee if it is not found, e.g. right
+        /// after the committee was created.
+        #[arg(long, default_value_t = 30)]
+        index_timeout_secs: u64,
+    },
+
+    /// Key rotation shortcut: run the checks of `validate-rotation` and check this member's role
+    /// in the rotation, then run `create-message`. Continuing members must provide `--old-share`,
+    /// new members must not.
+    RotateKeys {
+        /// My address, used to find my party ID in the committee.
+        #[arg(long)]
+        my_address: Address,
+
+        /// New committee object ID. Required unless set in the config file.
+        #[arg(long)]
+        committee_id: Option<Address>,
+
+        /// Old committee object ID, checked against the old committee of the new committee.
+        /// Defaults to the old committee onchain.
+        #[arg(long)]
+        old_committee_id: Option<Address>,
+
+        /// Network (mainnet, testnet, devnet, localnet or custom:<url>). Required unless set in the
+        /// config file.
+        #[arg(long, value_parser = NetworkValueParser)]
+        network: Option<Network>,
+
+        /// State directory (default: ./dkg-state).
+        #[arg(long, value_hint = ValueHint::DirPath)]
+        state_dir: Option<PathBuf>,
+
+        /// Path to the keys file (default: ./dkg-state/dkg.key).
+        #[arg(long, value_hint = ValueHint::FilePath)]
+        keys_file: Option<PathBuf>,
+
+        /// Old share (hex-encoded BCS, for continuing members only).
+        #[arg(long)]
+        old_share: Option<String>,
+
+        /// Skip the checks of `validate-rotation` and of the old share, and run `create-message`
+        /// directly.
+        #[arg(long)]
+        skip_validation: bool,
+    },
+
+    /// Check that a key rotation can start: the new committee is ready for the ceremony, enough
+    /// members continue from the old committee, and optionally that an address is a member.
+    /// Reports all failed checks at once and exits with a non-zero code if any fails.
+    ValidateRotation {
+        /// New committee object ID. Required unless set in the config file.
+        #[arg(long)]
+        committee_id: Option<Address>,
+
+        /// Old committee object ID, checked against the old committee of the new committee.
+        /// Defaults to the old committee onchain.
+        #[arg(long)]
+        old_committee_id: Option<Address>,
+
+        /// Network (mainnet, testnet, devnet, localnet or custom:<url>). Required unless set in the
+        /// config file.
+        #[arg(long, value_parser = NetworkValueParser)]
+        network: Option<Network>,
+
+        /// Address to check for membership in the new committee.
+        #[arg(long)]
+        my_address: Option<Address>,
+
+        /// Print the result as JSON to stdout.
+        #[arg(long)]
+        json: bool,
     },
 
     /// Process all messages and attempt to finalize if no complaints.
     ProcessAll {
         /// Directory containing message_*.json files from all parties.
-        #[arg(short, long)]
+        #[arg(short, long, value_hint = ValueHint::DirPath)]
         messages_dir: PathBuf,
-        /// State directory
-        #[arg(short = 's', long, default_value = "./dkg-state")]
-        state_dir: PathBuf,
-        /// Path to keys file
-        #[arg(short = 'k', long, default_value = "./dkg-state/dkg.key")]
-        keys_file: PathBuf,
+        /// State directory (default: ./dkg-state).
+        #[arg(short = 's', long, value_hint = ValueHint::DirPath)]
+        state_dir: Option<PathBuf>,
+        /// Path to keys file (default: ./dkg-state/dkg.key).
+        #[arg(short = 'k', long, value_hint = ValueHint::FilePath)]
+        keys_file: Option<PathBuf>,
+        /// Maximum number of messages with a complaint to exclude instead of failing. The protocol
+        /// tolerates up to `n - threshold` excluded parties.
+        #[arg(long, default_value_t = 0)]
+        max_complaints: usize,
+        /// Only verify and process the messages and store them in the state, without completing
+        /// the protocol, e.g. while waiting for offline parties. A later call without this flag
+        /// continues from the stored messages.
+        #[arg(long)]
+        write_messages_only: bool,
+        /// JSON file mapping party IDs to weights, as given to `create-message`.
+        #[arg(long, value_hint = ValueHint::FilePath)]
+        weights_file: Option<PathBuf>,
+        /// Print the public keys of the output as JSON to stdout, and status lines to stderr. The
+        /// key share is not printed.
+        #[arg(long, conflicts_with = "write_messages_only")]
+        json: bool,
+        /// Order in which the message files are read and processed.
+        #[arg(long, value_enum, default_value_t = MessageOrder::Name)]
+        sort: MessageOrder,
+        /// Snapshot of the committee from `export-committee`, to check that the state was
+        /// initialized for it, without network access. Without it, only the state is used.
+        #[arg(long, value_hint = ValueHint::FilePath)]
+        committee_snapshot: Option<PathBuf>,
+        /// Snapshot of the old committee from `export-committee`, as given to `create-message`,
+        /// for key rotation. With `--committee-snapshot`, the completed rotation is verified: the
+        /// messages of continuing members commit to their old partial PKs and the key server PK
+        /// is unchanged.
+        #[arg(
+            long,
+            value_hint = ValueHint::FilePath,
+            requires = "committee_snapshot",
+            conflicts_with = "write_messages_only"
+        )]
+        old_committee_snapshot: Option<PathBuf>,
     },
-}
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    let cli = Cli::parse();
+    /// Show which members have submitted their approval onchain, for a committee in PostDKG state.
+    ApprovalStatus {
+        /// Committee object ID. Required unless set in the config file.
+        #[arg(long)]
+        committee_id: Option<Address>,
 
-    match cli.command {
-        Commands::GenerateKeys { keys_file } => {
-            let enc_sk = PrivateKey::<G2Element>::new(&mut thread_rng());
-            let enc_pk = PublicKey::<G2Element>::from_private_key(&enc_sk);
-
-            let signing_kp = BLS12381KeyPair::generate(&mut thread_rng());
-            let signing_pk = signing_kp.public().clone();
-            let signing_sk = signing_kp.private();
-
-            let created_keys_file = KeysFile {
-                enc_sk,
-                enc_pk,
-                signing_sk,
-                signing_pk,
-            };
+        /// Network (mainnet, testnet, devnet, localnet or custom:<url>). Required unless set in the
+        /// config file.
+        #[arg(long, value_parser = NetworkValueParser)]
+        network: Option<Network>,
 
-            // Serialize to JSON
-            let json_content = serde_json::to_string_pretty(&created_keys_file)?;
+        /// Read the committee object at this version instead of the latest one, e.g. to audit the
+        /// approvals of a committee that was finalized since.
+        #[arg(long, value_name = "N")]
+        at_version: Option<u64>,
+    },
 
-            if let Some(parent) = keys_file.parent() {
-                fs::create_dir_all(parent)?;
-            }
+    /// Check that the partial public keys proposed for a committee in PostDKG state are consistent
+    /// with its proposed public key, before approving the proposal.
+    VerifyProposal {
+        /// Committee object ID. Required unless set in the config file.
+        #[arg(long)]
+        committee_id: Option<Address>,
 
-            write_secret_file(&keys_file, &json_content)?;
+        /// Network (mainnet, testnet, devnet, localnet or custom:<url>). Required unless set in the
+        /// config file.
+        #[arg(long, value_parser = NetworkValueParser)]
+        network: Option<Network>,
+    },
 
-            println!("Keys written to: {}", keys_file.display());
-            #[cfg(not(unix))]
-            println!("WARNING: On non-Unix systems, manually restrict file permissions");
-        }
+    /// Print a summary of a committee: its threshold, state and members, and its key server once
+    /// finalized.
+    ShowCommittee {
+        /// Committee object ID. Required unless set in the config file.
+        #[arg(long)]
+        committee_id: Option<Address>,
 
-        Commands::CreateMessage {
-            my_address,
-            committee_id,
-            network,
-            state_dir,
-            keys_file,
-            old_share,
-        } => {
-            let local_keys = KeysFile::load(&keys_file)?;
+        /// Network (mainnet, testnet, devnet, localnet or custom:<url>). Required unless set in the
+        /// config file.
+        #[arg(long, value_parser = NetworkValueParser)]
+        network: Option<Network>,
 
-            // Parse old share from command argument if provided. Provided for continuing members
-            // in key rotation.
-            let (my_old_share, my_old_pk) = if let Some(share_hex) = old_share {
-                let key_share: G2Scalar = bcs::from_bytes(&Hex::decode(&share_hex)?)?;
-                let key_pk = G2Element::generator() * key_share;
-                println!("Continuing member for key rotation, old share parsed.");
-                (Some(key_share), Some(key_pk))
-            } else {
-                (None, None)
-            };
+        /// Print object IDs, addresses and keys in full instead of abbreviated.
+        #[arg(long)]
+        wide: bool,
+    },
 
-            // Fetch current committee from onchain.
-            let mut grpc_client = create_grpc_client(&network)?;
-            let committee = fetch_committee_data(&mut grpc_client, &committee_id).await?;
+    /// Wait until the committee reaches a state, printing its progress whenever it changes, e.g.
+    /// to know when to run the next step of a ceremony.
+    Wait {
+        /// Committee object ID. Required unless set in the config file.
+        #[arg(long)]
+        committee_id: Option<Address>,
 
-            // Validate committee state is in Init state and contains my address.
-            committee.is_init()?;
-            if !committee.contains(&my_address) {
-                return Err(anyhow!(
-                    "Address {} is not a member of committee {}",
-                    my_address,
-                    committee_id
-                ));
-            }
+        /// Network (mainnet, testnet, devnet, localnet or custom:<url>). Required unless set in the
+        /// config file.
+        #[arg(long, value_parser = NetworkValueParser)]
+        network: Option<Network>,
 
-            println!(
-                "Fetched committee with {} members, threshold: {}",
-                committee.members.len(),
-                committee.threshold
-            );
+        /// State to wait for. Also done if the committee already moved past it.
+        #[arg(long, value_enum)]
+        until: WaitTarget,
 
-            // Fetch members info.
-            let members_info = committee.get_members_info()?;
-
-            let my_member_info = members_info
-                .get(&my_address)
-                .ok_or_else(|| anyhow!("Address {} not found in committee members", my_address))?;
-            let my_party_id = my_member_info.party_id;
-            let registered_enc_pk = &my_member_info.enc_pk;
-            let registered_signing_pk = &my_member_info.signing_pk;
-
-            // Validate PK locally vs registration onchain.
-            if &local_keys.enc_pk != registered_enc_pk
-                || &local_keys.signing_pk != registered_signing_pk
-            {
-                return Err(anyhow!(
-                    "Mismatched PK for address {}!\n\
-                    ECIES PK Derived from secret: {}\n\
-                    Registered onchain: {}\n\
-                    Signing PK Derived from secret: {}\n\
-                    Registered onchain: {}",
-                    my_address,
-                    format_pk_hex(&local_keys.enc_pk)?,
-                    format_pk_hex(&my_member_info.enc_pk)?,
-                    format_pk_hex(&local_keys.signing_pk)?,
-                    format_pk_hex(&my_member_info.signing_pk)?
-                ));
-            }
-            println!("Registered public keys onchain validated. My party ID: {my_party_id}");
-
-            // Get old committee params for key rotation.
-            let (old_threshold, new_to_old_mapping, expected_old_pks) = match committee
-                .old_committee_id
-            {
-                None => {
-                    if my_old_share.is_some() {
-                        return Err(anyhow!("--old-share should not be provided for fresh DKG."));
-                    }
-                    println!("No old committee ID, performing fresh DKG.");
-                    (None, None, None)
-                }
-                Some(old_committee_id) => {
-                    println!("Old committee ID: {old_committee_id}, performing key rotation.");
-
-                    let old_committee =
-                        fetch_committee_data(&mut grpc_client, &old_committee_id).await?;
-                    let old_threshold = Some(old_committee.threshold);
-                    let new_to_old_mapping = build_new_to_old_map(&committee, &old_committee);
-
-                    // Fetch partial key server info from the old committee's key server object.
-                    let old_partial_key_infos =
-                        fetch_partial_key_server_info(&mut grpc_client, &old_committee_id).await?;
-
-                    // Build mapping from old party ID to partial public key.
-                    let expected_old_pks: HashMap<u16, G2Element> = old_partial_key_infos
-                        .into_values()
-                        .map(|info| (info.party_id, info.partial_pk))
-                        .collect();
-
-                    // Validate my_old_share and membership in old committee.
-                    match my_old_share {
-                        Some(_) => {
-                            if !old_committee.contains(&my_address) {
-                                return Err(anyhow!(
-                                    "Invalid state: My address {} not found in old committee {} so I am a new member. Do not provide `--old-share` for key rotation.",
-                                    my_address,
-                                    old_committee_id
-                                ));
-                            }
-                            println!("Continuing member for key rotation.");
-                        }
-                        None => {
-                            if old_committee.contains(&my_address) {
-                                return Err(anyhow!(
-                                    "Invalid state: My address {} found in old committee {} so I am a continuing member. Must provide `--old-share` for key rotation.",
-                                    my_address,
-                                    old_committee_id
-                                ));
-                            }
-                            println!("New member for key rotation.");
-                        }
-                    }
-                    (
-                        old_threshold,
-                        Some(new_to_old_mapping),
-                        Some(expected_old_pks),
-                    )
-                }
-            };
+        /// Seconds between checks of the committee.
+        #[arg(long, default_value_t = 10)]
+        poll_interval_secs: u64,
 
-            // Create nodes for all parties with their enc_pks and collect signing pks.
-            let mut nodes = Vec::new();
-            let mut signing_pks = HashMap::new();
-            for (_, m) in members_info {
-                nodes.push(Node {
-                    id: m.party_id,
-                    pk: m.enc_pk,
-                    weight: 1,
-                });
-                signing_pks.insert(m.party_id, m.signing_pk);
-            }
-
-            // Create message if:
-            // - Fresh DKG: everyone creates a message (old_threshold is None).
-            // - Rotation: only continuing members create a message (my_old_share is Some).
-            let my_message = if old_threshold.is_none() || my_old_share.is_some() {
-                println!("Creating DKG message for party {my_party_id}...");
-                let random_oracle = RandomOracle::new(&committee_id.to_string());
-                let party = Party::<G2Element, G2Element>::new_advanced(
-                    local_keys.enc_sk.clone(),
-                    Nodes::new(nodes.clone())?.clone(),
-                    committee.threshold,
-                    random_oracle,
-                    my_old_share,
-                    old_threshold,
-                    &mut thread_rng(),
-                )?;
+        /// Maximum number of seconds to wait.
+        #[arg(long, default_value_t = 3600)]
+        timeout_secs: u64,
+    },
 
-                let message = party.create_message(&mut thread_rng())?;
-                let signed_message = sign_message(message.clone(), &local_keys.signing_sk);
+    /// List the message files collected so far, and the committee members that are still
+    /// missing. For key rotation, also show whether the old threshold of continuing members is
+    /// met.
+    ListMessages {
+        /// Directory containing message_*.json files.
+        #[arg(short, long, value_hint = ValueHint::DirPath)]
+        messages_dir: PathBuf,
 
-                // Write message to file.
-                let message_base64 = Base64::encode(bcs::to_bytes(&signed_message)?);
-                let message_file = PathBuf::from(format!("message_{my_party_id}.json"));
+        /// Committee object ID. Required unless set in the config file.
+        #[arg(long)]
+        committee_id: Option<Address>,
 
-                let message_json = serde_json::json!({
-                    "message": message_base64
-                });
-                fs::write(&message_file, serde_json::to_string_pretty(&message_json)?)?;
+        /// Network (mainnet, testnet, devnet, localnet or custom:<url>). Required unless set in the
+        /// config file.
+        #[arg(long, value_parser = NetworkValueParser)]
+        network: Option<Network>,
+    },
 
-                println!(
-                    "DKG message written to: {}. Share this file with the coordinator.",
-                    message_file.display()
-                );
-                Some(message)
-            } else {
-                println!("New member in rotation, skipping message creation.");
-                None
-            };
+    /// Verify a ceremony's public outputs as an observer, without a keys file. Checks that the
+    /// aggregate PK is consistent with the partial PKs, that a key rotation kept the aggregate PK,
+    /// and if messages are given, their signatures and commitments.
+    Observe {
+        /// Committee object ID. Required unless set in the config file.
+        #[arg(long)]
+        committee_id: Option<Address>,
 
-            let state = DkgState {
-                config: InitializedConfig {
-                    my_party_id,
-                    nodes: Nodes::new(nodes)?,
-                    committee_id,
-                    threshold: committee.threshold,
-                    signing_pks,
-                    old_threshold,
-                    new_to_old_mapping,
-                    expected_old_pks,
-                    my_old_share,
-                    my_old_pk,
-                },
-                my_message,
-                received_messages: HashMap::new(),
-                processed_messages: vec![],
-                confirmation: None,
-                output: None,
-            };
+        /// Network (mainnet, testnet, devnet, localnet or custom:<url>). Required unless set in the
+        /// config file.
+        #[arg(long, value_parser = NetworkValueParser)]
+        network: Option<Network>,
 
-            state.save(&state_dir)?;
-            println!("State saved to {state_dir:?}. Wait for coordinator to announce phase 3.");
-        }
-        Commands::ProcessAll {
-            messages_dir,
-            state_dir,
-            keys_file,
-        } => {
-            let mut state = DkgState::load(&state_dir)?;
-            let local_keys = KeysFile::load(&keys_file)?;
-
-            // Read all files from the messages directory.
-            let mut messages = Vec::new();
-            let entries = fs::read_dir(&messages_dir).map_err(|e| {
-                anyhow!(
-                    "Failed to read messages directory {:?}: {}",
-                    messages_dir,
-                    e
-                )
-            })?;
+        /// Directory containing the message_*.json files of the ceremony. For a fresh DKG, all
+        /// messages must be present.
+        #[arg(short, long, value_hint = ValueHint::DirPath)]
+        messages_dir: Option<PathBuf>,
 
-            for entry in entries {
-                let path = entry?.path();
+        /// Read the committee object at this version instead of the latest one, e.g. to verify
+        /// the PostDKG state of a committee that was finalized since.
+        #[arg(long, value_name = "N")]
+        committee_version: Option<u64>,
+    },
 
-                let content = fs::read_to_string(&path)
-                    .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    /// Export a snapshot of a committee, with its key server once finalized, for running
+    /// `create-message` on a machine without network access: with `--committee-snapshot` for a
+    /// committee in Init state, or with `--old-committee-snapshot` for a finalized committee that
+    /// is rotated.
+    ExportCommittee {
+        /// Committee object ID. Required unless set in the config file.
+        #[arg(long)]
+        committee_id: Option<Address>,
 
-                let json: serde_json::Value = serde_json::from_str(&content)
-                    .map_err(|e| anyhow!("Failed to parse {}: {}", path.display(), e))?;
+        /// Network (mainnet, testnet, devnet, localnet or custom:<url>). Required unless set in the
+        /// config file.
+        #[arg(long, value_parser = NetworkValueParser)]
+        network: Option<Network>,
 
-                let message_base64 = json["message"]
-                    .as_str()
-                    .ok_or_else(|| anyhow!("Missing 'message' field in {}", path.display()))?;
+        /// Path to write the snapshot JSON file, or the CSV or text export.
+        #[arg(short, long, value_hint = ValueHint::FilePath)]
+        output: PathBuf,
 
-                let signed_message: SignedMessage =
-                    bcs::from_bytes(&Base64::decode(message_base64)?).map_err(|e| {
-                        anyhow!(
-                            "Failed to deserialize message from {}: {}",
-                            path.display(),
-                            e
-                        )
-                    })?;
+        /// Format of the export. Only the JSON snapshot can be used by `create-message` and
+        /// `process-all`.
+        #[arg(long, value_enum, default_value_t)]
+        output_format: ExportFormat,
+    },
 
-                messages.push(signed_message);
-            }
+    /// Print the BCS bytes of my confirmation and of the processed messages it used as base64,
+    /// after `process-all` merged the processed messages. The used messages contain my decrypted
+    /// shares.
+    PrintProposalBytes {
+        /// State directory (default: ./dkg-state).
+        #[arg(short = 's', long, value_hint = ValueHint::DirPath)]
+        state_dir: Option<PathBuf>,
+    },
 
-            if messages.is_empty() {
-                return Err(anyhow!("No files found in directory: {:?}", messages_dir));
-            }
+    /// Print the progress of the ceremony in the local state: the messages received and processed
+    /// out of those expected, whether the protocol is completed, and the parties whose messages
+    /// are missing.
+    CeremonyStatus {
+        /// State directory (default: ./dkg-state).
+        #[arg(short = 's', long, value_hint = ValueHint::DirPath)]
+        state_dir: Option<PathBuf>,
 
-            println!("Processing {} message(s)...", messages.len());
+        /// Print the progress as JSON to stdout.
+        #[arg(long)]
+        json: bool,
+    },
 
-            if let Some(old_threshold) = state.config.old_threshold {
-                // Key rotation: need messages from old threshold members.
-                if messages.len() < old_threshold as usize {
-                    return Err(anyhow!(
-                        "Key rotation requires at least {} messages from old committee members, got {}.",
-                        old_threshold, messages.len()
-                    ));
-                }
-            } else {
-                // Fresh DKG: need messages from all parties.
-                let num_parties = state.config.nodes.num_nodes();
-                if messages.len() != state.config.nodes.num_nodes() {
-                    return Err(anyhow!(
-                        "Fresh DKG requires {} messages (one from each party), got {}.",
-                        num_parties,
-                        messages.len()
-                    ));
-                }
-            }
+    /// Merge the received and processed messages of other partial states of this party, e.g.
+    /// collected by different coordinators, into the local state before running `process-all`.
+    MergeStates {
+        /// State directory to merge into (default: ./dkg-state).
+        #[arg(short = 's', long, value_hint = ValueHint::DirPath)]
+        state_dir: Option<PathBuf>,
+        /// State directories to merge from.
+        #[arg(long, required = true, num_args = 1.., value_hint = ValueHint::DirPath)]
+        from: Vec<PathBuf>,
+    },
 
-            // Create party.
-            let party = Party::<G2Element, G2Element>::new_advanced(
-                local_keys.enc_sk.clone(),
-                state.config.nodes.clone(),
-                state.config.threshold,
-                RandomOracle::new(&state.config.committee_id.to_string()),
-                state.config.my_old_share,
-                state.config.old_threshold,
-                &mut thread_rng(),
-            )?;
+    /// Check that the committee is finalized onchain and that its key server has the key server
+    /// PK and my partial PK from the local DKG output.
+    VerifyOutput {
+        /// State directory (default: ./dkg-state).
+        #[arg(short = 's', long, value_hint = ValueHint::DirPath)]
+        state_dir: Option<PathBuf>,
 
-            // Process each message.
-            for signed_msg in messages {
-                let sender_party_id = signed_msg.message.sender;
-                println!("Processing message from party {sender_party_id}...");
+        /// Network (mainnet, testnet, devnet, localnet or custom:<url>). Required unless set in the
+        /// config file.
+        #[arg(long, value_parser = NetworkValueParser)]
+        network: Option<Network>,
+    },
 
-                // Verify signed message using onchain signing pk for each party.
-                let sender_signing_pk =
-                    state
-                        .config
-                        .signing_pks
-                        .get(&sender_party_id)
-                        .ok_or_else(|| {
-                            anyhow!("Signing public key not found for party {}", sender_party_id)
-                        })?;
-                verify_signature(&signed_msg, sender_signing_pk)?;
-
-                // For rotation, find the expected old partial PK for this sender.
-                let processed = if state.config.old_threshold.is_some() {
-                    let new_to_old_mapping =
-                        state.config.new_to_old_mapping.as_ref().ok_or_else(|| {
-                            anyhow!("Missing new-to-old mapping for key rotation")
-                        })?;
-                    let old_party_id =
-                        new_to_old_mapping.get(&sender_party_id).ok_or_else(|| {
-                            anyhow!(
-                                "Party {} not found in old committee mapping",
-                                sender_party_id
-                            )
-                        })?;
-                    let expected_old_pks =
-                        state.config.expected_old_pks.as_ref().ok_or_else(|| {
-                            anyhow!("Missing expected old partial PKs for key rotation")
-                        })?;
-                    let expected_pk = expected_old_pks.get(old_party_id).ok_or_else(|| {
-                        anyhow!("Partial PK not found for old party {}", old_party_id)
-                    })?;
-
-                    match party.process_message_and_check_pk(
-                        signed_msg.message.clone(),
-                        expected_pk,
-                        &mut thread_rng(),
-                    ) {
-                        Ok(proc) => proc,
-                        Err(e) => {
-                            return Err(anyhow!(
-                                "Key rotation verification failed for party {sender_party_id}: {e}",
-                            ));
-                        }
-                    }
-                } else {
-                    // Fresh DKG.
-                    party.process_message(signed_msg.message.clone(), &mut thread_rng())?
-                };
+    /// Securely delete the secret-bearing local state after the committee is finalized and the
+    /// share is installed in the key server. Runs the `verify-output` checks first and keeps a
+    /// non-secret transcript for audit.
+    Prune {
+        /// State directory (default: ./dkg-state).
+        #[arg(short = 's', long, value_hint = ValueHint::DirPath)]
+        state_dir: Option<PathBuf>,
 
-                if let Some(complaint) = &processed.complaint {
-                    return Err(anyhow!(
-                        "Do NOT propose onchain. Complaint found {:?} for party {}.",
-                        complaint,
-                        processed.message.sender
-                    ));
-                }
-                println!("Successfully message processed from party {sender_party_id}...");
-                state.processed_messages.push(processed);
-            }
+        /// Path to the keys file (default: ./dkg-state/dkg.key).
+        #[arg(long, value_hint = ValueHint::FilePath)]
+        keys_file: Option<PathBuf>,
 
-            // Merge processed messages.
-            let (confirmation, used_msgs) = party.merge(&state.processed_messages)?;
+        /// Network (mainnet, testnet, devnet, localnet or custom:<url>). Required unless set in the
+        /// config file.
+        #[arg(long, value_parser = NetworkValueParser)]
+        network: Option<Network>,
 
-            // Check complaints.
-            if !confirmation.complaints.is_empty() {
-                let complaints = confirmation.complaints.clone();
-                state.confirmation = Some((confirmation, used_msgs));
-                state.save(&state_dir)?;
-                return Err(anyhow!(
-                    "Do NOT propose onchain. Complaint(s) found {:?}.",
-                    complaints,
-                ));
-            }
+        /// Skip the interactive confirmations. Requires `--i-have-installed-my-share`.
+        #[arg(long, requires = "i_have_installed_my_share")]
+        yes: bool,
 
-            state.confirmation = Some((confirmation.clone(), used_msgs.clone()));
+        /// Confirm that the key share is installed in the key server. Requires `--yes`.
+        #[arg(long, requires = "yes")]
+        i_have_installed_my_share: bool,
+    },
 
-            // Complete the protocol.
-            let output = if state.config.old_threshold.is_some() {
-                // Key rotation: use complete_optimistic_key_rotation.
-                let new_to_old_mapping = state
-                    .config
-                    .new_to_old_mapping
-                    .as_ref()
-                    .ok_or_else(|| anyhow!("Missing new-to-old mapping for key rotation"))?;
-                let sender_to_old_map: HashMap<u16, u16> = new_to_old_mapping
-                    .iter()
-                    .map(|(new_id, old_id)| (*new_id, *old_id))
-                    .collect();
+    /// Export the key server configuration (key share, partial PK, key server PK and VSS
+    /// polynomial) after `process-all` completed.
+    ExportKeyServerConfig {
+        /// State directory (default: ./dkg-state).
+        #[arg(short = 's', long, value_hint = ValueHint::DirPath)]
+        state_dir: Option<PathBuf>,
+        /// Path to write the key server config JSON file. Contains the secret key share.
+        #[arg(short, long, value_hint = ValueHint::FilePath)]
+        output: PathBuf,
+    },
 
-                println!("Completing key rotation with mapping: {sender_to_old_map:?}");
-                party.complete_optimistic_key_rotation(&used_msgs, &sender_to_old_map)?
-            } else {
-                // Fresh DKG.
-                party.complete_optimistic(&used_msgs)?
-            };
+    /// Emergency reconstruction of the master key from the key shares of at least a threshold of
+    /// members, e.g. if the committee dissolves. Only supported for key type 0 (G2).
+    RecoverMaster {
+        /// Directory with one share file per party, with the fields `party_id` and `share`
+        /// (hex-encoded BCS). Key server config files from `export-key-server-config` can be used.
+        #[arg(long, value_hint = ValueHint::DirPath)]
+        shares_dir: PathBuf,
 
-            state.output = Some(output.clone());
+        /// VSS public polynomial of the committee (hex-encoded BCS), as `VSS_PK` printed by
+        /// `process-all`.
+        #[arg(long)]
+        vss_pk: String,
 
-            println!("============KEY SERVER PK AND PARTIAL PKS=====================");
-            println!("KEY_SERVER_PK={}", format_pk_hex(&output.vss_pk.c0())?);
+        /// Path to write the recovered master key. Must not exist.
+        #[arg(short, long, value_hint = ValueHint::FilePath)]
+        output: PathBuf,
 
-            // Get partial public keys for all parties in the new committee.
-            for party_id in 0..state.config.nodes.num_nodes() {
-                // party id is 0 index and share index is party id + 1
-                let share_index = NonZeroU16::new(party_id as u16 + 1).expect("must be valid");
-                let partial_pk = output.vss_pk.eval(share_index);
-                println!(
-                    "PARTY_{}_PARTIAL_PK={}",
-                    party_id,
-                    format_pk_hex(&partial_pk.value)?
-                );
-            }
+        /// Confirm that the master key, which no single member should ever hold, is
+        /// reconstructed.
+        #[arg(long)]
+        i_understand_this_reconstructs_the_master_key: bool,
+    },
 
-            println!("============YOUR PARTIAL KEY SHARE, KEEP SECRET=====================");
-            if let Some(shares) = &output.shares {
-                for share in shares {
-                    println!("MASTER_SHARE={}", format_pk_hex(&share.value)?);
-                }
-            }
+    /// Encrypt my key share to the encryption key of another operator taking over my party, e.g.
+    /// on a new machine. The share is read from the completed DKG state, or from a share file.
+    ExportShareTo {
+        /// Encryption public key of the recipient (hex-encoded BCS), `enc_pk` in their keys file.
+        #[arg(long)]
+        recipient_enc_pk: String,
 
-            println!("============FULL VSS POLYNOMIAL COEFFICIENTS=====================");
-            for i in 0..=output.vss_pk.degree() {
-                let coeff = output.vss_pk.coefficient(i);
-                println!("Coefficient {}: {}", i, format_pk_hex(coeff)?);
-            }
-        }
-    }
-    Ok(())
+        /// State directory (default: ./dkg-state).
+        #[arg(short = 's', long, value_hint = ValueHint::DirPath)]
+        state_dir: Option<PathBuf>,
+
+        /// Share file with `party_id` and `share`, instead of the DKG state. Requires the
+        /// committee ID.
+        #[arg(long, value_hint = ValueHint::FilePath, conflicts_with = "state_dir")]
+        share_file: Option<PathBuf>,
+
+        /// Committee object ID, only used with `--share-file`. Required unless set in the config
+        /// file.
+        #[arg(long, requires = "share_file")]
+        committee_id: Option<Address>,
+
+        /// Path to write the encrypted share JSON file.
+        #[arg(short, long, value_hint = ValueHint::FilePath)]
+        output: PathBuf,
+    },
+
+    /// Decrypt a share from `export-share-to` with the local keys, verify it against the partial
+    /// PK of the party onchain and save it as a share file.
+    ImportShare {
+        /// Encrypted share JSON file from `export-share-to`.
+        #[arg(short, long, value_hint = ValueHint::FilePath)]
+        input: PathBuf,
+
+        /// Path to the keys file (default: ./dkg-state/dkg.key).
+        #[arg(long, value_hint = ValueHint::FilePath)]
+        keys_file: Option<PathBuf>,
+
+        /// Network (mainnet, testnet, devnet, localnet or custom:<url>). Required unless set in the
+        /// config file.
+        #[arg(long, value_parser = NetworkValueParser)]
+        network: Option<Network>,
+
+        /// Path to write the share file. Must not exist.
+        #[arg(short, long, value_hint = ValueHint::FilePath)]
+        output: PathBuf,
+    },
+
+    /// Check connectivity to the network's fullnode and to each committee member's key server,
+    /// e.g. before starting a ceremony.
+    Ping {
+        /// Committee object ID. Required unless set in the config file.
+        #[arg(long)]
+        committee_id: Option<Address>,
+
+        /// Network (mainnet, testnet, devnet, localnet or custom:<url>). Required unless set in the
+        /// config file.
+        #[arg(long, value_parser = NetworkValueParser)]
+        network: Option<Network>,
+
+        /// Timeout in seconds for each check.
+        #[arg(long, default_value_t = 5)]
+        timeout_secs: u64,
+
+        /// Print the latencies and errors as JSON to stdout, and status lines to stderr.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Check that each committee member's key server reports healthy at its registered URL, or at
+    /// its partial key server URL once finalized. Exits with a non-zero code if any member is
+    /// unreachable.
+    CheckHealth {
+        /// Committee object ID. Required unless set in the config file.
+        #[arg(long)]
+        committee_id: Option<Address>,
+
+        /// Network (mainnet, testnet, devnet, localnet or custom:<url>). Required unless set in the
+        /// config file.
+        #[arg(long, value_parser = NetworkValueParser)]
+        network: Option<Network>,
+
+        /// Timeout in seconds for each member.
+        #[arg(long, default_value_t = 5)]
+        timeout_secs: u64,
+    },
+
+    /// Rehearse a ceremony locally: generate keys for simulated parties in a temporary
+    /// directory, and run message creation and processing for every party against a locally
+    /// fabricated committee. No network access or chain state is needed.
+    Simulate {
+        /// Number of simulated parties.
+        #[arg(long, default_value_t = 4)]
+        parties: u16,
+
+        /// Threshold of the simulated committee.
+        #[arg(long, default_value_t = 3)]
+        threshold: u16,
+
+        /// Also simulate a follow-up key rotation to a new committee.
+        #[arg(long)]
+        rotate: bool,
+    },
+
+    /// Inspect the dkg-cli.toml configuration.
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+
+    /// Print a shell completion script to stdout.
+    ///
+    /// For example, for bash: `dkg-cli completions bash > /etc/bash_completion.d/dkg-cli`.
+    Completions {
+        /// Shell to generate the completion script for.
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+
+    /// Generate man pages (roff) for dkg-cli and all its subcommands.
+    Manpages {
+        /// Directory to write the man pages to. Created if it does not exist.
+        #[arg(long, value_hint = ValueHint::DirPath)]
+        out_dir: PathBuf,
+    },
+
+    /// Print the JSON Schema of a file written by dkg-cli, for validating it in other tools.
+    /// Fields holding fastcrypto types accept any value.
+    Schema {
+        /// File format.
+        #[arg(long, value_enum)]
+        format: SchemaFormat,
+    },
+
+    /// Serve JSON-RPC 2.0 requests for driving a ceremony from other languages, one request and
+    /// one response per line. Status lines go to stderr and no secrets are returned. Methods:
+    /// generate_keys, create_message, process_bundle, status and verify_output.
+    RpcServe {
+        /// Read requests from stdin and write responses to stdout. Currently the only transport.
+        #[arg(long, required = true)]
+        stdio: bool,
+    },
 }
 
-/// Helper function to write a file with restricted permissions (owner only) in Unix systems.
-fn write_secret_file(path: &Path, content: &str) -> Result<()> {
-    fs::write(path, content)?;
-    #[cfg(unix)]
-    {
-        let mut perms = fs::metadata(path)?.permissions();
-        perms.set_mode(0o600);
-        fs::set_permissions(path, perms)?;
-    }
-    Ok(())
+/// Order of the message files in a messages directory.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+enum MessageOrder {
+    /// Lexicographically by file name.
+    #[default]
+    Name,
+    /// Numerically by the party ID in `message_<party ID>.json`. Other files come last, by name.
+    PartyId,
+}
+
+/// Formats of `export-committee`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+enum ExportFormat {
+    /// Committee snapshot with the key server once finalized.
+    #[default]
+    Json,
+    /// One row per member with the registered keys and URL, for spreadsheets.
+    Csv,
+    /// Summary as printed by `show-committee --wide`.
+    Text,
 }
 
-/// Helper function to format a BCS-serializable value as hex string with 0x prefix.
-fn format_pk_hex<T: Serialize>(pk: &T) -> Result<String> {
-    Ok(Hex::encode_with_format(&bcs::to_bytes(pk)?))
+/// File formats with a JSON Schema, see `dkg-cli schema`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum SchemaFormat {
+    /// `message_<party ID>.json` files written by `create-message`.
+    Message,
+    /// `state.json` in the state directory, for key type 0 (G2).
+    State,
+    /// `transcript.json` written by `prune`, for key type 0 (G2).
+    Transcript,
+    /// Key server config written by `export-key-server-config`.
+    KeyServerConfig,
+}
+
+impl SchemaFormat {
+    fn schema(self) -> RootSchema {
+        match self {
+            SchemaFormat::Message => schema::message_file_schema(),
+            SchemaFormat::State => schema::state_schema(),
+            SchemaFormat::Transcript => schema::transcript_schema(),
+            SchemaFormat::KeyServerConfig => schema::key_server_config_schema(),
+        }
+    }
 }
 
-/// Helper function to parse network string into Network enum.
-fn parse_network(s: &str) -> Result<Network> {
-    Network::from_str(s).map_err(|e| anyhow::anyhow!(e))
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Print the effective configuration after merging flags, config file and defaults, and
+    /// where each value came from.
+    Show {
+        /// Network (mainnet, testnet, devnet, localnet or custom:<url>).
+        #[arg(long, value_parser = NetworkValueParser)]
+        network: Option<Network>,
+        /// Committee object ID.
+        #[arg(long)]
+        committee_id: Option<Address>,
+        /// State directory.
+        #[arg(long, value_hint = ValueHint::DirPath)]
+        state_dir: Option<PathBuf>,
+        /// Path to the keys file.
+        #[arg(long, value_hint = ValueHint::FilePath)]
+        keys_file: Option<PathBuf>,
+    },
+}
+
+/// Argument parsing errors exit with [ExitCode::Usage] (clap's default), other errors are
+/// mapped to an exit code by [ExitCode::from_error]. Invocations failing to parse are not written
+/// to the audit log.
+#[tokio::main]
+async fn main() -> std::process::ExitCode {
+    let matches = Cli::command().get_matches();
+    let command = matches.subcommand_name().unwrap_or_default().to_string();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let audit_log = cli.audit_log.clone();
+
+    let audit = Arc::new(AuditContext::default());
+    let result = run(cli, audit.clone()).await;
+    let audit_result = audit_log.map_or(Ok(()), |path| {
+        let args = std::env::args_os()
+            .skip(1)
+            .map(|arg| arg.to_string_lossy().into_owned());
+        AuditEntry::new(&command, args, &result, &audit).append(&path)
+    });
+    match (result, audit_result) {
+        (Ok(()), Ok(())) => ExitCode::Success.into(),
+        (Ok(()), Err(e)) => {
+            eprintln!("Error: {e:?}");
+            ExitCode::Failure.into()
+        }
+        (Err(e), audit_result) => {
+            if let Err(audit_error) = audit_result {
+                eprintln!("Error: {audit_error:?}");
+            }
+            eprintln!("Error: {e:?}");
+            ExitCode::from_error(&e).into()
+        }
+    }
+}
+
+async fn run(cli: Cli, audit: Arc<AuditContext>) -> Result<()> {
+    #[cfg(feature = "test-determinism")]
+    let seed = cli.seed;
+    #[cfg(not(feature = "test-determinism"))]
+    let seed = None;
+    let mut rng = new_rng(seed);
+    let mut grpc_config = GrpcClientConfig {
+        auth_header: cli.rpc_auth_header,
+        ..GrpcClientConfig::default()
+    };
+    if let Some(path) = &cli.rpc_ca_cert {
+        grpc_config = grpc_config
+            .with_ca_cert_file(path)
+            .map_err(CliError::Usage)?;
+    }
+    let mut ctx = Context {
+        config_path: cli.config,
+        allow_non_production_network: cli.allow_non_production_network,
+        permissions: PermissionPolicy::new(cli.strict_permissions),
+        no_lock: cli.no_lock,
+        state_format: StateFormat::new(cli.bcs),
+        key_server_version: cli.key_server_version,
+        grpc_config,
+        audit,
+        ..Context::default()
+    };
+
+    match cli.command {
+        Commands::GenerateKeys {
+            keys_file,
+            extra_entropy_file,
+            interactive_entropy,
+        } => {
+            let config = EffectiveConfig::resolve(
+                &ctx,
+                Flags {
+                    keys_file,
+                    ..Default::default()
+                },
+            )?;
+            let keys_file = config.keys_file();
+            if let Ok(committee_id) = config.committee_id() {
+                ctx.audit.record_committee_id(committee_id);
+            }
+
+            let extra_entropy = match extra_entropy_file {
+                Some(path) => Some(fs::read(&path).map_err(|e| {
+                    CliError::Usage(anyhow!("Failed to read {}: {}", path.display(), e))
+                })?),
+                None if interactive_entropy => Some(read_interactive_entropy()?),
+                None => None,
+            };
+            let created_keys_file = match extra_entropy {
+                Some(extra) => {
+                    if extra.is_empty() {
+                        return Err(CliError::Usage(anyhow!("Extra entropy is empty")).into());
+                    }
+                    KeysFile::generate_with_extra_entropy(&mut rng, &extra)
+                }
+                None => KeysFile::generate(&mut rng),
+            };
+
+            // Serialize to JSON
+            let json_content = serde_json::to_string_pretty(&created_keys_file)?;
+
+            if let Some(parent) = keys_file.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            write_secret_file(&ctx, keys_file, &json_content)?;
+
+            println!("Keys written to: {}", keys_file.display());
+            #[cfg(not(unix))]
+            println!("WARNING: On non-Unix systems, manually restrict file permissions");
+            println!(
+                "Public key fingerprint: {}",
+                created_keys_file.public_key_fingerprint()
+            );
+            println!("Confirm it with the other members, e.g. with `show-public-keys`.");
+        }
+
+        Commands::MigrateKeys {
+            keys_file,
+            from_version,
+            to_version,
+        } => {
+            let config = EffectiveConfig::resolve(
+                &ctx,
+                Flags {
+                    keys_file,
+                    ..Default::default()
+                },
+            )?;
+            let keys_file = config.keys_file();
+            let json = {
+                let _lock = lock::lock_shared(keys_file, &ctx)?;
+                permissions::check_secret_file(keys_file, PermissionPolicy::Warn)?;
+                fs::read_to_string(keys_file).map_err(|e| {
+                    anyhow!("Failed to read keys file {}: {}", keys_file.display(), e)
+                })?
+            };
+            let migrated = KeysFile::migrate(&json, from_version, to_version)
+                .map_err(|e| CliError::Validation(anyhow!("{}: {}", keys_file.display(), e)))?;
+            write_secret_file(&ctx, keys_file, migrated)?;
+            println!(
+                "Keys file {} migrated from version {from_version} to {to_version}.",
+                keys_file.display()
+            );
+        }
+
+        Commands::ShowPublicKeys { keys_file } => {
+            let config = EffectiveConfig::resolve(
+                &ctx,
+                Flags {
+                    keys_file,
+                    ..Default::default()
+                },
+            )?;
+            let keys = KeysFile::load(config.keys_file(), &ctx)?;
+            println!("Fingerprint:    {}", keys.public_key_fingerprint());
+            println!("Encryption PK:  {}", format_pk_hex(&keys.enc_pk)?);
+            println!("Signing PK:     {}", format_pk_hex(&keys.signing_pk)?);
+        }
+
+        Commands::MigrateIndependent {
+            master_key_file,
+            my_address,
+            committee_id,
+            network,
+            output,
+        } => {
+            let config = EffectiveConfig::resolve(
+                &ctx,
+                Flags {
+                    network,
+                    committee_id,
+                    ..Default::default()
+                },
+            )?;
+            let network = config.network()?;
+            let committee_id = config.committee_id()?;
+            let rpc_timeout = config.rpc_timeout();
+            let master_key =
+                read_key_share_file(&master_key_file, ctx.permissions).map_err(CliError::Usage)?;
+
+            let grpc_client = create_grpc_client(&ctx, &network).map_err(CliError::Network)?;
+            let committee = with_rpc_timeout(
+                rpc_timeout,
+                with_spinner(
+                    format!("Fetching committee {committee_id}"),
+                    fetch_committee_data(&grpc_client, &committee_id),
+                ),
+            )
+            .await
+            .map_err(CliError::Network)?;
+            committee
+                .is_init()
+                .map_err(|e| CliError::StateConflict(e.into()))?;
+            let Some(key_server_id) = committee.old_committee_id else {
+                return Err(CliError::Validation(anyhow!(
+                    "Committee {committee_id} has no old committee ID. It must be the object ID of the independent key server."
+                ))
+                .into());
+            };
+            committee
+                .get_party_id(&my_address)
+                .map_err(|e| CliError::Validation(e.into()))?;
+
+            let key_server = with_rpc_timeout(
+                rpc_timeout,
+                with_spinner(
+                    format!("Fetching key server {key_server_id}"),
+                    fetch_key_server_v2(&grpc_client, &key_server_id),
+                ),
+            )
+            .await
+            .map_err(|e| {
+                CliError::Network(e.context(format!(
+                    "Old committee ID {key_server_id} is not a key server object"
+                )))
+            })?;
+            let key_type = check_independent_key_server(&master_key, &key_server)
+                .map_err(CliError::Verification)?;
+            println!(
+                "Master key matches key server {} ({key_server_id}), key type: {key_type}",
+                key_server.name
+            );
+
+            let snapshot =
+                CommitteeSnapshot::from_independent(key_server_id, my_address, &key_server)
+                    .map_err(CliError::Validation)?;
+            if let Some(parent) = output.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&output, serde_json::to_string_pretty(&snapshot)?)?;
+            println!(
+                "Old committee snapshot written to: {}. Digest: {}",
+                output.display(),
+                snapshot.digest
+            );
+            println!(
+                "Share the snapshot with all members for `create-message --old-committee-snapshot`, and run `create-message` with `--old-share-file {}`.",
+                master_key_file.display()
+            );
+        }
+
+        Commands::CreateMessage {
+            my_address,
+            committee_id,
+            network,
+            state_dir,
+            keys_file,
+            old_share,
+            old_share_file,
+            old_committee_snapshot,
+            committee_snapshot,
+            weights_file,
+            json,
+            index_timeout_secs,
+        } => {
+            ctx.json = json;
+            let weights = weights_file.as_deref().map(read_weights_file).transpose()?;
+            let config = EffectiveConfig::resolve(
+                &ctx,
+                Flags {
+                    network,
+                    committee_id,
+                    keys_file,
+                    state_dir,
+                },
+            )?;
+            let state_dir = config.state_dir();
+            let rpc_timeout = config.rpc_timeout();
+            let local_keys = KeysFile::load(config.keys_file(), &ctx)?;
+
+            // Parse old share from command argument if provided. Provided for continuing members
+            // in key rotation.
+            let my_old_share = match (old_share, old_share_file) {
+                (Some(share_hex), _) => Some(
+                    parse_key_share(&share_hex)
+                        .map_err(|e| CliError::Usage(anyhow!("Invalid --old-share: {e}")))?,
+                ),
+                (None, Some(path)) => {
+                    Some(read_key_share_file(&path, ctx.permissions).map_err(CliError::Usage)?)
+                }
+                (None, None) => None,
+            };
+            if my_old_share.is_some() {
+                status!(ctx, "Continuing member for key rotation, old share parsed.");
+            }
+
+            // Fetch current committee from onchain, unless a snapshot is given.
+            let mut grpc_client = None;
+            let committee = match &committee_snapshot {
+                Some(path) => {
+                    let committee = InitCommitteeSnapshot::load(path)
+                        .and_then(|snapshot| snapshot.to_committee())
+                        .map_err(CliError::Validation)?;
+                    if let Ok(committee_id) = config.committee_id() {
+                        if committee.id != committee_id {
+                            return Err(CliError::Validation(anyhow!(
+                                "Snapshot is of committee {}, expected {}",
+                                committee.id,
+                                committee_id
+                            ))
+                            .into());
+                        }
+                    }
+                    status!(ctx, "Using committee snapshot from {}.", path.display());
+                    committee
+                }
+                None => {
+                    let committee_id = config.committee_id()?;
+                    fetch_committee_when_indexed(
+                        &ctx,
+                        lazy_grpc_client(&ctx, &mut grpc_client, &config)?,
+                        &committee_id,
+                        rpc_timeout,
+                        Duration::from_secs(index_timeout_secs),
+                    )
+                    .await
+                    .map_err(CliError::Network)?
+                }
+            };
+
+            committee.validate().map_err(|issues| {
+                CliError::Validation(anyhow!(
+                    "Committee {} is invalid:\n{}",
+                    committee.id,
+                    issues
+                        .iter()
+                        .map(|issue| format!("- {issue}"))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                ))
+            })?;
+            ctx.audit.record_committee_id(committee.id);
+            if let Ok(party_id) = committee.get_party_id(&my_address) {
+                ctx.audit.record_party_id(party_id);
+            }
+            status!(
+                ctx,
+                "Committee with {} members, threshold: {}",
+                committee.members.len(),
+                committee.threshold
+            );
+
+            // Get the old committee for key rotation.
+            let old_snapshot = match committee.old_committee_id {
+                None => {
+                    if old_committee_snapshot.is_some() {
+                        return Err(CliError::Usage(anyhow!(
+                            "--old-committee-snapshot should not be provided for fresh DKG."
+                        ))
+                        .into());
+                    }
+                    status!(ctx, "No old committee ID, performing fresh DKG.");
+                    None
+                }
+                Some(old_committee_id) => {
+                    status!(
+                        ctx,
+                        "Old committee ID: {old_committee_id}, performing key rotation."
+                    );
+
+                    let snapshot = match &old_committee_snapshot {
+                        Some(path) => {
+                            let snapshot =
+                                CommitteeSnapshot::load(path).map_err(CliError::Validation)?;
+                            if snapshot.committee_id != old_committee_id {
+                                return Err(CliError::Validation(anyhow!(
+                                    "Snapshot is of committee {}, expected old committee {}",
+                                    snapshot.committee_id,
+                                    old_committee_id
+                                ))
+                                .into());
+                            }
+                            status!(ctx, "Using old committee snapshot from {}.", path.display());
+                            snapshot
+                        }
+                        None => {
+                            fetch_old_committee_snapshot(
+                                &ctx,
+                                lazy_grpc_client(&ctx, &mut grpc_client, &config)?,
+                                &old_committee_id,
+                                rpc_timeout,
+                            )
+                            .await?
+                        }
+                    };
+                    status!(ctx, "Old committee digest: {}", snapshot.digest);
+                    Some(snapshot)
+                }
+            };
+
+            // The instantiation is selected by the key type of the old committee's key server.
+            // A fresh DKG uses the default key type.
+            let key_type = match &old_snapshot {
+                Some(snapshot) => snapshot.key_type().map_err(CliError::Validation)?,
+                None => KeyType::default(),
+            };
+            status!(ctx, "Key type: {key_type}");
+            let created = match key_type {
+                KeyType::G2 => create_message::<G2Element>(
+                    &ctx,
+                    &committee,
+                    &my_address,
+                    &local_keys,
+                    my_old_share,
+                    old_snapshot.as_ref(),
+                    weights.as_ref(),
+                    Path::new("."),
+                    state_dir,
+                    &mut rng,
+                )?,
+                KeyType::G1 => create_message::<G1Element>(
+                    &ctx,
+                    &committee,
+                    &my_address,
+                    &local_keys,
+                    my_old_share,
+                    old_snapshot.as_ref(),
+                    weights.as_ref(),
+                    Path::new("."),
+                    state_dir,
+                    &mut rng,
+                )?,
+            };
+            ctx.print_json_result(&created)?;
+        }
+        Commands::RotateKeys {
+            my_address,
+            committee_id,
+            old_committee_id,
+            network,
+            state_dir,
+            keys_file,
+            old_share,
+            skip_validation,
+        } => {
+            let config = EffectiveConfig::resolve(
+                &ctx,
+                Flags {
+                    network,
+                    committee_id,
+                    keys_file,
+                    state_dir,
+                },
+            )?;
+            let committee_id = config.committee_id()?;
+            let state_dir = config.state_dir();
+            let rpc_timeout = config.rpc_timeout();
+            let local_keys = KeysFile::load(config.keys_file(), &ctx)?;
+            let my_old_share = old_share
+                .map(|share_hex| {
+                    parse_key_share(&share_hex)
+                        .map_err(|e| CliError::Usage(anyhow!("Invalid --old-share: {e}")))
+                })
+                .transpose()?;
+
+            let grpc_client =
+                create_grpc_client(&ctx, &config.network()?).map_err(CliError::Network)?;
+            let created = rotate_keys(
+                &ctx,
+                &grpc_client,
+                &committee_id,
+                old_committee_id,
+                &my_address,
+                &local_keys,
+                my_old_share,
+                skip_validation,
+                state_dir,
+                rpc_timeout,
+                &mut rng,
+            )
+            .await?;
+            ctx.print_json_result(&created)?;
+        }
+        Commands::ValidateRotation {
+            committee_id,
+            old_committee_id,
+            network,
+            my_address,
+            json,
+        } => {
+            ctx.json = json;
+            let config = EffectiveConfig::resolve(
+                &ctx,
+                Flags {
+                    network,
+                    committee_id,
+                    ..Default::default()
+                },
+            )?;
+            let committee_id = config.committee_id()?;
+            let grpc_client =
+                create_grpc_client(&ctx, &config.network()?).map_err(CliError::Network)?;
+            validate_rotation(
+                &ctx,
+                &grpc_client,
+                &committee_id,
+                old_committee_id,
+                my_address.as_ref(),
+                config.rpc_timeout(),
+            )
+            .await?;
+        }
+        Commands::ProcessAll {
+            messages_dir,
+            state_dir,
+            keys_file,
+            max_complaints,
+            write_messages_only,
+            weights_file,
+            json,
+            sort,
+            committee_snapshot,
+            old_committee_snapshot,
+        } => {
+            ctx.json = json;
+            let weights = weights_file.as_deref().map(read_weights_file).transpose()?;
+            let config = EffectiveConfig::resolve(
+                &ctx,
+                Flags {
+                    keys_file,
+                    state_dir,
+                    ..Default::default()
+                },
+            )?;
+            let state_dir = config.state_dir();
+            let local_keys = KeysFile::load(config.keys_file(), &ctx)?;
+            let committee = committee_snapshot
+                .as_deref()
+                .map(load_committee)
+                .transpose()
+                .map_err(CliError::Validation)?;
+            let old_committee = old_committee_snapshot
+                .as_deref()
+                .map(|path| CommitteeSnapshot::load(path).map(|snapshot| snapshot.to_committee()))
+                .transpose()
+                .map_err(CliError::Validation)?;
+            match state_key_type(state_dir)? {
+                KeyType::G2 => process_all::<G2Element>(
+                    &ctx,
+                    state_dir,
+                    &local_keys,
+                    &messages_dir,
+                    sort,
+                    max_complaints,
+                    write_messages_only,
+                    weights.as_ref(),
+                    committee.as_ref(),
+                    old_committee.as_ref(),
+                    &mut rng,
+                )?,
+                KeyType::G1 => process_all::<G1Element>(
+                    &ctx,
+                    state_dir,
+                    &local_keys,
+                    &messages_dir,
+                    sort,
+                    max_complaints,
+                    write_messages_only,
+                    weights.as_ref(),
+                    committee.as_ref(),
+                    old_committee.as_ref(),
+                    &mut rng,
+                )?,
+            }
+        }
+
+        Commands::ApprovalStatus {
+            committee_id,
+            network,
+            at_version,
+        } => {
+            let config = EffectiveConfig::resolve(
+                &ctx,
+                Flags {
+                    network,
+                    committee_id,
+                    ..Default::default()
+                },
+            )?;
+            let network = config.network()?;
+            let committee_id = config.committee_id()?;
+            let rpc_timeout = config.rpc_timeout();
+
+            let grpc_client = create_grpc_client(&ctx, &network).map_err(CliError::Network)?;
+            let committee = fetch_committee_at_version(
+                &ctx,
+                &grpc_client,
+                &committee_id,
+                at_version,
+                rpc_timeout,
+            )
+            .await?;
+            let approvals = committee
+                .get_approval_status()
+                .map_err(|e| CliError::StateConflict(e.into()))?;
+
+            println!("{:<6} {:<66} APPROVED", "PARTY", "ADDRESS");
+            for (party_id, address) in committee.members.iter().enumerate() {
+                let approved = if approvals[address] { "yes" } else { "NO" };
+                println!("{party_id:<6} {address:<66} {approved}");
+            }
+            let num_approved = approvals.values().filter(|approved| **approved).count();
+            println!(
+                "{num_approved} of {} member(s) approved.",
+                committee.members.len()
+            );
+        }
+
+        Commands::VerifyProposal {
+            committee_id,
+            network,
+        } => {
+            let config = EffectiveConfig::resolve(
+                &ctx,
+                Flags {
+                    network,
+                    committee_id,
+                    ..Default::default()
+                },
+            )?;
+            let network = config.network()?;
+            let committee_id = config.committee_id()?;
+            let rpc_timeout = config.rpc_timeout();
+
+            let grpc_client = create_grpc_client(&ctx, &network).map_err(CliError::Network)?;
+            let committee = with_rpc_timeout(
+                rpc_timeout,
+                with_spinner(
+                    format!("Fetching committee {committee_id}"),
+                    fetch_committee_data(&grpc_client, &committee_id),
+                ),
+            )
+            .await
+            .map_err(CliError::Network)?;
+            if !matches!(committee.state, CommitteeState::PostDKG { .. }) {
+                return Err(CliError::StateConflict(anyhow!(
+                    "Committee {committee_id} has no proposal, it is in {} state",
+                    committee.state
+                ))
+                .into());
+            }
+            let proposal = committee
+                .get_postdkg_info()
+                .map_err(|e| CliError::Verification(e.into()))?;
+            let partial_pks: Vec<_> = proposal
+                .partial_pks
+                .iter()
+                .map(|partial| (partial.party_id, partial.partial_pk))
+                .collect();
+            verify_partial_pk_consistency(&proposal.pk, &partial_pks, committee.threshold)
+                .map_err(|e| {
+                    CliError::Verification(e.context(format!(
+                        "Proposal of committee {committee_id} is inconsistent, do not approve it"
+                    )))
+                })?;
+            println!(
+                "Proposal of committee {committee_id} is consistent: the {} partial PKs match the PK for threshold {}.",
+                partial_pks.len(),
+                committee.threshold
+            );
+            println!("PK: {}", format_pk_hex(&proposal.pk)?);
+            println!(
+                "Approved by {} of {} member(s).",
+                proposal.approvals.len(),
+                committee.members.len()
+            );
+        }
+
+        Commands::ShowCommittee {
+            committee_id,
+            network,
+            wide,
+        } => {
+            let config = EffectiveConfig::resolve(
+                &ctx,
+                Flags {
+                    network,
+                    committee_id,
+                    ..Default::default()
+                },
+            )?;
+            let network = config.network()?;
+            let committee_id = config.committee_id()?;
+            let rpc_timeout = config.rpc_timeout();
+
+            let grpc_client = create_grpc_client(&ctx, &network).map_err(CliError::Network)?;
+            let committee = with_rpc_timeout(
+                rpc_timeout,
+                with_spinner(
+                    format!("Fetching committee {committee_id}"),
+                    fetch_committee_data(&grpc_client, &committee_id),
+                ),
+            )
+            .await
+            .map_err(CliError::Network)?;
+            if wide {
+                println!("{committee:#}");
+            } else {
+                println!("{committee}");
+            }
+
+            if matches!(committee.state, CommitteeState::Finalized) {
+                let (_, key_server) = with_rpc_timeout(
+                    rpc_timeout,
+                    with_spinner(
+                        format!("Fetching key server of committee {committee_id}"),
+                        fetch_committee_key_server(&ctx, &grpc_client, &committee_id),
+                    ),
+                )
+                .await
+                .map_err(CliError::Network)?;
+                if wide {
+                    println!("{key_server:#}");
+                } else {
+                    println!("{key_server}");
+                }
+            }
+        }
+
+        Commands::Wait {
+            committee_id,
+            network,
+            until,
+            poll_interval_secs,
+            timeout_secs,
+        } => {
+            let config = EffectiveConfig::resolve(
+                &ctx,
+                Flags {
+                    network,
+                    committee_id,
+                    ..Default::default()
+                },
+            )?;
+            let network = config.network()?;
+            let committee_id = config.committee_id()?;
+
+            let grpc_client = create_grpc_client(&ctx, &network).map_err(CliError::Network)?;
+            let mut last_progress = None;
+            watch_committee_state(
+                grpc_client,
+                committee_id,
+                |committee| {
+                    let progress = describe_progress(committee);
+                    if last_progress.as_ref() != Some(&progress) {
+                        println!("{progress}");
+                        last_progress = Some(progress);
+                    }
+                    until.is_reached(committee)
+                },
+                Duration::from_secs(poll_interval_secs),
+                Duration::from_secs(timeout_secs),
+            )
+            .await
+            .map_err(|e| CliError::Network(e.into()))?;
+            println!("Committee {committee_id} reached the target state.");
+        }
+
+        Commands::ListMessages {
+            messages_dir,
+            committee_id,
+            network,
+        } => {
+            let config = EffectiveConfig::resolve(
+                &ctx,
+                Flags {
+                    network,
+                    committee_id,
+                    ..Default::default()
+                },
+            )?;
+            let network = config.network()?;
+            let committee_id = config.committee_id()?;
+            let rpc_timeout = config.rpc_timeout();
+
+            let grpc_client = create_grpc_client(&ctx, &network).map_err(CliError::Network)?;
+            let committee = with_rpc_timeout(
+                rpc_timeout,
+                with_spinner(
+                    format!("Fetching committee {committee_id}"),
+                    fetch_committee_data(&grpc_client, &committee_id),
+                ),
+            )
+            .await
+            .map_err(CliError::Network)?;
+            let members_info = committee
+                .get_members_info()
+                .map_err(|e| CliError::Validation(e.into()))?;
+            let members_by_party_id: HashMap<u16, _> = members_info
+                .values()
+                .map(|member| (member.party_id, member))
+                .collect();
+
+            let paths = message_paths(&messages_dir, MessageOrder::Name)?;
+            let key_type = committee_key_type(&ctx, &grpc_client, &committee, rpc_timeout).await?;
+
+            // Parties with a message that has a valid signature.
+            let mut submitted = HashSet::new();
+            println!(
+                "{:<6} {:<66} {:<24} {:<20} SIGNATURE",
+                "PARTY", "ADDRESS", "FILE", "SUBMITTED"
+            );
+            for path in paths {
+                let file_name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                let submitted_at = fs::metadata(&path)
+                    .and_then(|metadata| metadata.modified())
+                    .map(|mtime| {
+                        DateTime::<Utc>::from(mtime).to_rfc3339_opts(SecondsFormat::Secs, true)
+                    })
+                    .unwrap_or_else(|_| "-".to_string());
+
+                let (party_id, address, status) = match key_type {
+                    KeyType::G2 => message_file_status::<G2Element>(
+                        &path,
+                        &members_by_party_id,
+                        &mut submitted,
+                    ),
+                    KeyType::G1 => message_file_status::<G1Element>(
+                        &path,
+                        &members_by_party_id,
+                        &mut submitted,
+                    ),
+                };
+                println!("{party_id:<6} {address:<66} {file_name:<24} {submitted_at:<20} {status}");
+            }
+
+            // For key rotation, only continuing members are required to submit a message.
+            let rotation = match committee.old_committee_id {
+                None => None,
+                Some(old_committee_id) => {
+                    let old_committee = with_rpc_timeout(
+                        rpc_timeout,
+                        with_spinner(
+                            format!("Fetching old committee {old_committee_id}"),
+                            fetch_committee_data(&grpc_client, &old_committee_id),
+                        ),
+                    )
+                    .await
+                    .map_err(CliError::Network)?;
+                    Some(
+                        RotationPlan::build(&committee, &old_committee)
+                            .map_err(CliError::Validation)?,
+                    )
+                }
+            };
+
+            println!(
+                "\n{} of {} member(s) submitted a valid message.",
+                submitted.len(),
+                committee.members.len()
+            );
+            let missing: Vec<_> = committee
+                .members
+                .iter()
+                .enumerate()
+                .filter(|(party_id, _)| !submitted.contains(&(*party_id as u16)))
+                .collect();
+            if missing.is_empty() {
+                println!("No missing members.");
+            } else {
+                println!("Missing members:");
+                for (party_id, address) in missing {
+                    let note = match &rotation {
+                        None => "",
+                        Some(plan) if plan.is_continuing(party_id as u16) => {
+                            " (continuing member, required)"
+                        }
+                        Some(_) => " (new member, not required)",
+                    };
+                    println!("  Party {party_id} ({address}){note}");
+                }
+            }
+
+            if let Some(plan) = rotation {
+                let continuing_submitted = plan
+                    .continuing
+                    .iter()
+                    .filter(|member| submitted.contains(&member.new_party_id))
+                    .count();
+                let old_threshold = plan.old_threshold;
+                let status = if continuing_submitted >= old_threshold as usize {
+                    "met"
+                } else {
+                    "NOT met"
+                };
+                println!(
+                    "Old threshold quorum {status}: {continuing_submitted} of {} continuing member(s) submitted, need {old_threshold}.",
+                    plan.continuing.len()
+                );
+            }
+        }
+        Commands::Observe {
+            committee_id,
+            network,
+            messages_dir,
+            committee_version,
+        } => {
+            let config = EffectiveConfig::resolve(
+                &ctx,
+                Flags {
+                    network,
+                    committee_id,
+                    ..Default::default()
+                },
+            )?;
+            let network = config.network()?;
+            let committee_id = config.committee_id()?;
+            let rpc_timeout = config.rpc_timeout();
+
+            let grpc_client = create_grpc_client(&ctx, &network).map_err(CliError::Network)?;
+            observe(
+                &ctx,
+                &grpc_client,
+                &committee_id,
+                committee_version,
+                messages_dir,
+                rpc_timeout,
+            )
+            .await?;
+        }
+        Commands::ExportCommittee {
+            committee_id,
+            network,
+            output,
+            output_format,
+        } => {
+            let config = EffectiveConfig::resolve(
+                &ctx,
+                Flags {
+                    network,
+                    committee_id,
+                    ..Default::default()
+                },
+            )?;
+            let network = config.network()?;
+            let committee_id = config.committee_id()?;
+            let rpc_timeout = config.rpc_timeout();
+
+            let grpc_client = create_grpc_client(&ctx, &network).map_err(CliError::Network)?;
+            let snapshot = with_rpc_timeout(
+                rpc_timeout,
+                with_spinner(
+                    format!("Fetching committee {committee_id}"),
+                    export_committee_snapshot(&grpc_client, &committee_id),
+                ),
+            )
+            .await
+            .map_err(CliError::Network)?;
+            let committee = snapshot.committee().map_err(CliError::Validation)?;
+            if let Some(parent) = output.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            match output_format {
+                ExportFormat::Csv => {
+                    fs::write(&output, committee.to_csv()?)?;
+                    println!("Committee members written to: {}", output.display());
+                }
+                ExportFormat::Text => {
+                    fs::write(&output, format!("{committee:#}\n"))?;
+                    println!("Committee summary written to: {}", output.display());
+                }
+                ExportFormat::Json => {
+                    fs::write(&output, snapshot.to_json()?)?;
+                    println!(
+                        "Committee snapshot written to: {}. Digest: {}",
+                        output.display(),
+                        snapshot.content_digest
+                    );
+
+                    // A committee in Init state has no key server yet, its snapshot is for
+                    // running create-message offline. The snapshot of a finalized committee is
+                    // for rotating it.
+                    match &committee.state {
+                        CommitteeState::Init { .. } => {
+                            println!("Use it with create-message --committee-snapshot.")
+                        }
+                        CommitteeState::Finalized => {
+                            let old_snapshot = CommitteeSnapshot::from_snapshot(&snapshot)
+                                .map_err(CliError::Validation)?;
+                            println!(
+                                "Use it with create-message --old-committee-snapshot to rotate the committee. Old committee digest: {}",
+                                old_snapshot.digest
+                            );
+                        }
+                        CommitteeState::PostDKG { .. } => {}
+                    }
+                }
+            }
+        }
+        Commands::PrintProposalBytes { state_dir } => {
+            let config = EffectiveConfig::resolve(
+                &ctx,
+                Flags {
+                    state_dir,
+                    ..Default::default()
+                },
+            )?;
+            let state_dir = config.state_dir();
+            let (confirmation, used_messages) =
+                match state_key_type(state_dir)? {
+                    KeyType::G2 => DkgState::<G2Element>::load(state_dir, &ctx)?
+                        .prepare_onchain_proposal_bytes(),
+                    KeyType::G1 => DkgState::<G1Element>::load(state_dir, &ctx)?
+                        .prepare_onchain_proposal_bytes(),
+                }
+                .map_err(CliError::StateConflict)?;
+            println!("CONFIRMATION={}", Base64::encode(confirmation));
+            println!("============USED MESSAGES CONTAIN YOUR DECRYPTED SHARES, KEEP SECRET=====================");
+            println!("USED_MESSAGES={}", Base64::encode(used_messages));
+        }
+        Commands::CeremonyStatus { state_dir, json } => {
+            let config = EffectiveConfig::resolve(
+                &ctx,
+                Flags {
+                    state_dir,
+                    ..Default::default()
+                },
+            )?;
+            let state_dir = config.state_dir();
+            let status = match state_key_type(state_dir)? {
+                KeyType::G2 => ceremony_status(&DkgState::<G2Element>::load(state_dir, &ctx)?),
+                KeyType::G1 => ceremony_status(&DkgState::<G1Element>::load(state_dir, &ctx)?),
+            };
+            if json {
+                println!("{}", serde_json::to_string_pretty(&status)?);
+            } else {
+                println!("{}", status.progress);
+                if status.missing_message_senders.is_empty() {
+                    println!("No missing messages.");
+                } else {
+                    println!(
+                        "Missing messages from parties: {}",
+                        status
+                            .missing_message_senders
+                            .iter()
+                            .map(u16::to_string)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    );
+                }
+            }
+        }
+        Commands::MergeStates { state_dir, from } => {
+            let config = EffectiveConfig::resolve(
+                &ctx,
+                Flags {
+                    state_dir,
+                    ..Default::default()
+                },
+            )?;
+            let state_dir = config.state_dir();
+            match state_key_type(state_dir)? {
+                KeyType::G2 => merge_states::<G2Element>(&ctx, state_dir, &from)?,
+                KeyType::G1 => merge_states::<G1Element>(&ctx, state_dir, &from)?,
+            }
+        }
+        Commands::VerifyOutput { state_dir, network } => {
+            let config = EffectiveConfig::resolve(
+                &ctx,
+                Flags {
+                    network,
+                    state_dir,
+                    ..Default::default()
+                },
+            )?;
+            let (state_dir, network) = (config.state_dir(), config.network()?);
+            let rpc_timeout = config.rpc_timeout();
+            let committee_id = match state_key_type(state_dir)? {
+                KeyType::G2 => {
+                    load_verified_state::<G2Element>(&ctx, state_dir, &network, rpc_timeout)
+                        .await?
+                        .config
+                        .committee_id
+                }
+                KeyType::G1 => {
+                    load_verified_state::<G1Element>(&ctx, state_dir, &network, rpc_timeout)
+                        .await?
+                        .config
+                        .committee_id
+                }
+            };
+            println!(
+                "Local DKG output matches the key server of finalized committee {committee_id}."
+            );
+        }
+        Commands::Prune {
+            state_dir,
+            keys_file,
+            network,
+            yes,
+            i_have_installed_my_share,
+        } => {
+            let config = EffectiveConfig::resolve(
+                &ctx,
+                Flags {
+                    network,
+                    keys_file,
+                    state_dir,
+                    ..Default::default()
+                },
+            )?;
+            let (state_dir, network) = (config.state_dir(), config.network()?);
+            let rpc_timeout = config.rpc_timeout();
+            // Only the transcript is kept once the secrets are deleted.
+            let transcript = match state_key_type(state_dir)? {
+                KeyType::G2 => serde_json::to_string_pretty(
+                    &load_verified_state::<G2Element>(&ctx, state_dir, &network, rpc_timeout)
+                        .await?
+                        .transcript(),
+                )?,
+                KeyType::G1 => serde_json::to_string_pretty(
+                    &load_verified_state::<G1Element>(&ctx, state_dir, &network, rpc_timeout)
+                        .await?
+                        .transcript(),
+                )?,
+            };
+            println!("Onchain verification passed.");
+
+            let state_file = state_file(state_dir)?;
+            let secret_files: Vec<&Path> = [state_file.as_path(), config.keys_file()]
+                .into_iter()
+                .filter(|path| path.exists())
+                .collect();
+            println!("The following files will be overwritten and deleted:");
+            for path in &secret_files {
+                println!("  {}", path.display());
+            }
+            if !(yes && i_have_installed_my_share) {
+                confirm("Type 'prune' to delete the files above", "prune")?;
+                confirm(
+                    "Type 'installed' to confirm that your key share is installed in the key server",
+                    "installed",
+                )?;
+            }
+
+            let transcript_file = state_dir.join("transcript.json");
+            fs::write(&transcript_file, transcript)?;
+            println!("Transcript written to: {}", transcript_file.display());
+            for path in secret_files {
+                secure_delete(path)?;
+                println!("Deleted: {}", path.display());
+            }
+        }
+        Commands::ExportKeyServerConfig { state_dir, output } => {
+            let config = EffectiveConfig::resolve(
+                &ctx,
+                Flags {
+                    state_dir,
+                    ..Default::default()
+                },
+            )?;
+            let state_dir = config.state_dir();
+            let key_server_config = match state_key_type(state_dir)? {
+                KeyType::G2 => {
+                    DkgState::<G2Element>::load(state_dir, &ctx)?.export_key_server_config()
+                }
+                KeyType::G1 => {
+                    DkgState::<G1Element>::load(state_dir, &ctx)?.export_key_server_config()
+                }
+            }
+            .map_err(CliError::StateConflict)?;
+
+            if let Some(parent) = output.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            write_secret_file(
+                &ctx,
+                &output,
+                &serde_json::to_string_pretty(&key_server_config)?,
+            )?;
+            println!("Key server config written to: {}", output.display());
+        }
+
+        Commands::RecoverMaster {
+            shares_dir,
+            vss_pk,
+            output,
+            i_understand_this_reconstructs_the_master_key,
+        } => {
+            if !i_understand_this_reconstructs_the_master_key {
+                return Err(CliError::Usage(anyhow!(
+                    "recover-master reconstructs the master key from the shares. Pass --i-understand-this-reconstructs-the-master-key to proceed."
+                ))
+                .into());
+            }
+            if output.exists() {
+                return Err(
+                    CliError::Usage(anyhow!("Output {} already exists", output.display())).into(),
+                );
+            }
+            let vss_pk: PublicPoly<G2Element> = Hex::decode(&vss_pk)
+                .map_err(anyhow::Error::from)
+                .and_then(|bytes| Ok(bcs::from_bytes(&bytes)?))
+                .map_err(|e| CliError::Usage(anyhow!("Invalid --vss-pk: {e}")))?;
+            let shares = read_shares_dir(&shares_dir, ctx.permissions)?;
+            println!(
+                "Read {} share(s), threshold is {}.",
+                shares.len(),
+                vss_pk.degree() + 1
+            );
+
+            let master_key = recover_master_key(&vss_pk, &shares)?;
+            if let Some(parent) = output.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            write_secret_file(&ctx, &output, &format_pk_hex(&master_key)?)?;
+            println!(
+                "Master key for KEY_SERVER_PK={} written to: {}. Keep it secret!",
+                format_pk_hex(vss_pk.c0())?,
+                output.display()
+            );
+        }
+
+        Commands::ExportShareTo {
+            recipient_enc_pk,
+            state_dir,
+            share_file,
+            committee_id,
+            output,
+        } => {
+            let config = EffectiveConfig::resolve(
+                &ctx,
+                Flags {
+                    committee_id,
+                    state_dir,
+                    ..Default::default()
+                },
+            )?;
+            let recipient_enc_pk: PublicKey<G2Element> = Hex::decode(&recipient_enc_pk)
+                .map_err(anyhow::Error::from)
+                .and_then(|bytes| Ok(bcs::from_bytes(&bytes)?))
+                .map_err(|e| CliError::Usage(anyhow!("Invalid --recipient-enc-pk: {e}")))?;
+            let (committee_id, party_id, share) = match share_file {
+                Some(path) => {
+                    let (party_id, share) = read_share_file(&path, ctx.permissions)?;
+                    (config.committee_id()?, party_id, share)
+                }
+                None => match state_key_type(config.state_dir())? {
+                    KeyType::G2 => {
+                        own_share(&DkgState::<G2Element>::load(config.state_dir(), &ctx)?)?
+                    }
+                    KeyType::G1 => {
+                        own_share(&DkgState::<G1Element>::load(config.state_dir(), &ctx)?)?
+                    }
+                },
+            };
+
+            let handoff =
+                ShareHandoff::encrypt(committee_id, party_id, &share, &recipient_enc_pk, &mut rng)?;
+            if let Some(parent) = output.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&output, serde_json::to_string_pretty(&handoff)?)?;
+            println!(
+                "Share of party {party_id} in committee {committee_id} encrypted to: {}. Only the recipient can decrypt it with `import-share`.",
+                output.display()
+            );
+        }
+
+        Commands::ImportShare {
+            input,
+            keys_file,
+            network,
+            output,
+        } => {
+            let config = EffectiveConfig::resolve(
+                &ctx,
+                Flags {
+                    network,
+                    keys_file,
+                    ..Default::default()
+                },
+            )?;
+            let network = config.network()?;
+            let rpc_timeout = config.rpc_timeout();
+            let local_keys = KeysFile::load(config.keys_file(), &ctx)?;
+            if output.exists() {
+                return Err(
+                    CliError::Usage(anyhow!("Output {} already exists", output.display())).into(),
+                );
+            }
+            let content = fs::read_to_string(&input).map_err(|e| {
+                CliError::Usage(anyhow!("Failed to read {}: {}", input.display(), e))
+            })?;
+            let handoff: ShareHandoff = serde_json::from_str(&content).map_err(|e| {
+                CliError::Usage(anyhow!("Failed to parse {}: {}", input.display(), e))
+            })?;
+
+            let grpc_client = create_grpc_client(&ctx, &network).map_err(CliError::Network)?;
+            let partial_pks = fetch_partial_pks::<G2Element>(
+                &ctx,
+                &grpc_client,
+                &handoff.committee_id,
+                rpc_timeout,
+            )
+            .await?;
+            let partial_pk = partial_pks.get(&handoff.party_id).ok_or_else(|| {
+                CliError::Validation(anyhow!(
+                    "No partial PK for party {} in committee {}",
+                    handoff.party_id,
+                    handoff.committee_id
+                ))
+            })?;
+            let share = handoff.decrypt(&local_keys, partial_pk)?;
+
+            if let Some(parent) = output.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            write_share_file(&ctx, &output, handoff.party_id, &share)?;
+            println!(
+                "Share of party {} in committee {} verified against the onchain partial PK and written to: {}",
+                handoff.party_id,
+                handoff.committee_id,
+                output.display()
+            );
+        }
+
+        Commands::Ping {
+            committee_id,
+            network,
+            timeout_secs,
+            json,
+        } => {
+            ctx.json = json;
+            let config = EffectiveConfig::resolve(
+                &ctx,
+                Flags {
+                    network,
+                    committee_id,
+                    ..Default::default()
+                },
+            )?;
+            let network = config.network()?;
+            let committee_id = config.committee_id()?;
+            let timeout = Duration::from_secs(timeout_secs);
+
+            let grpc_client = create_grpc_client(&ctx, &network).map_err(CliError::Network)?;
+            let (committee, latency) = ping_committee_node(&grpc_client, &committee_id, timeout)
+                .await
+                .map_err(CliError::Network)?;
+            status!(ctx, "Fullnode reachable, latency: {latency:?}");
+
+            let statuses = with_spinner(
+                format!("Checking {} member(s)", committee.members.len()),
+                GrpcHealthChecker::check_all(&grpc_client, &committee, timeout),
+            )
+            .await
+            .map_err(|e| CliError::Network(e.into()))?;
+            let mut members = Vec::with_capacity(committee.members.len());
+            for (party_id, member) in committee.members.iter().enumerate() {
+                let (latency_ms, error) = match &statuses[member] {
+                    HealthStatus::Healthy { latency_ms } => {
+                        status!(
+                            ctx,
+                            "Party {party_id} ({member}): reachable, latency: {latency_ms} ms"
+                        );
+                        (Some(*latency_ms), None)
+                    }
+                    HealthStatus::Unreachable { error } => {
+                        status!(ctx, "Party {party_id} ({member}): UNREACHABLE: {error}");
+                        (None, Some(error.clone()))
+                    }
+                };
+                members.push(MemberPing {
+                    party_id: party_id as u16,
+                    address: *member,
+                    latency_ms,
+                    error,
+                });
+            }
+            let unreachable = members.iter().filter(|m| m.error.is_some()).count();
+            ctx.print_json_result(&PingResult {
+                committee_id,
+                fullnode_latency_ms: latency.as_millis() as u64,
+                members,
+            })?;
+            if unreachable > 0 {
+                return Err(CliError::Network(anyhow!(
+                    "{unreachable} of {} member(s) unreachable",
+                    committee.members.len()
+                ))
+                .into());
+            }
+        }
+
+        Commands::CheckHealth {
+            committee_id,
+            network,
+            timeout_secs,
+        } => {
+            let config = EffectiveConfig::resolve(
+                &ctx,
+                Flags {
+                    network,
+                    committee_id,
+                    ..Default::default()
+                },
+            )?;
+            let network = config.network()?;
+            let committee_id = config.committee_id()?;
+            let timeout = Duration::from_secs(timeout_secs);
+
+            let grpc_client = create_grpc_client(&ctx, &network).map_err(CliError::Network)?;
+            let committee = with_rpc_timeout(
+                config.rpc_timeout(),
+                with_spinner(
+                    format!("Fetching committee {committee_id}"),
+                    fetch_committee_data(&grpc_client, &committee_id),
+                ),
+            )
+            .await
+            .map_err(CliError::Network)?;
+            let statuses = with_spinner(
+                format!("Checking {} member(s)", committee.members.len()),
+                GrpcHealthChecker::check_all(&grpc_client, &committee, timeout),
+            )
+            .await
+            .map_err(|e| CliError::Network(e.into()))?;
+
+            println!("{:<6} {:<66} {:<12} DETAILS", "PARTY", "ADDRESS", "STATUS");
+            let mut unreachable = 0;
+            for (party_id, member) in committee.members.iter().enumerate() {
+                let (status, details) = match &statuses[member] {
+                    HealthStatus::Healthy { latency_ms } => {
+                        ("HEALTHY", format!("latency: {latency_ms} ms"))
+                    }
+                    HealthStatus::Unreachable { error } => {
+                        unreachable += 1;
+                        ("UNREACHABLE", error.clone())
+                    }
+                };
+                println!(
+                    "{:<6} {:<66} {:<12} {}",
+                    party_id,
+                    member.to_string(),
+                    status,
+                    details
+                );
+            }
+            if unreachable > 0 {
+                return Err(CliError::Network(anyhow!(
+                    "{unreachable} of {} member(s) unreachable",
+                    committee.members.len()
+                ))
+                .into());
+            }
+            println!("All {} member(s) healthy.", committee.members.len());
+        }
+
+        Commands::Simulate {
+            parties,
+            threshold,
+            rotate,
+        } => {
+            simulate::simulate(parties, threshold, rotate, &mut rng)?;
+        }
+
+        Commands::Config {
+            command:
+                ConfigCommand::Show {
+                    network,
+                    committee_id,
+                    state_dir,
+                    keys_file,
+                },
+        } => {
+            let config = EffectiveConfig::resolve(
+                &ctx,
+                Flags {
+                    network,
+                    committee_id,
+                    keys_file,
+                    state_dir,
+                },
+            )?;
+            println!("{config}");
+        }
+
+        Commands::Completions { shell } => {
+            clap_complete::generate(
+                shell,
+                &mut Cli::command(),
+                "dkg-cli",
+                &mut std::io::stdout(),
+            );
+        }
+
+        Commands::Manpages { out_dir } => {
+            fs::create_dir_all(&out_dir)?;
+            clap_mangen::generate_to(Cli::command(), &out_dir)?;
+            println!("Man pages written to: {}", out_dir.display());
+        }
+
+        Commands::Schema { format } => {
+            println!("{}", serde_json::to_string_pretty(&format.schema())?);
+        }
+
+        Commands::RpcServe { stdio: _ } => {
+            ctx.json = true;
+            let config = EffectiveConfig::resolve(&ctx, Flags::default())?;
+            let mut server = RpcServer::new(ctx, config.rpc_timeout(), rng);
+            server
+                .serve(std::io::stdin().lock(), std::io::stdout())
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Result of `ping`, printed with `--json`.
+#[derive(Serialize)]
+struct PingResult {
+    committee_id: Address,
+    fullnode_latency_ms: u64,
+    members: Vec<MemberPing>,
+}
+
+/// Latency of a member's node, or the error if it is unreachable.
+#[derive(Serialize)]
+struct MemberPing {
+    party_id: u16,
+    address: Address,
+    latency_ms: Option<u64>,
+    error: Option<String>,
+}
+
+/// Progress of the local state, printed by `ceremony-status`.
+#[derive(Serialize)]
+struct CeremonyStatus {
+    #[serde(flatten)]
+    progress: ProgressSummary,
+    percent_complete: u8,
+    missing_message_senders: Vec<u16>,
+}
+
+fn ceremony_status<G: KeyGroup>(state: &DkgState<G>) -> CeremonyStatus {
+    let progress = state.progress_summary();
+    CeremonyStatus {
+        percent_complete: progress.percent_complete(),
+        progress,
+        missing_message_senders: state.missing_message_senders(),
+    }
+}
+
+/// Parse an aggregate public key from onchain bytes.
+fn parse_pk<G: KeyGroup>(bytes: &[u8]) -> Result<G> {
+    bcs::from_bytes(bytes).map_err(|e| {
+        CliError::Validation(anyhow!("Failed to deserialize aggregate PK: {e}")).into()
+    })
+}
+
+/// Fetch the key server of a committee, at the version given by `--key-server-version` or else
+/// at the latest version onchain.
+async fn fetch_committee_key_server(
+    ctx: &Context,
+    fetcher: &dyn CommitteeFetcher,
+    committee_id: &Address,
+) -> Result<(Address, KeyServerV2), SealCommitteeError> {
+    fetcher
+        .fetch_key_server(committee_id, ctx.key_server_version)
+        .await
+}
+
+/// Fetch a committee, at `version` if given and otherwise at its latest version. The version and
+/// digest read are printed for the record. A missing version is a usage error.
+async fn fetch_committee_at_version(
+    ctx: &Context,
+    fetcher: &dyn CommitteeFetcher,
+    committee_id: &Address,
+    version: Option<u64>,
+    rpc_timeout: Option<Duration>,
+) -> Result<SealCommittee> {
+    let Some(version) = version else {
+        return with_rpc_timeout(
+            rpc_timeout,
+            with_spinner(
+                format!("Fetching committee {committee_id}"),
+                fetch_committee_data(fetcher, committee_id),
+            ),
+        )
+        .await
+        .map_err(|e| CliError::Network(e).into());
+    };
+    let committee = with_rpc_timeout(
+        rpc_timeout,
+        with_spinner(
+            format!("Fetching committee {committee_id} at version {version}"),
+            fetch_committee_data_at_version(fetcher, committee_id, version),
+        ),
+    )
+    .await
+    .map_err(|e| match e.downcast_ref::<SealCommitteeError>() {
+        Some(SealCommitteeError::VersionNotFound { .. }) => CliError::Usage(e),
+        _ => CliError::Network(e),
+    })?;
+    status!(
+        ctx,
+        "Read committee at version {}, digest {}.",
+        committee.version,
+        committee.digest
+    );
+    Ok(committee.value)
+}
+
+/// Fetch the partial public keys by party ID from a committee's key server, in the key group `G`
+/// of the committee.
+async fn fetch_partial_pks<G: KeyGroup>(
+    ctx: &Context,
+    fetcher: &dyn CommitteeFetcher,
+    committee_id: &Address,
+    rpc_timeout: Option<Duration>,
+) -> Result<HashMap<u16, G>> {
+    let (_, key_server) = with_rpc_timeout(
+        rpc_timeout,
+        with_spinner(
+            format!("Fetching partial key servers of committee {committee_id}"),
+            fetch_committee_key_server(ctx, fetcher, committee_id),
+        ),
+    )
+    .await
+    .map_err(CliError::Network)?;
+    let ServerType::Committee {
+        partial_key_servers,
+        ..
+    } = key_server.server_type
+    else {
+        return Err(CliError::Validation(anyhow!(
+            "Key server of committee {committee_id} is not of type Committee"
+        ))
+        .into());
+    };
+    partial_key_servers
+        .0
+        .contents
+        .iter()
+        .map(|entry| {
+            let party_id = entry.value.party_id;
+            let partial_pk = bcs::from_bytes(&entry.value.partial_pk).map_err(|e| {
+                CliError::Validation(anyhow!(
+                    "Failed to deserialize partial PK of party {party_id}: {e}"
+                ))
+            })?;
+            Ok((party_id, partial_pk))
+        })
+        .collect()
+}
+
+/// Party ID, address and signature status of a message file in the key group `G` for
+/// `list-messages`. The sender of a message with a valid signature is added to `submitted`.
+fn message_file_status<G: KeyGroup>(
+    path: &Path,
+    members_by_party_id: &HashMap<u16, &ParsedMemberInfo>,
+    submitted: &mut HashSet<u16>,
+) -> (String, String, String) {
+    let signed_message = match read_message_file::<G>(path) {
+        Ok(signed_message) => signed_message,
+        Err(e) => return ("-".to_string(), "-".to_string(), format!("UNREADABLE: {e}")),
+    };
+    let sender = signed_message.message.sender;
+    let Some(member) = members_by_party_id.get(&sender) else {
+        return (
+            sender.to_string(),
+            "-".to_string(),
+            "UNKNOWN PARTY".to_string(),
+        );
+    };
+    let status = match verify_signature(&signed_message, &member.signing_pk) {
+        Ok(()) => {
+            submitted.insert(sender);
+            "valid".to_string()
+        }
+        Err(e) => format!("INVALID: {e}"),
+    };
+    (sender.to_string(), member.address.to_string(), status)
+}
+
+/// Committee ID, party ID and key share of a completed DKG state, for `export-share-to`.
+fn own_share<G: KeyGroup>(state: &DkgState<G>) -> Result<(Address, u16, G2Scalar)> {
+    let share = state
+        .output
+        .as_ref()
+        .and_then(|output| output.shares.as_ref())
+        .and_then(|shares| shares.first())
+        .ok_or_else(|| {
+            CliError::StateConflict(anyhow!(
+                "No key share in the DKG state. Run process-all first."
+            ))
+        })?
+        .value;
+    Ok((state.config.committee_id, state.config.my_party_id, share))
+}
+
+/// Load the DKG state in `state_dir` and check its output against the finalized committee onchain,
+/// see [verify_output_onchain].
+async fn load_verified_state<G: KeyGroup>(
+    ctx: &Context,
+    state_dir: &Path,
+    network: &Network,
+    rpc_timeout: Option<Duration>,
+) -> Result<DkgState<G>> {
+    let state = DkgState::<G>::load(state_dir, ctx)?;
+    verify_output_onchain(ctx, &state, network, rpc_timeout).await?;
+    Ok(state)
+}
+
+/// Check that the committee of the state is finalized onchain, and that its key server has the
+/// key server PK and my partial PK from the local DKG output, both in the group of the state.
+async fn verify_output_onchain<G: KeyGroup>(
+    ctx: &Context,
+    state: &DkgState<G>,
+    network: &Network,
+    rpc_timeout: Option<Duration>,
+) -> Result<()> {
+    let committee_id = state.config.committee_id;
+    let output = state.output.as_ref().ok_or_else(|| {
+        CliError::StateConflict(anyhow!("DKG is not completed. Run process-all first."))
+    })?;
+    let my_partial_pk = output
+        .shares
+        .as_ref()
+        .and_then(|shares| shares.first())
+        .map(|share| output.vss_pk.eval(share.index).value)
+        .ok_or_else(|| {
+            CliError::StateConflict(anyhow!(
+                "No key share in DKG output for party {}",
+                state.config.my_party_id
+            ))
+        })?;
+
+    let grpc_client = create_grpc_client(ctx, network).map_err(CliError::Network)?;
+    let committee = with_rpc_timeout(
+        rpc_timeout,
+        with_spinner(
+            format!("Fetching committee {committee_id}"),
+            fetch_committee_data(&grpc_client, &committee_id),
+        ),
+    )
+    .await
+    .map_err(CliError::Network)?;
+    committee
+        .is_finalized()
+        .map_err(|e| CliError::StateConflict(e.into()))?;
+
+    let (_, key_server) = with_rpc_timeout(
+        rpc_timeout,
+        with_spinner(
+            format!("Fetching key server of committee {committee_id}"),
+            fetch_committee_key_server(ctx, &grpc_client, &committee_id),
+        ),
+    )
+    .await
+    .map_err(CliError::Network)?;
+    if parse_pk::<G>(&key_server.pk)? != *output.vss_pk.c0() {
+        return Err(CliError::Verification(anyhow!(
+            "Key server PK onchain does not match the local DKG output"
+        ))
+        .into());
+    }
+    let ServerType::Committee {
+        partial_key_servers,
+        ..
+    } = &key_server.server_type
+    else {
+        return Err(CliError::Verification(anyhow!(
+            "Key server of committee {committee_id} is not of type Committee"
+        ))
+        .into());
+    };
+    let onchain_partial_pk = partial_key_servers
+        .0
+        .contents
+        .iter()
+        .find(|entry| entry.value.party_id == state.config.my_party_id)
+        .map(|entry| {
+            bcs::from_bytes::<G>(&entry.value.partial_pk)
+                .map_err(|e| CliError::Validation(anyhow!("Failed to deserialize partial PK: {e}")))
+        })
+        .transpose()?;
+    if onchain_partial_pk != Some(my_partial_pk) {
+        return Err(CliError::Verification(anyhow!(
+            "Partial PK of party {} onchain does not match the local DKG output",
+            state.config.my_party_id
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// Ask for confirmation on the terminal, the user must type `expected`.
+fn confirm(prompt: &str, expected: &str) -> Result<()> {
+    if !std::io::stdin().is_terminal() {
+        return Err(CliError::Usage(anyhow!(
+            "Confirmation required. Run in a terminal or pass --yes --i-have-installed-my-share."
+        ))
+        .into());
+    }
+    print!("{prompt}: ");
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    if answer.trim() != expected {
+        return Err(CliError::Usage(anyhow!("Aborted, nothing was deleted.")).into());
+    }
+    Ok(())
+}
+
+/// Read random keystrokes from the terminal for `generate-keys --interactive-entropy`. The arrival
+/// time of each line is mixed in as well.
+fn read_interactive_entropy() -> Result<Vec<u8>> {
+    if !std::io::stdin().is_terminal() {
+        return Err(CliError::Usage(anyhow!(
+            "--interactive-entropy requires a terminal, use --extra-entropy-file instead."
+        ))
+        .into());
+    }
+    println!(
+        "Mash the keyboard and press Enter, repeat until at least {MIN_INTERACTIVE_ENTROPY_CHARS} characters are typed."
+    );
+    let mut entropy = Vec::new();
+    let mut chars = 0;
+    while chars < MIN_INTERACTIVE_ENTROPY_CHARS {
+        let mut line = String::new();
+        if std::io::stdin().read_line(&mut line)? == 0 {
+            return Err(CliError::Usage(anyhow!("Aborted, no keys were written.")).into());
+        }
+        let line = line.trim_end();
+        chars += line.chars().count();
+        entropy.extend_from_slice(line.as_bytes());
+        entropy.extend_from_slice(&Utc::now().timestamp_nanos_opt().unwrap_or(0).to_le_bytes());
+        if chars < MIN_INTERACTIVE_ENTROPY_CHARS {
+            println!("{chars}/{MIN_INTERACTIVE_ENTROPY_CHARS} characters, keep going.");
+        }
+    }
+    Ok(entropy)
+}
+
+/// Overwrite a file with zeros and flush it to disk before deleting it.
+fn secure_delete(path: &Path) -> Result<()> {
+    let len = fs::metadata(path)?.len();
+    let mut file = fs::OpenOptions::new().write(true).open(path)?;
+    file.write_all(&vec![0u8; len as usize])?;
+    file.sync_all()?;
+    drop(file);
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+/// Helper function to write a file with restricted permissions (owner only) in Unix systems. An
+/// exclusive lock is held while writing, see [lock]. The file is replaced atomically, so it is
+/// never left partially written, see [write_file_atomic].
+fn write_secret_file(ctx: &Context, path: &Path, content: impl AsRef<[u8]>) -> Result<()> {
+    let _lock = lock::lock_exclusive(path, ctx)?;
+    write_file_atomic(path, |file| Ok(file.write_all(content.as_ref())?))
+}
+
+/// Write `<path>.tmp` with `write` and rename it to `path`. The temporary file is owner only on
+/// Unix from its creation, and is removed if `write` fails. Rename is atomic on Unix, and on
+/// Windows replaces the existing file on a best-effort basis.
+fn write_file_atomic(path: &Path, write: impl FnOnce(&mut fs::File) -> Result<()>) -> Result<()> {
+    let mut tmp_path = path.as_os_str().to_os_string();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options.open(&tmp_path)?;
+    #[cfg(unix)]
+    {
+        // The mode is only applied to new files, not to a leftover temporary file.
+        let mut perms = file.metadata()?.permissions();
+        perms.set_mode(0o600);
+        file.set_permissions(perms)?;
+    }
+
+    let result = write(&mut file).and_then(|()| Ok(file.sync_all()?));
+    drop(file);
+    if let Err(e) = result {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e);
+    }
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// RNG used for all randomness in a command. Seeded deterministically only if `--seed` is given,
+/// which requires the `test-determinism` feature.
+fn new_rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(seed) => {
+            eprintln!("WARNING: Using deterministic RNG with seed {seed}. For testing only!");
+            StdRng::seed_from_u64(seed)
+        }
+        None => StdRng::from_entropy(),
+    }
+}
+
+/// Apply the configured RPC timeout, if any, to a network request.
+async fn with_rpc_timeout<T, E: Into<anyhow::Error>>(
+    timeout: Option<Duration>,
+    request: impl Future<Output = Result<T, E>>,
+) -> Result<T> {
+    match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, request)
+            .await
+            .map_err(|_| anyhow!("RPC request timed out after {timeout:?}"))?
+            .map_err(Into::into),
+        None => request.await.map_err(Into::into),
+    }
+}
+
+/// Fetch a committee. If the fullnode has not indexed it yet, e.g. right after the committee was
+/// created, wait up to `index_timeout` for it.
+async fn fetch_committee_when_indexed(
+    ctx: &Context,
+    grpc_client: &Client,
+    committee_id: &Address,
+    rpc_timeout: Option<Duration>,
+    index_timeout: Duration,
+) -> Result<SealCommittee> {
+    let fetched = with_rpc_timeout(
+        rpc_timeout,
+        with_spinner(
+            format!("Fetching committee {committee_id}"),
+            fetch_committee_data(grpc_client, committee_id),
+        ),
+    )
+    .await;
+    match fetched {
+        Err(e)
+            if e.downcast_ref::<SealCommitteeError>()
+                .is_some_and(SealCommitteeError::is_not_indexed) =>
+        {
+            status!(
+                ctx,
+                "Waiting for fullnode to index object {committee_id}..."
+            );
+            Ok(with_spinner(
+                format!("Waiting for committee {committee_id}"),
+                wait_for_object::<SealCommittee, _>(
+                    grpc_client,
+                    committee_id,
+                    index_timeout,
+                    INDEX_POLL_INTERVAL,
+                ),
+            )
+            .await?)
+        }
+        fetched => fetched,
+    }
+}
+
+/// gRPC client for the given network with the settings of `--rpc-auth-header` and
+/// `--rpc-ca-cert`.
+fn create_grpc_client(ctx: &Context, network: &Network) -> Result<Client> {
+    create_grpc_client_with_config(&network.node_url(), &ctx.grpc_config)
+}
+
+/// gRPC client for the configured network, created on first use so that commands running
+/// offline from snapshots do not require `--network`.
+fn lazy_grpc_client<'a>(
+    ctx: &Context,
+    client: &'a mut Option<Client>,
+    config: &EffectiveConfig,
+) -> Result<&'a Client> {
+    if client.is_none() {
+        *client = Some(create_grpc_client(ctx, &config.network()?).map_err(CliError::Network)?);
+    }
+    Ok(client.as_ref().expect("must be set"))
+}
+
+/// Value parser for `--network`. Parses with [Network::from_str], so `custom:<url>` is accepted,
+/// and lists the named networks for help and shell completion.
+#[derive(Clone)]
+struct NetworkValueParser;
+
+impl TypedValueParser for NetworkValueParser {
+    type Value = Network;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> Result<Network, clap::Error> {
+        let value = StringValueParser::new().parse_ref(cmd, arg, value)?;
+        Network::from_str(&value).map_err(|e| {
+            clap::Error::raw(clap::error::ErrorKind::InvalidValue, format!("{e}\n")).with_cmd(cmd)
+        })
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        Some(Box::new(
+            ["mainnet", "testnet", "devnet", "localnet"]
+                .into_iter()
+                .map(PossibleValue::new),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ceremony::{check_state_matches_committee, process_messages, save_message_file};
+    use dkg_core::types::{sign_message, InitializedConfig, SignedMessage};
+    use dkg_core::DkgPhase;
+    use fastcrypto::groups::GroupElement;
+    use fastcrypto_tbls::dkg_v1::Party;
+    use fastcrypto_tbls::nodes::{Node, Nodes};
+    use fastcrypto_tbls::random_oracle::RandomOracle;
+    use std::num::NonZeroU16;
+    use tempfile::TempDir;
+
+    /// Create the local states of all parties for a fresh DKG with the given keys, as
+    /// `create-message` would, along with their signed messages.
+    fn fresh_dkg_states<G: KeyGroup>(
+        keys: &[KeysFile],
+        rng: &mut StdRng,
+    ) -> (Vec<DkgState<G>>, Vec<SignedMessage<G>>) {
+        let committee_id = Address::ZERO;
+        let threshold = keys.len() as u16;
+        let nodes = Nodes::new(
+            keys.iter()
+                .enumerate()
+                .map(|(party_id, k)| Node {
+                    id: party_id as u16,
+                    pk: k.enc_pk.clone(),
+                    weight: 1,
+                })
+                .collect(),
+        )
+        .unwrap();
+        let signing_pks: HashMap<u16, _> = keys
+            .iter()
+            .enumerate()
+            .map(|(party_id, k)| (party_id as u16, k.signing_pk.clone()))
+            .collect();
+
+        keys.iter()
+            .enumerate()
+            .map(|(party_id, k)| {
+                let party = Party::<G, G2Element>::new_advanced(
+                    k.enc_sk.clone(),
+                    nodes.clone(),
+                    threshold,
+                    RandomOracle::new(&committee_id.to_string()),
+                    None,
+                    None,
+                    rng,
+                )
+                .unwrap();
+                let message = party.create_message(rng).unwrap();
+                let config = InitializedConfig {
+                    config_version: InitializedConfig::CURRENT_VERSION,
+                    my_party_id: party_id as u16,
+                    nodes: nodes.clone(),
+                    committee_id,
+                    threshold,
+                    signing_pks: signing_pks.clone(),
+                    old_threshold: None,
+                    new_to_old_mapping: None,
+                    expected_old_pks: None,
+                    my_old_share: None,
+                    my_old_pk: None,
+                    old_committee_digest: None,
+                };
+                let state = DkgState::new(config, Some(message.clone()));
+                (state, sign_message(message, &k.signing_sk))
+            })
+            .unzip()
+    }
+
+    /// Generate keys for `num_parties` parties for a fresh DKG, save keys and local state of
+    /// party 0 in `dir` and return the signed messages of all parties.
+    fn setup_fresh_dkg(dir: &Path, num_parties: u16) -> Vec<SignedMessage> {
+        setup_fresh_dkg_of_key_group(dir, num_parties)
+    }
+
+    /// As [setup_fresh_dkg], with the master key in the group `G`.
+    fn setup_fresh_dkg_of_key_group<G: KeyGroup>(
+        dir: &Path,
+        num_parties: u16,
+    ) -> Vec<SignedMessage<G>> {
+        let mut rng = StdRng::from_entropy();
+        let keys: Vec<KeysFile> = (0..num_parties)
+            .map(|_| KeysFile::generate(&mut rng))
+            .collect();
+        let (states, messages) = fresh_dkg_states(&keys, &mut rng);
+        states[0]
+            .save(&dir.join("state"), &Context::for_testing())
+            .unwrap();
+        write_secret_file(
+            &Context::for_testing(),
+            &dir.join("dkg.key"),
+            &serde_json::to_string_pretty(&keys[0]).unwrap(),
+        )
+        .unwrap();
+        messages
+    }
+
+    fn write_message_file<G: KeyGroup>(messages_dir: &Path, message: &SignedMessage<G>) {
+        fs::create_dir_all(messages_dir).unwrap();
+        let json = serde_json::json!({
+            "message": message.to_base64().unwrap()
+        });
+        fs::write(
+            messages_dir.join(format!("message_{}.json", message.message.sender)),
+            json.to_string(),
+        )
+        .unwrap();
+    }
+
+    async fn process_all_exit_code(dir: &Path) -> ExitCode {
+        let cli = Cli::try_parse_from([
+            "dkg-cli",
+            "process-all",
+            "--messages-dir",
+            dir.join("messages").to_str().unwrap(),
+            "--state-dir",
+            dir.join("state").to_str().unwrap(),
+            "--keys-file",
+            dir.join("dkg.key").to_str().unwrap(),
+        ])
+        .unwrap();
+        ExitCode::from_error(&run(cli, Arc::default()).await.unwrap_err())
+    }
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_generate_completions() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::PowerShell] {
+            let mut buf = Vec::new();
+            clap_complete::generate(shell, &mut Cli::command(), "dkg-cli", &mut buf);
+            let script = String::from_utf8(buf).unwrap();
+            assert!(script.contains("create-message"), "{shell}");
+            // PowerShell completions do not include argument values.
+            if shell != Shell::PowerShell {
+                assert!(script.contains("mainnet"), "{shell}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_generate_manpages() {
+        let dir = TempDir::new().unwrap();
+        clap_mangen::generate_to(Cli::command(), dir.path()).unwrap();
+        for page in [
+            "dkg-cli.1",
+            "dkg-cli-create-message.1",
+            "dkg-cli-process-all.1",
+        ] {
+            let content = fs::read_to_string(dir.path().join(page)).unwrap();
+            assert!(content.contains(".TH"), "{page}");
+        }
+    }
+
+    async fn run_args(args: &[&str]) -> Result<()> {
+        let cli =
+            Cli::try_parse_from(std::iter::once("dkg-cli").chain(args.iter().copied())).unwrap();
+        run(cli, Arc::default()).await
+    }
+
+    #[tokio::test]
+    async fn test_migrate_keys() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("dkg.key");
+        let keys = KeysFile::generate(&mut StdRng::from_entropy());
+        write_secret_file(
+            &Context::for_testing(),
+            &path,
+            serde_json::to_string(&keys).unwrap(),
+        )
+        .unwrap();
+        let args = [
+            "migrate-keys",
+            "--keys-file",
+            path.to_str().unwrap(),
+            "--from-version",
+            "1",
+        ];
+
+        run_args(&args).await.unwrap();
+        let migrated = KeysFile::load(&path, &Context::for_testing()).unwrap();
+        assert_eq!(migrated.version, LATEST_KEYS_FILE_VERSION);
+        assert_eq!(migrated.enc_pk, keys.enc_pk);
+
+        // The keys file is no longer at version 1.
+        let err = run_args(&args).await.unwrap_err();
+        assert_eq!(ExitCode::from_error(&err), ExitCode::Validation);
+    }
+
+    #[tokio::test]
+    async fn test_export_key_server_config() {
+        let dir = TempDir::new().unwrap();
+        let messages = setup_fresh_dkg(dir.path(), 2);
+        for message in &messages {
+            write_message_file(&dir.path().join("messages"), message);
+        }
+        let state_dir = dir.path().join("state");
+        let output = dir.path().join("key-server-config.json");
+
+        // Export fails before the DKG is completed.
+        let export_args = [
+            "export-key-server-config",
+            "--state-dir",
+            state_dir.to_str().unwrap(),
+            "--output",
+            output.to_str().unwrap(),
+        ];
+        let err = run_args(&export_args).await.unwrap_err();
+        assert_eq!(ExitCode::from_error(&err), ExitCode::StateConflict);
+
+        run_args(&[
+            "process-all",
+            "--messages-dir",
+            dir.path().join("messages").to_str().unwrap(),
+            "--state-dir",
+            state_dir.to_str().unwrap(),
+            "--keys-file",
+            dir.path().join("dkg.key").to_str().unwrap(),
+        ])
+        .await
+        .unwrap();
+        run_args(&export_args).await.unwrap();
+
+        let config: dkg_core::types::KeyServerConfig =
+            serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        let state = DkgState::<G2Element>::load(&state_dir, &Context::for_testing()).unwrap();
+        let vss_pk = &state.output.as_ref().unwrap().vss_pk;
+        assert_eq!(config.committee_id, Address::ZERO.to_string());
+        assert_eq!(config.party_id, 0);
+        assert_eq!(config.threshold, 2);
+        assert_eq!(
+            config.key_server_pk_hex,
+            format_pk_hex(vss_pk.c0()).unwrap()
+        );
+        assert_eq!(
+            config.partial_pk_hex,
+            format_pk_hex(&vss_pk.eval(NonZeroU16::new(1).unwrap()).value).unwrap()
+        );
+        assert_eq!(config.vss_coefficients.len(), 2);
+    }
+
+    /// Check that `value` validates against the schema of `format`.
+    fn assert_matches_schema(format: SchemaFormat, value: &serde_json::Value) {
+        let schema = serde_json::to_value(format.schema()).unwrap();
+        let validator = jsonschema::JSONSchema::compile(&schema).unwrap();
+        if let Err(errors) = validator.validate(value) {
+            let errors: Vec<_> = errors
+                .map(|e| format!("{} at {}", e, e.instance_path))
+                .collect();
+            panic!("{format:?} does not match its schema: {errors:?}");
+        }
+    }
+
+    fn read_json(path: &Path) -> serde_json::Value {
+        serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_written_files_match_schemas() {
+        let dir = TempDir::new().unwrap();
+        let messages = setup_fresh_dkg(dir.path(), 3);
+        let messages_dir = dir.path().join("messages");
+        fs::create_dir_all(&messages_dir).unwrap();
+        for message in &messages {
+            let path = save_message_file(&messages_dir, message).unwrap();
+            assert_matches_schema(SchemaFormat::Message, &read_json(&path));
+        }
+        let state_dir = dir.path().join("state");
+        assert_matches_schema(
+            SchemaFormat::State,
+            &read_json(&state_dir.join("state.json")),
+        );
+
+        run_args(&[
+            "process-all",
+            "--messages-dir",
+            messages_dir.to_str().unwrap(),
+            "--state-dir",
+            state_dir.to_str().unwrap(),
+            "--keys-file",
+            dir.path().join("dkg.key").to_str().unwrap(),
+        ])
+        .await
+        .unwrap();
+        assert_matches_schema(
+            SchemaFormat::State,
+            &read_json(&state_dir.join("state.json")),
+        );
+
+        let output = dir.path().join("key-server-config.json");
+        run_args(&[
+            "export-key-server-config",
+            "--state-dir",
+            state_dir.to_str().unwrap(),
+            "--output",
+            output.to_str().unwrap(),
+        ])
+        .await
+        .unwrap();
+        assert_matches_schema(SchemaFormat::KeyServerConfig, &read_json(&output));
+
+        // As written by `prune`, which needs the committee onchain.
+        let state = DkgState::<G2Element>::load(&state_dir, &Context::for_testing()).unwrap();
+        assert_matches_schema(
+            SchemaFormat::Transcript,
+            &serde_json::to_value(state.transcript()).unwrap(),
+        );
+
+        // A message file without the message or with a non-base64 message is rejected.
+        let schema = serde_json::to_value(SchemaFormat::Message.schema()).unwrap();
+        let validator = jsonschema::JSONSchema::compile(&schema).unwrap();
+        assert!(!validator.is_valid(&serde_json::json!({})));
+        assert!(!validator.is_valid(&serde_json::json!({ "message": "not base64!" })));
+    }
+
+    #[test]
+    fn test_schema_fields() {
+        // Changing the fields of a file format must update this list.
+        let required = |format: SchemaFormat| {
+            let schema = serde_json::to_value(format.schema()).unwrap();
+            let mut required: Vec<String> =
+                serde_json::from_value(schema["required"].clone()).unwrap();
+            required.sort();
+            required
+        };
+        assert_eq!(required(SchemaFormat::Message), ["message"]);
+        assert_eq!(
+            required(SchemaFormat::State),
+            [
+                "config",
+                "confirmation",
+                "my_message",
+                "output",
+                "processed_messages",
+                "received_messages"
+            ]
+        );
+        assert_eq!(
+            required(SchemaFormat::KeyServerConfig),
+            [
+                "committee_id",
+                "key_server_pk_hex",
+                "partial_key_share_hex",
+                "partial_pk_hex",
+                "party_id",
+                "threshold",
+                "vss_coefficients"
+            ]
+        );
+
+        // The state schema is for key type 0.
+        let schema = serde_json::to_value(SchemaFormat::State.schema()).unwrap();
+        assert_eq!(schema["properties"]["key_type"]["const"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_process_all_write_messages_only() {
+        let dir = TempDir::new().unwrap();
+        let messages = setup_fresh_dkg(dir.path(), 3);
+        let messages_dir = dir.path().join("messages");
+        let state_dir = dir.path().join("state");
+        let keys_file = dir.path().join("dkg.key");
+        let args = [
+            "process-all",
+            "--messages-dir",
+            messages_dir.to_str().unwrap(),
+            "--state-dir",
+            state_dir.to_str().unwrap(),
+            "--keys-file",
+            keys_file.to_str().unwrap(),
+        ];
+        let load = || DkgState::<G2Element>::load(&state_dir, &Context::for_testing()).unwrap();
+
+        // Party 2 is offline.
+        write_message_file(&messages_dir, &messages[0]);
+        write_message_file(&messages_dir, &messages[1]);
+        run_args(&[&args[..], &["--write-messages-only"]].concat())
+            .await
+            .unwrap();
+        let state = load();
+        assert_eq!(state.phase(), DkgPhase::MessagesProcessed);
+        assert_eq!(state.processed_messages.len(), 2);
+        assert!(!state.has_sufficient_processed_messages());
+
+        write_message_file(&messages_dir, &messages[2]);
+        run_args(&args).await.unwrap();
+        assert_eq!(load().phase(), DkgPhase::Completed);
+    }
+
+    #[test]
+    fn test_state_load_shared_lock() {
+        let dir = TempDir::new().unwrap();
+        setup_fresh_dkg(dir.path(), 3);
+        let state_dir = dir.path().join("state");
+        let ctx = Context::for_testing();
+
+        // Loading fails while the state is being written, unless locking is disabled.
+        let writer = lock::lock_exclusive(&state_file(&state_dir).unwrap(), &ctx).unwrap();
+        let err = DkgState::<G2Element>::load(&state_dir, &ctx).unwrap_err();
+        assert_eq!(ExitCode::from_error(&err), ExitCode::StateConflict);
+        let no_lock = Context {
+            no_lock: true,
+            ..Context::for_testing()
+        };
+        DkgState::<G2Element>::load(&state_dir, &no_lock).unwrap();
+        drop(writer);
+        DkgState::<G2Element>::load(&state_dir, &ctx).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_state_bcs_format() {
+        let dir = TempDir::new().unwrap();
+        let messages = setup_fresh_dkg(dir.path(), 3);
+        for message in &messages {
+            write_message_file(&dir.path().join("messages"), message);
+        }
+        let state_dir = dir.path().join("state");
+        let json_file = state_dir.join("state.json");
+        let bcs_file = state_dir.join("state.bcs");
+
+        let json_ctx = Context::for_testing();
+        let bcs_ctx = Context {
+            state_format: StateFormat::Bcs,
+            ..Context::for_testing()
+        };
+
+        // Saving in BCS replaces the JSON state, and commands load either format.
+        let state = DkgState::<G2Element>::load(&state_dir, &json_ctx).unwrap();
+        state.save(&state_dir, &bcs_ctx).unwrap();
+        assert!(!json_file.exists());
+        assert_eq!(state_file(&state_dir).unwrap(), bcs_file);
+        assert_eq!(state_key_type(&state_dir).unwrap(), KeyType::G2);
+        run_args(&[
+            "process-all",
+            "--messages-dir",
+            dir.path().join("messages").to_str().unwrap(),
+            "--state-dir",
+            state_dir.to_str().unwrap(),
+            "--keys-file",
+            dir.path().join("dkg.key").to_str().unwrap(),
+        ])
+        .await
+        .unwrap();
+        let state = DkgState::<G2Element>::load(&state_dir, &json_ctx).unwrap();
+        assert_eq!(state.phase(), DkgPhase::Completed);
+
+        state.save(&state_dir, &bcs_ctx).unwrap();
+        let bcs_len = fs::metadata(&bcs_file).unwrap().len();
+        state.save(&state_dir, &json_ctx).unwrap();
+        assert!(!bcs_file.exists());
+        assert!(bcs_len < fs::metadata(&json_file).unwrap().len());
+
+        // Two states in one directory are ambiguous.
+        fs::write(&bcs_file, state.to_bcs().unwrap()).unwrap();
+        assert!(DkgState::<G2Element>::load(&state_dir, &Context::default()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_merge_states() {
+        let dir = TempDir::new().unwrap();
+        let messages = setup_fresh_dkg(dir.path(), 3);
+        for message in &messages {
+            write_message_file(&dir.path().join("messages"), message);
+        }
+        let state_dir = dir.path().join("state");
+        let keys = KeysFile::load(&dir.path().join("dkg.key"), &Context::for_testing()).unwrap();
+
+        // Two coordinators each processed an overlapping subset of the messages.
+        let sub_dirs = [dir.path().join("sub_0"), dir.path().join("sub_1")];
+        for (sub_dir, subset) in sub_dirs.iter().zip([&messages[..2], &messages[1..]]) {
+            let mut state =
+                DkgState::<G2Element>::load(&state_dir, &Context::for_testing()).unwrap();
+            let party = Party::<G2Element, G2Element>::new_advanced(
+                keys.enc_sk.clone(),
+                state.config.nodes.clone(),
+                state.config.threshold,
+                RandomOracle::new(&state.config.committee_id.to_string()),
+                None,
+                None,
+                &mut StdRng::from_entropy(),
+            )
+            .unwrap();
+            for signed_msg in subset {
+                let processed = party
+                    .process_message(signed_msg.message.clone(), &mut StdRng::from_entropy())
+                    .unwrap();
+                state.processed_messages.push(processed);
+            }
+            state.save(sub_dir, &Context::for_testing()).unwrap();
+        }
+
+        run_args(&[
+            "merge-states",
+            "--state-dir",
+            state_dir.to_str().unwrap(),
+            "--from",
+            sub_dirs[0].to_str().unwrap(),
+            sub_dirs[1].to_str().unwrap(),
+        ])
+        .await
+        .unwrap();
+        assert_eq!(
+            DkgState::<G2Element>::load(&state_dir, &Context::for_testing())
+                .unwrap()
+                .processed_messages
+                .len(),
+            3
+        );
+
+        // All messages are already processed, process-all only merges and completes.
+        run_args(&[
+            "process-all",
+            "--messages-dir",
+            dir.path().join("messages").to_str().unwrap(),
+            "--state-dir",
+            state_dir.to_str().unwrap(),
+            "--keys-file",
+            dir.path().join("dkg.key").to_str().unwrap(),
+        ])
+        .await
+        .unwrap();
+        assert!(
+            DkgState::<G2Element>::load(&state_dir, &Context::for_testing())
+                .unwrap()
+                .output
+                .is_some()
+        );
+
+        // States of another committee cannot be merged.
+        let mut other = DkgState::<G2Element>::load(&sub_dirs[0], &Context::for_testing()).unwrap();
+        other.config.committee_id = Address::TWO;
+        let err = DkgState::<G2Element>::load(&state_dir, &Context::for_testing())
+            .unwrap()
+            .merge_with(&other)
+            .unwrap_err();
+        assert!(err.to_string().contains("different committees"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_export_share_to() {
+        let dir = TempDir::new().unwrap();
+        let messages = setup_fresh_dkg(dir.path(), 2);
+        for message in &messages {
+            write_message_file(&dir.path().join("messages"), message);
+        }
+        let state_dir = dir.path().join("state");
+        let output = dir.path().join("handoff.json");
+        let recipient = KeysFile::generate(&mut StdRng::from_entropy());
+        let export_args = [
+            "export-share-to",
+            "--recipient-enc-pk",
+            &format_pk_hex(&recipient.enc_pk).unwrap(),
+            "--state-dir",
+            state_dir.to_str().unwrap(),
+            "--output",
+            output.to_str().unwrap(),
+        ]
+        .map(String::from);
+        let export_args: Vec<&str> = export_args.iter().map(String::as_str).collect();
+
+        // Export fails before the DKG is completed.
+        let err = run_args(&export_args).await.unwrap_err();
+        assert_eq!(ExitCode::from_error(&err), ExitCode::StateConflict);
+
+        run_args(&[
+            "process-all",
+            "--messages-dir",
+            dir.path().join("messages").to_str().unwrap(),
+            "--state-dir",
+            state_dir.to_str().unwrap(),
+            "--keys-file",
+            dir.path().join("dkg.key").to_str().unwrap(),
+        ])
+        .await
+        .unwrap();
+        run_args(&export_args).await.unwrap();
+
+        let handoff: ShareHandoff =
+            serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        let state = DkgState::<G2Element>::load(&state_dir, &Context::for_testing()).unwrap();
+        let output = state.output.unwrap();
+        let share = output.shares.unwrap()[0].value;
+        let partial_pk = output.vss_pk.eval(NonZeroU16::new(1).unwrap()).value;
+        assert_eq!(handoff.party_id, 0);
+        assert_eq!(handoff.committee_id, Address::ZERO);
+        assert_eq!(handoff.decrypt(&recipient, &partial_pk).unwrap(), share);
+
+        // Only the recipient can decrypt it.
+        let keys = KeysFile::load(&dir.path().join("dkg.key"), &Context::for_testing()).unwrap();
+        assert!(handoff.decrypt(&keys, &partial_pk).is_err());
+    }
+
+    #[test]
+    fn test_check_state_matches_committee() {
+        let mut rng = StdRng::from_entropy();
+        let keys: Vec<KeysFile> = (0..3).map(|_| KeysFile::generate(&mut rng)).collect();
+        let (states, _) = fresh_dkg_states::<G2Element>(&keys, &mut rng);
+        let members: Vec<Address> = (1..=3).map(|i| Address::new([i; 32])).collect();
+        // SealCommittee is not Clone, so build a fresh committee for each case.
+        let committee = || {
+            members.iter().zip(&keys).fold(
+                SealCommittee::new_for_testing(Address::ZERO, 3, members.clone()),
+                |committee, (address, k)| {
+                    committee.with_member_info(
+                        *address,
+                        seal_committee::MemberInfo::new(
+                            k.enc_pk.clone(),
+                            k.signing_pk.clone(),
+                            "https://example.com".to_string(),
+                        ),
+                    )
+                },
+            )
+        };
+        check_state_matches_committee(&states[0], &committee()).unwrap();
+        check_state_matches_committee(
+            &states[0],
+            &committee().with_state(CommitteeState::Finalized),
+        )
+        .unwrap();
+
+        // Keys registered by another member.
+        let swapped = committee().with_member_info(
+            members[0],
+            seal_committee::MemberInfo::new(
+                keys[1].enc_pk.clone(),
+                keys[1].signing_pk.clone(),
+                "https://example.com".to_string(),
+            ),
+        );
+        let err = check_state_matches_committee(&states[0], &swapped).unwrap_err();
+        assert!(err.to_string().contains("Keys of party 0"), "{err}");
+
+        let mut other = committee();
+        other.id = Address::new([9; 32]);
+        assert!(check_state_matches_committee(&states[0], &other).is_err());
+        let mut lower_threshold = committee();
+        lower_threshold.threshold = 2;
+        assert!(check_state_matches_committee(&states[0], &lower_threshold).is_err());
+    }
+
+    #[test]
+    fn test_ceremony_status() {
+        let mut rng = StdRng::from_entropy();
+        let keys: Vec<KeysFile> = (0..3).map(|_| KeysFile::generate(&mut rng)).collect();
+        let (mut states, messages) = fresh_dkg_states::<G2Element>(&keys, &mut rng);
+
+        let status = ceremony_status(&states[0]);
+        assert_eq!(status.missing_message_senders, vec![1, 2]);
+        assert_eq!(status.percent_complete, 16);
+        let json = serde_json::to_value(&status).unwrap();
+        assert_eq!(json["total_expected"], 3);
+        assert_eq!(json["messages_received"], 1);
+        assert_eq!(json["missing_message_senders"], serde_json::json!([1, 2]));
+
+        process_messages(
+            &Context::default(),
+            &mut states[0],
+            &keys[0],
+            messages,
+            0,
+            &mut rng,
+        )
+        .unwrap();
+        let status = ceremony_status(&states[0]);
+        assert!(status.missing_message_senders.is_empty());
+        assert!(status.progress.is_complete);
+        assert_eq!(status.percent_complete, 100);
+    }
+
+    #[test]
+    fn test_verify_proposal_from_dkg_output() {
+        let mut rng = StdRng::from_entropy();
+        let keys: Vec<KeysFile> = (0..3).map(|_| KeysFile::generate(&mut rng)).collect();
+        let (mut states, messages) = fresh_dkg_states::<G2Element>(&keys, &mut rng);
+        let (output, _) = process_messages(
+            &Context::default(),
+            &mut states[0],
+            &keys[0],
+            messages,
+            0,
+            &mut rng,
+        )
+        .unwrap();
+
+        // The partial PKs proposed onchain by the members.
+        let mut partial_pks: Vec<(u16, G2Element)> = (0..3u16)
+            .map(|party_id| {
+                let index = NonZeroU16::new(party_id + 1).unwrap();
+                (party_id, output.vss_pk.eval(index).value)
+            })
+            .collect();
+        let threshold = states[0].config.threshold;
+        verify_partial_pk_consistency(output.vss_pk.c0(), &partial_pks, threshold).unwrap();
+
+        partial_pks[2].1 = partial_pks[1].1;
+        let err = verify_partial_pk_consistency(output.vss_pk.c0(), &partial_pks, threshold)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("does not match the aggregate PK"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_recover_master() {
+        let dir = TempDir::new().unwrap();
+        let mut rng = StdRng::from_entropy();
+        let keys: Vec<KeysFile> = (0..3).map(|_| KeysFile::generate(&mut rng)).collect();
+        let (states, messages) = fresh_dkg_states::<G2Element>(&keys, &mut rng);
+        let shares_dir = dir.path().join("shares");
+        fs::create_dir_all(&shares_dir).unwrap();
+        let mut outputs = Vec::new();
+        for (mut state, k) in states.into_iter().zip(&keys) {
+            let (output, _) = process_messages(
+                &Context::default(),
+                &mut state,
+                k,
+                messages.clone(),
+                0,
+                &mut rng,
+            )
+            .unwrap();
+            let config = state.export_key_server_config().unwrap();
+            write_secret_file(
+                &Context::for_testing(),
+                &shares_dir.join(format!("share_{}.json", config.party_id)),
+                &serde_json::to_string(&config).unwrap(),
+            )
+            .unwrap();
+            outputs.push(output);
+        }
+        let vss_pk = &outputs[0].vss_pk;
+        let output = dir.path().join("master.key");
+        let args = |confirm: bool| {
+            let mut args = vec![
+                "recover-master".to_string(),
+                "--shares-dir".to_string(),
+                shares_dir.to_str().unwrap().to_string(),
+                "--vss-pk".to_string(),
+                format_pk_hex(vss_pk).unwrap(),
+                "--output".to_string(),
+                output.to_str().unwrap().to_string(),
+            ];
+            if confirm {
+                args.push("--i-understand-this-reconstructs-the-master-key".to_string());
+            }
+            args
+        };
+        let run_recover = |confirm: bool| {
+            let args = args(confirm);
+            async move {
+                let args: Vec<&str> = args.iter().map(String::as_str).collect();
+                run_args(&args).await
+            }
+        };
+
+        // Recovery must be confirmed explicitly.
+        let err = run_recover(false).await.unwrap_err();
+        assert_eq!(ExitCode::from_error(&err), ExitCode::Usage);
+        assert!(!output.exists());
+
+        run_recover(true).await.unwrap();
+        let master_key = migrate::read_key_share_file(&output, PermissionPolicy::Strict).unwrap();
+        assert!(G2Element::generator() * master_key == *vss_pk.c0());
+
+        // An inconsistent share set is rejected.
+        fs::remove_file(&output).unwrap();
+        let tampered = serde_json::json!({
+            "party_id": 1,
+            "share": format_pk_hex(&(master_key + master_key)).unwrap(),
+        });
+        write_secret_file(
+            &Context::for_testing(),
+            &shares_dir.join("share_1.json"),
+            &tampered.to_string(),
+        )
+        .unwrap();
+        let err = run_recover(true).await.unwrap_err();
+        assert_eq!(ExitCode::from_error(&err), ExitCode::Verification);
+        assert!(!output.exists());
+
+        // Fewer shares than the threshold.
+        fs::remove_file(shares_dir.join("share_1.json")).unwrap();
+        let err = run_recover(true).await.unwrap_err();
+        assert!(
+            err.to_string().contains("need at least the threshold 3"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_message_file_status_g1() {
+        let dir = TempDir::new().unwrap();
+        let mut rng = StdRng::from_entropy();
+        let keys: Vec<KeysFile> = (0..2).map(|_| KeysFile::generate(&mut rng)).collect();
+        let (_, messages) = fresh_dkg_states::<G1Element>(&keys, &mut rng);
+        write_message_file(dir.path(), &messages[0]);
+        let path = dir.path().join("message_0.json");
+        let members: Vec<ParsedMemberInfo> = keys
+            .iter()
+            .enumerate()
+            .map(|(party_id, k)| ParsedMemberInfo {
+                party_id: party_id as u16,
+                address: Address::ZERO,
+                enc_pk: k.enc_pk.clone(),
+                signing_pk: k.signing_pk.clone(),
+            })
+            .collect();
+        let mut members_by_party_id: HashMap<u16, _> = members
+            .iter()
+            .map(|member| (member.party_id, member))
+            .collect();
+
+        let mut submitted = HashSet::new();
+        let (party_id, _, status) =
+            message_file_status::<G1Element>(&path, &members_by_party_id, &mut submitted);
+        assert_eq!((party_id.as_str(), status.as_str()), ("0", "valid"));
+        assert_eq!(submitted, HashSet::from([0]));
+
+        // A G1 message is unreadable as a G2 message.
+        let mut submitted = HashSet::new();
+        let (_, _, status) =
+            message_file_status::<G2Element>(&path, &members_by_party_id, &mut submitted);
+        assert!(status.starts_with("UNREADABLE"), "{status}");
+        assert!(submitted.is_empty());
+
+        // Signed by another member's key.
+        members_by_party_id.insert(0, &members[1]);
+        let (_, _, status) =
+            message_file_status::<G1Element>(&path, &members_by_party_id, &mut submitted);
+        assert!(status.starts_with("INVALID"), "{status}");
+    }
+
+    #[tokio::test]
+    async fn test_process_all_g1() {
+        let dir = TempDir::new().unwrap();
+        let messages = setup_fresh_dkg_of_key_group::<G1Element>(dir.path(), 3);
+        for message in &messages {
+            write_message_file(&dir.path().join("messages"), message);
+        }
+        let state_dir = dir.path().join("state");
+        assert_eq!(state_key_type(&state_dir).unwrap(), KeyType::G1);
+
+        // The state is merged and processed with the G1 instantiation selected by its tag.
+        let sub_dir = dir.path().join("sub");
+        fs::create_dir_all(&sub_dir).unwrap();
+        fs::copy(state_dir.join("state.json"), sub_dir.join("state.json")).unwrap();
+        run_args(&[
+            "merge-states",
+            "--state-dir",
+            state_dir.to_str().unwrap(),
+            "--from",
+            sub_dir.to_str().unwrap(),
+        ])
+        .await
+        .unwrap();
+        run_args(&[
+            "process-all",
+            "--messages-dir",
+            dir.path().join("messages").to_str().unwrap(),
+            "--state-dir",
+            state_dir.to_str().unwrap(),
+            "--keys-file",
+            dir.path().join("dkg.key").to_str().unwrap(),
+        ])
+        .await
+        .unwrap();
+        let state = DkgState::<G1Element>::load(&state_dir, &Context::for_testing()).unwrap();
+        let output = state.output.unwrap();
+        assert_eq!(output.vss_pk.degree(), 2);
+
+        // The commands reading the output select the instantiation the same way.
+        let config_file = dir.path().join("key_server_config.json");
+        run_args(&[
+            "export-key-server-config",
+            "--state-dir",
+            state_dir.to_str().unwrap(),
+            "--output",
+            config_file.to_str().unwrap(),
+        ])
+        .await
+        .unwrap();
+        let config: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&config_file).unwrap()).unwrap();
+        assert_eq!(
+            config["key_server_pk_hex"],
+            format_pk_hex(output.vss_pk.c0()).unwrap()
+        );
+        let handoff_file = dir.path().join("handoff.json");
+        let recipient = KeysFile::generate(&mut StdRng::from_entropy());
+        run_args(&[
+            "export-share-to",
+            "--recipient-enc-pk",
+            &format_pk_hex(&recipient.enc_pk).unwrap(),
+            "--state-dir",
+            state_dir.to_str().unwrap(),
+            "--output",
+            handoff_file.to_str().unwrap(),
+        ])
+        .await
+        .unwrap();
+        let handoff: ShareHandoff =
+            serde_json::from_str(&fs::read_to_string(&handoff_file).unwrap()).unwrap();
+        assert_eq!(handoff.party_id, state.config.my_party_id);
+
+        // A G1 state cannot be loaded as a G2 state.
+        let err = DkgState::<G2Element>::load(&state_dir, &Context::for_testing()).unwrap_err();
+        assert!(err.to_string().contains("key type 1"), "{err}");
+    }
+
+    #[cfg(feature = "test-determinism")]
+    #[tokio::test]
+    async fn test_seeded_ceremony_is_reproducible() {
+        /// Run a fresh DKG for 3 parties with fixed seeds and return the outputs of all parties.
+        async fn seeded_ceremony() -> Vec<Vec<u8>> {
+            let dir = TempDir::new().unwrap();
+            let messages_dir = dir.path().join("messages");
+            let party_dir = |party_id: u16| dir.path().join(format!("party_{party_id}"));
+
+            for party_id in 0..3 {
+                run_args(&[
+                    "generate-keys",
+                    "--seed",
+                    &party_id.to_string(),
+                    "--keys-file",
+                    party_dir(party_id).join("dkg.key").to_str().unwrap(),
+                ])
+                .await
+                .unwrap();
+            }
+            let keys: Vec<KeysFile> = (0..3)
+                .map(|party_id| {
+                    KeysFile::load(
+                        &party_dir(party_id).join("dkg.key"),
+                        &Context::for_testing(),
+                    )
+                    .unwrap()
+                })
+                .collect();
+
+            // create-message needs the committee onchain, so create the states directly.
+            let (states, messages) =
+                fresh_dkg_states::<G2Element>(&keys, &mut StdRng::seed_from_u64(100));
+            for (party_id, state) in states.iter().enumerate() {
+                state
+                    .save(
+                        &party_dir(party_id as u16).join("state"),
+                        &Context::for_testing(),
+                    )
+                    .unwrap();
+            }
+            for message in &messages {
+                write_message_file(&messages_dir, message);
+            }
+
+            let mut outputs = vec![];
+            for party_id in 0..3u16 {
+                let state_dir = party_dir(party_id).join("state");
+                run_args(&[
+                    "process-all",
+                    "--seed",
+                    &(200 + party_id).to_string(),
+                    "--messages-dir",
+                    messages_dir.to_str().unwrap(),
+                    "--state-dir",
+                    state_dir.to_str().unwrap(),
+                    "--keys-file",
+                    party_dir(party_id).join("dkg.key").to_str().unwrap(),
+                ])
+                .await
+                .unwrap();
+                let output = DkgState::<G2Element>::load(&state_dir, &Context::for_testing())
+                    .unwrap()
+                    .output
+                    .unwrap();
+                outputs.push(bcs::to_bytes(&output).unwrap());
+            }
+            outputs
+        }
+
+        assert_eq!(seeded_ceremony().await, seeded_ceremony().await);
+    }
+
+    #[tokio::test]
+    async fn test_simulate() {
+        run_args(&["simulate", "--parties", "4", "--threshold", "3", "--rotate"])
+            .await
+            .unwrap();
+        // Without slack, all members continue in the rotation.
+        run_args(&["simulate", "--parties", "3", "--threshold", "3", "--rotate"])
+            .await
+            .unwrap();
+
+        let err = run_args(&["simulate", "--parties", "3", "--threshold", "4"])
+            .await
+            .unwrap_err();
+        assert_eq!(ExitCode::from_error(&err), ExitCode::Usage);
+    }
+
+    #[test]
+    fn test_read_message_file() {
+        let dir = TempDir::new().unwrap();
+        let messages = setup_fresh_dkg(dir.path(), 2);
+        let messages_dir = dir.path().join("messages");
+        write_message_file(&messages_dir, &messages[1]);
+
+        let read = read_message_file::<G2Element>(&messages_dir.join("message_1.json")).unwrap();
+        assert_eq!(read.message.sender, 1);
+        assert_eq!(read.signature, messages[1].signature);
+
+        let invalid = messages_dir.join("invalid.json");
+        fs::write(&invalid, r#"{"msg": "abc"}"#).unwrap();
+        let err = read_message_file::<G2Element>(&invalid)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Missing 'message' field"), "{err}");
+    }
+
+    #[test]
+    fn test_message_paths_order() {
+        let dir = TempDir::new().unwrap();
+        for name in [
+            "message_10.json",
+            "notes.txt",
+            "message_2.json",
+            "message_1.json",
+        ] {
+            fs::write(dir.path().join(name), "").unwrap();
+        }
+        let names = |order| {
+            message_paths(dir.path(), order)
+                .unwrap()
+                .iter()
+                .map(|path| path.file_name().unwrap().to_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(MessageOrder::Name),
+            [
+                "message_1.json",
+                "message_10.json",
+                "message_2.json",
+                "notes.txt"
+            ]
+        );
+        assert_eq!(
+            names(MessageOrder::PartyId),
+            [
+                "message_1.json",
+                "message_2.json",
+                "message_10.json",
+                "notes.txt"
+            ]
+        );
+
+        let args = ["dkg-cli", "process-all", "--messages-dir", "./messages"];
+        let parsed = Cli::try_parse_from(args.iter().chain(&["--sort", "party-id"])).unwrap();
+        assert!(matches!(
+            parsed.command,
+            Commands::ProcessAll {
+                sort: MessageOrder::PartyId,
+                ..
+            }
+        ));
+        let with = |extra: &[&'static str]| Cli::try_parse_from(args.iter().chain(extra));
+        assert!(with(&["--old-committee-snapshot", "./old.json"]).is_err());
+        assert!(with(&[
+            "--old-committee-snapshot",
+            "./old.json",
+            "--committee-snapshot",
+            "./committee.json"
+        ])
+        .is_ok());
+    }
+
+    #[test]
+    fn test_prune_confirmation_flags() {
+        let args = ["dkg-cli", "prune", "--network", "testnet"];
+        let with = |extra: &[&'static str]| Cli::try_parse_from(args.iter().chain(extra));
+        assert!(with(&["--yes"]).is_err());
+        assert!(with(&["--i-have-installed-my-share"]).is_err());
+        assert!(with(&["--yes", "--i-have-installed-my-share"]).is_ok());
+    }
+
+    #[test]
+    fn test_custom_network() {
+        let args = ["dkg-cli", "show-committee"];
+        let network = |extra: &[&'static str]| match Cli::try_parse_from(args.iter().chain(extra))
+            .map(|cli| cli.command)
+        {
+            Ok(Commands::ShowCommittee { network, .. }) => Ok(network),
+            Ok(_) => unreachable!(),
+            Err(e) => Err(e),
+        };
+        assert_eq!(
+            network(&["--network", "testnet"]).unwrap(),
+            Some(Network::Testnet)
+        );
+        assert_eq!(
+            network(&["--network", "custom:http://127.0.0.1:9000"]).unwrap(),
+            Some(Network::Custom {
+                grpc_url: "http://127.0.0.1:9000".to_string()
+            })
+        );
+        assert!(network(&["--network", "custom:"]).is_err());
+        assert!(network(&["--network", "moonnet"]).is_err());
+    }
+
+    #[test]
+    fn test_approval_status_at_version() {
+        let args = ["dkg-cli", "approval-status"];
+        let version = |extra: &[&'static str]| match Cli::try_parse_from(args.iter().chain(extra))
+            .map(|cli| cli.command)
+        {
+            Ok(Commands::ApprovalStatus { at_version, .. }) => Ok(at_version),
+            Ok(_) => unreachable!(),
+            Err(e) => Err(e),
+        };
+        assert_eq!(version(&[]).unwrap(), None);
+        assert_eq!(version(&["--at-version", "42"]).unwrap(), Some(42));
+        assert!(version(&["--at-version", "latest"]).is_err());
+    }
+
+    #[test]
+    fn test_export_committee_format() {
+        let args = [
+            "dkg-cli",
+            "export-committee",
+            "--output",
+            "./committee.json",
+        ];
+        let format = |extra: &[&'static str]| match Cli::try_parse_from(args.iter().chain(extra))
+            .map(|cli| cli.command)
+        {
+            Ok(Commands::ExportCommittee { output_format, .. }) => Some(output_format),
+            _ => None,
+        };
+        assert_eq!(format(&[]), Some(ExportFormat::Json));
+        assert_eq!(format(&["--output-format", "csv"]), Some(ExportFormat::Csv));
+        assert_eq!(
+            format(&["--output-format", "text"]),
+            Some(ExportFormat::Text)
+        );
+        assert_eq!(format(&["--output-format", "xlsx"]), None);
+    }
+
+    #[test]
+    fn test_secure_delete() {
+        let dir = TempDir::new().unwrap();
+        setup_fresh_dkg(dir.path(), 2);
+        let state = DkgState::<G2Element>::load(&dir.path().join("state"), &Context::for_testing())
+            .unwrap();
+        let transcript = serde_json::to_value(state.transcript()).unwrap();
+        assert!(transcript.get("my_old_share").is_none());
+        assert!(transcript.get("processed_messages").is_none());
+
+        let keys_file = dir.path().join("dkg.key");
+        secure_delete(&keys_file).unwrap();
+        assert!(!keys_file.exists());
+    }
+
+    #[test]
+    fn test_write_secret_file_atomic() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("secret.json");
+        write_secret_file(&Context::for_testing(), &path, "old").unwrap();
+
+        // A write failing halfway leaves the target unchanged and removes the temporary file.
+        let err = write_file_atomic(&path, |file| {
+            file.write_all(b"partial")?;
+            Err(anyhow!("interrupted"))
+        })
+        .unwrap_err();
+        assert_eq!(err.to_string(), "interrupted");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "old");
+        assert!(!dir.path().join("secret.json.tmp").exists());
+
+        // A temporary file left by a killed process is overwritten.
+        fs::write(
+            dir.path().join("secret.json.tmp"),
+            "leftover from a killed process",
+        )
+        .unwrap();
+        write_secret_file(&Context::for_testing(), &path, "new").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert!(!dir.path().join("secret.json.tmp").exists());
+        #[cfg(unix)]
+        assert_eq!(
+            fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+    }
+
+    #[test]
+    fn test_exit_code_invalid_arguments() {
+        let err = Cli::try_parse_from(["dkg-cli", "process-all"])
+            .err()
+            .unwrap();
+        assert_eq!(err.exit_code(), ExitCode::Usage as i32);
+    }
+
+    #[tokio::test]
+    async fn test_exit_code_missing_messages_dir() {
+        let dir = TempDir::new().unwrap();
+        setup_fresh_dkg(dir.path(), 2);
+        assert_eq!(process_all_exit_code(dir.path()).await, ExitCode::Usage);
+    }
+
+    #[tokio::test]
+    async fn test_exit_code_missing_messages() {
+        let dir = TempDir::new().unwrap();
+        let messages = setup_fresh_dkg(dir.path(), 2);
+        write_message_file(&dir.path().join("messages"), &messages[0]);
+        assert_eq!(
+            process_all_exit_code(dir.path()).await,
+            ExitCode::Validation
+        );
+    }
+
+    #[tokio::test]
+    async fn test_exit_code_invalid_signature() {
+        let dir = TempDir::new().unwrap();
+        let mut messages = setup_fresh_dkg(dir.path(), 2);
+        messages[1].signature = messages[0].signature.clone();
+        for message in &messages {
+            write_message_file(&dir.path().join("messages"), message);
+        }
+        assert_eq!(
+            process_all_exit_code(dir.path()).await,
+            ExitCode::Verification
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_committee_at_version() {
+        use seal_committee::StaticCommitteeFetcher;
+
+        let committee_id =
+            Address::from_str("0x82283c1056bb18832428034d20e0af5ed098bc58f8815363c33eb3a9b3fba867")
+                .unwrap();
+        let fetcher = StaticCommitteeFetcher::default()
+            .with_move_object(
+                committee_id,
+                Hex::decode(
+                    include_str!("../../seal-committee/tests/fixtures/committee_finalized.hex")
+                        .trim(),
+                )
+                .unwrap(),
+            )
+            .unwrap();
+
+        for version in [Some(1), None] {
+            let committee = fetch_committee_at_version(
+                &Context::default(),
+                &fetcher,
+                &committee_id,
+                version,
+                None,
+            )
+            .await
+            .unwrap();
+            assert_eq!(committee.id, committee_id);
+        }
+        let err =
+            fetch_committee_at_version(&Context::default(), &fetcher, &committee_id, Some(2), None)
+                .await
+                .unwrap_err();
+        assert_eq!(ExitCode::from_error(&err), ExitCode::Usage);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_old_committee_snapshot() {
+        use seal_committee::{
+            key_server_field_wrapper_id, key_server_v2_field_id, StaticCommitteeFetcher,
+        };
+
+        // Recorded testnet objects of a finalized committee and its key server.
+        let committee_id =
+            Address::from_str("0x82283c1056bb18832428034d20e0af5ed098bc58f8815363c33eb3a9b3fba867")
+                .unwrap();
+        let ks_obj_id =
+            Address::from_str("0x5b4b868b22f4e1e87d3938f29aefc71a1e1ddf7352e214088c9eaf37e31efd31")
+                .unwrap();
+        let fixture = |hex: &str| Hex::decode(hex.trim()).unwrap();
+        let fetcher = StaticCommitteeFetcher::default()
+            .with_move_object(
+                committee_id,
+                fixture(include_str!(
+                    "../../seal-committee/tests/fixtures/committee_finalized.hex"
+                )),
+            )
+            .unwrap()
+            .with_move_object(
+                key_server_field_wrapper_id(&committee_id).unwrap(),
+                fixture(include_str!(
+                    "../../seal-committee/tests/fixtures/field_wrapper.hex"
+                )),
+            )
+            .unwrap()
+            .with_move_object(
+                ks_obj_id,
+                fixture(include_str!(
+                    "../../seal-committee/tests/fixtures/key_server.hex"
+                )),
+            )
+            .unwrap()
+            .with_move_object(
+                key_server_v2_field_id(&ks_obj_id).unwrap(),
+                fixture(include_str!(
+                    "../../seal-committee/tests/fixtures/key_server_v2_committee.hex"
+                )),
+            )
+            .unwrap();
+
+        let snapshot =
+            fetch_old_committee_snapshot(&Context::default(), &fetcher, &committee_id, None)
+                .await
+                .unwrap();
+        assert_eq!(snapshot.committee_id, committee_id);
+        assert_eq!(snapshot.partial_pks.len(), snapshot.members.len());
+
+        let partial_pks =
+            fetch_partial_pks::<G2Element>(&Context::default(), &fetcher, &committee_id, None)
+                .await
+                .unwrap();
+        assert_eq!(partial_pks.len(), snapshot.members.len());
+
+        // The snapshot written by export-committee yields the same old committee digest.
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("committee.json");
+        let exported = export_committee_snapshot(&fetcher, &committee_id)
+            .await
+            .unwrap();
+        fs::write(&path, exported.to_json().unwrap()).unwrap();
+        assert_eq!(
+            CommitteeSnapshot::load(&path).unwrap().digest,
+            snapshot.digest
+        );
+        assert!(InitCommitteeSnapshot::load(&path).is_err());
+
+        let err = fetch_old_committee_snapshot(&Context::default(), &fetcher, &Address::ZERO, None)
+            .await
+            .unwrap_err();
+        assert_eq!(ExitCode::from_error(&err), ExitCode::Network);
+    }
 }
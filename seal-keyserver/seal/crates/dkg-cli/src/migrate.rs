@@ -0,0 +1,125 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Migration of an independent key server to a committee with the same public key.
+
+use crate::permissions::{check_secret_file, PermissionPolicy};
+use anyhow::{anyhow, Result};
+use dkg_core::{KeyGroup, KeyType};
+use fastcrypto::encoding::{Encoding, Hex};
+use fastcrypto::groups::bls12381::{G1Element, G2Element, Scalar as G2Scalar};
+use seal_committee::{KeyServerV2, ServerType};
+use std::fs;
+use std::path::Path;
+
+/// Parse a master key or key share (hex-encoded BCS), as in the key server's `MASTER_KEY`.
+pub(crate) fn parse_key_share(share_hex: &str) -> Result<G2Scalar> {
+    let bytes = Hex::decode(share_hex.trim())?;
+    Ok(bcs::from_bytes(&bytes)?)
+}
+
+/// Read a master key or key share from a secret file, checking its permissions according to
+/// `policy`.
+pub(crate) fn read_key_share_file(path: &Path, policy: PermissionPolicy) -> Result<G2Scalar> {
+    check_secret_file(path, policy)?;
+    let content = fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    parse_key_share(&content).map_err(|e| anyhow!("Invalid key in {}: {}", path.display(), e))
+}
+
+/// Check that `key_server` is an independent key server with the public key of `master_key`, and
+/// return its key type.
+pub(crate) fn check_independent_key_server(
+    master_key: &G2Scalar,
+    key_server: &KeyServerV2,
+) -> Result<KeyType> {
+    if !matches!(key_server.server_type, ServerType::Independent { .. }) {
+        return Err(anyhow!(
+            "Key server {} is not an independent key server",
+            key_server.name
+        ));
+    }
+    let key_type = KeyType::try_from(key_server.key_type)?;
+    match key_type {
+        KeyType::G2 => check_public_key::<G2Element>(master_key, &key_server.pk)?,
+        KeyType::G1 => check_public_key::<G1Element>(master_key, &key_server.pk)?,
+    }
+    Ok(key_type)
+}
+
+fn check_public_key<G: KeyGroup>(master_key: &G2Scalar, pk_bytes: &[u8]) -> Result<()> {
+    let pk: G = bcs::from_bytes(pk_bytes)
+        .map_err(|e| anyhow!("Failed to deserialize key server public key: {e}"))?;
+    if G::generator() * master_key != pk {
+        return Err(anyhow!(
+            "Master key does not match the public key of the key server"
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::CommitteeSnapshot;
+    use fastcrypto::groups::GroupElement;
+    use sui_sdk_types::Address;
+
+    fn independent_key_server(pk: Vec<u8>, key_type: u8) -> KeyServerV2 {
+        KeyServerV2 {
+            name: "independent".to_string(),
+            key_type,
+            pk,
+            server_type: ServerType::Independent {
+                url: "https://seal.example.com".to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_check_independent_key_server() {
+        let master_key = G2Scalar::from(7u128);
+        let pk = bcs::to_bytes(&(G2Element::generator() * master_key)).unwrap();
+        let key_server = independent_key_server(pk.clone(), 0);
+        assert_eq!(
+            check_independent_key_server(&master_key, &key_server).unwrap(),
+            KeyType::G2
+        );
+
+        let err = check_independent_key_server(&G2Scalar::from(8u128), &key_server)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("does not match"), "{err}");
+
+        // The public key is interpreted according to the key type.
+        let g1_pk = bcs::to_bytes(&(G1Element::generator() * master_key)).unwrap();
+        assert_eq!(
+            check_independent_key_server(&master_key, &independent_key_server(g1_pk, 1)).unwrap(),
+            KeyType::G1
+        );
+        assert!(check_independent_key_server(&master_key, &independent_key_server(pk, 1)).is_err());
+    }
+
+    #[test]
+    fn test_independent_snapshot() {
+        let master_key = G2Scalar::from(7u128);
+        let pk = G2Element::generator() * master_key;
+        let key_server = independent_key_server(bcs::to_bytes(&pk).unwrap(), 0);
+        let member = Address::new([1; 32]);
+        let snapshot =
+            CommitteeSnapshot::from_independent(Address::new([9; 32]), member, &key_server)
+                .unwrap();
+
+        // The master key is the share of the single member, party 0.
+        let committee = snapshot.to_committee();
+        assert_eq!(committee.threshold, 1);
+        assert_eq!(committee.get_party_id(&member).unwrap(), 0);
+        assert_eq!(snapshot.parse_partial_pks::<G2Element>().unwrap()[&0], pk);
+
+        let share_hex = Hex::encode_with_format(bcs::to_bytes(&master_key).unwrap());
+        assert_eq!(
+            parse_key_share(&format!("{share_hex}\n")).unwrap(),
+            master_key
+        );
+    }
+}
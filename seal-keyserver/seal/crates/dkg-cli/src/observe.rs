@@ -0,0 +1,503 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Observer mode, verifying a ceremony's public outputs without any secrets.
+
+use anyhow::{anyhow, Result};
+use dkg_core::types::{verify_all_signatures, SignedMessage};
+use dkg_core::{KeyGroup, KeyType};
+use fastcrypto::bls12381::min_sig::BLS12381PublicKey;
+use fastcrypto::groups::bls12381::{G1Element, G2Element};
+use fastcrypto_tbls::polynomial::{Eval, Poly};
+use seal_committee::{
+    fetch_committee_data, CommitteeFetcher, CommitteeState, RotationPlan, SealCommittee,
+};
+use std::collections::HashMap;
+use std::fmt;
+use std::num::NonZeroU16;
+use std::path::PathBuf;
+use std::time::Duration;
+use sui_sdk_types::Address;
+
+use crate::ceremony::{message_paths, read_message_file};
+use crate::context::Context;
+use crate::errors::CliError;
+use crate::progress::with_spinner;
+use crate::{
+    fetch_committee_at_version, fetch_committee_key_server, fetch_partial_pks, parse_pk,
+    with_rpc_timeout, MessageOrder,
+};
+
+/// Result of a single observer check.
+pub(crate) enum Verdict {
+    Pass,
+    Fail(anyhow::Error),
+    Skipped(String),
+}
+
+impl From<Result<()>> for Verdict {
+    fn from(result: Result<()>) -> Self {
+        match result {
+            Ok(()) => Verdict::Pass,
+            Err(e) => Verdict::Fail(e),
+        }
+    }
+}
+
+/// A named observer check and its verdict.
+pub(crate) struct Check {
+    pub name: &'static str,
+    pub verdict: Verdict,
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.verdict {
+            Verdict::Pass => write!(f, "[PASS] {}", self.name),
+            Verdict::Fail(e) => write!(f, "[FAIL] {}: {}", self.name, e),
+            Verdict::Skipped(reason) => write!(f, "[SKIP] {}: {}", self.name, reason),
+        }
+    }
+}
+
+/// Share index of a party, each party has weight 1.
+fn share_index(party_id: u16) -> Result<NonZeroU16> {
+    party_id
+        .checked_add(1)
+        .and_then(NonZeroU16::new)
+        .ok_or_else(|| anyhow!("Invalid party ID {party_id}"))
+}
+
+/// Check that interpolating the partial public keys at zero gives the aggregate public key, both
+/// from the first `threshold` parties and from all parties.
+pub(crate) fn check_pk_interpolation<G: KeyGroup>(
+    pk: &G,
+    partial_pks: &HashMap<u16, G>,
+    threshold: u16,
+) -> Result<()> {
+    if partial_pks.len() < threshold as usize {
+        return Err(anyhow!(
+            "Only {} partial PKs published, threshold is {}",
+            partial_pks.len(),
+            threshold
+        ));
+    }
+    let mut evals = partial_pks
+        .iter()
+        .map(|(party_id, partial_pk)| {
+            Ok(Eval {
+                index: share_index(*party_id)?,
+                value: *partial_pk,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    evals.sort_by_key(|eval| eval.index);
+
+    let from_threshold =
+        Poly::<G>::recover_c0_msm(threshold, evals.iter().take(threshold as usize))?;
+    if from_threshold != *pk {
+        return Err(anyhow!(
+            "Interpolation of the first {threshold} partial PKs does not match the aggregate PK"
+        ));
+    }
+    let from_all = Poly::<G>::recover_c0_msm(evals.len() as u16, evals.iter())?;
+    if from_all != *pk {
+        return Err(anyhow!(
+            "Interpolation of all {} partial PKs does not match the aggregate PK",
+            evals.len()
+        ));
+    }
+    Ok(())
+}
+
+/// Check that every message is signed by the registered signing key of its sender.
+pub(crate) fn check_message_signatures<G: KeyGroup>(
+    messages: &[SignedMessage<G>],
+    signing_pks: &HashMap<u16, BLS12381PublicKey>,
+) -> Result<()> {
+    verify_all_signatures(messages, signing_pks).map_err(|failures| {
+        anyhow!(
+            "{}",
+            failures
+                .iter()
+                .map(|(_, e)| e.to_string())
+                .collect::<Vec<_>>()
+                .join("; ")
+        )
+    })?;
+    Ok(())
+}
+
+/// For a fresh DKG, check that the sum of the commitments in all messages is consistent with the
+/// aggregate and partial public keys.
+pub(crate) fn check_fresh_commitments<G: KeyGroup>(
+    messages: &[SignedMessage<G>],
+    pk: &G,
+    partial_pks: &HashMap<u16, G>,
+) -> Result<()> {
+    let c0 = messages
+        .iter()
+        .fold(G::zero(), |acc, m| acc + *m.message.vss_pk.c0());
+    if c0 != *pk {
+        return Err(anyhow!(
+            "Sum of message commitments does not match the aggregate PK"
+        ));
+    }
+    for (party_id, partial_pk) in partial_pks {
+        let index = share_index(*party_id)?;
+        let expected = messages
+            .iter()
+            .fold(G::zero(), |acc, m| acc + m.message.vss_pk.eval(index).value);
+        if expected != *partial_pk {
+            return Err(anyhow!(
+                "Sum of message commitments does not match the partial PK of party {party_id}"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// For a key rotation, check that the commitment of every message is to the sender's partial key
+/// in the old committee.
+pub(crate) fn check_rotation_commitments<G: KeyGroup>(
+    messages: &[SignedMessage<G>],
+    new_to_old_mapping: &HashMap<u16, u16>,
+    old_partial_pks: &HashMap<u16, G>,
+) -> Result<()> {
+    for signed_msg in messages {
+        let sender = signed_msg.message.sender;
+        let old_party_id = new_to_old_mapping
+            .get(&sender)
+            .ok_or_else(|| anyhow!("Party {sender} is not a continuing member"))?;
+        let old_partial_pk = old_partial_pks
+            .get(old_party_id)
+            .ok_or_else(|| anyhow!("Partial PK not found for old party {old_party_id}"))?;
+        if signed_msg.message.vss_pk.c0() != old_partial_pk {
+            return Err(anyhow!(
+                "Commitment of party {sender} does not match its old partial PK (old party {old_party_id})"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Run `observe`: fetch the committee of `committee_id`, at `committee_version` if given, and
+/// verify the public outputs of its DKG in the key group of the committee, and the messages in
+/// `messages_dir` if given.
+pub(crate) async fn observe(
+    ctx: &Context,
+    fetcher: &dyn CommitteeFetcher,
+    committee_id: &Address,
+    committee_version: Option<u64>,
+    messages_dir: Option<PathBuf>,
+    rpc_timeout: Option<Duration>,
+) -> Result<()> {
+    let committee =
+        fetch_committee_at_version(ctx, fetcher, committee_id, committee_version, rpc_timeout)
+            .await?;
+
+    match committee_key_type(ctx, fetcher, &committee, rpc_timeout).await? {
+        KeyType::G2 => {
+            observe_committee::<G2Element>(ctx, fetcher, &committee, messages_dir, rpc_timeout)
+                .await
+        }
+        KeyType::G1 => {
+            observe_committee::<G1Element>(ctx, fetcher, &committee, messages_dir, rpc_timeout)
+                .await
+        }
+    }
+}
+
+/// Key type of the DKG of a committee: that of the old committee's key server for a key rotation,
+/// as in `create-message`, and the default key type for a fresh DKG.
+pub(crate) async fn committee_key_type(
+    ctx: &Context,
+    fetcher: &dyn CommitteeFetcher,
+    committee: &SealCommittee,
+    rpc_timeout: Option<Duration>,
+) -> Result<KeyType> {
+    let Some(old_committee_id) = committee.old_committee_id else {
+        return Ok(KeyType::default());
+    };
+    let (_, old_key_server) = with_rpc_timeout(
+        rpc_timeout,
+        with_spinner(
+            format!("Fetching key server of old committee {old_committee_id}"),
+            fetch_committee_key_server(ctx, fetcher, &old_committee_id),
+        ),
+    )
+    .await
+    .map_err(CliError::Network)?;
+    Ok(KeyType::try_from(old_key_server.key_type).map_err(CliError::Validation)?)
+}
+
+/// Verify the public outputs of the DKG of `committee` in the key group `G` for `observe`, and
+/// the messages in `messages_dir` if given.
+async fn observe_committee<G: KeyGroup>(
+    ctx: &Context,
+    fetcher: &dyn CommitteeFetcher,
+    committee: &SealCommittee,
+    messages_dir: Option<PathBuf>,
+    rpc_timeout: Option<Duration>,
+) -> Result<()> {
+    let committee_id = committee.id;
+
+    // Aggregate PK and partial PKs by party ID. Signing PKs are only available onchain
+    // before the committee is finalized.
+    let (pk, partial_pks, signing_pks) = match &committee.state {
+        CommitteeState::Init { .. } => {
+            return Err(CliError::StateConflict(anyhow!(
+                "Committee {committee_id} is in Init state, DKG is not completed"
+            ))
+            .into());
+        }
+        CommitteeState::PostDKG { .. } => {
+            let partial_pks = committee
+                .get_postdkg_partial_pks::<G>()
+                .map_err(|e| CliError::Validation(e.into()))?;
+            let signing_pks: HashMap<u16, _> = committee
+                .get_members_info()
+                .map_err(|e| CliError::Validation(e.into()))?
+                .into_values()
+                .map(|info| (info.party_id, info.signing_pk))
+                .collect();
+            let pk = parse_pk::<G>(
+                committee
+                    .get_postdkg_pk_bytes()
+                    .map_err(|e| CliError::Validation(e.into()))?,
+            )?;
+            (pk, partial_pks, Some(signing_pks))
+        }
+        CommitteeState::Finalized => {
+            let (_, key_server) = with_rpc_timeout(
+                rpc_timeout,
+                with_spinner(
+                    format!("Fetching key server of committee {committee_id}"),
+                    fetch_committee_key_server(ctx, fetcher, &committee_id),
+                ),
+            )
+            .await
+            .map_err(CliError::Network)?;
+            let partial_pks = fetch_partial_pks(ctx, fetcher, &committee_id, rpc_timeout).await?;
+            (parse_pk::<G>(&key_server.pk)?, partial_pks, None)
+        }
+    };
+
+    let mut checks = vec![Check {
+        name: "Aggregate PK is the interpolation of the partial PKs at zero",
+        verdict: check_pk_interpolation(&pk, &partial_pks, committee.threshold).into(),
+    }];
+
+    // For key rotation, the continuing members and the old committee's partial PKs.
+    let rotation = match committee.old_committee_id {
+        None => None,
+        Some(old_committee_id) => {
+            let old_committee = with_rpc_timeout(
+                rpc_timeout,
+                with_spinner(
+                    format!("Fetching old committee {old_committee_id}"),
+                    fetch_committee_data(fetcher, &old_committee_id),
+                ),
+            )
+            .await
+            .map_err(CliError::Network)?;
+            let (_, old_key_server) = with_rpc_timeout(
+                rpc_timeout,
+                with_spinner(
+                    format!("Fetching key server of old committee {old_committee_id}"),
+                    fetch_committee_key_server(ctx, fetcher, &old_committee_id),
+                ),
+            )
+            .await
+            .map_err(CliError::Network)?;
+            let old_pk = parse_pk::<G>(&old_key_server.pk)?;
+            checks.push(Check {
+                name: "Aggregate PK is unchanged from the old committee",
+                verdict: if old_pk == pk {
+                    Verdict::Pass
+                } else {
+                    Verdict::Fail(anyhow!(
+                        "Aggregate PK differs from old committee {old_committee_id}"
+                    ))
+                },
+            });
+            let old_partial_pks =
+                fetch_partial_pks(ctx, fetcher, &old_committee_id, rpc_timeout).await?;
+            let plan =
+                RotationPlan::build(committee, &old_committee).map_err(CliError::Validation)?;
+            Some((plan.new_to_old, old_partial_pks))
+        }
+    };
+
+    if let Some(messages_dir) = messages_dir {
+        // Other files in the shared directory are reported and skipped, as in
+        // list-messages.
+        let mut messages = vec![];
+        for path in message_paths(&messages_dir, MessageOrder::Name)? {
+            match read_message_file::<G>(&path) {
+                Ok(message) => messages.push(message),
+                Err(e) => eprintln!("WARNING: Skipping unreadable file: {e}"),
+            }
+        }
+        checks.push(Check {
+            name: "Message signatures are valid",
+            verdict: match &signing_pks {
+                Some(signing_pks) => check_message_signatures(&messages, signing_pks).into(),
+                None => Verdict::Skipped(
+                    "signing PKs are not available onchain after finalization".to_string(),
+                ),
+            },
+        });
+        checks.push(Check {
+            name: "Message commitments are consistent with the final keys",
+            verdict: match &rotation {
+                None => check_fresh_commitments(&messages, &pk, &partial_pks),
+                Some((new_to_old_mapping, old_partial_pks)) => {
+                    check_rotation_commitments(&messages, new_to_old_mapping, old_partial_pks)
+                }
+            }
+            .into(),
+        });
+    }
+
+    for check in &checks {
+        println!("{check}");
+    }
+    let failed = checks
+        .iter()
+        .filter(|check| matches!(check.verdict, Verdict::Fail(_)))
+        .count();
+    if failed > 0 {
+        return Err(CliError::Verification(anyhow!(
+            "{failed} of {} check(s) failed",
+            checks.len()
+        ))
+        .into());
+    }
+    println!("All checks passed.");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dkg_core::types::sign_message;
+    use fastcrypto::bls12381::min_sig::BLS12381KeyPair;
+    use fastcrypto::groups::bls12381::{G1Element, G2Element};
+    use fastcrypto::traits::KeyPair;
+    use fastcrypto_tbls::dkg_v1::Party;
+    use fastcrypto_tbls::ecies_v1::{PrivateKey, PublicKey};
+    use fastcrypto_tbls::nodes::{Node, Nodes};
+    use fastcrypto_tbls::random_oracle::RandomOracle;
+    use rand::thread_rng;
+
+    /// Signed messages of a fresh DKG with `n` parties and threshold `t`, and signing PKs.
+    fn fresh_messages<G: KeyGroup>(
+        n: u16,
+        t: u16,
+    ) -> (Vec<SignedMessage<G>>, HashMap<u16, BLS12381PublicKey>) {
+        let enc_sks: Vec<_> = (0..n)
+            .map(|_| PrivateKey::<G2Element>::new(&mut thread_rng()))
+            .collect();
+        let nodes = Nodes::new(
+            enc_sks
+                .iter()
+                .enumerate()
+                .map(|(id, sk)| Node {
+                    id: id as u16,
+                    pk: PublicKey::from_private_key(sk),
+                    weight: 1,
+                })
+                .collect(),
+        )
+        .unwrap();
+        let mut signing_pks = HashMap::new();
+        let messages = enc_sks
+            .into_iter()
+            .enumerate()
+            .map(|(id, enc_sk)| {
+                let party = Party::<G, G2Element>::new_advanced(
+                    enc_sk,
+                    nodes.clone(),
+                    t,
+                    RandomOracle::new("observe"),
+                    None,
+                    None,
+                    &mut thread_rng(),
+                )
+                .unwrap();
+                let signing_kp = BLS12381KeyPair::generate(&mut thread_rng());
+                signing_pks.insert(id as u16, signing_kp.public().clone());
+                sign_message(
+                    party.create_message(&mut thread_rng()).unwrap(),
+                    &signing_kp.private(),
+                )
+            })
+            .collect();
+        (messages, signing_pks)
+    }
+
+    /// Aggregate and partial PKs as the sum of the messages' commitments.
+    fn aggregate<G: KeyGroup>(messages: &[SignedMessage<G>], n: u16) -> (G, HashMap<u16, G>) {
+        let pk = messages
+            .iter()
+            .fold(G::zero(), |acc, m| acc + *m.message.vss_pk.c0());
+        let partial_pks = (0..n)
+            .map(|party_id| {
+                let index = share_index(party_id).unwrap();
+                let partial_pk = messages
+                    .iter()
+                    .fold(G::zero(), |acc, m| acc + m.message.vss_pk.eval(index).value);
+                (party_id, partial_pk)
+            })
+            .collect();
+        (pk, partial_pks)
+    }
+
+    /// Checks of a fresh DKG, and that they fail for tampered inputs, in the key group `G`.
+    fn fresh_dkg_checks<G: KeyGroup>() {
+        let (messages, signing_pks) = fresh_messages::<G>(3, 2);
+        let (pk, mut partial_pks) = aggregate(&messages, 3);
+
+        check_pk_interpolation(&pk, &partial_pks, 2).unwrap();
+        check_message_signatures(&messages, &signing_pks).unwrap();
+        check_fresh_commitments(&messages, &pk, &partial_pks).unwrap();
+
+        // Signing PKs of another party.
+        let mut swapped = signing_pks.clone();
+        swapped.insert(0, signing_pks[&1].clone());
+        assert!(check_message_signatures(&messages, &swapped).is_err());
+
+        // Missing message.
+        assert!(check_fresh_commitments(&messages[1..], &pk, &partial_pks).is_err());
+
+        // Tampered partial PK.
+        partial_pks.insert(2, G::generator());
+        assert!(check_pk_interpolation(&pk, &partial_pks, 2).is_err());
+        assert!(check_fresh_commitments(&messages, &pk, &partial_pks).is_err());
+    }
+
+    #[test]
+    fn test_fresh_dkg_checks() {
+        fresh_dkg_checks::<G2Element>();
+    }
+
+    #[test]
+    fn test_fresh_dkg_checks_g1() {
+        fresh_dkg_checks::<G1Element>();
+    }
+
+    #[test]
+    fn test_rotation_commitments() {
+        let (messages, _) = fresh_messages::<G2Element>(2, 2);
+        let old_partial_pks: HashMap<u16, G2Element> = messages
+            .iter()
+            .map(|m| (m.message.sender + 5, *m.message.vss_pk.c0()))
+            .collect();
+        let mapping = HashMap::from([(0, 5), (1, 6)]);
+        check_rotation_commitments(&messages, &mapping, &old_partial_pks).unwrap();
+
+        let wrong_mapping = HashMap::from([(0, 6), (1, 5)]);
+        assert!(check_rotation_commitments(&messages, &wrong_mapping, &old_partial_pks).is_err());
+    }
+}
@@ -0,0 +1,133 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Checks that files containing secrets, i.e. the keys file and the DKG state, are not accessible
+//! by group or others.
+
+use crate::errors::CliError;
+use anyhow::{anyhow, Result};
+use std::io::IsTerminal;
+use std::path::Path;
+
+#[cfg(unix)]
+use std::fs;
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+
+/// How to handle a file with secrets that is accessible by group or others.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum PermissionPolicy {
+    /// Print a warning, and offer to restrict the permissions when running interactively.
+    #[default]
+    Warn,
+    /// Fail, set with `--strict-permissions`.
+    Strict,
+}
+
+impl PermissionPolicy {
+    pub(crate) fn new(strict: bool) -> Self {
+        if strict {
+            PermissionPolicy::Strict
+        } else {
+            PermissionPolicy::Warn
+        }
+    }
+}
+
+/// Check the permissions of a file with secrets before loading it. Always passes on non-Unix
+/// systems.
+pub(crate) fn check_secret_file(path: &Path, policy: PermissionPolicy) -> Result<()> {
+    let Some(mode) = permissive_mode(path)? else {
+        return Ok(());
+    };
+    if policy == PermissionPolicy::Strict {
+        return Err(CliError::Validation(anyhow!(
+            "{} has mode {:o} and is accessible by group or others. Restrict it with `chmod 600 {}`.",
+            path.display(),
+            mode,
+            path.display()
+        ))
+        .into());
+    }
+    eprintln!(
+        "WARNING: {} has mode {:o} and is accessible by group or others. It contains secrets!",
+        path.display(),
+        mode
+    );
+    if !std::io::stdin().is_terminal() {
+        eprintln!(
+            "Restrict it with `chmod 600 {}`, or pass --strict-permissions to fail instead.",
+            path.display()
+        );
+        return Ok(());
+    }
+    eprint!("Restrict permissions of {} to 600? [y/N]: ", path.display());
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    if answer.trim().eq_ignore_ascii_case("y") {
+        restrict_permissions(path)?;
+        eprintln!("Permissions of {} restricted to 600.", path.display());
+    }
+    Ok(())
+}
+
+/// The permission bits of the file if any group or other bits are set.
+#[cfg(unix)]
+fn permissive_mode(path: &Path) -> Result<Option<u32>> {
+    let mode = fs::metadata(path)?.permissions().mode() & 0o777;
+    Ok((mode & 0o077 != 0).then_some(mode))
+}
+
+#[cfg(not(unix))]
+fn permissive_mode(_path: &Path) -> Result<Option<u32>> {
+    Ok(None)
+}
+
+#[cfg(unix)]
+fn restrict_permissions(path: &Path) -> Result<()> {
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn restrict_permissions(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_permissive_mode() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("dkg.key");
+        fs::write(&path, "secret").unwrap();
+
+        for (mode, permissive) in [
+            (0o600, false),
+            (0o400, false),
+            (0o700, false),
+            (0o640, true),
+            (0o604, true),
+            (0o644, true),
+            (0o666, true),
+        ] {
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+            assert_eq!(
+                permissive_mode(&path).unwrap(),
+                permissive.then_some(mode),
+                "mode {mode:o}"
+            );
+            assert_eq!(
+                check_secret_file(&path, PermissionPolicy::Strict).is_ok(),
+                !permissive,
+                "mode {mode:o}"
+            );
+        }
+
+        restrict_permissions(&path).unwrap();
+        assert_eq!(permissive_mode(&path).unwrap(), None);
+    }
+}
@@ -0,0 +1,93 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Progress bars and spinners for long-running operations.
+//!
+//! Progress is drawn on stderr and hidden when stdout is not a terminal. Phase timings are also
+//! emitted as tracing events.
+
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressFinish, ProgressStyle};
+use std::io::IsTerminal;
+use std::time::{Duration, Instant};
+
+const TICK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Create a progress bar that is cleared when dropped, e.g. on early return with an error.
+fn new_bar(len: Option<u64>) -> ProgressBar {
+    let draw_target = if std::io::stdout().is_terminal() {
+        ProgressDrawTarget::stderr()
+    } else {
+        ProgressDrawTarget::hidden()
+    };
+    ProgressBar::with_draw_target(len, draw_target).with_finish(ProgressFinish::AndClear)
+}
+
+/// A named phase of a command, shown as a progress bar over a number of steps.
+pub(crate) struct Phase {
+    name: &'static str,
+    bar: ProgressBar,
+    start: Instant,
+}
+
+impl Phase {
+    /// Start a phase with `len` steps. Use `len = 0` for a phase without steps, shown as a
+    /// spinner.
+    pub(crate) fn start(name: &'static str, len: u64) -> Self {
+        let bar = if len == 0 {
+            let bar = new_bar(None);
+            bar.set_style(ProgressStyle::with_template("{spinner} {msg} [{elapsed}]").unwrap());
+            bar.enable_steady_tick(TICK_INTERVAL);
+            bar
+        } else {
+            let bar = new_bar(Some(len));
+            bar.set_style(
+                ProgressStyle::with_template("{msg} [{bar:40}] {pos}/{len} [{elapsed}]").unwrap(),
+            );
+            bar
+        };
+        bar.set_message(name);
+        tracing::info!(phase = name, steps = len, "Phase started");
+        Self {
+            name,
+            bar,
+            start: Instant::now(),
+        }
+    }
+
+    /// Print a line to stdout without garbling the progress bar.
+    pub(crate) fn println(&self, line: impl AsRef<str>) {
+        self.bar.suspend(|| println!("{}", line.as_ref()));
+    }
+
+    /// Mark one step as done.
+    pub(crate) fn inc(&self) {
+        self.bar.inc(1);
+    }
+
+    /// Clear the progress bar and record the phase duration.
+    pub(crate) fn finish(self) {
+        self.bar.finish_and_clear();
+        tracing::info!(
+            phase = self.name,
+            elapsed_ms = self.start.elapsed().as_millis() as u64,
+            "Phase completed"
+        );
+    }
+}
+
+/// Show a spinner with the given message while awaiting a request, e.g. a gRPC fetch.
+pub(crate) async fn with_spinner<F: Future>(message: String, request: F) -> F::Output {
+    let spinner = new_bar(None);
+    spinner.set_style(ProgressStyle::with_template("{spinner} {msg} [{elapsed}]").unwrap());
+    spinner.set_message(message.clone());
+    spinner.enable_steady_tick(TICK_INTERVAL);
+
+    let start = Instant::now();
+    let output = request.await;
+    spinner.finish_and_clear();
+    tracing::info!(
+        elapsed_ms = start.elapsed().as_millis() as u64,
+        "{message} completed"
+    );
+    output
+}
@@ -0,0 +1,92 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Key share files, as read by `recover-master` and written by `import-share`.
+
+use crate::context::Context;
+use crate::errors::CliError;
+use crate::migrate::parse_key_share;
+use crate::permissions::{check_secret_file, PermissionPolicy};
+use crate::write_secret_file;
+use anyhow::{anyhow, Result};
+use dkg_core::types::format_pk_hex;
+use fastcrypto::groups::bls12381::Scalar as G2Scalar;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Key share of a party. Key server config files from `export-key-server-config` can be used as
+/// share files as is.
+#[derive(Serialize, Deserialize)]
+struct ShareFile {
+    party_id: u16,
+    /// Hex-encoded BCS key share.
+    #[serde(alias = "partial_key_share_hex")]
+    share: String,
+}
+
+/// Read the key shares by party ID from all files in `shares_dir`, checking their permissions
+/// according to `policy`.
+pub(crate) fn read_shares_dir(
+    shares_dir: &Path,
+    policy: PermissionPolicy,
+) -> Result<HashMap<u16, G2Scalar>> {
+    let entries = fs::read_dir(shares_dir).map_err(|e| {
+        CliError::Usage(anyhow!(
+            "Failed to read shares directory {}: {}",
+            shares_dir.display(),
+            e
+        ))
+    })?;
+
+    let mut shares = HashMap::new();
+    for entry in entries {
+        let path = entry?.path();
+        let (party_id, share) = read_share_file(&path, policy)?;
+        if shares.insert(party_id, share).is_some() {
+            return Err(CliError::Usage(anyhow!(
+                "Duplicate share of party {} in {}",
+                party_id,
+                path.display()
+            ))
+            .into());
+        }
+    }
+
+    if shares.is_empty() {
+        return Err(CliError::Usage(anyhow!(
+            "No files found in directory: {}",
+            shares_dir.display()
+        ))
+        .into());
+    }
+    Ok(shares)
+}
+
+/// Read the party ID and key share from a share file, checking its permissions according to
+/// `policy`.
+pub(crate) fn read_share_file(path: &Path, policy: PermissionPolicy) -> Result<(u16, G2Scalar)> {
+    check_secret_file(path, policy)?;
+    let content = fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    let file: ShareFile = serde_json::from_str(&content)
+        .map_err(|e| CliError::Usage(anyhow!("Failed to parse {}: {}", path.display(), e)))?;
+    let share = parse_key_share(&file.share)
+        .map_err(|e| CliError::Usage(anyhow!("Invalid share in {}: {}", path.display(), e)))?;
+    Ok((file.party_id, share))
+}
+
+/// Write a share file with restricted permissions.
+pub(crate) fn write_share_file(
+    ctx: &Context,
+    path: &Path,
+    party_id: u16,
+    share: &G2Scalar,
+) -> Result<()> {
+    let file = ShareFile {
+        party_id,
+        share: format_pk_hex(share)?,
+    };
+    write_secret_file(ctx, path, &serde_json::to_string_pretty(&file)?)
+}
@@ -0,0 +1,473 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Key rotation: fetching both committees, the checks of `validate-rotation` and `rotate-keys`,
+//! and the rotation summary printed by `create-message`.
+
+use anyhow::{anyhow, Result};
+use dkg_core::types::KeysFile;
+use dkg_core::{KeyGroup, KeyType};
+use fastcrypto::groups::bls12381::{G1Element, G2Element, Scalar as G2Scalar};
+use rand::rngs::StdRng;
+use seal_committee::{
+    fetch_committee_data, fetch_rotation_context, CommitteeFetcher, CommitteeValidator,
+    RotationPlan, SealCommittee,
+};
+use serde::Serialize;
+use std::path::Path;
+use std::time::Duration;
+use sui_sdk_types::Address;
+
+use crate::ceremony::{create_message, CreatedMessage};
+use crate::context::{status, Context};
+use crate::errors::CliError;
+use crate::progress::with_spinner;
+use crate::snapshot::CommitteeSnapshot;
+use crate::{fetch_committee_key_server, with_rpc_timeout};
+
+/// Run `rotate-keys`: fetch the new committee of `committee_id` and its old committee, check the
+/// rotation and this member's role in it unless `skip_validation` is set, and create the message
+/// as `create-message` does.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn rotate_keys(
+    ctx: &Context,
+    fetcher: &dyn CommitteeFetcher,
+    committee_id: &Address,
+    old_committee_id: Option<Address>,
+    my_address: &Address,
+    local_keys: &KeysFile,
+    my_old_share: Option<G2Scalar>,
+    skip_validation: bool,
+    state_dir: &Path,
+    rpc_timeout: Option<Duration>,
+    rng: &mut StdRng,
+) -> Result<CreatedMessage> {
+    let (committee, old_snapshot) =
+        fetch_rotation(ctx, fetcher, committee_id, old_committee_id, rpc_timeout).await?;
+    let key_type = old_snapshot.key_type().map_err(CliError::Validation)?;
+
+    match old_snapshot
+        .members
+        .iter()
+        .position(|member| member == my_address)
+    {
+        Some(old_party_id) => {
+            status!(
+                ctx,
+                "Rotation path: continuing member (old party ID {old_party_id})."
+            )
+        }
+        None => status!(ctx, "Rotation path: new member."),
+    }
+    if skip_validation {
+        eprintln!("WARNING: Skipping rotation pre-checks.");
+    } else {
+        match key_type {
+            KeyType::G2 => check_rotation::<G2Element>(
+                &committee,
+                &old_snapshot,
+                my_address,
+                my_old_share.as_ref(),
+            )?,
+            KeyType::G1 => check_rotation::<G1Element>(
+                &committee,
+                &old_snapshot,
+                my_address,
+                my_old_share.as_ref(),
+            )?,
+        }
+        status!(ctx, "Rotation pre-checks passed.");
+    }
+
+    status!(ctx, "Key type: {key_type}");
+    match key_type {
+        KeyType::G2 => create_message::<G2Element>(
+            ctx,
+            &committee,
+            my_address,
+            local_keys,
+            my_old_share,
+            Some(&old_snapshot),
+            None,
+            Path::new("."),
+            state_dir,
+            rng,
+        ),
+        KeyType::G1 => create_message::<G1Element>(
+            ctx,
+            &committee,
+            my_address,
+            local_keys,
+            my_old_share,
+            Some(&old_snapshot),
+            None,
+            Path::new("."),
+            state_dir,
+            rng,
+        ),
+    }
+}
+
+/// Run `validate-rotation`: fetch the new committee of `committee_id` and its old committee, and
+/// fail listing all failed checks of [rotation_failures]. With `--json`, the failures are also
+/// printed as JSON.
+pub(crate) async fn validate_rotation(
+    ctx: &Context,
+    fetcher: &dyn CommitteeFetcher,
+    committee_id: &Address,
+    old_committee_id: Option<Address>,
+    my_address: Option<&Address>,
+    rpc_timeout: Option<Duration>,
+) -> Result<()> {
+    let (committee, old_snapshot) =
+        fetch_rotation(ctx, fetcher, committee_id, old_committee_id, rpc_timeout).await?;
+    let failures = rotation_failures(&committee, &old_snapshot, my_address);
+    if ctx.json {
+        ctx.print_json_result(&RotationValidation {
+            committee_id: *committee_id,
+            old_committee_id: old_snapshot.committee_id,
+            failures: failures.iter().map(|e| e.to_string()).collect(),
+        })?;
+    }
+    if !failures.is_empty() {
+        return Err(CliError::Validation(anyhow!(
+            "Rotation from committee {} to {} failed {} check(s):\n{}",
+            old_snapshot.committee_id,
+            committee_id,
+            failures.len(),
+            format_failures(&failures)
+        ))
+        .into());
+    }
+    status!(
+        ctx,
+        "Rotation from committee {} to {committee_id} can start.",
+        old_snapshot.committee_id
+    );
+    Ok(())
+}
+
+/// Result of `validate-rotation`, printed with `--json`.
+#[derive(Serialize)]
+struct RotationValidation {
+    committee_id: Address,
+    old_committee_id: Address,
+    failures: Vec<String>,
+}
+
+/// Fetch the new committee of a key rotation and a snapshot of its old committee. With
+/// `old_committee_id` given, it is checked against the old committee of the new committee.
+async fn fetch_rotation(
+    ctx: &Context,
+    fetcher: &dyn CommitteeFetcher,
+    committee_id: &Address,
+    old_committee_id: Option<Address>,
+    rpc_timeout: Option<Duration>,
+) -> Result<(SealCommittee, CommitteeSnapshot)> {
+    // With the old committee ID given, both committees and the old key server are fetched at once.
+    let (committee, fetched_old_snapshot) = match old_committee_id {
+        Some(old_committee_id) => {
+            let context = with_rpc_timeout(
+                rpc_timeout,
+                with_spinner(
+                    format!(
+                        "Fetching committee {committee_id} and old committee {old_committee_id}"
+                    ),
+                    fetch_rotation_context(
+                        fetcher,
+                        committee_id,
+                        &old_committee_id,
+                        ctx.key_server_version,
+                    ),
+                ),
+            )
+            .await
+            .map_err(CliError::Network)?;
+            let old_snapshot =
+                CommitteeSnapshot::new(&context.old_committee, &context.old_key_server)
+                    .map_err(CliError::Validation)?;
+            (context.committee, Some(old_snapshot))
+        }
+        None => {
+            let committee = with_rpc_timeout(
+                rpc_timeout,
+                with_spinner(
+                    format!("Fetching committee {committee_id}"),
+                    fetch_committee_data(fetcher, committee_id),
+                ),
+            )
+            .await
+            .map_err(CliError::Network)?;
+            (committee, None)
+        }
+    };
+    let onchain_old_committee_id = committee.old_committee_id.ok_or_else(|| {
+        CliError::StateConflict(anyhow!(
+            "Committee {committee_id} has no old committee. Use create-message for a fresh DKG."
+        ))
+    })?;
+    if let Some(old_committee_id) = old_committee_id {
+        if old_committee_id != onchain_old_committee_id {
+            return Err(CliError::Validation(anyhow!(
+                "Committee {committee_id} rotates from {onchain_old_committee_id}, not {old_committee_id}"
+            ))
+            .into());
+        }
+    }
+    let old_snapshot = match fetched_old_snapshot {
+        Some(snapshot) => snapshot,
+        None => {
+            fetch_old_committee_snapshot(ctx, fetcher, &onchain_old_committee_id, rpc_timeout)
+                .await?
+        }
+    };
+    Ok((committee, old_snapshot))
+}
+
+/// Fetch the old committee of a key rotation and its key server with the partial public keys.
+pub(crate) async fn fetch_old_committee_snapshot(
+    ctx: &Context,
+    fetcher: &dyn CommitteeFetcher,
+    old_committee_id: &Address,
+    rpc_timeout: Option<Duration>,
+) -> Result<CommitteeSnapshot> {
+    let (old_committee, (_, old_key_server)) = with_rpc_timeout(
+        rpc_timeout,
+        with_spinner(
+            format!("Fetching old committee {old_committee_id} and its key server"),
+            async {
+                tokio::try_join!(
+                    fetcher.fetch_committee(old_committee_id),
+                    fetch_committee_key_server(ctx, fetcher, old_committee_id),
+                )
+            },
+        ),
+    )
+    .await
+    .map_err(CliError::Network)?;
+    Ok(CommitteeSnapshot::new(&old_committee, &old_key_server).map_err(CliError::Validation)?)
+}
+
+/// Checks of `validate-rotation`: the new committee passes the [CommitteeValidator] rules, the
+/// rotation from the old committee is possible, and `my_address`, if given, is a member of the new
+/// committee. Returns all failures, empty if the rotation can start.
+fn rotation_failures(
+    committee: &SealCommittee,
+    old_snapshot: &CommitteeSnapshot,
+    my_address: Option<&Address>,
+) -> Vec<anyhow::Error> {
+    let mut failures = CommitteeValidator::default().check_all(committee);
+    if let Err(e) = RotationPlan::build(committee, &old_snapshot.to_committee())
+        .and_then(|plan| plan.feasible())
+    {
+        failures.push(e);
+    }
+    if let Some(Err(e)) = my_address.map(|address| committee.get_party_id(address)) {
+        failures.push(e.into());
+    }
+    failures
+}
+
+/// Pre-checks of `rotate-keys`: the checks of [rotation_failures], and that `my_old_share` is given
+/// exactly for a continuing member and matches its partial public key in the old committee.
+fn check_rotation<G: KeyGroup>(
+    committee: &SealCommittee,
+    old_snapshot: &CommitteeSnapshot,
+    my_address: &Address,
+    my_old_share: Option<&G2Scalar>,
+) -> Result<()> {
+    let failures = rotation_failures(committee, old_snapshot, Some(my_address));
+    if !failures.is_empty() {
+        return Err(CliError::Validation(anyhow!(
+            "Rotation from committee {} to {} failed {} check(s):\n{}",
+            old_snapshot.committee_id,
+            committee.id,
+            failures.len(),
+            format_failures(&failures)
+        ))
+        .into());
+    }
+
+    let old_party_id = old_snapshot
+        .members
+        .iter()
+        .position(|member| member == my_address);
+    match (old_party_id, my_old_share) {
+        (Some(_), None) => Err(CliError::Usage(anyhow!(
+            "Continuing member of the old committee must provide --old-share"
+        ))
+        .into()),
+        (None, Some(_)) => Err(CliError::Usage(anyhow!(
+            "New member must not provide --old-share, it is not in the old committee"
+        ))
+        .into()),
+        (None, None) => Ok(()),
+        (Some(old_party_id), Some(share)) => {
+            let partial_pks = old_snapshot
+                .parse_partial_pks::<G>()
+                .map_err(CliError::Validation)?;
+            let partial_pk = partial_pks.get(&(old_party_id as u16)).ok_or_else(|| {
+                CliError::Validation(anyhow!(
+                    "Partial PK of old party {old_party_id} not found in old committee"
+                ))
+            })?;
+            if G::generator() * share != *partial_pk {
+                return Err(CliError::Validation(anyhow!(
+                    "--old-share does not match the partial PK of old party {old_party_id}"
+                ))
+                .into());
+            }
+            Ok(())
+        }
+    }
+}
+
+/// One failure per line, as a list.
+fn format_failures(failures: &[anyhow::Error]) -> String {
+    failures
+        .iter()
+        .map(|e| format!("- {e}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Print the rotation plan. Warns if every continuing member is required, and fails if the
+/// rotation is impossible because there are fewer continuing members than the old threshold.
+pub(crate) fn report_rotation_plan(ctx: &Context, plan: &RotationPlan) -> Result<()> {
+    print_rotation_plan(ctx, plan);
+    plan.feasible().map_err(CliError::Validation)?;
+    if plan.slack() == Some(0) {
+        eprintln!(
+            "WARNING: No slack in rotation. All {} continuing members must submit a message for process-all to succeed.",
+            plan.continuing.len()
+        );
+    }
+    Ok(())
+}
+
+/// Print the threshold and membership changes of a key rotation.
+fn print_rotation_plan(ctx: &Context, plan: &RotationPlan) {
+    status!(ctx, "Rotation summary:");
+    status!(
+        ctx,
+        "  Threshold: {}-of-{} -> {}-of-{}",
+        plan.old_threshold,
+        plan.old_size(),
+        plan.new_threshold,
+        plan.new_size()
+    );
+    status!(ctx, "  Continuing members ({}):", plan.continuing.len());
+    for member in &plan.continuing {
+        status!(
+            ctx,
+            "    {} (party {} -> {})",
+            member.address,
+            member.old_party_id,
+            member.new_party_id
+        );
+    }
+    for (label, members) in [("New", &plan.joining), ("Departing", &plan.departing)] {
+        status!(ctx, "  {label} members ({}):", members.len());
+        for member in members {
+            status!(ctx, "    {} (party {})", member.address, member.party_id);
+        }
+    }
+    status!(
+        ctx,
+        "  Required continuing members for process-all: {} of {}",
+        plan.old_threshold,
+        plan.continuing.len()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dkg_core::types::format_pk_hex;
+    use fastcrypto::groups::GroupElement;
+    use rand::SeedableRng;
+    use seal_committee::CommitteeState;
+
+    #[test]
+    fn test_check_rotation() {
+        let mut rng = StdRng::from_entropy();
+        let addresses: Vec<Address> = (1..=4).map(|i| Address::new([i; 32])).collect();
+        let old_snapshot = |threshold| CommitteeSnapshot {
+            committee_id: Address::new([9; 32]),
+            threshold,
+            members: addresses[..3].to_vec(),
+            partial_pks: (0..3u16)
+                .map(|party_id| {
+                    let partial_pk = G2Element::generator() * G2Scalar::from(party_id as u128 + 1);
+                    (party_id, format_pk_hex(&partial_pk).unwrap())
+                })
+                .collect(),
+            key_type: 0,
+            digest: String::new(),
+        };
+
+        // Old parties 1 and 2 continue, address 4 joins.
+        let members = addresses[1..].to_vec();
+        let contents = members
+            .iter()
+            .map(|address| {
+                let keys = KeysFile::generate(&mut rng);
+                sui_types::collection_types::Entry {
+                    key: *address,
+                    value: seal_committee::MemberInfo {
+                        enc_pk: keys.enc_pk,
+                        signing_pk: keys.signing_pk,
+                        url: format!("https://{address}.example.com"),
+                    },
+                }
+            })
+            .collect();
+        let committee = SealCommittee::new(
+            Address::new([10; 32]),
+            2,
+            members,
+            CommitteeState::Init {
+                members_info: seal_committee::VecMap(sui_types::collection_types::VecMap {
+                    contents,
+                }),
+            },
+            Some(Address::new([9; 32])),
+        );
+
+        let old = old_snapshot(2);
+        let continuing = &addresses[1];
+        let joining = &addresses[3];
+        let share = G2Scalar::from(2u128);
+        check_rotation::<G2Element>(&committee, &old, continuing, Some(&share)).unwrap();
+        check_rotation::<G2Element>(&committee, &old, joining, None).unwrap();
+
+        let cases = [
+            (continuing, None, "must provide --old-share"),
+            (continuing, Some(G2Scalar::from(3u128)), "does not match"),
+            (joining, Some(share), "must not provide --old-share"),
+            (&addresses[0], None, "not a member"),
+        ];
+        for (address, share, expected) in cases {
+            let err = check_rotation::<G2Element>(&committee, &old, address, share.as_ref())
+                .unwrap_err()
+                .to_string();
+            assert!(err.contains(expected), "{err}");
+        }
+
+        // Two continuing members cannot meet an old threshold of 3.
+        let err = check_rotation::<G2Element>(&committee, &old_snapshot(3), joining, None)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("found 2 continuing members, need at least 3 (old threshold)"),
+            "{err}"
+        );
+
+        // All failures are reported at once.
+        assert!(rotation_failures(&committee, &old, Some(continuing)).is_empty());
+        let failures = rotation_failures(&committee, &old_snapshot(3), Some(&addresses[0]));
+        assert_eq!(failures.len(), 2);
+        assert!(failures[0].to_string().contains("need at least 3"));
+        assert!(failures[1].to_string().contains("not a member"));
+    }
+}
@@ -0,0 +1,690 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! JSON-RPC 2.0 server for `rpc-serve --stdio`, for driving a ceremony from other languages
+//! without parsing the human-readable output.
+//!
+//! Each request and each response is one line of JSON on stdin and stdout. Status lines go to
+//! stderr. Responses never contain secrets: keys files, states and key shares are only referenced
+//! by their path.
+//!
+//! Errors of a method have the code `-32000 - exit code`, e.g. `-32005` for a complaint, and the
+//! exit code in `data.exit_code`, see [ExitCode].
+
+use crate::ceremony::{create_message, output_summary, process_and_save, read_messages_dir};
+use crate::config::check_network;
+use crate::context::Context;
+use crate::errors::{CliError, ExitCode};
+use crate::migrate::read_key_share_file;
+use crate::progress::with_spinner;
+use crate::rotation::fetch_old_committee_snapshot;
+use crate::snapshot::{CommitteeSnapshot, InitCommitteeSnapshot};
+use crate::storage::{state_key_type, DkgStateStorage, KeysFileStorage};
+use crate::{
+    create_grpc_client, load_verified_state, with_rpc_timeout, write_secret_file, MessageOrder,
+};
+use anyhow::{anyhow, Result};
+use dkg_core::types::{format_pk_hex, DkgState, KeysFile};
+use dkg_core::{DkgPhase, KeyGroup, KeyType};
+use fastcrypto::groups::bls12381::{G1Element, G2Element};
+use rand::rngs::StdRng;
+use seal_committee::{fetch_committee_data, Network, SealCommittee};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use sui_sdk_types::Address;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// Errors of a method have the code `SERVER_ERROR_BASE - exit code`.
+const SERVER_ERROR_BASE: i64 = -32000;
+
+#[derive(Deserialize)]
+struct Request {
+    jsonrpc: String,
+    /// Absent for a notification, which gets no response.
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Serialize, Debug)]
+struct RpcError {
+    code: i64,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<Value>,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    /// Error of a method, classified by its exit code.
+    fn from_error(err: &anyhow::Error) -> Self {
+        let exit_code = ExitCode::from_error(err);
+        Self {
+            code: SERVER_ERROR_BASE - exit_code as i64,
+            message: format!("{err:#}"),
+            data: Some(json!({ "exit_code": exit_code as u8 })),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GenerateKeysParams {
+    keys_file: PathBuf,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CreateMessageParams {
+    my_address: Address,
+    keys_file: PathBuf,
+    state_dir: PathBuf,
+    /// Directory to write `message_<party ID>.json` to.
+    messages_dir: PathBuf,
+    /// Committee snapshot from `export-committee`. If not given, the committee is fetched.
+    committee_snapshot: Option<PathBuf>,
+    committee_id: Option<Address>,
+    network: Option<String>,
+    /// Old committee snapshot for a key rotation. If not given, it is fetched.
+    old_committee_snapshot: Option<PathBuf>,
+    /// Old key share of a continuing member in a key rotation.
+    old_share_file: Option<PathBuf>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProcessBundleParams {
+    state_dir: PathBuf,
+    keys_file: PathBuf,
+    messages_dir: PathBuf,
+    #[serde(default)]
+    max_complaints: usize,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StatusParams {
+    state_dir: PathBuf,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct VerifyOutputParams {
+    state_dir: PathBuf,
+    network: String,
+}
+
+/// Server state shared by all requests of a session.
+pub(crate) struct RpcServer {
+    ctx: Context,
+    rpc_timeout: Option<Duration>,
+    rng: StdRng,
+}
+
+impl RpcServer {
+    pub(crate) fn new(ctx: Context, rpc_timeout: Option<Duration>, rng: StdRng) -> Self {
+        Self {
+            ctx,
+            rpc_timeout,
+            rng,
+        }
+    }
+
+    /// Answer requests from `input` on `output`, one per line, until `input` is closed.
+    pub(crate) async fn serve(
+        &mut self,
+        input: impl BufRead,
+        mut output: impl Write,
+    ) -> Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(response) = self.handle(&line).await {
+                writeln!(output, "{response}")?;
+                output.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handle one request line. Returns the response, or `None` for a notification.
+    async fn handle(&mut self, line: &str) -> Option<Value> {
+        let value: Value = match serde_json::from_str(line) {
+            Ok(value) => value,
+            Err(e) => {
+                return Some(error_response(
+                    Value::Null,
+                    RpcError::new(PARSE_ERROR, e.to_string()),
+                ))
+            }
+        };
+        let request: Request = match serde_json::from_value(value) {
+            Ok(request) => request,
+            Err(e) => {
+                return Some(error_response(
+                    Value::Null,
+                    RpcError::new(INVALID_REQUEST, e.to_string()),
+                ));
+            }
+        };
+        if request.jsonrpc != "2.0" {
+            return Some(error_response(
+                request.id.unwrap_or(Value::Null),
+                RpcError::new(INVALID_REQUEST, "Only JSON-RPC 2.0 is supported"),
+            ));
+        }
+
+        let result = self.call(&request.method, request.params).await;
+        let id = request.id?;
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(e) => error_response(id, e),
+        })
+    }
+
+    async fn call(&mut self, method: &str, params: Value) -> Result<Value, RpcError> {
+        let result = match method {
+            "generate_keys" => self.generate_keys(parse_params(params)?),
+            "create_message" => self.create_message(parse_params(params)?).await,
+            "process_bundle" => self.process_bundle(parse_params(params)?),
+            "status" => self.status(parse_params(params)?),
+            "verify_output" => self.verify_output(parse_params(params)?).await,
+            _ => {
+                return Err(RpcError::new(
+                    METHOD_NOT_FOUND,
+                    format!("Unknown method: {method}"),
+                ));
+            }
+        };
+        result.map_err(|e| RpcError::from_error(&e))
+    }
+
+    /// Generate a keys file. Only the public keys are returned.
+    fn generate_keys(&mut self, params: GenerateKeysParams) -> Result<Value> {
+        let keys = KeysFile::generate(&mut self.rng);
+        if let Some(parent) = params.keys_file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        write_secret_file(
+            &self.ctx,
+            &params.keys_file,
+            &serde_json::to_string_pretty(&keys)?,
+        )?;
+        Ok(json!({
+            "keys_file": params.keys_file,
+            "enc_pk": format_pk_hex(&keys.enc_pk)?,
+            "signing_pk": format_pk_hex(&keys.signing_pk)?,
+        }))
+    }
+
+    async fn create_message(&mut self, params: CreateMessageParams) -> Result<Value> {
+        let local_keys = KeysFile::load(&params.keys_file, &self.ctx)?;
+        let my_old_share = params
+            .old_share_file
+            .as_deref()
+            .map(|path| read_key_share_file(path, self.ctx.permissions).map_err(CliError::Usage))
+            .transpose()?;
+        let network = params
+            .network
+            .as_deref()
+            .map(|network| parse_network(network, self.ctx.allow_non_production_network))
+            .transpose()?;
+
+        let committee = match &params.committee_snapshot {
+            Some(path) => {
+                let committee = InitCommitteeSnapshot::load(path)
+                    .and_then(|snapshot| snapshot.to_committee())
+                    .map_err(CliError::Validation)?;
+                if let Some(committee_id) = params.committee_id {
+                    if committee.id != committee_id {
+                        return Err(CliError::Validation(anyhow!(
+                            "Snapshot is of committee {}, expected {}",
+                            committee.id,
+                            committee_id
+                        ))
+                        .into());
+                    }
+                }
+                committee
+            }
+            None => {
+                let committee_id = params.committee_id.ok_or_else(|| {
+                    CliError::Usage(anyhow!("committee_id or committee_snapshot is required"))
+                })?;
+                let grpc_client = create_grpc_client(&self.ctx, &require_network(&network)?)
+                    .map_err(CliError::Network)?;
+                with_rpc_timeout(
+                    self.rpc_timeout,
+                    with_spinner(
+                        format!("Fetching committee {committee_id}"),
+                        fetch_committee_data(&grpc_client, &committee_id),
+                    ),
+                )
+                .await
+                .map_err(CliError::Network)?
+            }
+        };
+        let old_snapshot = self
+            .old_committee_snapshot(&committee, params.old_committee_snapshot, &network)
+            .await?;
+
+        let key_type = match &old_snapshot {
+            Some(snapshot) => snapshot.key_type().map_err(CliError::Validation)?,
+            None => KeyType::default(),
+        };
+        fs::create_dir_all(&params.messages_dir)?;
+        let created = match key_type {
+            KeyType::G2 => create_message::<G2Element>(
+                &self.ctx,
+                &committee,
+                &params.my_address,
+                &local_keys,
+                my_old_share,
+                old_snapshot.as_ref(),
+                None,
+                &params.messages_dir,
+                &params.state_dir,
+                &mut self.rng,
+            )?,
+            KeyType::G1 => create_message::<G1Element>(
+                &self.ctx,
+                &committee,
+                &params.my_address,
+                &local_keys,
+                my_old_share,
+                old_snapshot.as_ref(),
+                None,
+                &params.messages_dir,
+                &params.state_dir,
+                &mut self.rng,
+            )?,
+        };
+        Ok(serde_json::to_value(created)?)
+    }
+
+    /// Old committee of a key rotation, from a snapshot file or fetched from `network`.
+    async fn old_committee_snapshot(
+        &self,
+        committee: &SealCommittee,
+        path: Option<PathBuf>,
+        network: &Option<Network>,
+    ) -> Result<Option<CommitteeSnapshot>> {
+        let Some(old_committee_id) = committee.old_committee_id else {
+            if path.is_some() {
+                return Err(CliError::Usage(anyhow!(
+                    "old_committee_snapshot should not be given for fresh DKG"
+                ))
+                .into());
+            }
+            return Ok(None);
+        };
+        let snapshot = match path {
+            Some(path) => {
+                let snapshot = CommitteeSnapshot::load(&path).map_err(CliError::Validation)?;
+                if snapshot.committee_id != old_committee_id {
+                    return Err(CliError::Validation(anyhow!(
+                        "Snapshot is of committee {}, expected old committee {}",
+                        snapshot.committee_id,
+                        old_committee_id
+                    ))
+                    .into());
+                }
+                snapshot
+            }
+            None => {
+                let grpc_client = create_grpc_client(&self.ctx, &require_network(network)?)
+                    .map_err(CliError::Network)?;
+                fetch_old_committee_snapshot(
+                    &self.ctx,
+                    &grpc_client,
+                    &old_committee_id,
+                    self.rpc_timeout,
+                )
+                .await?
+            }
+        };
+        Ok(Some(snapshot))
+    }
+
+    /// Process all messages in `messages_dir` and complete the protocol. The key share is stored
+    /// in the state and not returned.
+    fn process_bundle(&mut self, params: ProcessBundleParams) -> Result<Value> {
+        let local_keys = KeysFile::load(&params.keys_file, &self.ctx)?;
+        match state_key_type(&params.state_dir)? {
+            KeyType::G2 => self.process_bundle_of::<G2Element>(&params, &local_keys),
+            KeyType::G1 => self.process_bundle_of::<G1Element>(&params, &local_keys),
+        }
+    }
+
+    fn process_bundle_of<G: KeyGroup>(
+        &mut self,
+        params: &ProcessBundleParams,
+        local_keys: &KeysFile,
+    ) -> Result<Value> {
+        let mut state = DkgState::<G>::load(&params.state_dir, &self.ctx)?;
+        let messages = read_messages_dir(&params.messages_dir, MessageOrder::Name)?;
+        let (output, excluded) = process_and_save(
+            &self.ctx,
+            &params.state_dir,
+            &mut state,
+            local_keys,
+            messages,
+            params.max_complaints,
+            &mut self.rng,
+        )?;
+        output_summary(&state, &output, &excluded)
+    }
+
+    fn status(&self, params: StatusParams) -> Result<Value> {
+        match state_key_type(&params.state_dir)? {
+            KeyType::G2 => self.status_of::<G2Element>(&params.state_dir),
+            KeyType::G1 => self.status_of::<G1Element>(&params.state_dir),
+        }
+    }
+
+    fn status_of<G: KeyGroup>(&self, state_dir: &Path) -> Result<Value> {
+        let state = DkgState::<G>::load(state_dir, &self.ctx)?;
+        let phase = match state.phase() {
+            DkgPhase::Initialized => "initialized",
+            DkgPhase::MessagesProcessed => "messages_processed",
+            DkgPhase::Merged => "merged",
+            DkgPhase::Completed => "completed",
+        };
+        let key_server_pk = state
+            .output
+            .as_ref()
+            .map(|output| format_pk_hex(output.vss_pk.c0()))
+            .transpose()?;
+        Ok(json!({
+            "committee_id": state.config.committee_id,
+            "party_id": state.config.my_party_id,
+            "key_type": G::KEY_TYPE as u8,
+            "phase": phase,
+            "processed_messages": state.processed_messages.len(),
+            "required_processed_messages": state.required_processed_messages(),
+            "key_server_pk": key_server_pk,
+        }))
+    }
+
+    async fn verify_output(&mut self, params: VerifyOutputParams) -> Result<Value> {
+        let network = parse_network(&params.network, self.ctx.allow_non_production_network)?;
+        let state_dir = &params.state_dir;
+        let committee_id = match state_key_type(state_dir)? {
+            KeyType::G2 => {
+                load_verified_state::<G2Element>(&self.ctx, state_dir, &network, self.rpc_timeout)
+                    .await?
+                    .config
+                    .committee_id
+            }
+            KeyType::G1 => {
+                load_verified_state::<G1Element>(&self.ctx, state_dir, &network, self.rpc_timeout)
+                    .await?
+                    .config
+                    .committee_id
+            }
+        };
+        Ok(json!({
+            "committee_id": committee_id,
+            "verified": true,
+        }))
+    }
+}
+
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|e| RpcError {
+        code: INVALID_PARAMS,
+        message: format!("Invalid params: {e}"),
+        data: Some(json!({ "exit_code": ExitCode::Usage as u8 })),
+    })
+}
+
+fn parse_network(network: &str, allow_non_production: bool) -> Result<Network> {
+    let network = Network::from_str(network).map_err(|e| CliError::Usage(anyhow!(e)))?;
+    check_network(&network, allow_non_production)?;
+    Ok(network)
+}
+
+fn require_network(network: &Option<Network>) -> Result<Network> {
+    network
+        .clone()
+        .ok_or_else(|| CliError::Usage(anyhow!("network is required to fetch from chain")).into())
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": error })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::MemberSnapshot;
+    use rand::SeedableRng;
+    use tempfile::TempDir;
+
+    /// Client sending requests to a [RpcServer] over the line protocol.
+    struct TestClient {
+        server: RpcServer,
+        next_id: u64,
+        /// All response lines, to check that no secret was returned.
+        transcript: String,
+    }
+
+    impl TestClient {
+        fn new() -> Self {
+            Self {
+                server: RpcServer::new(
+                    Context {
+                        json: true,
+                        ..Context::for_testing()
+                    },
+                    None,
+                    StdRng::from_entropy(),
+                ),
+                next_id: 0,
+                transcript: String::new(),
+            }
+        }
+
+        async fn send_line(&mut self, line: &str) -> Option<Value> {
+            let mut output = Vec::new();
+            self.server
+                .serve(format!("{line}\n").as_bytes(), &mut output)
+                .await
+                .unwrap();
+            let output = String::from_utf8(output).unwrap();
+            self.transcript.push_str(&output);
+            (!output.is_empty()).then(|| serde_json::from_str(&output).unwrap())
+        }
+
+        async fn request(&mut self, method: &str, params: Value) -> Value {
+            self.next_id += 1;
+            let request = json!({
+                "jsonrpc": "2.0",
+                "id": self.next_id,
+                "method": method,
+                "params": params,
+            });
+            let response = self.send_line(&request.to_string()).await.unwrap();
+            assert_eq!(response["id"], self.next_id);
+            response
+        }
+
+        async fn call(&mut self, method: &str, params: Value) -> Value {
+            let response = self.request(method, params).await;
+            assert!(response.get("error").is_none(), "{response}");
+            response["result"].clone()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_simulated_ceremony() {
+        let dir = TempDir::new().unwrap();
+        let messages_dir = dir.path().join("messages");
+        let party_dir = |party_id: usize| dir.path().join(format!("party_{party_id}"));
+        let mut client = TestClient::new();
+
+        let mut members_info = Vec::new();
+        for party_id in 0..3 {
+            let keys = client
+                .call(
+                    "generate_keys",
+                    json!({ "keys_file": party_dir(party_id).join("dkg.key") }),
+                )
+                .await;
+            members_info.push(MemberSnapshot {
+                address: Address::new([party_id as u8 + 1; 32]),
+                enc_pk: keys["enc_pk"].as_str().unwrap().to_string(),
+                signing_pk: keys["signing_pk"].as_str().unwrap().to_string(),
+                url: format!("https://party-{party_id}.example.com"),
+            });
+        }
+        let committee_id = Address::new([9; 32]);
+        let snapshot_path = dir.path().join("committee.json");
+        let snapshot = InitCommitteeSnapshot {
+            committee_id,
+            threshold: 2,
+            members: members_info.iter().map(|member| member.address).collect(),
+            old_committee_id: None,
+            members_info,
+        };
+        fs::write(&snapshot_path, serde_json::to_string(&snapshot).unwrap()).unwrap();
+
+        for (party_id, address) in snapshot.members.iter().enumerate() {
+            let created = client
+                .call(
+                    "create_message",
+                    json!({
+                        "my_address": address,
+                        "keys_file": party_dir(party_id).join("dkg.key"),
+                        "state_dir": party_dir(party_id).join("state"),
+                        "messages_dir": messages_dir,
+                        "committee_snapshot": snapshot_path,
+                    }),
+                )
+                .await;
+            assert_eq!(created["party_id"], party_id);
+            assert!(messages_dir
+                .join(format!("message_{party_id}.json"))
+                .exists());
+        }
+        let status = client
+            .call("status", json!({ "state_dir": party_dir(0).join("state") }))
+            .await;
+        assert_eq!(status["phase"], "initialized");
+        assert_eq!(status["key_server_pk"], Value::Null);
+
+        let mut key_server_pks = Vec::new();
+        for party_id in 0..3 {
+            let output = client
+                .call(
+                    "process_bundle",
+                    json!({
+                        "state_dir": party_dir(party_id).join("state"),
+                        "keys_file": party_dir(party_id).join("dkg.key"),
+                        "messages_dir": messages_dir,
+                    }),
+                )
+                .await;
+            assert_eq!(output["committee_id"], json!(committee_id));
+            assert_eq!(output["excluded_parties"], json!([]));
+            key_server_pks.push(output["key_server_pk"].clone());
+
+            let status = client
+                .call(
+                    "status",
+                    json!({ "state_dir": party_dir(party_id).join("state") }),
+                )
+                .await;
+            assert_eq!(status["phase"], "completed");
+            assert_eq!(status["key_server_pk"], output["key_server_pk"]);
+        }
+        assert!(key_server_pks.iter().all(|pk| *pk == key_server_pks[0]));
+
+        // Neither the secret keys nor the key shares are returned.
+        for party_id in 0..3 {
+            let keys: Value = serde_json::from_str(
+                &fs::read_to_string(party_dir(party_id).join("dkg.key")).unwrap(),
+            )
+            .unwrap();
+            for secret in ["enc_sk", "signing_sk"] {
+                let secret = keys[secret].as_str().unwrap();
+                assert!(!client.transcript.contains(secret));
+            }
+            let state = DkgState::<G2Element>::load(
+                &party_dir(party_id).join("state"),
+                &Context::for_testing(),
+            )
+            .unwrap();
+            for share in state.output.unwrap().shares.unwrap() {
+                let share = format_pk_hex(&share.value).unwrap();
+                assert!(!client.transcript.contains(&share[2..]));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_errors() {
+        let dir = TempDir::new().unwrap();
+        let mut client = TestClient::new();
+
+        let response = client.send_line("not json").await.unwrap();
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+        assert_eq!(response["id"], Value::Null);
+
+        let response = client
+            .send_line(r#"{"id": 1, "method": "status"}"#)
+            .await
+            .unwrap();
+        assert_eq!(response["error"]["code"], INVALID_REQUEST);
+
+        let response = client.request("unknown", json!({})).await;
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+        let response = client
+            .request("status", json!({ "state_dir": dir.path(), "extra": 1 }))
+            .await;
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+        assert_eq!(response["error"]["data"]["exit_code"], 2);
+
+        // Errors of a method carry the exit code.
+        let response = client
+            .request(
+                "verify_output",
+                json!({ "state_dir": dir.path(), "network": "regtest" }),
+            )
+            .await;
+        assert_eq!(response["error"]["code"], -32002);
+        assert_eq!(
+            response["error"]["data"]["exit_code"],
+            ExitCode::Usage as u8
+        );
+
+        // A notification gets no response.
+        let notification = json!({
+            "jsonrpc": "2.0",
+            "method": "status",
+            "params": { "state_dir": dir.path() },
+        });
+        assert!(client.send_line(&notification.to_string()).await.is_none());
+    }
+}
@@ -0,0 +1,407 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Local end-to-end simulation of a DKG ceremony and an optional key rotation, for rehearsing a
+//! ceremony without any network access or chain state.
+
+use crate::ceremony::{print_public_keys, process_messages, read_messages_dir, save_message_file};
+use crate::context::Context;
+use crate::errors::CliError;
+use crate::permissions::PermissionPolicy;
+use crate::rotation::report_rotation_plan;
+use crate::storage::{DkgStateStorage, KeysFileStorage};
+use crate::{write_secret_file, MessageOrder};
+use anyhow::{anyhow, Result};
+use dkg_core::types::{DkgState, KeysFile};
+use dkg_core::{prepare_ceremony, OldCommittee};
+use fastcrypto::groups::bls12381::{G2Element, Scalar as G2Scalar};
+use fastcrypto::groups::GroupElement;
+use fastcrypto_tbls::dkg_v1::Output;
+use rand::rngs::StdRng;
+use rand::RngCore;
+use seal_committee::{CommitteeState, MemberInfo, RotationPlan, SealCommittee, VecMap};
+use std::collections::HashMap;
+use std::fs;
+use std::num::NonZeroU16;
+use std::path::{Path, PathBuf};
+use sui_sdk_types::Address;
+use sui_types::collection_types::Entry;
+
+/// Context of the simulation. There is no `--json`, everything is printed to stdout, and the
+/// files of the simulated parties must not be accessible by others.
+fn context() -> Context {
+    Context {
+        permissions: PermissionPolicy::Strict,
+        ..Context::default()
+    }
+}
+
+/// A simulated committee member with its own directory for the keys file and the state.
+struct SimulatedParty {
+    address: Address,
+    dir: PathBuf,
+}
+
+impl SimulatedParty {
+    /// Generate keys for a new party and write them to `party_<index>/dkg.key`.
+    fn new(base_dir: &Path, index: usize, rng: &mut StdRng) -> Result<Self> {
+        let party = Self {
+            address: random_address(rng),
+            dir: base_dir.join(format!("party_{index}")),
+        };
+        fs::create_dir_all(&party.dir)?;
+        let keys = KeysFile::generate(rng);
+        write_secret_file(
+            &context(),
+            &party.keys_file(),
+            &serde_json::to_string_pretty(&keys)?,
+        )?;
+        Ok(party)
+    }
+
+    fn keys_file(&self) -> PathBuf {
+        self.dir.join("dkg.key")
+    }
+
+    fn state_dir(&self) -> PathBuf {
+        self.dir.join("state")
+    }
+}
+
+/// Old committee and the outputs of its members, for a key rotation.
+struct OldCeremony<'a> {
+    committee: &'a SealCommittee,
+    outputs: &'a HashMap<Address, Output<G2Element, G2Element>>,
+}
+
+impl<'a> OldCeremony<'a> {
+    /// Old committee with its partial public keys, as published onchain when it was finalized.
+    fn old_committee(&self) -> Result<OldCommittee<'a>> {
+        let old_vss_pk = &any_output(self.outputs)?.vss_pk;
+        Ok(OldCommittee {
+            committee: self.committee,
+            partial_pks: (0..self.committee.members.len() as u16)
+                .map(|party_id| (party_id, old_vss_pk.eval(share_index(party_id)).value))
+                .collect(),
+            digest: None,
+        })
+    }
+}
+
+/// Run a fresh DKG with `num_parties` simulated parties, and if `rotate` is set, a follow-up key
+/// rotation where one member joins and, if the threshold allows it, one member leaves. Every step
+/// uses the same code as the real commands, but with a locally fabricated committee.
+pub(crate) fn simulate(
+    num_parties: u16,
+    threshold: u16,
+    rotate: bool,
+    rng: &mut StdRng,
+) -> Result<()> {
+    if threshold == 0 || threshold > num_parties {
+        return Err(CliError::Usage(anyhow!(
+            "Threshold must be between 1 and the number of parties ({num_parties}), got {threshold}"
+        ))
+        .into());
+    }
+    let base_dir = tempfile::Builder::new()
+        .prefix("dkg-cli-simulate-")
+        .tempdir()?;
+    println!("Simulating in {}", base_dir.path().display());
+
+    let parties = (0..num_parties as usize)
+        .map(|index| SimulatedParty::new(base_dir.path(), index, rng))
+        .collect::<Result<Vec<_>>>()?;
+    let committee = fabricate_committee(&parties, threshold, None, rng)?;
+    println!("\n=== Fresh DKG ===");
+    let outputs = run_ceremony(&committee, &parties, None, base_dir.path(), rng)?;
+
+    if rotate {
+        let joining = SimulatedParty::new(base_dir.path(), parties.len(), rng)?;
+        // One member leaves if enough members continue to meet the old threshold.
+        let departing = usize::from(num_parties > threshold);
+        let mut new_parties = vec![joining];
+        new_parties.extend(parties.into_iter().skip(departing));
+        let new_committee = fabricate_committee(&new_parties, threshold, Some(committee.id), rng)?;
+
+        println!("\n=== Key rotation ===");
+        let old = OldCeremony {
+            committee: &committee,
+            outputs: &outputs,
+        };
+        run_ceremony(
+            &new_committee,
+            &new_parties,
+            Some(old),
+            base_dir.path(),
+            rng,
+        )?;
+    }
+
+    println!("\nSimulation completed successfully.");
+    Ok(())
+}
+
+/// Run all phases of a ceremony for every member of `committee`, as `create-message` and
+/// `process-all` would, and check that all members derive the same output.
+fn run_ceremony(
+    committee: &SealCommittee,
+    parties: &[SimulatedParty],
+    old: Option<OldCeremony<'_>>,
+    base_dir: &Path,
+    rng: &mut StdRng,
+) -> Result<HashMap<Address, Output<G2Element, G2Element>>> {
+    let ctx = context();
+    println!(
+        "Simulated committee {} with {} members, threshold: {}",
+        committee.id,
+        committee.members.len(),
+        committee.threshold
+    );
+    if let Some(old) = &old {
+        let plan = RotationPlan::build(committee, old.committee).map_err(CliError::Validation)?;
+        report_rotation_plan(&ctx, &plan)?;
+    }
+    let members_info = committee
+        .get_members_info()
+        .map_err(|e| CliError::Validation(e.into()))?;
+
+    // Phase 2: every member creates its state, and its message unless it is a new member.
+    let messages_dir = base_dir.join(format!("messages_{}", committee.id));
+    fs::create_dir_all(&messages_dir)?;
+    for party in parties {
+        let my_old_share = match &old {
+            Some(old) => old.outputs.get(&party.address).map(key_share).transpose()?,
+            None => None,
+        };
+        let old_committee = old.as_ref().map(OldCeremony::old_committee).transpose()?;
+        let local_keys = KeysFile::load(&party.keys_file(), &ctx)?;
+        let (state, signed_message) = prepare_ceremony(
+            committee,
+            &party.address,
+            &local_keys,
+            my_old_share,
+            old_committee,
+            None,
+            rng,
+        )?;
+        if let Some(signed_message) = signed_message {
+            save_message_file(&messages_dir, &signed_message)?;
+        }
+        state.save(&party.state_dir(), &ctx)?;
+    }
+
+    // Phase 3: every member processes all messages.
+    let mut outputs = HashMap::new();
+    for party in parties {
+        println!("Party {}:", members_info[&party.address].party_id);
+        let state_dir = party.state_dir();
+        let mut state = DkgState::<G2Element>::load(&state_dir, &ctx)?;
+        let local_keys = KeysFile::load(&party.keys_file(), &ctx)?;
+        let messages = read_messages_dir(&messages_dir, MessageOrder::Name)?;
+        let (output, _) = process_messages(&ctx, &mut state, &local_keys, messages, 0, rng)?;
+        state.save(&state_dir, &ctx)?;
+        outputs.insert(party.address, output);
+    }
+
+    verify_outputs(committee, &outputs, old.as_ref())?;
+    print_public_keys(&ctx, any_output(&outputs)?, committee.members.len())?;
+    Ok(outputs)
+}
+
+/// Check that all members derived the same VSS polynomial, that each key share matches it, and
+/// for a key rotation that the key server public key is unchanged.
+fn verify_outputs(
+    committee: &SealCommittee,
+    outputs: &HashMap<Address, Output<G2Element, G2Element>>,
+    old: Option<&OldCeremony<'_>>,
+) -> Result<()> {
+    let vss_pk = &any_output(outputs)?.vss_pk;
+    for (party_id, address) in committee.members.iter().enumerate() {
+        let output = outputs
+            .get(address)
+            .ok_or_else(|| anyhow!("Missing output of party {party_id}"))?;
+        if output.vss_pk != *vss_pk {
+            return Err(CliError::Verification(anyhow!(
+                "Party {party_id} derived a different VSS polynomial"
+            ))
+            .into());
+        }
+        let share = key_share(output)?;
+        if G2Element::generator() * share != vss_pk.eval(share_index(party_id as u16)).value {
+            return Err(CliError::Verification(anyhow!(
+                "Key share of party {party_id} does not match the VSS polynomial"
+            ))
+            .into());
+        }
+    }
+    if let Some(old) = old {
+        if any_output(old.outputs)?.vss_pk.c0() != vss_pk.c0() {
+            return Err(CliError::Verification(anyhow!(
+                "Key rotation changed the key server public key"
+            ))
+            .into());
+        }
+    }
+    println!(
+        "All {} parties derived the same VSS polynomial.",
+        outputs.len()
+    );
+    Ok(())
+}
+
+/// Committee in Init state where all parties have registered the public keys from their keys
+/// files.
+fn fabricate_committee(
+    parties: &[SimulatedParty],
+    threshold: u16,
+    old_committee_id: Option<Address>,
+    rng: &mut StdRng,
+) -> Result<SealCommittee> {
+    let contents = parties
+        .iter()
+        .enumerate()
+        .map(|(index, party)| {
+            let keys = KeysFile::load(&party.keys_file(), &context())?;
+            Ok(Entry {
+                key: party.address,
+                value: MemberInfo {
+                    enc_pk: keys.enc_pk,
+                    signing_pk: keys.signing_pk,
+                    url: format!("https://party-{index}.example.com"),
+                },
+            })
+        })
+        .collect::<Result<_>>()?;
+    Ok(SealCommittee::new(
+        random_address(rng),
+        threshold,
+        parties.iter().map(|party| party.address).collect(),
+        CommitteeState::Init {
+            members_info: VecMap(sui_types::collection_types::VecMap { contents }),
+        },
+        old_committee_id,
+    ))
+}
+
+fn random_address(rng: &mut StdRng) -> Address {
+    let mut bytes = [0u8; 32];
+    rng.fill_bytes(&mut bytes);
+    Address::new(bytes)
+}
+
+/// Share index of a party, each party has weight 1.
+fn share_index(party_id: u16) -> NonZeroU16 {
+    NonZeroU16::new(party_id + 1).expect("must be valid")
+}
+
+fn key_share(output: &Output<G2Element, G2Element>) -> Result<G2Scalar> {
+    output
+        .shares
+        .as_ref()
+        .and_then(|shares| shares.first())
+        .map(|share| share.value)
+        .ok_or_else(|| anyhow!("Missing key share in output"))
+}
+
+fn any_output(
+    outputs: &HashMap<Address, Output<G2Element, G2Element>>,
+) -> Result<&Output<G2Element, G2Element>> {
+    outputs.values().next().ok_or_else(|| anyhow!("No outputs"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dkg_core::recover_master_key;
+    use rand::SeedableRng;
+    use tempfile::TempDir;
+
+    fn new_parties(
+        base_dir: &Path,
+        indices: std::ops::Range<usize>,
+        rng: &mut StdRng,
+    ) -> Vec<SimulatedParty> {
+        indices
+            .map(|index| SimulatedParty::new(base_dir, index, rng).unwrap())
+            .collect()
+    }
+
+    /// Key shares by party ID in `committee`.
+    fn key_shares(
+        committee: &SealCommittee,
+        outputs: &HashMap<Address, Output<G2Element, G2Element>>,
+    ) -> HashMap<u16, G2Scalar> {
+        committee
+            .members
+            .iter()
+            .enumerate()
+            .map(|(party_id, address)| (party_id as u16, key_share(&outputs[address]).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_fresh_dkg() {
+        let dir = TempDir::new().unwrap();
+        let mut rng = StdRng::seed_from_u64(0);
+        let parties = new_parties(dir.path(), 0..5, &mut rng);
+        let committee = fabricate_committee(&parties, 3, None, &mut rng).unwrap();
+        let outputs = run_ceremony(&committee, &parties, None, dir.path(), &mut rng).unwrap();
+
+        assert_eq!(outputs.len(), 5);
+        let vss_pk = &any_output(&outputs).unwrap().vss_pk;
+        assert!(outputs.values().all(|output| output.vss_pk == *vss_pk));
+        // Any threshold of the shares reconstructs the same master key.
+        let mut shares = key_shares(&committee, &outputs);
+        let master_key = recover_master_key(vss_pk, &shares).unwrap();
+        shares.retain(|party_id, _| *party_id >= 2);
+        assert_eq!(recover_master_key(vss_pk, &shares).unwrap(), master_key);
+    }
+
+    #[test]
+    fn test_key_rotation() {
+        let dir = TempDir::new().unwrap();
+        let mut rng = StdRng::seed_from_u64(1);
+        let old_parties = new_parties(dir.path(), 0..5, &mut rng);
+        let old_committee = fabricate_committee(&old_parties, 3, None, &mut rng).unwrap();
+        let old_outputs =
+            run_ceremony(&old_committee, &old_parties, None, dir.path(), &mut rng).unwrap();
+        let old_vss_pk = any_output(&old_outputs).unwrap().vss_pk.clone();
+        let master_key =
+            recover_master_key(&old_vss_pk, &key_shares(&old_committee, &old_outputs)).unwrap();
+
+        // Parties 0 and 3 leave, two members join, and continuing members get new party IDs.
+        let mut old_parties = old_parties.into_iter().map(Some).collect::<Vec<_>>();
+        let mut parties = new_parties(dir.path(), 5..7, &mut rng);
+        parties.insert(1, old_parties[4].take().unwrap());
+        parties.push(old_parties[1].take().unwrap());
+        parties.push(old_parties[2].take().unwrap());
+        let departing: Vec<_> = old_parties.into_iter().flatten().collect();
+        assert_eq!(departing.len(), 2);
+
+        let committee = fabricate_committee(&parties, 3, Some(old_committee.id), &mut rng).unwrap();
+        let old = OldCeremony {
+            committee: &old_committee,
+            outputs: &old_outputs,
+        };
+        let outputs = run_ceremony(&committee, &parties, Some(old), dir.path(), &mut rng).unwrap();
+
+        // The aggregate key is preserved.
+        let vss_pk = &any_output(&outputs).unwrap().vss_pk;
+        assert_eq!(vss_pk.c0(), old_vss_pk.c0());
+        assert!(departing
+            .iter()
+            .all(|party| !outputs.contains_key(&party.address)));
+
+        // New members received valid shares, and a threshold of new shares that includes them
+        // reconstructs the same master key.
+        let mut shares = key_shares(&committee, &outputs);
+        for party_id in [0u16, 2] {
+            assert_eq!(
+                G2Element::generator() * shares[&party_id],
+                vss_pk.eval(share_index(party_id)).value
+            );
+        }
+        shares.retain(|party_id, _| [0, 2, 4].contains(party_id));
+        assert_eq!(recover_master_key(vss_pk, &shares).unwrap(), master_key);
+    }
+}
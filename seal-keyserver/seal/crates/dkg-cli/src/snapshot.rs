@@ -0,0 +1,428 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Snapshots of committees for running `create-message` offline: of an old committee for key
+//! rotation, and of the current committee in Init state. Both are read from the
+//! [seal_committee::CommitteeSnapshot] written by `export-committee`, or from the files of the
+//! same name written by earlier versions.
+
+use anyhow::{anyhow, Result};
+use dkg_core::types::format_pk_hex;
+use dkg_core::{KeyGroup, KeyType};
+use fastcrypto::encoding::{Encoding, Hex};
+use fastcrypto::hash::{Blake2b256, HashFunction};
+use seal_committee::{CommitteeState, KeyServerV2, MemberInfo, SealCommittee, ServerType, VecMap};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use sui_sdk_types::Address;
+use sui_types::collection_types::Entry;
+
+/// Old committee data needed for key rotation, exported by `export-committee` so that
+/// `create-message` can run without fetching the old committee.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CommitteeSnapshot {
+    /// Committee object ID.
+    pub committee_id: Address,
+    /// Threshold of the committee.
+    pub threshold: u16,
+    /// Member addresses, ordered by party ID.
+    pub members: Vec<Address>,
+    /// Partial public keys (hex-encoded BCS) by party ID, from the committee's key server.
+    pub partial_pks: BTreeMap<u16, String>,
+    /// Key type of the committee's key server. Snapshots without it are of key type 0.
+    #[serde(default)]
+    pub key_type: u8,
+    /// Digest of all fields above. Stored in the DKG state so that parties can check they
+    /// rotated against the same old committee.
+    pub digest: String,
+}
+
+impl CommitteeSnapshot {
+    /// Create a snapshot from a committee and its key server.
+    pub fn new(committee: &SealCommittee, key_server: &KeyServerV2) -> Result<Self> {
+        let ServerType::Committee {
+            partial_key_servers,
+            ..
+        } = &key_server.server_type
+        else {
+            return Err(anyhow!("KeyServer is not of type Committee"));
+        };
+        let partial_pks = partial_key_servers
+            .0
+            .contents
+            .iter()
+            .map(|entry| {
+                (
+                    entry.value.party_id,
+                    Hex::encode_with_format(&entry.value.partial_pk),
+                )
+            })
+            .collect();
+        let mut snapshot = Self {
+            committee_id: committee.id,
+            threshold: committee.threshold,
+            members: committee.members.clone(),
+            partial_pks,
+            key_type: key_server.key_type,
+            digest: String::new(),
+        };
+        snapshot.digest = snapshot.compute_digest()?;
+        Ok(snapshot)
+    }
+
+    /// Create a snapshot of the 1-of-1 old committee that an independent key server is migrated
+    /// from. `member` holds the master key as its share, so its partial public key is the key
+    /// server's public key. The committee ID is the key server object ID.
+    pub fn from_independent(
+        key_server_id: Address,
+        member: Address,
+        key_server: &KeyServerV2,
+    ) -> Result<Self> {
+        if !matches!(key_server.server_type, ServerType::Independent { .. }) {
+            return Err(anyhow!("KeyServer is not of type Independent"));
+        }
+        let mut snapshot = Self {
+            committee_id: key_server_id,
+            threshold: 1,
+            members: vec![member],
+            partial_pks: BTreeMap::from([(0, Hex::encode_with_format(&key_server.pk))]),
+            key_type: key_server.key_type,
+            digest: String::new(),
+        };
+        snapshot.digest = snapshot.compute_digest()?;
+        Ok(snapshot)
+    }
+
+    /// Create a snapshot from the snapshot of a finalized committee and its key server.
+    pub fn from_snapshot(snapshot: &seal_committee::CommitteeSnapshot) -> Result<Self> {
+        let committee = snapshot.committee()?;
+        let key_server = snapshot.key_server.as_ref().ok_or_else(|| {
+            anyhow!(
+                "Committee {} is not finalized. Current state: {}",
+                committee.id,
+                committee.state.name()
+            )
+        })?;
+        Self::new(&committee, &key_server.key_server()?)
+    }
+
+    /// Load a snapshot from a JSON file and check its digest.
+    pub fn load(path: &Path) -> Result<Self> {
+        let snapshot: Self = match load_snapshot_file(path)? {
+            SnapshotFile::Committee(snapshot) => return Self::from_snapshot(&snapshot),
+            SnapshotFile::Other(snapshot) => snapshot,
+        };
+        let digest = snapshot.compute_digest()?;
+        if digest != snapshot.digest {
+            return Err(anyhow!(
+                "Snapshot {} digest mismatch: expected {}, computed {}",
+                path.display(),
+                snapshot.digest,
+                digest
+            ));
+        }
+        Ok(snapshot)
+    }
+
+    /// Blake2b256 digest of the BCS bytes of all fields except the digest itself. The key type is
+    /// only included if not 0, so that digests of snapshots from before it was added are unchanged.
+    fn compute_digest(&self) -> Result<String> {
+        let mut bytes = bcs::to_bytes(&(
+            &self.committee_id,
+            self.threshold,
+            &self.members,
+            &self.partial_pks,
+        ))?;
+        if self.key_type != 0 {
+            bytes.push(self.key_type);
+        }
+        Ok(Hex::encode_with_format(Blake2b256::digest(&bytes).digest))
+    }
+
+    /// Key type of the committee's key server, which selects the DKG instantiation.
+    pub fn key_type(&self) -> Result<KeyType> {
+        KeyType::try_from(self.key_type)
+    }
+
+    /// Partial public keys by party ID.
+    pub fn parse_partial_pks<G: KeyGroup>(&self) -> Result<HashMap<u16, G>> {
+        self.partial_pks
+            .iter()
+            .map(|(party_id, hex)| {
+                let partial_pk = Hex::decode(hex)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|bytes| Ok(bcs::from_bytes(&bytes)?))
+                    .map_err(|e| anyhow!("Invalid partial PK for party {party_id}: {e}"))?;
+                Ok((*party_id, partial_pk))
+            })
+            .collect()
+    }
+
+    /// The committee as needed for computing the new-to-old party mapping. The state is not part
+    /// of the snapshot and is set to finalized.
+    pub fn to_committee(&self) -> SealCommittee {
+        SealCommittee::new(
+            self.committee_id,
+            self.threshold,
+            self.members.clone(),
+            CommitteeState::Finalized,
+            None,
+        )
+    }
+}
+
+/// Committee in Init state with the keys registered by its members, exported by
+/// `export-committee` so that `create-message --committee-snapshot` can run without fetching the
+/// committee.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct InitCommitteeSnapshot {
+    /// Committee object ID.
+    pub committee_id: Address,
+    /// Threshold of the committee.
+    pub threshold: u16,
+    /// Member addresses, ordered by party ID.
+    pub members: Vec<Address>,
+    /// Old committee object ID, for key rotation.
+    pub old_committee_id: Option<Address>,
+    /// Registered keys of the members who have registered.
+    pub members_info: Vec<MemberSnapshot>,
+}
+
+/// Keys and URL registered by a member.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MemberSnapshot {
+    pub address: Address,
+    /// Hex-encoded BCS encryption public key.
+    pub enc_pk: String,
+    /// Hex-encoded BCS signing public key.
+    pub signing_pk: String,
+    pub url: String,
+}
+
+impl InitCommitteeSnapshot {
+    /// Create a snapshot of a committee in Init state.
+    pub fn new(committee: &SealCommittee) -> Result<Self> {
+        let CommitteeState::Init { members_info } = &committee.state else {
+            return Err(anyhow!(
+                "Committee {} is not in Init state. Current state: {}",
+                committee.id,
+                committee.state
+            ));
+        };
+        let members_info = members_info
+            .0
+            .contents
+            .iter()
+            .map(|entry| {
+                Ok(MemberSnapshot {
+                    address: entry.key,
+                    enc_pk: format_pk_hex(&entry.value.enc_pk)?,
+                    signing_pk: format_pk_hex(&entry.value.signing_pk)?,
+                    url: entry.value.url.clone(),
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            committee_id: committee.id,
+            threshold: committee.threshold,
+            members: committee.members.clone(),
+            old_committee_id: committee.old_committee_id,
+            members_info,
+        })
+    }
+
+    /// Load a snapshot from a JSON file.
+    pub fn load(path: &Path) -> Result<Self> {
+        match load_snapshot_file(path)? {
+            SnapshotFile::Committee(snapshot) => Self::new(&snapshot.committee()?),
+            SnapshotFile::Other(snapshot) => Ok(snapshot),
+        }
+    }
+
+    /// The committee in Init state with the registered keys.
+    pub fn to_committee(&self) -> Result<SealCommittee> {
+        let contents = self
+            .members_info
+            .iter()
+            .map(|member| {
+                Ok(Entry {
+                    key: member.address,
+                    value: MemberInfo {
+                        enc_pk: parse_hex(&member.enc_pk).map_err(|e| {
+                            anyhow!("Invalid encryption PK of member {}: {e}", member.address)
+                        })?,
+                        signing_pk: parse_hex(&member.signing_pk).map_err(|e| {
+                            anyhow!("Invalid signing PK of member {}: {e}", member.address)
+                        })?,
+                        url: member.url.clone(),
+                    },
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(SealCommittee::new(
+            self.committee_id,
+            self.threshold,
+            self.members.clone(),
+            CommitteeState::Init {
+                members_info: VecMap(sui_types::collection_types::VecMap { contents }),
+            },
+            self.old_committee_id,
+        ))
+    }
+}
+
+/// Load the committee of a [seal_committee::CommitteeSnapshot] written by `export-committee`, in
+/// any state.
+pub fn load_committee(path: &Path) -> Result<SealCommittee> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read snapshot {}: {}", path.display(), e))?;
+    seal_committee::CommitteeSnapshot::from_json(&content)
+        .and_then(|snapshot| snapshot.committee())
+        .map_err(|e| anyhow!("Invalid snapshot {}: {e:#}", path.display()))
+}
+
+fn parse_hex<T: DeserializeOwned>(hex: &str) -> Result<T> {
+    Ok(bcs::from_bytes(&Hex::decode(hex)?)?)
+}
+
+/// A snapshot file as written by `export-committee`, or in a format of earlier versions.
+enum SnapshotFile<T> {
+    Committee(seal_committee::CommitteeSnapshot),
+    Other(T),
+}
+
+/// Read a snapshot file. A [seal_committee::CommitteeSnapshot] is recognized by its content
+/// digest, which is checked.
+fn load_snapshot_file<T: DeserializeOwned>(path: &Path) -> Result<SnapshotFile<T>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read snapshot {}: {}", path.display(), e))?;
+    let value: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| anyhow!("Failed to parse snapshot {}: {}", path.display(), e))?;
+    if value.get("content_digest").is_some() {
+        let snapshot = seal_committee::CommitteeSnapshot::from_json(&content)
+            .map_err(|e| anyhow!("Invalid snapshot {}: {e:#}", path.display()))?;
+        return Ok(SnapshotFile::Committee(snapshot));
+    }
+    serde_json::from_value(value)
+        .map(SnapshotFile::Other)
+        .map_err(|e| anyhow!("Failed to parse snapshot {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fastcrypto::groups::bls12381::{G2Element, Scalar};
+    use fastcrypto::groups::GroupElement;
+    use seal_committee::move_types::PartialKeyServer;
+    use seal_committee::VecMap;
+    use sui_types::collection_types::Entry;
+    use tempfile::TempDir;
+
+    fn snapshot() -> CommitteeSnapshot {
+        let members: Vec<Address> = (1..=3).map(|i| Address::new([i; 32])).collect();
+        let committee = SealCommittee::new(
+            Address::new([9; 32]),
+            2,
+            members.clone(),
+            CommitteeState::Finalized,
+            None,
+        );
+        let contents = members
+            .iter()
+            .enumerate()
+            .map(|(party_id, address)| Entry {
+                key: *address,
+                value: PartialKeyServer {
+                    partial_pk: bcs::to_bytes(
+                        &(G2Element::generator() * Scalar::from(party_id as u128 + 1)),
+                    )
+                    .unwrap(),
+                    url: format!("https://party-{party_id}.example.com"),
+                    party_id: party_id as u16,
+                },
+            })
+            .collect();
+        let key_server = KeyServerV2 {
+            name: "committee".to_string(),
+            key_type: 0,
+            pk: vec![],
+            server_type: ServerType::Committee {
+                version: 0,
+                threshold: 2,
+                partial_key_servers: VecMap(sui_types::collection_types::VecMap { contents }),
+            },
+        };
+        CommitteeSnapshot::new(&committee, &key_server).unwrap()
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("snapshot.json");
+        let snapshot = snapshot();
+        fs::write(&path, serde_json::to_string_pretty(&snapshot).unwrap()).unwrap();
+
+        let loaded = CommitteeSnapshot::load(&path).unwrap();
+        assert_eq!(loaded.digest, snapshot.digest);
+        assert_eq!(
+            loaded.parse_partial_pks::<G2Element>().unwrap(),
+            snapshot.parse_partial_pks::<G2Element>().unwrap()
+        );
+        assert_eq!(loaded.key_type().unwrap(), KeyType::G2);
+        assert_eq!(loaded.to_committee().members, snapshot.members);
+
+        // Tampering with the threshold is detected.
+        let mut tampered = snapshot;
+        tampered.threshold = 1;
+        fs::write(&path, serde_json::to_string_pretty(&tampered).unwrap()).unwrap();
+        let err = CommitteeSnapshot::load(&path).unwrap_err().to_string();
+        assert!(err.contains("digest mismatch"), "{err}");
+    }
+
+    #[test]
+    fn test_init_snapshot_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("committee.json");
+        let mut rng = rand::thread_rng();
+        let keys = dkg_core::KeysFile::generate(&mut rng);
+        let member = Address::new([1; 32]);
+        let committee = SealCommittee::new(
+            Address::new([9; 32]),
+            1,
+            vec![member, Address::new([2; 32])],
+            CommitteeState::Init {
+                members_info: VecMap(sui_types::collection_types::VecMap {
+                    contents: vec![Entry {
+                        key: member,
+                        value: MemberInfo {
+                            enc_pk: keys.enc_pk.clone(),
+                            signing_pk: keys.signing_pk.clone(),
+                            url: "https://party-0.example.com".to_string(),
+                        },
+                    }],
+                }),
+            },
+            Some(Address::new([8; 32])),
+        );
+        let snapshot = InitCommitteeSnapshot::new(&committee).unwrap();
+        fs::write(&path, serde_json::to_string_pretty(&snapshot).unwrap()).unwrap();
+
+        let loaded = InitCommitteeSnapshot::load(&path)
+            .unwrap()
+            .to_committee()
+            .unwrap();
+        assert_eq!(loaded.id, committee.id);
+        assert_eq!(loaded.members, committee.members);
+        assert_eq!(loaded.old_committee_id, committee.old_committee_id);
+        let members_info = loaded.get_members_info().unwrap();
+        assert_eq!(members_info[&member].party_id, 0);
+        assert_eq!(members_info[&member].signing_pk, keys.signing_pk);
+
+        // Only committees in Init state.
+        let finalized = committee.with_state(CommitteeState::Finalized);
+        assert!(InitCommitteeSnapshot::new(&finalized).is_err());
+    }
+}
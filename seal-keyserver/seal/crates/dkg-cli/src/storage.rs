@@ -0,0 +1,166 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reading and writing the keys file and the DKG state on disk.
+
+use crate::context::Context;
+use crate::lock::lock_shared;
+use crate::permissions::check_secret_file;
+use crate::{secure_delete, write_secret_file};
+use anyhow::{anyhow, Result};
+use dkg_core::types::{DkgState, KeysFile};
+use dkg_core::{stored_key_type, stored_key_type_bcs, KeyGroup, KeyType};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Format of a state file in a state directory.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum StateFormat {
+    /// `state.json`, the default.
+    #[default]
+    Json,
+    /// `state.bcs`, selected with `--bcs`.
+    Bcs,
+}
+
+impl StateFormat {
+    pub(crate) fn new(bcs: bool) -> Self {
+        if bcs {
+            StateFormat::Bcs
+        } else {
+            StateFormat::Json
+        }
+    }
+
+    fn file_name(self) -> &'static str {
+        match self {
+            StateFormat::Json => "state.json",
+            StateFormat::Bcs => "state.bcs",
+        }
+    }
+
+    fn other(self) -> Self {
+        match self {
+            StateFormat::Json => StateFormat::Bcs,
+            StateFormat::Bcs => StateFormat::Json,
+        }
+    }
+
+    /// Format of the state in `state_dir`. JSON if there is no state yet.
+    pub(crate) fn stored(state_dir: &Path) -> Result<Self> {
+        let json = state_dir.join(StateFormat::Json.file_name()).exists();
+        let bcs = state_dir.join(StateFormat::Bcs.file_name()).exists();
+        match (json, bcs) {
+            (true, true) => Err(anyhow!(
+                "Both state.json and state.bcs exist in {}, remove the stale one",
+                state_dir.display()
+            )),
+            (false, true) => Ok(StateFormat::Bcs),
+            _ => Ok(StateFormat::Json),
+        }
+    }
+}
+
+/// Path of the state file in `state_dir`, in the format it is stored in.
+pub(crate) fn state_file(state_dir: &Path) -> Result<PathBuf> {
+    Ok(state_dir.join(StateFormat::stored(state_dir)?.file_name()))
+}
+
+pub(crate) trait KeysFileStorage: Sized {
+    /// Load keys from a file, checking its permissions according to the policy of `ctx`. A
+    /// shared lock is held while reading.
+    fn load(path: &Path, ctx: &Context) -> Result<Self>;
+}
+
+impl KeysFileStorage for KeysFile {
+    fn load(path: &Path, ctx: &Context) -> Result<Self> {
+        let _lock = lock_shared(path, ctx)?;
+        check_secret_file(path, ctx.permissions)?;
+        let keys_content = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read keys file {}: {}", path.display(), e))?;
+        let keys: Self = serde_json::from_str(&keys_content)
+            .map_err(|e| anyhow!("Failed to parse keys file: {}", e))?;
+        keys.check_version()?;
+        Ok(keys)
+    }
+}
+
+pub(crate) trait DkgStateStorage: Sized {
+    /// Save state in the given directory in the format of `ctx`, replacing a state in the other
+    /// format.
+    fn save(&self, state_dir: &Path, ctx: &Context) -> Result<()>;
+
+    /// Load state from the given directory in the format it is stored in, checking its
+    /// permissions according to the policy of `ctx`. A shared lock is held while reading.
+    fn load(state_dir: &Path, ctx: &Context) -> Result<Self>;
+}
+
+impl<G: KeyGroup> DkgStateStorage for DkgState<G> {
+    fn save(&self, state_dir: &Path, ctx: &Context) -> Result<()> {
+        let content = match ctx.state_format {
+            StateFormat::Json => serde_json::to_string_pretty(self)?.into_bytes(),
+            StateFormat::Bcs => self.to_bcs()?,
+        };
+        write_state(ctx, state_dir, &content)
+    }
+
+    fn load(state_dir: &Path, ctx: &Context) -> Result<Self> {
+        let format = StateFormat::stored(state_dir)?;
+        let path = state_dir.join(format.file_name());
+        let _lock = lock_shared(&path, ctx)?;
+        check_secret_file(&path, ctx.permissions)?;
+        match format {
+            StateFormat::Json => Self::from_json(&fs::read_to_string(path)?),
+            StateFormat::Bcs => Self::from_bcs(&fs::read(path)?),
+        }
+    }
+}
+
+/// Write a state file in the format of `ctx`, and delete a state file in the other format so
+/// that the state directory holds a single state.
+fn write_state(ctx: &Context, state_dir: &Path, content: &[u8]) -> Result<()> {
+    let format = ctx.state_format;
+    fs::create_dir_all(state_dir)?;
+    write_secret_file(ctx, &state_dir.join(format.file_name()), content)?;
+    let stale = state_dir.join(format.other().file_name());
+    if stale.exists() {
+        secure_delete(&stale)?;
+    }
+    Ok(())
+}
+
+/// Key type of the state in the given directory, to select the instantiation for loading it.
+pub(crate) fn state_key_type(state_dir: &Path) -> Result<KeyType> {
+    let format = StateFormat::stored(state_dir)?;
+    let path = state_dir.join(format.file_name());
+    let bytes =
+        fs::read(&path).map_err(|e| anyhow!("Failed to read state {}: {}", path.display(), e))?;
+    match format {
+        StateFormat::Json => stored_key_type(std::str::from_utf8(&bytes)?),
+        StateFormat::Bcs => stored_key_type_bcs(&bytes),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_keys_file_without_version() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("dkg.key");
+        let keys = KeysFile::generate(&mut thread_rng());
+
+        // Keys files written before versioning have no version field.
+        let mut json = serde_json::to_value(&keys).unwrap();
+        json.as_object_mut().unwrap().remove("version");
+        write_secret_file(&Context::for_testing(), &path, &json.to_string()).unwrap();
+        assert_eq!(
+            KeysFile::load(&path, &Context::for_testing())
+                .unwrap()
+                .version,
+            1
+        );
+    }
+}
@@ -0,0 +1,129 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Target states for `wait`, and the progress of a committee towards them.
+
+use clap::ValueEnum;
+use seal_committee::{CommitteeState, SealCommittee};
+
+/// Committee state to wait for with `wait --until`. A target is also reached once the committee
+/// moved past it, e.g. `post-dkg` by a finalized committee.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub(crate) enum WaitTarget {
+    /// All members registered, so that the ceremony can start.
+    InitComplete,
+    /// The DKG output was proposed onchain.
+    PostDkg,
+    /// The committee is finalized.
+    Finalized,
+}
+
+impl WaitTarget {
+    /// Whether the committee reached or passed this target.
+    pub(crate) fn is_reached(self, committee: &SealCommittee) -> bool {
+        match (self, &committee.state) {
+            (_, CommitteeState::Finalized) => true,
+            (Self::Finalized, _) => false,
+            (_, CommitteeState::PostDKG { .. }) => true,
+            (Self::PostDkg, _) => false,
+            (Self::InitComplete, CommitteeState::Init { .. }) => {
+                committee.get_members_info().is_ok()
+            }
+        }
+    }
+}
+
+/// One-line progress of a committee, printed by `wait` whenever it changes.
+pub(crate) fn describe_progress(committee: &SealCommittee) -> String {
+    let num_members = committee.members.len();
+    match &committee.state {
+        CommitteeState::Init { members_info } => format!(
+            "Init: {} of {num_members} member(s) registered",
+            members_info.0.contents.len()
+        ),
+        CommitteeState::PostDKG { approvals, .. } => format!(
+            "PostDKG: {} of {num_members} member(s) approved",
+            approvals.contents.len()
+        ),
+        CommitteeState::Finalized => "Finalized".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dkg_core::types::KeysFile;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use seal_committee::{MemberInfo, VecMap};
+    use sui_sdk_types::Address;
+    use sui_types::collection_types::{Entry, VecSet};
+
+    type MembersInfo = VecMap<Address, MemberInfo>;
+
+    fn committee(num_registered: usize, state: fn(MembersInfo) -> CommitteeState) -> SealCommittee {
+        let members: Vec<Address> = (1..=3).map(|i| Address::new([i; 32])).collect();
+        let mut rng = StdRng::from_entropy();
+        let contents = members[..num_registered]
+            .iter()
+            .map(|address| {
+                let keys = KeysFile::generate(&mut rng);
+                Entry {
+                    key: *address,
+                    value: MemberInfo {
+                        enc_pk: keys.enc_pk,
+                        signing_pk: keys.signing_pk,
+                        url: format!("https://{address}.example.com"),
+                    },
+                }
+            })
+            .collect();
+        SealCommittee::new(
+            Address::new([10; 32]),
+            2,
+            members,
+            state(VecMap(sui_types::collection_types::VecMap { contents })),
+            None,
+        )
+    }
+
+    fn init(members_info: MembersInfo) -> CommitteeState {
+        CommitteeState::Init { members_info }
+    }
+
+    fn post_dkg(members_info: MembersInfo) -> CommitteeState {
+        CommitteeState::PostDKG {
+            members_info,
+            partial_pks: vec![],
+            pk: vec![],
+            approvals: VecSet {
+                contents: vec![Address::new([1; 32])],
+            },
+        }
+    }
+
+    #[test]
+    fn test_wait_targets() {
+        let registering = committee(2, init);
+        let registered = committee(3, init);
+        let proposed = committee(3, post_dkg);
+        let finalized = committee(0, |_| CommitteeState::Finalized);
+
+        let reached = |target: WaitTarget| {
+            [&registering, &registered, &proposed, &finalized].map(|c| target.is_reached(c))
+        };
+        assert_eq!(reached(WaitTarget::InitComplete), [false, true, true, true]);
+        assert_eq!(reached(WaitTarget::PostDkg), [false, false, true, true]);
+        assert_eq!(reached(WaitTarget::Finalized), [false, false, false, true]);
+
+        assert_eq!(
+            describe_progress(&registering),
+            "Init: 2 of 3 member(s) registered"
+        );
+        assert_eq!(
+            describe_progress(&proposed),
+            "PostDKG: 1 of 3 member(s) approved"
+        );
+        assert_eq!(describe_progress(&finalized), "Finalized");
+    }
+}
@@ -0,0 +1,256 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! End-to-end test of the `dkg-cli` binary as operators run it: a fresh 3-of-5 DKG followed by a
+//! key rotation to 2-of-4, offline with committee snapshots instead of fetching onchain data.
+
+use assert_cmd::Command;
+use fastcrypto::encoding::{Encoding, Hex};
+use fastcrypto::hash::{Blake2b256, HashFunction};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use sui_sdk_types::Address;
+use tempfile::TempDir;
+
+/// An operator with its own working directory for the keys file, states and message files.
+struct Operator {
+    address: Address,
+    dir: PathBuf,
+}
+
+impl Operator {
+    fn new(base_dir: &Path, index: u8) -> Self {
+        let operator = Self {
+            address: Address::new([index + 1; 32]),
+            dir: base_dir.join(format!("operator_{index}")),
+        };
+        fs::create_dir_all(&operator.dir).unwrap();
+        operator
+            .dkg_cli(&["generate-keys", "--keys-file", "dkg.key"])
+            .success();
+        operator
+    }
+
+    fn dkg_cli(&self, args: &[&str]) -> assert_cmd::assert::Assert {
+        Command::cargo_bin("dkg-cli")
+            .unwrap()
+            .current_dir(&self.dir)
+            .args(args)
+            .assert()
+    }
+
+    /// Run a command with `--json` and parse its output.
+    fn dkg_cli_json(&self, args: &[&str]) -> Value {
+        let output = self.dkg_cli(&[args, &["--json"]].concat()).success();
+        serde_json::from_slice(&output.get_output().stdout).unwrap()
+    }
+
+    /// Registered member info, as in an Init committee snapshot.
+    fn member_info(&self) -> Value {
+        let keys: Value =
+            serde_json::from_str(&fs::read_to_string(self.dir.join("dkg.key")).unwrap()).unwrap();
+        json!({
+            "address": self.address,
+            "enc_pk": keys["enc_pk"],
+            "signing_pk": keys["signing_pk"],
+            "url": format!("https://{}.example.com", self.dir.file_name().unwrap().display()),
+        })
+    }
+}
+
+/// Write a snapshot of a committee in Init state, as exported by `export-committee`.
+fn write_committee_snapshot(
+    path: &Path,
+    committee_id: Address,
+    threshold: u16,
+    members: &[&Operator],
+    old_committee_id: Option<Address>,
+) {
+    let snapshot = json!({
+        "committee_id": committee_id,
+        "threshold": threshold,
+        "members": members.iter().map(|operator| operator.address).collect::<Vec<_>>(),
+        "old_committee_id": old_committee_id,
+        "members_info": members.iter().map(|operator| operator.member_info()).collect::<Vec<_>>(),
+    });
+    fs::write(path, serde_json::to_string_pretty(&snapshot).unwrap()).unwrap();
+}
+
+/// Write a snapshot of a finalized committee with its partial public keys, as exported by
+/// `export-committee`.
+fn write_old_committee_snapshot(
+    path: &Path,
+    committee_id: Address,
+    threshold: u16,
+    members: &[&Operator],
+    partial_pks: &BTreeMap<u16, String>,
+) {
+    let members: Vec<Address> = members.iter().map(|operator| operator.address).collect();
+    let bytes = bcs::to_bytes(&(&committee_id, threshold, &members, partial_pks)).unwrap();
+    let snapshot = json!({
+        "committee_id": committee_id,
+        "threshold": threshold,
+        "members": members,
+        "partial_pks": partial_pks,
+        "digest": Hex::encode_with_format(Blake2b256::digest(&bytes).digest),
+    });
+    fs::write(path, serde_json::to_string_pretty(&snapshot).unwrap()).unwrap();
+}
+
+/// Run create-message for every member, collect the message files into every member's messages
+/// directory, and run process-all for every member. Returns the process-all outputs.
+fn run_ceremony(
+    members: &[&Operator],
+    name: &str,
+    committee_snapshot: &Path,
+    extra_args: &[Vec<String>],
+) -> Vec<Value> {
+    let state_dir = format!("state_{name}");
+    let messages_dir = format!("messages_{name}");
+    let mut message_files = Vec::new();
+    for (operator, extra_args) in members.iter().zip(extra_args) {
+        let mut args = vec![
+            "create-message",
+            "--my-address",
+            &operator.address.to_string(),
+            "--committee-snapshot",
+            committee_snapshot.to_str().unwrap(),
+            "--state-dir",
+            &state_dir,
+            "--keys-file",
+            "dkg.key",
+        ]
+        .into_iter()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+        args.extend(extra_args.iter().cloned());
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let result = operator.dkg_cli_json(&args);
+        if let Some(message_file) = result["message_file"].as_str() {
+            message_files.push(operator.dir.join(message_file));
+        }
+    }
+
+    // Every member receives all message files.
+    for operator in members {
+        let dir = operator.dir.join(&messages_dir);
+        fs::create_dir_all(&dir).unwrap();
+        for message_file in &message_files {
+            fs::copy(message_file, dir.join(message_file.file_name().unwrap())).unwrap();
+        }
+    }
+
+    members
+        .iter()
+        .map(|operator| {
+            operator.dkg_cli_json(&[
+                "process-all",
+                "--messages-dir",
+                &messages_dir,
+                "--state-dir",
+                &state_dir,
+                "--keys-file",
+                "dkg.key",
+            ])
+        })
+        .collect()
+}
+
+/// Check that all members derived the same public keys, and return them.
+fn check_outputs(outputs: &[Value], num_members: usize) -> (String, BTreeMap<u16, String>) {
+    for (party_id, output) in outputs.iter().enumerate() {
+        assert_eq!(output["party_id"], party_id);
+        assert_eq!(output["key_server_pk"], outputs[0]["key_server_pk"]);
+        assert_eq!(output["partial_pks"], outputs[0]["partial_pks"]);
+        assert_eq!(output["vss_pk"], outputs[0]["vss_pk"]);
+        assert_eq!(output["excluded_parties"], json!([]));
+    }
+    let partial_pks: BTreeMap<u16, String> =
+        serde_json::from_value(outputs[0]["partial_pks"].clone()).unwrap();
+    assert_eq!(partial_pks.len(), num_members);
+    (
+        outputs[0]["key_server_pk"].as_str().unwrap().to_string(),
+        partial_pks,
+    )
+}
+
+#[test]
+fn test_fresh_dkg_and_rotation() {
+    let base_dir = TempDir::new().unwrap();
+    let operators: Vec<_> = (0..6)
+        .map(|index| Operator::new(base_dir.path(), index))
+        .collect();
+
+    // Fresh 3-of-5 DKG.
+    let old_members: Vec<_> = operators[..5].iter().collect();
+    let old_committee_id = Address::from_str(&format!("0x{}", "a1".repeat(32))).unwrap();
+    let committee_snapshot = base_dir.path().join("committee_old.json");
+    write_committee_snapshot(&committee_snapshot, old_committee_id, 3, &old_members, None);
+    let no_extra_args: Vec<Vec<String>> = (0..5).map(|_| vec![]).collect();
+    let outputs = run_ceremony(&old_members, "old", &committee_snapshot, &no_extra_args);
+    let (key_server_pk, old_partial_pks) = check_outputs(&outputs, 5);
+
+    // Rotation to 2-of-4: members 0 and 3 leave, operator 5 joins, and members 4, 1 and 2
+    // continue with new party IDs.
+    let members = [&operators[5], &operators[4], &operators[1], &operators[2]];
+    let old_snapshot = base_dir.path().join("old_committee.json");
+    write_old_committee_snapshot(
+        &old_snapshot,
+        old_committee_id,
+        3,
+        &old_members,
+        &old_partial_pks,
+    );
+    let committee_id = Address::from_str(&format!("0x{}", "b2".repeat(32))).unwrap();
+    let committee_snapshot = base_dir.path().join("committee_new.json");
+    write_committee_snapshot(
+        &committee_snapshot,
+        committee_id,
+        2,
+        &members,
+        Some(old_committee_id),
+    );
+
+    let extra_args: Vec<Vec<String>> = members
+        .iter()
+        .enumerate()
+        .map(|(index, operator)| {
+            let mut args = vec![
+                "--old-committee-snapshot".to_string(),
+                old_snapshot.to_str().unwrap().to_string(),
+            ];
+            // Continuing members provide their old share.
+            if index > 0 {
+                operator
+                    .dkg_cli(&[
+                        "export-key-server-config",
+                        "--state-dir",
+                        "state_old",
+                        "--output",
+                        "key_server_old.json",
+                    ])
+                    .success();
+                let config: Value = serde_json::from_str(
+                    &fs::read_to_string(operator.dir.join("key_server_old.json")).unwrap(),
+                )
+                .unwrap();
+                args.push("--old-share".to_string());
+                args.push(
+                    config["partial_key_share_hex"]
+                        .as_str()
+                        .unwrap()
+                        .to_string(),
+                );
+            }
+            args
+        })
+        .collect();
+    let outputs = run_ceremony(&members, "new", &committee_snapshot, &extra_args);
+    let (new_key_server_pk, _) = check_outputs(&outputs, 4);
+
+    // The key server public key is unchanged.
+    assert_eq!(new_key_server_pk, key_server_pk);
+}
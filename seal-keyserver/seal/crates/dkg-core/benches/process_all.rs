@@ -0,0 +1,181 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Benchmarks for processing the messages of a fresh DKG as in `process-all`, by committee size.
+//! Each step is measured separately for a single party, plus the end-to-end time of
+//! [process_bundle] and [finalize].
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use dkg_core::types::{verify_signature, DkgState, KeysFile, SignedMessage};
+use dkg_core::{finalize, prepare_ceremony, process_bundle};
+use fastcrypto::groups::bls12381::G2Element;
+use fastcrypto_tbls::dkg_v1::Party;
+use fastcrypto_tbls::random_oracle::RandomOracle;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use seal_committee::{CommitteeState, MemberInfo, SealCommittee, VecMap};
+use sui_sdk_types::Address;
+use sui_types::collection_types::Entry;
+
+const COMMITTEE_SIZES: [usize; 3] = [5, 20, 50];
+
+/// Address of member `i`, unique for up to 2^16 members.
+fn member_address(i: usize) -> Address {
+    let mut bytes = [0u8; 32];
+    bytes[30..].copy_from_slice(&(i as u16).to_be_bytes());
+    Address::new(bytes)
+}
+
+/// A fresh DKG with `size` parties and threshold `size / 2 + 1`: the keys and the state of party
+/// 0 before processing, and the signed messages of all parties.
+struct Ceremony {
+    keys: KeysFile,
+    state_json: String,
+    messages: Vec<SignedMessage>,
+}
+
+impl Ceremony {
+    fn new(size: usize, rng: &mut StdRng) -> Self {
+        let keys: Vec<KeysFile> = (0..size).map(|_| KeysFile::generate(rng)).collect();
+        let members: Vec<Address> = (0..size).map(member_address).collect();
+        let contents = members
+            .iter()
+            .zip(&keys)
+            .map(|(address, keys)| Entry {
+                key: *address,
+                value: MemberInfo {
+                    enc_pk: keys.enc_pk.clone(),
+                    signing_pk: keys.signing_pk.clone(),
+                    url: format!("https://{address}.example.com"),
+                },
+            })
+            .collect();
+        let committee = SealCommittee::new(
+            Address::new([1; 32]),
+            (size / 2 + 1) as u16,
+            members.clone(),
+            CommitteeState::Init {
+                members_info: VecMap(sui_types::collection_types::VecMap { contents }),
+            },
+            None,
+        );
+
+        let mut state_json = String::new();
+        let mut messages = Vec::new();
+        for (party_id, (address, keys)) in members.iter().zip(&keys).enumerate() {
+            let (state, message) =
+                prepare_ceremony::<G2Element, _>(&committee, address, keys, None, None, rng)
+                    .unwrap();
+            messages.push(message.unwrap());
+            if party_id == 0 {
+                state_json = serde_json::to_string(&state).unwrap();
+            }
+        }
+        let keys = keys.into_iter().next().unwrap();
+        Self {
+            keys,
+            state_json,
+            messages,
+        }
+    }
+
+    fn state(&self) -> DkgState {
+        serde_json::from_str(&self.state_json).unwrap()
+    }
+
+    fn party(&self, rng: &mut StdRng) -> Party<G2Element, G2Element> {
+        let state = self.state();
+        Party::new_advanced(
+            self.keys.enc_sk.clone(),
+            state.config.nodes,
+            state.config.threshold,
+            RandomOracle::new(&state.config.committee_id.to_string()),
+            None,
+            None,
+            rng,
+        )
+        .unwrap()
+    }
+}
+
+fn bench_process_all(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut group = c.benchmark_group("process_all");
+    group.sample_size(10);
+    for size in COMMITTEE_SIZES {
+        let ceremony = Ceremony::new(size, &mut rng);
+        let state = ceremony.state();
+        let party = ceremony.party(&mut rng);
+        let processed: Vec<_> = ceremony
+            .messages
+            .iter()
+            .map(|signed| {
+                party
+                    .process_message(signed.message.clone(), &mut rng)
+                    .unwrap()
+            })
+            .collect();
+        let (_, used_msgs) = party.merge(&processed).unwrap();
+
+        group.bench_with_input(
+            BenchmarkId::new("verify_signatures", size),
+            &ceremony,
+            |b, ceremony| {
+                b.iter(|| {
+                    for signed in &ceremony.messages {
+                        let signing_pk = &state.config.signing_pks[&signed.message.sender];
+                        verify_signature(signed, signing_pk).unwrap();
+                    }
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("process_message", size),
+            &ceremony,
+            |b, ceremony| {
+                b.iter(|| {
+                    for signed in &ceremony.messages {
+                        party
+                            .process_message(signed.message.clone(), &mut rng)
+                            .unwrap();
+                    }
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("merge", size),
+            &processed,
+            |b, processed| b.iter(|| party.merge(processed).unwrap()),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("complete_optimistic", size),
+            &used_msgs,
+            |b, used_msgs| b.iter(|| party.complete_optimistic(used_msgs).unwrap()),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("end_to_end", size),
+            &ceremony,
+            |b, ceremony| {
+                b.iter_batched(
+                    || ceremony.state(),
+                    |mut state| {
+                        process_bundle(
+                            &mut state,
+                            &ceremony.keys,
+                            ceremony.messages.clone(),
+                            0,
+                            &mut rng,
+                        )
+                        .unwrap();
+                        finalize(&mut state, &ceremony.keys, &mut rng).unwrap()
+                    },
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_process_all);
+criterion_main!(benches);
@@ -0,0 +1,93 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Benchmarks comparing the JSON and BCS formats of a completed state of a 20-member committee,
+//! the largest state written during a ceremony. The sizes are printed before the timings.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use dkg_core::types::{DkgState, KeysFile};
+use dkg_core::{finalize, prepare_ceremony, process_bundle};
+use fastcrypto::groups::bls12381::G2Element;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use seal_committee::{CommitteeState, MemberInfo, SealCommittee, VecMap};
+use sui_sdk_types::Address;
+use sui_types::collection_types::Entry;
+
+const COMMITTEE_SIZE: usize = 20;
+
+/// Completed state of party 0 of a fresh DKG with `size` parties and threshold `size / 2 + 1`.
+fn completed_state(size: usize, rng: &mut StdRng) -> DkgState {
+    let keys: Vec<KeysFile> = (0..size).map(|_| KeysFile::generate(rng)).collect();
+    let members: Vec<Address> = (0..size)
+        .map(|i| {
+            let mut bytes = [0u8; 32];
+            bytes[30..].copy_from_slice(&(i as u16).to_be_bytes());
+            Address::new(bytes)
+        })
+        .collect();
+    let contents = members
+        .iter()
+        .zip(&keys)
+        .map(|(address, keys)| Entry {
+            key: *address,
+            value: MemberInfo {
+                enc_pk: keys.enc_pk.clone(),
+                signing_pk: keys.signing_pk.clone(),
+                url: format!("https://{address}.example.com"),
+            },
+        })
+        .collect();
+    let committee = SealCommittee::new(
+        Address::new([1; 32]),
+        (size / 2 + 1) as u16,
+        members.clone(),
+        CommitteeState::Init {
+            members_info: VecMap(sui_types::collection_types::VecMap { contents }),
+        },
+        None,
+    );
+
+    let mut states = Vec::new();
+    let mut messages = Vec::new();
+    for (address, keys) in members.iter().zip(&keys) {
+        let (state, message) =
+            prepare_ceremony::<G2Element, _>(&committee, address, keys, None, None, rng).unwrap();
+        messages.extend(message);
+        states.push(state);
+    }
+    let mut state = states.swap_remove(0);
+    process_bundle(&mut state, &keys[0], messages, 0, rng).unwrap();
+    finalize(&mut state, &keys[0], rng).unwrap();
+    state
+}
+
+fn bench_state_format(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0);
+    let state = completed_state(COMMITTEE_SIZE, &mut rng);
+    let json = serde_json::to_string_pretty(&state).unwrap();
+    let bcs = state.to_bcs().unwrap();
+    println!(
+        "State of a {COMMITTEE_SIZE}-member committee: JSON {} bytes, BCS {} bytes ({:.1}x smaller)",
+        json.len(),
+        bcs.len(),
+        json.len() as f64 / bcs.len() as f64
+    );
+
+    let mut group = c.benchmark_group("state_format");
+    group.sample_size(10);
+    group.bench_function("to_json", |b| {
+        b.iter(|| serde_json::to_string_pretty(&state).unwrap())
+    });
+    group.bench_function("from_json", |b| {
+        b.iter(|| DkgState::<G2Element>::from_json(&json).unwrap())
+    });
+    group.bench_function("to_bcs", |b| b.iter(|| state.to_bcs().unwrap()));
+    group.bench_function("from_bcs", |b| {
+        b.iter(|| DkgState::<G2Element>::from_bcs(&bcs).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_state_format);
+criterion_main!(benches);
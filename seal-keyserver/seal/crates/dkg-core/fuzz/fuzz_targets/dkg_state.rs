@@ -0,0 +1,18 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! State files, as loaded by every command that takes a state directory.
+
+#![no_main]
+
+use dkg_core::types::DkgState;
+use fastcrypto::groups::bls12381::{G1Element, G2Element};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(json) = std::str::from_utf8(data) {
+        let _ = dkg_core::stored_key_type(json);
+        let _ = DkgState::<G2Element>::from_json(json);
+        let _ = DkgState::<G1Element>::from_json(json);
+    }
+});
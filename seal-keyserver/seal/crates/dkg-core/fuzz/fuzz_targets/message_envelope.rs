@@ -0,0 +1,17 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Message files from a shared directory, as read by `process-all`.
+
+#![no_main]
+
+use dkg_core::types::SignedMessage;
+use fastcrypto::groups::bls12381::{G1Element, G2Element};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(json) = std::str::from_utf8(data) {
+        let _ = SignedMessage::<G2Element>::from_envelope_json(json);
+        let _ = SignedMessage::<G1Element>::from_envelope_json(json);
+    }
+});
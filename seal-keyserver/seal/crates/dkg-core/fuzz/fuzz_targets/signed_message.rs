@@ -0,0 +1,20 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Signed messages as base64 of the BCS bytes, and the BCS bytes themselves.
+
+#![no_main]
+
+use dkg_core::types::SignedMessage;
+use fastcrypto::groups::bls12381::{G1Element, G2Element};
+use libfuzzer_sys::fuzz_target;
+use std::str::FromStr;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = bcs::from_bytes::<SignedMessage<G2Element>>(data);
+    let _ = bcs::from_bytes::<SignedMessage<G1Element>>(data);
+    if let Ok(s) = std::str::from_utf8(data) {
+        let _ = SignedMessage::<G2Element>::from_str(s);
+        let _ = SignedMessage::<G1Element>::from_str(s);
+    }
+});
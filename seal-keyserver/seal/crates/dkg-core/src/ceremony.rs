@@ -0,0 +1,1465 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Steps of a DKG ceremony for a single party, for both fresh DKG and key rotation:
+//! [prepare_ceremony] creates the local state and message, [process_bundle] verifies and
+//! processes the messages of all parties, and [finalize] completes the protocol. Messages can also
+//! be verified and stored ahead of time with [process_messages_only].
+
+use crate::errors::CeremonyError;
+use crate::groups::KeyGroup;
+use crate::types::{
+    sign_message, verify_all_signatures, DkgState, InitializedConfig, KeysFile, SignedMessage,
+};
+use anyhow::{anyhow, Result};
+use fastcrypto::bls12381::min_sig::BLS12381PublicKey;
+use fastcrypto::groups::bls12381::{G2Element, Scalar as G2Scalar};
+use fastcrypto::groups::GroupElement;
+use fastcrypto::traits::AllowedRng;
+use fastcrypto_tbls::dkg_v1::{Complaint, Output, Party, ProcessedMessage};
+use fastcrypto_tbls::nodes::{Node, Nodes};
+use fastcrypto_tbls::random_oracle::RandomOracle;
+use seal_committee::{ParsedMemberInfo, RotationPlan, SealCommittee};
+use std::collections::{HashMap, HashSet};
+use sui_sdk_types::Address;
+
+/// Old committee of a key rotation.
+pub struct OldCommittee<'a, G: KeyGroup = G2Element> {
+    pub committee: &'a SealCommittee,
+    /// Partial public keys of the old committee's key server, by old party ID.
+    pub partial_pks: HashMap<u16, G>,
+    /// Digest of the old committee data, stored in the state so that parties can check they
+    /// rotated against the same old committee.
+    pub digest: Option<String>,
+}
+
+/// Outcome of [process_bundle].
+pub struct ProcessOutcome {
+    /// Senders of the messages processed in this bundle.
+    pub processed: Vec<u16>,
+    /// Senders of the messages skipped because they were already processed.
+    pub skipped: Vec<u16>,
+    /// Senders of the messages with a complaint, excluded from the merge. At most the
+    /// `max_complaints` given to [process_bundle].
+    pub excluded: Vec<ExcludedParty>,
+    /// Complaints found when merging all processed messages. If not empty, do NOT propose
+    /// onchain and do not call [finalize].
+    pub complaints: Vec<Complaint<G2Element>>,
+}
+
+/// A party whose message was excluded from the merge because of a complaint.
+pub struct ExcludedParty {
+    pub party_id: u16,
+    /// Reason of the complaint.
+    pub reason: String,
+}
+
+/// Nodes for all parties with their encryption public keys, and the signing public keys by party
+/// ID.
+pub fn committee_nodes<'a>(
+    members_info: impl IntoIterator<Item = &'a ParsedMemberInfo>,
+) -> Result<(Nodes<G2Element>, HashMap<u16, BLS12381PublicKey>)> {
+    let mut nodes = Vec::new();
+    let mut signing_pks = HashMap::new();
+    for m in members_info {
+        nodes.push(Node {
+            id: m.party_id,
+            pk: m.enc_pk.clone(),
+            weight: 1,
+        });
+        signing_pks.insert(m.party_id, m.signing_pk.clone());
+    }
+    Ok((Nodes::new(nodes)?, signing_pks))
+}
+
+/// Create the local state of `my_address` for a ceremony of `committee`, after checking that the
+/// committee is in Init state and that `keys` match the keys registered by `my_address`.
+///
+/// In a fresh DKG every party creates a signed message. In a key rotation, `old_committee` must be
+/// given and only continuing members, who must provide `old_share`, create a message.
+///
+/// Parties have weight 1 unless given in `weights`, see [InitializedConfig::nodes_with_weights].
+pub fn prepare_ceremony<G: KeyGroup, R: AllowedRng>(
+    committee: &SealCommittee,
+    my_address: &Address,
+    keys: &KeysFile,
+    old_share: Option<G2Scalar>,
+    old_committee: Option<OldCommittee<'_, G>>,
+    weights: Option<&HashMap<u16, u16>>,
+    rng: &mut R,
+) -> Result<(DkgState<G>, Option<SignedMessage<G>>)> {
+    committee
+        .is_init()
+        .map_err(|e| CeremonyError::StateConflict(e.into()))?;
+    let my_party_id = committee
+        .get_party_id(my_address)
+        .map_err(|e| CeremonyError::Validation(e.into()))?;
+
+    let members_info = committee
+        .get_members_info()
+        .map_err(|e| CeremonyError::Validation(e.into()))?;
+
+    // Validate PK locally vs registration onchain.
+    committee
+        .verify_member_registration(my_address, &keys.enc_pk, &keys.signing_pk)
+        .map_err(|e| CeremonyError::Validation(e.into()))?;
+
+    let (old_threshold, new_to_old_mapping, expected_old_pks, old_committee_digest) = match (
+        committee.old_committee_id,
+        old_committee,
+    ) {
+        (None, old_committee) => {
+            if old_share.is_some() {
+                return Err(CeremonyError::InvalidInput(anyhow!(
+                    "Old share should not be provided for fresh DKG."
+                ))
+                .into());
+            }
+            if old_committee.is_some() {
+                return Err(CeremonyError::InvalidInput(anyhow!(
+                    "Old committee should not be provided for fresh DKG."
+                ))
+                .into());
+            }
+            (None, None, None, None)
+        }
+        (Some(old_committee_id), None) => {
+            return Err(CeremonyError::InvalidInput(anyhow!(
+                "Old committee {old_committee_id} is required for key rotation."
+            ))
+            .into());
+        }
+        (Some(old_committee_id), Some(old)) => {
+            if old.committee.id != old_committee_id {
+                return Err(CeremonyError::Validation(anyhow!(
+                    "Old committee is {}, expected {}",
+                    old.committee.id,
+                    old_committee_id
+                ))
+                .into());
+            }
+            let plan =
+                RotationPlan::build(committee, old.committee).map_err(CeremonyError::Validation)?;
+            plan.feasible().map_err(CeremonyError::Validation)?;
+            let new_to_old_mapping = plan.new_to_old;
+
+            // Validate old share and membership in old committee.
+            let is_continuing = old.committee.contains(my_address);
+            if old_share.is_some() && !is_continuing {
+                return Err(CeremonyError::InvalidInput(anyhow!(
+                        "Invalid state: My address {} not found in old committee {} so I am a new member. Do not provide an old share for key rotation.",
+                        my_address,
+                        old_committee_id
+                    ))
+                    .into());
+            }
+            if old_share.is_none() && is_continuing {
+                return Err(CeremonyError::InvalidInput(anyhow!(
+                        "Invalid state: My address {} found in old committee {} so I am a continuing member. Must provide an old share for key rotation.",
+                        my_address,
+                        old_committee_id
+                    ))
+                    .into());
+            }
+            (
+                Some(old.committee.threshold),
+                Some(new_to_old_mapping),
+                Some(old.partial_pks),
+                old.digest,
+            )
+        }
+    };
+
+    let (nodes, signing_pks) = committee_nodes(members_info.values())?;
+    let mut config = InitializedConfig {
+        config_version: InitializedConfig::CURRENT_VERSION,
+        my_party_id,
+        nodes,
+        committee_id: committee.id,
+        threshold: committee.threshold,
+        signing_pks,
+        old_threshold,
+        new_to_old_mapping,
+        expected_old_pks,
+        my_old_share: old_share,
+        my_old_pk: old_share.map(|share| G::generator() * share),
+        old_committee_digest,
+    };
+    if let Some(weights) = weights {
+        config.nodes = config
+            .nodes_with_weights(weights)
+            .map_err(CeremonyError::InvalidInput)?;
+    }
+
+    // Create message if:
+    // - Fresh DKG: everyone creates a message (old_threshold is None).
+    // - Rotation: only continuing members create a message (my_old_share is Some).
+    let signed_message = if config.old_threshold.is_none() || config.my_old_share.is_some() {
+        let message = new_party(&config, keys, rng)?.create_message(rng)?;
+        Some(sign_message(message, &keys.signing_sk))
+    } else {
+        None
+    };
+    let state = DkgState::new(config, signed_message.as_ref().map(|m| m.message.clone()));
+    Ok((state, signed_message))
+}
+
+/// Verify and process the messages of all parties, and merge all processed messages. Messages
+/// from senders that were already processed, e.g. merged from other states, are skipped.
+///
+/// A fresh DKG requires a message from every party and a key rotation messages from at least the
+/// old threshold of continuing members.
+///
+/// Messages with a complaint against their sender are excluded from the merge if there are at most
+/// `max_complaints` of them and enough messages remain to complete the protocol, and otherwise
+/// fail. The protocol tolerates up to `n - threshold` excluded parties.
+///
+/// `state` is left unchanged on errors. Otherwise, the processed messages and the confirmation are
+/// stored in `state`, also if there are complaints.
+pub fn process_bundle<G: KeyGroup, R: AllowedRng>(
+    state: &mut DkgState<G>,
+    keys: &KeysFile,
+    mut messages: Vec<SignedMessage<G>>,
+    max_complaints: usize,
+    rng: &mut R,
+) -> Result<ProcessOutcome> {
+    let processed_senders: HashSet<u16> = state
+        .processed_messages
+        .iter()
+        .map(|processed| processed.message.sender)
+        .collect();
+    let (skipped, remaining): (Vec<_>, Vec<_>) = messages
+        .drain(..)
+        .partition(|m| processed_senders.contains(&m.message.sender));
+    let messages = remaining;
+    let num_messages = messages.len() + processed_senders.len();
+
+    if let Some(old_threshold) = state.config.old_threshold {
+        // Key rotation: need messages from old threshold members.
+        if num_messages < old_threshold as usize {
+            return Err(CeremonyError::Validation(anyhow!(
+                "Key rotation requires at least {} messages from old committee members, got {}.",
+                old_threshold,
+                num_messages
+            ))
+            .into());
+        }
+    } else {
+        // Fresh DKG: need messages from all parties.
+        let num_parties = state.config.nodes.num_nodes();
+        if num_messages != num_parties {
+            return Err(CeremonyError::Validation(anyhow!(
+                "Fresh DKG requires {} messages (one from each party), got {}.",
+                num_parties,
+                num_messages
+            ))
+            .into());
+        }
+    }
+
+    let party = new_party(&state.config, keys, rng)?;
+    let (processed_now, senders, excluded) = verify_and_process(state, &party, messages, rng)?;
+
+    if excluded.len() > max_complaints {
+        return Err(CeremonyError::Verification(anyhow!(
+            "Do NOT propose onchain. Complaint(s) found for {} parties, at most {} tolerated: {}",
+            excluded.len(),
+            max_complaints,
+            excluded
+                .iter()
+                .map(|party| format!("party {}: {}", party.party_id, party.reason))
+                .collect::<Vec<_>>()
+                .join("; ")
+        ))
+        .into());
+    }
+    let num_included = processed_senders.len() + processed_now.len();
+    let required = state.config.old_threshold.unwrap_or(state.config.threshold) as usize;
+    if !excluded.is_empty() && num_included < required {
+        return Err(CeremonyError::Verification(anyhow!(
+            "Do NOT propose onchain. Only {} messages left after excluding {} parties with complaints, {} required.",
+            num_included,
+            excluded.len(),
+            required
+        ))
+        .into());
+    }
+
+    // Merge processed messages.
+    let mut processed_messages = state.processed_messages.clone();
+    processed_messages.extend(processed_now);
+    let (confirmation, used_msgs) = party.merge(&processed_messages)?;
+    let complaints = confirmation.complaints.clone();
+    state.processed_messages = processed_messages;
+    state.confirmation = Some((confirmation, used_msgs));
+
+    Ok(ProcessOutcome {
+        processed: senders,
+        skipped: skipped.iter().map(|m| m.message.sender).collect(),
+        excluded,
+        complaints,
+    })
+}
+
+/// Verify and process messages like [process_bundle], but only store the processed messages in
+/// `state` without merging them, e.g. to verify incoming messages while waiting for offline
+/// parties. Messages from senders that were already processed are skipped, and messages with a
+/// complaint are excluded and not stored. No minimum number of messages is required; a later
+/// [process_bundle] continues from the stored messages once
+/// [DkgState::has_sufficient_processed_messages] holds.
+///
+/// `state` is left unchanged on errors.
+pub fn process_messages_only<G: KeyGroup, R: AllowedRng>(
+    state: &mut DkgState<G>,
+    keys: &KeysFile,
+    mut messages: Vec<SignedMessage<G>>,
+    rng: &mut R,
+) -> Result<ProcessOutcome> {
+    if state.output.is_some() {
+        return Err(CeremonyError::StateConflict(anyhow!("DKG is already completed")).into());
+    }
+    let processed_senders: HashSet<u16> = state
+        .processed_messages
+        .iter()
+        .map(|processed| processed.message.sender)
+        .collect();
+    let (skipped, messages): (Vec<_>, Vec<_>) = messages
+        .drain(..)
+        .partition(|m| processed_senders.contains(&m.message.sender));
+
+    let party = new_party(&state.config, keys, rng)?;
+    let (processed_now, senders, excluded) = verify_and_process(state, &party, messages, rng)?;
+    state.processed_messages.extend(processed_now);
+    // Merged messages are outdated now.
+    state.confirmation = None;
+
+    Ok(ProcessOutcome {
+        processed: senders,
+        skipped: skipped.iter().map(|m| m.message.sender).collect(),
+        excluded,
+        complaints: vec![],
+    })
+}
+
+/// Verify the signatures of the messages and process them. Returns the processed messages
+/// without a complaint and their senders, and the parties of the messages with a complaint.
+fn verify_and_process<G: KeyGroup, R: AllowedRng>(
+    state: &DkgState<G>,
+    party: &Party<G, G2Element>,
+    messages: Vec<SignedMessage<G>>,
+    rng: &mut R,
+) -> Result<(
+    Vec<ProcessedMessage<G, G2Element>>,
+    Vec<u16>,
+    Vec<ExcludedParty>,
+)> {
+    // Verify all signed messages using the onchain signing pk of each party, reporting every
+    // failure at once.
+    verify_all_signatures(&messages, &state.config.signing_pks).map_err(|failures| {
+        let reasons = failures
+            .iter()
+            .map(|(_, e)| format!("- {e}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        CeremonyError::Verification(anyhow!(
+            "{} message(s) failed signature verification:\n{reasons}",
+            failures.len()
+        ))
+    })?;
+
+    let mut processed_now = Vec::new();
+    let mut senders = Vec::new();
+    let mut excluded = Vec::new();
+    for signed_msg in messages {
+        let sender_party_id = signed_msg.message.sender;
+
+        // For rotation, check the commitment against the sender's old partial PK.
+        let processed = if state.config.old_threshold.is_some() {
+            let expected_pk = expected_old_pk(&state.config, sender_party_id)?;
+            party
+                .process_message_and_check_pk(signed_msg.message, expected_pk, rng)
+                .map_err(|e| {
+                    CeremonyError::Verification(anyhow!(
+                        "Key rotation verification failed for party {sender_party_id}: {e}"
+                    ))
+                })?
+        } else {
+            // Fresh DKG.
+            party
+                .process_message(signed_msg.message, rng)
+                .map_err(|e| CeremonyError::Verification(e.into()))?
+        };
+
+        if processed.complaint.is_some() {
+            excluded.push(ExcludedParty {
+                party_id: sender_party_id,
+                reason: format!(
+                    "Encrypted share for party {} cannot be decrypted or does not match the commitment",
+                    state.config.my_party_id
+                ),
+            });
+            continue;
+        }
+        processed_now.push(processed);
+        senders.push(sender_party_id);
+    }
+
+    Ok((processed_now, senders, excluded))
+}
+
+/// Complete the protocol from the merged messages of [process_bundle]. The output is also stored
+/// in `state`.
+pub fn finalize<G: KeyGroup, R: AllowedRng>(
+    state: &mut DkgState<G>,
+    keys: &KeysFile,
+    rng: &mut R,
+) -> Result<Output<G, G2Element>> {
+    let (confirmation, used_msgs) = state
+        .confirmation
+        .as_ref()
+        .ok_or_else(|| anyhow!("Messages are not processed yet"))?;
+    if !confirmation.complaints.is_empty() {
+        return Err(CeremonyError::Verification(anyhow!(
+            "Do NOT propose onchain. Complaint(s) found {:?}.",
+            confirmation.complaints
+        ))
+        .into());
+    }
+
+    let party = new_party(&state.config, keys, rng)?;
+    let output = if state.config.old_threshold.is_some() {
+        // Key rotation: use complete_optimistic_key_rotation.
+        let new_to_old_mapping = state
+            .config
+            .new_to_old_mapping
+            .as_ref()
+            .ok_or_else(|| anyhow!("Missing new-to-old mapping for key rotation"))?;
+        party.complete_optimistic_key_rotation(used_msgs, new_to_old_mapping)?
+    } else {
+        // Fresh DKG.
+        party.complete_optimistic(used_msgs)?
+    };
+    state.output = Some(output.clone());
+    Ok(output)
+}
+
+fn new_party<G: KeyGroup, R: AllowedRng>(
+    config: &InitializedConfig<G>,
+    keys: &KeysFile,
+    rng: &mut R,
+) -> Result<Party<G, G2Element>> {
+    Ok(Party::<G, G2Element>::new_advanced(
+        keys.enc_sk.clone(),
+        config.nodes.clone(),
+        config.threshold,
+        RandomOracle::new(&config.committee_id.to_string()),
+        config.my_old_share,
+        config.old_threshold,
+        rng,
+    )?)
+}
+
+/// Partial public key in the old committee of a continuing member, for key rotation.
+fn expected_old_pk<G: KeyGroup>(config: &InitializedConfig<G>, sender_party_id: u16) -> Result<&G> {
+    let new_to_old_mapping = config
+        .new_to_old_mapping
+        .as_ref()
+        .ok_or_else(|| anyhow!("Missing new-to-old mapping for key rotation"))?;
+    let old_party_id = new_to_old_mapping.get(&sender_party_id).ok_or_else(|| {
+        CeremonyError::Validation(anyhow!(
+            "Party {} not found in old committee mapping",
+            sender_party_id
+        ))
+    })?;
+    let expected_old_pks = config
+        .expected_old_pks
+        .as_ref()
+        .ok_or_else(|| anyhow!("Missing expected old partial PKs for key rotation"))?;
+    Ok(expected_old_pks.get(old_party_id).ok_or_else(|| {
+        CeremonyError::Validation(anyhow!(
+            "Partial PK not found for old party {}",
+            old_party_id
+        ))
+    })?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::groups::{stored_key_type, stored_key_type_bcs, KeyType};
+    use crate::types::{check_state_version, DkgPhase, ProgressSummary};
+    use crate::utils::{CommitteeRotationVerifier, VerificationWarning};
+    use fastcrypto::groups::bls12381::G1Element;
+    use rand::rngs::StdRng;
+    use rand::{RngCore, SeedableRng};
+    use seal_committee::{CommitteeState, MemberInfo, VecMap};
+    use std::num::NonZeroU16;
+    use sui_types::collection_types::Entry;
+
+    type Outputs<G> = HashMap<Address, Output<G, G2Element>>;
+
+    struct TestParty {
+        address: Address,
+        keys: KeysFile,
+    }
+
+    fn new_party_keys(rng: &mut StdRng) -> TestParty {
+        TestParty {
+            address: random_address(rng),
+            keys: KeysFile::generate(rng),
+        }
+    }
+
+    fn random_address(rng: &mut StdRng) -> Address {
+        let mut bytes = [0u8; 32];
+        rng.fill_bytes(&mut bytes);
+        Address::new(bytes)
+    }
+
+    /// Committee in Init state where all parties have registered their public keys.
+    fn test_committee(
+        parties: &[TestParty],
+        threshold: u16,
+        old_committee_id: Option<Address>,
+        rng: &mut StdRng,
+    ) -> SealCommittee {
+        let contents = parties
+            .iter()
+            .enumerate()
+            .map(|(index, party)| Entry {
+                key: party.address,
+                value: MemberInfo {
+                    enc_pk: party.keys.enc_pk.clone(),
+                    signing_pk: party.keys.signing_pk.clone(),
+                    url: format!("https://party-{index}.example.com"),
+                },
+            })
+            .collect();
+        SealCommittee::new(
+            random_address(rng),
+            threshold,
+            parties.iter().map(|party| party.address).collect(),
+            CommitteeState::Init {
+                members_info: VecMap(sui_types::collection_types::VecMap { contents }),
+            },
+            old_committee_id,
+        )
+    }
+
+    fn share_index(party_id: u16) -> NonZeroU16 {
+        NonZeroU16::new(party_id + 1).unwrap()
+    }
+
+    fn key_share<G: KeyGroup>(output: &Output<G, G2Element>) -> G2Scalar {
+        output.shares.as_ref().unwrap()[0].value
+    }
+
+    /// Old committee with the partial public keys derived from the outputs of its members.
+    fn to_old_committee<'a, G: KeyGroup>(
+        committee: &'a SealCommittee,
+        outputs: &Outputs<G>,
+    ) -> OldCommittee<'a, G> {
+        let vss_pk = &outputs.values().next().unwrap().vss_pk;
+        OldCommittee {
+            committee,
+            partial_pks: (0..committee.members.len() as u16)
+                .map(|party_id| (party_id, vss_pk.eval(share_index(party_id)).value))
+                .collect(),
+            digest: None,
+        }
+    }
+
+    /// Run all steps for all parties and check that they derive the same output.
+    fn run_ceremony<G: KeyGroup>(
+        committee: &SealCommittee,
+        parties: &[TestParty],
+        old: Option<(&SealCommittee, &Outputs<G>)>,
+        rng: &mut StdRng,
+    ) -> Outputs<G> {
+        run_ceremony_with_states(committee, parties, old, rng).0
+    }
+
+    /// [run_ceremony], also returning the final states of all parties.
+    fn run_ceremony_with_states<G: KeyGroup>(
+        committee: &SealCommittee,
+        parties: &[TestParty],
+        old: Option<(&SealCommittee, &Outputs<G>)>,
+        rng: &mut StdRng,
+    ) -> (Outputs<G>, Vec<DkgState<G>>) {
+        let mut states = Vec::new();
+        let mut messages = Vec::new();
+        for party in parties {
+            let old_share = old.and_then(|(_, outputs)| outputs.get(&party.address).map(key_share));
+            let (state, message) = prepare_ceremony(
+                committee,
+                &party.address,
+                &party.keys,
+                old_share,
+                old.map(|(committee, outputs)| to_old_committee(committee, outputs)),
+                None,
+                rng,
+            )
+            .unwrap();
+            assert_eq!(message.is_some(), old.is_none() || old_share.is_some());
+            messages.extend(message);
+            states.push(state);
+        }
+
+        let mut outputs = HashMap::new();
+        for (party, state) in parties.iter().zip(states.iter_mut()) {
+            let outcome = process_bundle(state, &party.keys, messages.clone(), 0, rng).unwrap();
+            assert_eq!(outcome.processed.len(), messages.len());
+            assert!(outcome.skipped.is_empty());
+            assert!(outcome.complaints.is_empty());
+            let output = finalize(state, &party.keys, rng).unwrap();
+            assert!(state.output.as_ref().map(|o| &o.vss_pk) == Some(&output.vss_pk));
+            outputs.insert(party.address, output);
+        }
+
+        let vss_pk = &outputs.values().next().unwrap().vss_pk;
+        for (party_id, address) in committee.members.iter().enumerate() {
+            let output = &outputs[address];
+            assert_eq!(output.vss_pk, *vss_pk);
+            assert_eq!(
+                G::generator() * key_share(output),
+                vss_pk.eval(share_index(party_id as u16)).value
+            );
+        }
+        (outputs, states)
+    }
+
+    fn ceremony_error<T>(result: Result<T>) -> CeremonyError {
+        result
+            .err()
+            .expect("must fail")
+            .downcast::<CeremonyError>()
+            .expect("must be a ceremony error")
+    }
+
+    fn fresh_dkg<G: KeyGroup>(seed: u64) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let parties: Vec<_> = (0..4).map(|_| new_party_keys(&mut rng)).collect();
+        let committee = test_committee(&parties, 3, None, &mut rng);
+        let outputs = run_ceremony::<G>(&committee, &parties, None, &mut rng);
+        assert_eq!(outputs.len(), 4);
+    }
+
+    fn key_rotation<G: KeyGroup>(seed: u64) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let old_parties: Vec<_> = (0..4).map(|_| new_party_keys(&mut rng)).collect();
+        let old = test_committee(&old_parties, 3, None, &mut rng);
+        let old_outputs = run_ceremony::<G>(&old, &old_parties, None, &mut rng);
+
+        // One member joins, the first member leaves, and the threshold increases.
+        let mut parties = vec![new_party_keys(&mut rng)];
+        parties.extend(old_parties.into_iter().skip(1));
+        let committee = test_committee(&parties, 4, Some(old.id), &mut rng);
+        let (outputs, states) =
+            run_ceremony_with_states(&committee, &parties, Some((&old, &old_outputs)), &mut rng);
+
+        // The key server public key is unchanged.
+        assert_eq!(
+            outputs.values().next().unwrap().vss_pk.c0(),
+            old_outputs.values().next().unwrap().vss_pk.c0()
+        );
+        for (party, state) in parties.iter().zip(&states) {
+            let warnings = CommitteeRotationVerifier
+                .verify(&committee, &old, state, &outputs[&party.address])
+                .unwrap();
+            assert!(warnings.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_fresh_dkg() {
+        fresh_dkg::<G2Element>(0);
+    }
+
+    #[test]
+    fn test_fresh_dkg_g1() {
+        fresh_dkg::<G1Element>(0);
+    }
+
+    #[test]
+    fn test_key_rotation() {
+        key_rotation::<G2Element>(1);
+    }
+
+    #[test]
+    fn test_key_rotation_g1() {
+        key_rotation::<G1Element>(1);
+    }
+
+    #[test]
+    fn test_state_key_type() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let parties: Vec<_> = (0..2).map(|_| new_party_keys(&mut rng)).collect();
+        let committee = test_committee(&parties, 2, None, &mut rng);
+        let (state, _) = prepare_ceremony::<G1Element, _>(
+            &committee,
+            &parties[0].address,
+            &parties[0].keys,
+            None,
+            None,
+            None,
+            &mut rng,
+        )
+        .unwrap();
+        let json = serde_json::to_string(&state).unwrap();
+        assert_eq!(stored_key_type(&json).unwrap(), KeyType::G1);
+        assert!(serde_json::from_str::<DkgState<G1Element>>(&json).is_ok());
+        let err = serde_json::from_str::<DkgState<G2Element>>(&json)
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.contains("key type 1"), "{err}");
+
+        // States written before tagging are G2 states.
+        let (state, _) = prepare_ceremony::<G2Element, _>(
+            &committee,
+            &parties[0].address,
+            &parties[0].keys,
+            None,
+            None,
+            None,
+            &mut rng,
+        )
+        .unwrap();
+        let mut json = serde_json::to_value(&state).unwrap();
+        json.as_object_mut().unwrap().remove("key_type");
+        let json = json.to_string();
+        assert_eq!(stored_key_type(&json).unwrap(), KeyType::G2);
+        assert!(serde_json::from_str::<DkgState<G2Element>>(&json).is_ok());
+    }
+
+    #[test]
+    fn test_state_config_version() {
+        let mut rng = StdRng::seed_from_u64(6);
+        let parties: Vec<_> = (0..2).map(|_| new_party_keys(&mut rng)).collect();
+        let committee = test_committee(&parties, 2, None, &mut rng);
+        let (state, _) = prepare_ceremony::<G2Element, _>(
+            &committee,
+            &parties[0].address,
+            &parties[0].keys,
+            None,
+            None,
+            None,
+            &mut rng,
+        )
+        .unwrap();
+        let mut json = serde_json::to_value(&state).unwrap();
+        assert_eq!(
+            json["config"]["config_version"],
+            InitializedConfig::CURRENT_VERSION
+        );
+        check_state_version(&json.to_string()).unwrap();
+
+        // States written before versioning are version 1.
+        json["config"]
+            .as_object_mut()
+            .unwrap()
+            .remove("config_version");
+        check_state_version(&json.to_string()).unwrap();
+
+        json["config"]["config_version"] = 0.into();
+        let err = check_state_version(&json.to_string())
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("State file version 0 is incompatible with current version 1"),
+            "{err}"
+        );
+        assert!(err.contains("older dkg-cli"), "{err}");
+
+        json["config"]["config_version"] = 2.into();
+        let err = check_state_version(&json.to_string())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("newer dkg-cli"), "{err}");
+    }
+
+    #[test]
+    fn test_messages_from_expected_senders() {
+        let mut rng = StdRng::seed_from_u64(8);
+        let parties: Vec<_> = (0..3).map(|_| new_party_keys(&mut rng)).collect();
+        let committee = test_committee(&parties, 2, None, &mut rng);
+        let (mut states, messages): (Vec<_>, Vec<_>) = parties
+            .iter()
+            .map(|party| {
+                let (state, message) = prepare_ceremony::<G2Element, _>(
+                    &committee,
+                    &party.address,
+                    &party.keys,
+                    None,
+                    None,
+                    None,
+                    &mut rng,
+                )
+                .unwrap();
+                (state, message.unwrap())
+            })
+            .unzip();
+        let mut state = states.remove(0);
+
+        // Fresh DKG expects all parties.
+        assert_eq!(
+            state.messages_from_expected_senders(&messages[1..]),
+            (vec![1, 2], vec![0])
+        );
+        assert_eq!(
+            state.messages_from_expected_senders(&[]),
+            (vec![], vec![0, 1, 2])
+        );
+
+        // Key rotation expects the continuing members only.
+        state.config.new_to_old_mapping = Some(HashMap::from([(0, 1), (2, 0)]));
+        assert_eq!(
+            state.messages_from_expected_senders(&messages[1..]),
+            (vec![2], vec![0])
+        );
+    }
+
+    #[test]
+    fn test_verify_all_signatures() {
+        let mut rng = StdRng::seed_from_u64(10);
+        let parties: Vec<_> = (0..3).map(|_| new_party_keys(&mut rng)).collect();
+        let committee = test_committee(&parties, 2, None, &mut rng);
+        let (states, mut messages): (Vec<_>, Vec<_>) = parties
+            .iter()
+            .map(|party| {
+                let (state, message) = prepare_ceremony::<G2Element, _>(
+                    &committee,
+                    &party.address,
+                    &party.keys,
+                    None,
+                    None,
+                    None,
+                    &mut rng,
+                )
+                .unwrap();
+                (state, message.unwrap())
+            })
+            .unzip();
+        let signing_pks = &states[0].config.signing_pks;
+        assert_eq!(
+            verify_all_signatures(&messages, signing_pks).unwrap(),
+            vec![0, 1, 2]
+        );
+
+        // Both failures are reported.
+        messages[0].signature = messages[1].signature.clone();
+        let mut missing_pk = signing_pks.clone();
+        missing_pk.remove(&2);
+        let failures = verify_all_signatures(&messages, &missing_pk).unwrap_err();
+        assert_eq!(
+            failures
+                .iter()
+                .map(|(party_id, _)| *party_id)
+                .collect::<Vec<_>>(),
+            vec![0, 2]
+        );
+        assert!(failures[0].1.to_string().contains("Invalid signature"));
+        assert!(failures[1].1.to_string().contains("not found"));
+
+        // Processing fails before any message is processed.
+        let mut state = states.into_iter().next().unwrap();
+        let err = process_bundle(&mut state, &parties[0].keys, messages, 0, &mut rng)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("1 message(s) failed signature verification"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_prepare_onchain_proposal_bytes() {
+        let mut rng = StdRng::seed_from_u64(9);
+        let parties: Vec<_> = (0..3).map(|_| new_party_keys(&mut rng)).collect();
+        let committee = test_committee(&parties, 2, None, &mut rng);
+        let (_, mut states) =
+            run_ceremony_with_states::<G2Element>(&committee, &parties, None, &mut rng);
+        let mut state = states.remove(0);
+
+        let (confirmation_bcs, used_messages_bcs) = state.prepare_onchain_proposal_bytes().unwrap();
+        let (confirmation, used_messages) = state.confirmation.as_ref().unwrap();
+        assert_eq!(confirmation_bcs, bcs::to_bytes(confirmation).unwrap());
+        assert_eq!(used_messages_bcs, bcs::to_bytes(used_messages).unwrap());
+
+        state.confirmation = None;
+        assert!(state.prepare_onchain_proposal_bytes().is_err());
+    }
+
+    #[test]
+    fn test_state_bcs() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let parties: Vec<_> = (0..4).map(|_| new_party_keys(&mut rng)).collect();
+        let committee = test_committee(&parties, 3, None, &mut rng);
+        let (_, states) =
+            run_ceremony_with_states::<G1Element>(&committee, &parties, None, &mut rng);
+
+        let bcs = states[0].to_bcs().unwrap();
+        assert_eq!(stored_key_type_bcs(&bcs).unwrap(), KeyType::G1);
+        let state = DkgState::<G1Element>::from_bcs(&bcs).unwrap();
+        assert_eq!(state.to_bcs().unwrap(), bcs);
+        assert_eq!(state.phase(), DkgPhase::Completed);
+        assert!(bcs.len() < serde_json::to_string(&state).unwrap().len());
+
+        let err = DkgState::<G2Element>::from_bcs(&bcs)
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.contains("key type 1"), "{err}");
+
+        let mut newer = bcs.clone();
+        newer[1] = InitializedConfig::CURRENT_VERSION + 1;
+        let err = DkgState::<G1Element>::from_bcs(&newer)
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.contains("newer dkg-cli"), "{err}");
+        assert!(DkgState::<G1Element>::from_bcs(&[]).is_err());
+    }
+
+    #[test]
+    fn test_prepare_ceremony_errors() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let old_parties: Vec<_> = (0..3).map(|_| new_party_keys(&mut rng)).collect();
+        let old = test_committee(&old_parties, 2, None, &mut rng);
+        let outsider = new_party_keys(&mut rng);
+        let some_share = Some(G2Scalar::from(1u128));
+
+        // Not a member.
+        let result = prepare_ceremony::<G2Element, _>(
+            &old,
+            &outsider.address,
+            &outsider.keys,
+            None,
+            None,
+            None,
+            &mut rng,
+        );
+        assert!(matches!(
+            ceremony_error(result),
+            CeremonyError::Validation(_)
+        ));
+
+        // Keys do not match the registered keys.
+        let result = prepare_ceremony::<G2Element, _>(
+            &old,
+            &old_parties[0].address,
+            &outsider.keys,
+            None,
+            None,
+            None,
+            &mut rng,
+        );
+        assert!(matches!(
+            ceremony_error(result),
+            CeremonyError::Validation(_)
+        ));
+
+        // Old share for a fresh DKG.
+        let result = prepare_ceremony::<G2Element, _>(
+            &old,
+            &old_parties[0].address,
+            &old_parties[0].keys,
+            some_share,
+            None,
+            None,
+            &mut rng,
+        );
+        assert!(matches!(
+            ceremony_error(result),
+            CeremonyError::InvalidInput(_)
+        ));
+
+        let old_outputs = run_ceremony::<G2Element>(&old, &old_parties, None, &mut rng);
+        let mut parties = vec![outsider];
+        parties.extend(old_parties);
+        let committee = test_committee(&parties, 2, Some(old.id), &mut rng);
+        let joining = &parties[0];
+        let continuing = &parties[1];
+
+        // Rotation without the old committee.
+        let result = prepare_ceremony::<G2Element, _>(
+            &committee,
+            &continuing.address,
+            &continuing.keys,
+            some_share,
+            None,
+            None,
+            &mut rng,
+        );
+        assert!(matches!(
+            ceremony_error(result),
+            CeremonyError::InvalidInput(_)
+        ));
+
+        // Continuing member without an old share.
+        let result = prepare_ceremony::<G2Element, _>(
+            &committee,
+            &continuing.address,
+            &continuing.keys,
+            None,
+            Some(to_old_committee(&old, &old_outputs)),
+            None,
+            &mut rng,
+        );
+        assert!(matches!(
+            ceremony_error(result),
+            CeremonyError::InvalidInput(_)
+        ));
+
+        // New member with an old share.
+        let result = prepare_ceremony::<G2Element, _>(
+            &committee,
+            &joining.address,
+            &joining.keys,
+            some_share,
+            Some(to_old_committee(&old, &old_outputs)),
+            None,
+            &mut rng,
+        );
+        assert!(matches!(
+            ceremony_error(result),
+            CeremonyError::InvalidInput(_)
+        ));
+
+        // Old committee is not the committee being rotated.
+        let result = prepare_ceremony::<G2Element, _>(
+            &committee,
+            &joining.address,
+            &joining.keys,
+            None,
+            Some(to_old_committee(&committee, &old_outputs)),
+            None,
+            &mut rng,
+        );
+        assert!(matches!(
+            ceremony_error(result),
+            CeremonyError::Validation(_)
+        ));
+    }
+
+    #[test]
+    fn test_prepare_ceremony_weights() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let parties: Vec<_> = (0..3).map(|_| new_party_keys(&mut rng)).collect();
+        let committee = test_committee(&parties, 2, None, &mut rng);
+
+        let weights = HashMap::from([(1, 3)]);
+        let (state, message) = prepare_ceremony::<G2Element, _>(
+            &committee,
+            &parties[0].address,
+            &parties[0].keys,
+            None,
+            None,
+            Some(&weights),
+            &mut rng,
+        )
+        .unwrap();
+        assert!(message.is_some());
+        let node_weights: Vec<_> = state.config.nodes.iter().map(|n| n.weight).collect();
+        assert_eq!(node_weights, [1, 3, 1]);
+        assert_eq!(state.config.nodes.total_weight(), 5);
+
+        // Party 3 is not in the committee.
+        let result = prepare_ceremony::<G2Element, _>(
+            &committee,
+            &parties[0].address,
+            &parties[0].keys,
+            None,
+            None,
+            Some(&HashMap::from([(3, 2)])),
+            &mut rng,
+        );
+        assert!(matches!(
+            ceremony_error(result),
+            CeremonyError::InvalidInput(_)
+        ));
+    }
+
+    #[test]
+    fn test_process_bundle_errors() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let parties: Vec<_> = (0..3).map(|_| new_party_keys(&mut rng)).collect();
+        let committee = test_committee(&parties, 2, None, &mut rng);
+        let (mut states, messages): (Vec<_>, Vec<_>) = parties
+            .iter()
+            .map(|party| {
+                let (state, message) = prepare_ceremony::<G2Element, _>(
+                    &committee,
+                    &party.address,
+                    &party.keys,
+                    None,
+                    None,
+                    None,
+                    &mut rng,
+                )
+                .unwrap();
+                (state, message.unwrap())
+            })
+            .unzip();
+        let keys = &parties[0].keys;
+        let state = &mut states[0];
+
+        // Not processed yet.
+        assert!(finalize(state, keys, &mut rng).is_err());
+
+        // Missing message.
+        let result = process_bundle(state, keys, messages[..2].to_vec(), 0, &mut rng);
+        assert!(matches!(
+            ceremony_error(result),
+            CeremonyError::Validation(_)
+        ));
+
+        // Message signed by another party.
+        let mut forged = messages.clone();
+        forged[1] = sign_message(forged[1].message.clone(), &parties[2].keys.signing_sk);
+        let result = process_bundle(state, keys, forged, 0, &mut rng);
+        assert!(matches!(
+            ceremony_error(result),
+            CeremonyError::Verification(_)
+        ));
+        assert!(state.confirmation.is_none());
+
+        let outcome = process_bundle(state, keys, messages.clone(), 0, &mut rng).unwrap();
+        assert_eq!(outcome.processed, vec![0, 1, 2]);
+        let output = finalize(state, keys, &mut rng).unwrap();
+
+        // Messages that are already processed, e.g. merged from another state, are skipped.
+        let (mut restarted, _) = prepare_ceremony::<G2Element, _>(
+            &committee,
+            &parties[0].address,
+            keys,
+            None,
+            None,
+            None,
+            &mut rng,
+        )
+        .unwrap();
+        restarted.processed_messages = state.processed_messages[..1].to_vec();
+        let outcome = process_bundle(&mut restarted, keys, messages, 0, &mut rng).unwrap();
+        assert_eq!(outcome.skipped, vec![0]);
+        assert_eq!(outcome.processed, vec![1, 2]);
+        assert!(finalize(&mut restarted, keys, &mut rng).unwrap().vss_pk == output.vss_pk);
+    }
+
+    #[test]
+    fn test_process_messages_only() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let parties: Vec<_> = (0..3).map(|_| new_party_keys(&mut rng)).collect();
+        let committee = test_committee(&parties, 2, None, &mut rng);
+        let (mut states, messages): (Vec<_>, Vec<_>) = parties
+            .iter()
+            .map(|party| {
+                let (state, message) = prepare_ceremony::<G2Element, _>(
+                    &committee,
+                    &party.address,
+                    &party.keys,
+                    None,
+                    None,
+                    None,
+                    &mut rng,
+                )
+                .unwrap();
+                (state, message.unwrap())
+            })
+            .unzip();
+        let keys = &parties[0].keys;
+        let state = &mut states[0];
+        assert_eq!(state.phase(), DkgPhase::Initialized);
+        // Only the own message.
+        assert_eq!(state.missing_message_senders(), vec![1, 2]);
+        assert_eq!(
+            state.progress_summary(),
+            ProgressSummary {
+                total_expected: 3,
+                messages_received: 1,
+                messages_processed: 0,
+                has_confirmation: false,
+                is_complete: false,
+            }
+        );
+        assert_eq!(state.progress_summary().percent_complete(), 16);
+
+        // Party 2 is offline.
+        let outcome = process_messages_only(state, keys, messages[..2].to_vec(), &mut rng).unwrap();
+        assert_eq!(outcome.processed, vec![0, 1]);
+        assert_eq!(state.phase(), DkgPhase::MessagesProcessed);
+        assert!(!state.has_sufficient_processed_messages());
+        assert_eq!(state.missing_message_senders(), vec![2]);
+        let progress = state.progress_summary();
+        assert_eq!(
+            (progress.messages_received, progress.messages_processed),
+            (2, 2)
+        );
+        assert_eq!(
+            progress.to_string(),
+            "Messages received:  2 of 3
+Messages processed: 2 of 3
+Confirmation:       no
+Complete:           no (66%)"
+        );
+        let json = serde_json::to_value(&progress).unwrap();
+        assert_eq!(json["messages_processed"], 2);
+        assert_eq!(json["is_complete"], false);
+
+        let outcome = process_messages_only(state, keys, messages.clone(), &mut rng).unwrap();
+        assert_eq!(outcome.skipped, vec![0, 1]);
+        assert_eq!(outcome.processed, vec![2]);
+        assert!(state.has_sufficient_processed_messages());
+
+        // Continue without further messages.
+        let outcome = process_bundle(state, keys, vec![], 0, &mut rng).unwrap();
+        assert!(outcome.processed.is_empty());
+        assert_eq!(state.phase(), DkgPhase::Merged);
+        assert!(state.progress_summary().has_confirmation);
+        let output = finalize(state, keys, &mut rng).unwrap();
+        assert_eq!(state.phase(), DkgPhase::Completed);
+        assert!(state.missing_message_senders().is_empty());
+        assert_eq!(state.progress_summary().percent_complete(), 100);
+        assert!(matches!(
+            ceremony_error(process_messages_only(state, keys, vec![], &mut rng)),
+            CeremonyError::StateConflict(_)
+        ));
+
+        let other = &mut states[1];
+        process_bundle(other, &parties[1].keys, messages, 0, &mut rng).unwrap();
+        let other_output = finalize(other, &parties[1].keys, &mut rng).unwrap();
+        assert!(output.vss_pk == other_output.vss_pk);
+    }
+
+    #[test]
+    fn test_merge_with_outdated_confirmation() {
+        let mut rng = StdRng::seed_from_u64(13);
+        let parties: Vec<_> = (0..3).map(|_| new_party_keys(&mut rng)).collect();
+        let old = test_committee(&parties, 2, None, &mut rng);
+        let old_outputs = run_ceremony::<G2Element>(&old, &parties, None, &mut rng);
+
+        // All members continue, so the messages of any two of them complete the rotation.
+        let committee = test_committee(&parties, 2, Some(old.id), &mut rng);
+        let (mut states, messages): (Vec<_>, Vec<_>) = parties
+            .iter()
+            .map(|party| {
+                let (state, message) = prepare_ceremony(
+                    &committee,
+                    &party.address,
+                    &party.keys,
+                    Some(key_share(&old_outputs[&party.address])),
+                    Some(to_old_committee(&old, &old_outputs)),
+                    None,
+                    &mut rng,
+                )
+                .unwrap();
+                (state, message.unwrap())
+            })
+            .unzip();
+        let keys = &parties[0].keys;
+        let state = &mut states[0];
+        let copy = |state: &DkgState| -> DkgState {
+            serde_json::from_value(serde_json::to_value(state).unwrap()).unwrap()
+        };
+        let mut other = copy(state);
+        process_bundle(state, keys, messages[..2].to_vec(), 0, &mut rng).unwrap();
+        process_messages_only(&mut other, keys, messages[2..].to_vec(), &mut rng).unwrap();
+        let mut completed = copy(state);
+        finalize(&mut completed, keys, &mut rng).unwrap();
+
+        // The confirmation does not cover the message of party 2.
+        assert_eq!(state.phase(), DkgPhase::Merged);
+        state.merge_with(&other).unwrap();
+        assert_eq!(state.processed_messages.len(), 3);
+        assert_eq!(state.phase(), DkgPhase::MessagesProcessed);
+        process_bundle(state, keys, vec![], 0, &mut rng).unwrap();
+        assert_eq!(state.phase(), DkgPhase::Merged);
+
+        // Nothing new to merge, so the confirmation is kept.
+        state.merge_with(&other).unwrap();
+        assert_eq!(state.phase(), DkgPhase::Merged);
+
+        // A completed state is left unchanged.
+        assert!(matches!(
+            ceremony_error(completed.merge_with(&other)),
+            CeremonyError::StateConflict(_)
+        ));
+        assert_eq!(completed.processed_messages.len(), 2);
+        assert_eq!(completed.phase(), DkgPhase::Completed);
+    }
+
+    #[test]
+    fn test_committee_rotation_verifier() {
+        let mut rng = StdRng::seed_from_u64(12);
+        let old_parties: Vec<_> = (0..4).map(|_| new_party_keys(&mut rng)).collect();
+        let old = test_committee(&old_parties, 3, None, &mut rng);
+        let old_outputs = run_ceremony::<G2Element>(&old, &old_parties, None, &mut rng);
+
+        // New parties 1 to 3 continue as old parties 1 to 3.
+        let mut parties = vec![new_party_keys(&mut rng)];
+        parties.extend(old_parties.into_iter().skip(1));
+        let committee = test_committee(&parties, 4, Some(old.id), &mut rng);
+        let (outputs, states) =
+            run_ceremony_with_states(&committee, &parties, Some((&old, &old_outputs)), &mut rng);
+        let output = &outputs[&parties[2].address];
+        let copy_state = || -> DkgState {
+            serde_json::from_value(serde_json::to_value(&states[2]).unwrap()).unwrap()
+        };
+        let verifier = CommitteeRotationVerifier;
+        assert!(verifier
+            .verify(&committee, &old, &states[2], output)
+            .unwrap()
+            .is_empty());
+
+        // Not a rotation from the old committee.
+        let other = test_committee(&parties, 4, None, &mut rng);
+        let result = verifier.verify(&other, &old, &states[2], output);
+        assert!(matches!(
+            ceremony_error(result),
+            CeremonyError::Validation(_)
+        ));
+
+        // Inconsistent mapping.
+        let mut state = copy_state();
+        state
+            .config
+            .new_to_old_mapping
+            .as_mut()
+            .unwrap()
+            .insert(0, 0);
+        let result = verifier.verify(&committee, &old, &state, output);
+        assert!(matches!(
+            ceremony_error(result),
+            CeremonyError::Validation(_)
+        ));
+
+        // Missing data is reported as warnings.
+        let mut state = copy_state();
+        state.received_messages.remove(&1);
+        state.processed_messages.retain(|p| p.message.sender != 1);
+        state.config.expected_old_pks.as_mut().unwrap().remove(&3);
+        assert_eq!(
+            verifier.verify(&committee, &old, &state, output).unwrap(),
+            vec![
+                VerificationWarning::MissingMessage { party_id: 1 },
+                VerificationWarning::MissingOldPartialPk {
+                    party_id: 3,
+                    old_party_id: 3
+                },
+            ]
+        );
+        state.config.expected_old_pks = None;
+        assert_eq!(
+            verifier.verify(&committee, &old, &state, output).unwrap(),
+            vec![VerificationWarning::MissingOldPartialPks]
+        );
+
+        // A message not committing to the old partial PK.
+        let mut state = copy_state();
+        state
+            .config
+            .expected_old_pks
+            .as_mut()
+            .unwrap()
+            .insert(2, G2Element::generator());
+        let result = verifier.verify(&committee, &old, &state, output);
+        assert!(matches!(
+            ceremony_error(result),
+            CeremonyError::Verification(_)
+        ));
+
+        // An output of the wrong degree.
+        let result = verifier.verify(
+            &committee,
+            &old,
+            &states[2],
+            &old_outputs[&parties[2].address],
+        );
+        assert!(matches!(
+            ceremony_error(result),
+            CeremonyError::Verification(_)
+        ));
+    }
+
+    #[test]
+    fn test_process_bundle_max_complaints() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let parties: Vec<_> = (0..4).map(|_| new_party_keys(&mut rng)).collect();
+        let committee = test_committee(&parties, 2, None, &mut rng);
+        let mut messages = Vec::new();
+        let mut states = Vec::new();
+        for party in &parties {
+            let (state, message) = prepare_ceremony::<G2Element, _>(
+                &committee,
+                &party.address,
+                &party.keys,
+                None,
+                None,
+                None,
+                &mut rng,
+            )
+            .unwrap();
+            messages.push(message.unwrap());
+            states.push(state);
+        }
+
+        // Party 3 encrypts the share of party 0 to the wrong key.
+        let wrong_key = KeysFile::generate(&mut rng);
+        let nodes = Nodes::new(
+            parties
+                .iter()
+                .enumerate()
+                .map(|(party_id, party)| Node {
+                    id: party_id as u16,
+                    pk: if party_id == 0 {
+                        wrong_key.enc_pk.clone()
+                    } else {
+                        party.keys.enc_pk.clone()
+                    },
+                    weight: 1,
+                })
+                .collect(),
+        )
+        .unwrap();
+        let dealer = Party::<G2Element, G2Element>::new_advanced(
+            parties[3].keys.enc_sk.clone(),
+            nodes,
+            committee.threshold,
+            RandomOracle::new(&committee.id.to_string()),
+            None,
+            None,
+            &mut rng,
+        )
+        .unwrap();
+        messages[3] = sign_message(
+            dealer.create_message(&mut rng).unwrap(),
+            &parties[3].keys.signing_sk,
+        );
+
+        let keys = &parties[0].keys;
+        let state = &mut states[0];
+        let result = process_bundle(state, keys, messages.clone(), 0, &mut rng);
+        assert!(matches!(
+            ceremony_error(result),
+            CeremonyError::Verification(_)
+        ));
+        assert!(state.processed_messages.is_empty());
+
+        let outcome = process_bundle(state, keys, messages, 1, &mut rng).unwrap();
+        assert_eq!(outcome.processed, vec![0, 1, 2]);
+        assert_eq!(
+            outcome
+                .excluded
+                .iter()
+                .map(|party| party.party_id)
+                .collect::<Vec<_>>(),
+            vec![3]
+        );
+        assert!(outcome.complaints.is_empty());
+        let output = finalize(state, keys, &mut rng).unwrap();
+        assert_eq!(
+            G2Element::generator() * key_share(&output),
+            output.vss_pk.eval(share_index(0)).value
+        );
+    }
+}
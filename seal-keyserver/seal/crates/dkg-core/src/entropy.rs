@@ -0,0 +1,80 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Mixing of user-supplied entropy into key generation, as defense in depth against a compromised
+//! OS RNG.
+//!
+//! The construction is `seed = R XOR Blake2b256(EXTRA_ENTROPY_DST || extra)`, where `R` is 32
+//! bytes drawn from the RNG *after* `extra` is fixed. The keys are then derived from a CSPRNG
+//! seeded with `seed`. Since `R` is independent of `extra`, `seed` is uniformly random whenever
+//! `R` is, so the keys are at least as random as with the RNG alone. If the RNG is compromised but
+//! `extra` has enough entropy, `seed` is still unpredictable.
+
+use fastcrypto::hash::{Blake2b256, HashFunction};
+use fastcrypto::traits::AllowedRng;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// Domain separation tag for hashing the extra entropy.
+pub const EXTRA_ENTROPY_DST: &[u8] = b"seal-dkg-extra-entropy";
+
+/// Mix the extra entropy into a 32-byte seed from the RNG, see the module docs.
+pub fn mix_entropy(rng_seed: [u8; 32], extra: &[u8]) -> [u8; 32] {
+    let mut hash = Blake2b256::new();
+    hash.update(EXTRA_ENTROPY_DST);
+    hash.update(extra);
+    let digest = hash.finalize().digest;
+
+    let mut seed = rng_seed;
+    seed.iter_mut().zip(digest).for_each(|(s, d)| *s ^= d);
+    seed
+}
+
+/// A CSPRNG seeded with output of `rng` mixed with the extra entropy.
+pub fn mixed_rng<R: AllowedRng>(rng: &mut R, extra: &[u8]) -> StdRng {
+    let mut rng_seed = [0u8; 32];
+    rng.fill_bytes(&mut rng_seed);
+    StdRng::from_seed(mix_entropy(rng_seed, extra))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fastcrypto::encoding::{Encoding, Hex};
+
+    #[test]
+    fn test_mix_entropy_known_answers() {
+        let cases: [([u8; 32], &[u8], &str); 3] = [
+            (
+                [0; 32],
+                b"",
+                "5a372f9029b49ebce49761a8ed954ae8fac03a553a4274f0eefe7dfb05bd40c7",
+            ),
+            (
+                [0; 32],
+                b"abc",
+                "3e0ee1fa27b94d74b9f1807a6eddbd20a8b7ae33f9cd06c8320ff68f881db919",
+            ),
+            (
+                std::array::from_fn(|i| i as u8),
+                b"abc",
+                "3e0fe3f923bc4b73b1f88a7162d0b32fb8a6bc20edd810df2a16ec949400a706",
+            ),
+        ];
+        for (rng_seed, extra, expected) in cases {
+            assert_eq!(Hex::encode(mix_entropy(rng_seed, extra)), expected);
+        }
+    }
+
+    #[test]
+    fn test_mix_entropy_depends_on_both_inputs() {
+        let rng_seed = [7u8; 32];
+        assert_ne!(mix_entropy(rng_seed, b"a"), mix_entropy(rng_seed, b"b"));
+        assert_ne!(mix_entropy(rng_seed, b"a"), mix_entropy([8u8; 32], b"a"));
+        // XOR with a fixed digest is a bijection of the RNG seed.
+        let digest = mix_entropy([0; 32], b"a");
+        let mut expected = rng_seed;
+        expected.iter_mut().zip(digest).for_each(|(s, d)| *s ^= d);
+        assert_eq!(mix_entropy(rng_seed, b"a"), expected);
+    }
+}
@@ -0,0 +1,43 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Error classification for the DKG ceremony.
+
+use std::fmt;
+
+/// Classified ceremony error. Wrapped in [anyhow::Error] and recovered by downcasting, e.g. to
+/// map it to an exit code. Unclassified errors are returned as plain [anyhow::Error].
+#[derive(Debug)]
+pub enum CeremonyError {
+    /// Invalid input from the caller, e.g. an old share for a fresh DKG.
+    InvalidInput(anyhow::Error),
+    /// Local data does not match the committee, e.g. registered public keys or membership.
+    Validation(anyhow::Error),
+    /// A complaint was found or a cryptographic verification failed. Do not propose onchain.
+    Verification(anyhow::Error),
+    /// The committee is not in the state required for the step.
+    StateConflict(anyhow::Error),
+}
+
+impl CeremonyError {
+    fn inner(&self) -> &anyhow::Error {
+        match self {
+            CeremonyError::InvalidInput(e)
+            | CeremonyError::Validation(e)
+            | CeremonyError::Verification(e)
+            | CeremonyError::StateConflict(e) => e,
+        }
+    }
+}
+
+impl fmt::Display for CeremonyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.inner())
+    }
+}
+
+impl std::error::Error for CeremonyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.inner().source()
+    }
+}
@@ -0,0 +1,132 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Group instantiations of the DKG. The key server master key and the partial keys are in the
+//! key group `G`, while shares are always encrypted to the ECIES keys in G2 registered onchain, so
+//! the protocol runs with the group pair `(G, G2Element)`.
+
+use anyhow::{anyhow, Result};
+use fastcrypto::groups::bls12381::{G1Element, G2Element, Scalar};
+use fastcrypto::groups::{GroupElement, MultiScalarMul};
+use schemars::r#gen::SchemaGenerator;
+use schemars::schema::{Schema, SchemaObject};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Key type of a key server, as in the `key_type` field of `KeyServerV2`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum KeyType {
+    /// Boneh-Franklin IBE over BLS12-381 with the master key in G2.
+    #[default]
+    G2 = 0,
+    /// Master key in G1 (MinPk-style) for cheaper derivations. Not yet supported onchain.
+    G1 = 1,
+}
+
+impl TryFrom<u8> for KeyType {
+    type Error = anyhow::Error;
+
+    fn try_from(key_type: u8) -> Result<Self> {
+        match key_type {
+            0 => Ok(KeyType::G2),
+            1 => Ok(KeyType::G1),
+            _ => Err(anyhow!("Unsupported key type {key_type}")),
+        }
+    }
+}
+
+impl fmt::Display for KeyType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyType::G2 => write!(f, "{} (G2)", *self as u8),
+            KeyType::G1 => write!(f, "{} (G1)", *self as u8),
+        }
+    }
+}
+
+/// Group of the key server master key and partial keys.
+pub trait KeyGroup:
+    GroupElement<ScalarType = Scalar> + MultiScalarMul + Serialize + DeserializeOwned
+{
+    const KEY_TYPE: KeyType;
+}
+
+impl KeyGroup for G2Element {
+    const KEY_TYPE: KeyType = KeyType::G2;
+}
+
+impl KeyGroup for G1Element {
+    const KEY_TYPE: KeyType = KeyType::G1;
+}
+
+/// Tag of the instantiation of a serialized state. Serialized as the key type of `G`, and fails to
+/// deserialize for any other key type so that a state of one instantiation cannot be loaded as
+/// another. States written before tagging have no tag and are G2 states.
+pub struct KeyTypeTag<G>(PhantomData<G>);
+
+impl<G> Default for KeyTypeTag<G> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<G: KeyGroup> Serialize for KeyTypeTag<G> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(G::KEY_TYPE as u8)
+    }
+}
+
+impl<'de, G: KeyGroup> Deserialize<'de> for KeyTypeTag<G> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let key_type = u8::deserialize(deserializer)?;
+        if key_type != G::KEY_TYPE as u8 {
+            return Err(serde::de::Error::custom(format!(
+                "State is for key type {key_type}, expected {}",
+                G::KEY_TYPE
+            )));
+        }
+        Ok(Self::default())
+    }
+}
+
+impl<G: KeyGroup> JsonSchema for KeyTypeTag<G> {
+    fn schema_name() -> String {
+        "KeyTypeTag".to_string()
+    }
+
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            const_value: Some((G::KEY_TYPE as u8).into()),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+/// Key type of a serialized state without deserializing it, to select the instantiation for
+/// loading it.
+pub fn stored_key_type(json: &str) -> Result<KeyType> {
+    #[derive(Deserialize)]
+    struct Tagged {
+        #[serde(default)]
+        key_type: u8,
+    }
+    KeyType::try_from(serde_json::from_str::<Tagged>(json)?.key_type)
+}
+
+/// As [stored_key_type], for a state serialized with BCS. The key type tag is its first byte.
+pub fn stored_key_type_bcs(bytes: &[u8]) -> Result<KeyType> {
+    KeyType::try_from(
+        *bytes
+            .first()
+            .ok_or_else(|| anyhow!("State is empty, expected a key type"))?,
+    )
+}
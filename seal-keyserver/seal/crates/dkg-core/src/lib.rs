@@ -0,0 +1,27 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Core logic of the DKG ceremony and key rotation for Seal committees, without any IO. Used by
+//! `dkg-cli`, which adds fetching onchain data, files and printing.
+
+pub mod ceremony;
+pub mod entropy;
+pub mod errors;
+pub mod groups;
+pub mod recovery;
+pub mod schema;
+pub mod types;
+pub mod utils;
+
+pub use ceremony::{
+    committee_nodes, finalize, prepare_ceremony, process_bundle, process_messages_only,
+    ExcludedParty, OldCommittee, ProcessOutcome,
+};
+pub use errors::CeremonyError;
+pub use groups::{stored_key_type, stored_key_type_bcs, KeyGroup, KeyType};
+pub use recovery::recover_master_key;
+pub use types::{
+    check_state_version, check_state_version_bcs, DkgPhase, DkgState, InitializedConfig, KeysFile,
+    MessageFile, ProgressSummary, ShareHandoff, SignedMessage,
+};
+pub use utils::{CommitteeRotationVerifier, VerificationWarning};
@@ -0,0 +1,152 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Emergency reconstruction of the master key from a threshold of key shares, e.g. if the
+//! committee dissolves or its onchain objects become unusable.
+
+use crate::errors::CeremonyError;
+use crate::groups::KeyGroup;
+use anyhow::{anyhow, Result};
+use fastcrypto::groups::bls12381::Scalar as G2Scalar;
+use fastcrypto::groups::{GroupElement, Scalar};
+use fastcrypto_tbls::polynomial::PublicPoly;
+use std::collections::HashMap;
+use std::num::NonZeroU16;
+
+/// Reconstruct the master key from the key shares of at least a threshold of parties, by party
+/// ID. Each share is checked against the party's partial public key from the VSS public
+/// polynomial `vss_pk` of the committee, so that an inconsistent share set is rejected instead of
+/// reconstructing a wrong key. The result is checked against the key server public key
+/// `vss_pk.c0()`.
+pub fn recover_master_key<G: KeyGroup>(
+    vss_pk: &PublicPoly<G>,
+    shares: &HashMap<u16, G2Scalar>,
+) -> Result<G2Scalar> {
+    let threshold = vss_pk.degree() as usize + 1;
+    if shares.len() < threshold {
+        return Err(CeremonyError::InvalidInput(anyhow!(
+            "Got {} share(s), need at least the threshold {}",
+            shares.len(),
+            threshold
+        ))
+        .into());
+    }
+
+    for (party_id, share) in shares {
+        let index = party_id
+            .checked_add(1)
+            .and_then(NonZeroU16::new)
+            .ok_or_else(|| CeremonyError::InvalidInput(anyhow!("Invalid party ID {party_id}")))?;
+        if G::generator() * *share != vss_pk.eval(index).value {
+            return Err(CeremonyError::Verification(anyhow!(
+                "Share of party {party_id} does not match its partial public key"
+            ))
+            .into());
+        }
+    }
+
+    let master_key = interpolate_at_zero(shares)?;
+    if G::generator() * master_key != *vss_pk.c0() {
+        return Err(CeremonyError::Verification(anyhow!(
+            "Recovered master key does not match the key server public key"
+        ))
+        .into());
+    }
+    Ok(master_key)
+}
+
+/// Lagrange interpolation at zero of the shares, where the share of party `i` is the evaluation
+/// at `i + 1`.
+fn interpolate_at_zero(shares: &HashMap<u16, G2Scalar>) -> Result<G2Scalar> {
+    let points: Vec<(G2Scalar, G2Scalar)> = shares
+        .iter()
+        .map(|(party_id, share)| (G2Scalar::from(*party_id as u128 + 1), *share))
+        .collect();
+    let mut result = G2Scalar::zero();
+    for (i, (x_i, y_i)) in points.iter().enumerate() {
+        let mut numerator = G2Scalar::from(1u128);
+        let mut denominator = G2Scalar::from(1u128);
+        for (j, (x_j, _)) in points.iter().enumerate() {
+            if i != j {
+                numerator = numerator * *x_j;
+                denominator = denominator * (*x_j - *x_i);
+            }
+        }
+        result = result + *y_i * numerator * denominator.inverse()?;
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fastcrypto::groups::bls12381::{G1Element, G2Element};
+    use fastcrypto_tbls::polynomial::Poly;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// Shares of all `n` parties of a random polynomial of degree `threshold - 1`, and its
+    /// commitment in `G`.
+    fn random_shares<G: KeyGroup>(
+        n: u16,
+        threshold: u16,
+        seed: u64,
+    ) -> (G2Scalar, PublicPoly<G>, HashMap<u16, G2Scalar>) {
+        let poly = Poly::<G2Scalar>::rand(threshold - 1, &mut StdRng::seed_from_u64(seed));
+        let shares = (0..n)
+            .map(|party_id| {
+                let index = NonZeroU16::new(party_id + 1).unwrap();
+                (party_id, poly.eval(index).value)
+            })
+            .collect();
+        (*poly.c0(), poly.commit(), shares)
+    }
+
+    fn recover<G: KeyGroup>() {
+        let (master_key, vss_pk, mut shares) = random_shares::<G>(5, 3, 1);
+        assert_eq!(recover_master_key(&vss_pk, &shares).unwrap(), master_key);
+
+        // Any threshold of shares is enough.
+        shares.remove(&0);
+        shares.remove(&3);
+        assert_eq!(recover_master_key(&vss_pk, &shares).unwrap(), master_key);
+
+        shares.remove(&4);
+        let err = recover_master_key(&vss_pk, &shares).unwrap_err();
+        assert!(
+            err.to_string().contains("need at least the threshold 3"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_recover_master_key() {
+        recover::<G2Element>();
+    }
+
+    #[test]
+    fn test_recover_master_key_g1() {
+        recover::<G1Element>();
+    }
+
+    #[test]
+    fn test_recover_master_key_inconsistent_shares() {
+        let (_, vss_pk, mut shares) = random_shares::<G2Element>(4, 3, 2);
+        let tampered = shares[&2] + G2Scalar::from(1u128);
+        shares.insert(2, tampered);
+        let err = recover_master_key(&vss_pk, &shares).unwrap_err();
+        assert!(
+            matches!(
+                err.downcast_ref::<CeremonyError>(),
+                Some(CeremonyError::Verification(_))
+            ),
+            "{err}"
+        );
+        assert!(err.to_string().contains("party 2"), "{err}");
+
+        // Shares of another committee.
+        let (_, other_vss_pk, _) = random_shares::<G2Element>(4, 3, 3);
+        let (_, _, shares) = random_shares::<G2Element>(4, 3, 2);
+        assert!(recover_master_key(&other_vss_pk, &shares).is_err());
+    }
+}
@@ -0,0 +1,81 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! JSON Schemas of the files written by `dkg-cli`, for tooling that reads them.
+//!
+//! Fields holding fastcrypto types use the serde format of fastcrypto, which is not described
+//! here, so their schemas accept any value.
+
+use schemars::r#gen::SchemaGenerator;
+use schemars::schema::{
+    InstanceType, Metadata, RootSchema, Schema, SchemaObject, StringValidation,
+};
+use schemars::schema_for;
+
+use crate::types::{DkgState, DkgTranscript, KeyServerConfig, MessageFile};
+
+/// Schema of a `message_<party ID>.json` file.
+pub fn message_file_schema() -> RootSchema {
+    schema_for!(MessageFile)
+}
+
+/// Schema of `state.json`, for key type 0 (G2).
+pub fn state_schema() -> RootSchema {
+    schema_for!(DkgState)
+}
+
+/// Schema of `transcript.json` written by `prune`, for key type 0 (G2).
+pub fn transcript_schema() -> RootSchema {
+    schema_for!(DkgTranscript)
+}
+
+/// Schema of the key server config written by `export-key-server-config`.
+pub fn key_server_config_schema() -> RootSchema {
+    schema_for!(KeyServerConfig)
+}
+
+fn string_schema(pattern: &str, description: &str) -> Schema {
+    SchemaObject {
+        instance_type: Some(InstanceType::String.into()),
+        string: Some(Box::new(StringValidation {
+            pattern: Some(pattern.to_string()),
+            ..Default::default()
+        })),
+        metadata: Some(Box::new(Metadata {
+            description: Some(description.to_string()),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+    .into()
+}
+
+/// A Sui address or object ID, as 0x-prefixed hex.
+pub(crate) fn address_schema(_: &mut SchemaGenerator) -> Schema {
+    string_schema("^0x[0-9a-fA-F]{64}$", "Sui address, 0x-prefixed hex")
+}
+
+/// Hex of BCS bytes, 0x-prefixed.
+pub(crate) fn hex_schema(_: &mut SchemaGenerator) -> Schema {
+    string_schema("^0x([0-9a-fA-F]{2})*$", "BCS bytes, 0x-prefixed hex")
+}
+
+/// Base64 of BCS bytes.
+pub(crate) fn base64_schema(_: &mut SchemaGenerator) -> Schema {
+    string_schema(
+        "^([A-Za-z0-9+/]{4})*([A-Za-z0-9+/]{2}==|[A-Za-z0-9+/]{3}=)?$",
+        "BCS bytes, base64",
+    )
+}
+
+/// A value serialized by fastcrypto.
+pub(crate) fn fastcrypto_schema(_: &mut SchemaGenerator) -> Schema {
+    SchemaObject {
+        metadata: Some(Box::new(Metadata {
+            description: Some("Serialized by fastcrypto".to_string()),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+    .into()
+}
@@ -0,0 +1,1073 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Type definitions for the DKG protocol state, keys and messages.
+
+use crate::entropy::mixed_rng;
+use crate::errors::CeremonyError;
+use crate::groups::{KeyGroup, KeyTypeTag};
+use anyhow::{anyhow, Result};
+use fastcrypto::bls12381::min_sig::{
+    BLS12381KeyPair, BLS12381PrivateKey, BLS12381PublicKey, BLS12381Signature,
+};
+use fastcrypto::encoding::{Base64, Encoding, Hex};
+use fastcrypto::groups::bls12381::{G2Element, Scalar as G2Scalar};
+use fastcrypto::groups::GroupElement;
+use fastcrypto::hash::{HashFunction, Sha256};
+use fastcrypto::traits::{AllowedRng, KeyPair, Signer, VerifyingKey};
+use fastcrypto_tbls::dkg_v1::{Message, Output, ProcessedMessage, UsedProcessedMessages};
+use fastcrypto_tbls::ecies_v1::{MultiRecipientEncryption, PrivateKey, PublicKey};
+use fastcrypto_tbls::nodes::{Node, Nodes};
+use fastcrypto_tbls::polynomial::PublicPoly;
+use fastcrypto_tbls::random_oracle::RandomOracle;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use sui_sdk_types::Address;
+
+// JSON hex serializers/deserializers using serde modules.
+macro_rules! json_hex_serde_module {
+    ($module:ident, $type:ty) => {
+        mod $module {
+            use super::*;
+
+            pub fn serialize<S>(value: &$type, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                let bytes = bcs::to_bytes(value).map_err(serde::ser::Error::custom)?;
+                serializer.serialize_str(&Hex::encode_with_format(&bytes))
+            }
+
+            pub fn deserialize<'de, D>(deserializer: D) -> Result<$type, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                let hex_str: String = Deserialize::deserialize(deserializer)?;
+                let bytes = Hex::decode(&hex_str).map_err(serde::de::Error::custom)?;
+                bcs::from_bytes(&bytes).map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+json_hex_serde_module!(enc_sk_serde, PrivateKey<G2Element>);
+json_hex_serde_module!(enc_pk_serde, PublicKey<G2Element>);
+json_hex_serde_module!(signing_sk_serde, BLS12381PrivateKey);
+json_hex_serde_module!(signing_pk_serde, BLS12381PublicKey);
+json_hex_serde_module!(encryption_serde, MultiRecipientEncryption<G2Element>);
+
+/// Format version of keys files written by `generate-keys`.
+pub const CURRENT_KEYS_FILE_VERSION: u8 = 1;
+
+/// Latest supported keys file format version. Version 2 has the same fields as version 1 and only
+/// exists to validate the migration path.
+pub const LATEST_KEYS_FILE_VERSION: u8 = 2;
+
+/// Keys files written before versioning have no version field and are version 1.
+fn default_keys_file_version() -> u8 {
+    1
+}
+
+/// Keys file structure for JSON serialization/deserialization.
+#[derive(Serialize, Deserialize)]
+pub struct KeysFile {
+    /// Format version, see [CURRENT_KEYS_FILE_VERSION].
+    #[serde(default = "default_keys_file_version")]
+    pub version: u8,
+    #[serde(with = "enc_sk_serde")]
+    pub enc_sk: PrivateKey<G2Element>,
+    #[serde(with = "enc_pk_serde")]
+    pub enc_pk: PublicKey<G2Element>,
+    #[serde(with = "signing_sk_serde")]
+    pub signing_sk: BLS12381PrivateKey,
+    #[serde(with = "signing_pk_serde")]
+    pub signing_pk: BLS12381PublicKey,
+    /// Whether user-supplied entropy was mixed into the key generation, see [crate::entropy].
+    #[serde(default)]
+    pub extra_entropy: bool,
+}
+
+impl KeysFile {
+    /// Generate new random encryption and signing keys.
+    pub fn generate<R: AllowedRng>(rng: &mut R) -> Self {
+        let enc_sk = PrivateKey::<G2Element>::new(rng);
+        let enc_pk = PublicKey::<G2Element>::from_private_key(&enc_sk);
+        let signing_kp = BLS12381KeyPair::generate(rng);
+        Self {
+            version: CURRENT_KEYS_FILE_VERSION,
+            enc_sk,
+            enc_pk,
+            signing_pk: signing_kp.public().clone(),
+            signing_sk: signing_kp.private(),
+            extra_entropy: false,
+        }
+    }
+
+    /// Generate new random encryption and signing keys from output of `rng` mixed with the extra
+    /// entropy `extra`, see [crate::entropy].
+    pub fn generate_with_extra_entropy<R: AllowedRng>(rng: &mut R, extra: &[u8]) -> Self {
+        Self {
+            extra_entropy: true,
+            ..Self::generate(&mut mixed_rng(rng, extra))
+        }
+    }
+
+    /// Short identifier of the public keys, for members to confirm each other's keys over a side
+    /// channel, e.g. read out on a call. The first 8 bytes of the SHA-256 hash of the BCS of
+    /// `enc_pk` followed by `signing_pk`, as two hyphenated hex groups like `a1b2c3d4-e5f6a7b8`.
+    pub fn public_key_fingerprint(&self) -> String {
+        let mut hasher = Sha256::default();
+        hasher.update(bcs::to_bytes(&self.enc_pk).expect("Serialization failed"));
+        hasher.update(bcs::to_bytes(&self.signing_pk).expect("Serialization failed"));
+        let digest = hasher.finalize().digest;
+        format!(
+            "{}-{}",
+            Hex::encode(&digest[..4]),
+            Hex::encode(&digest[4..8])
+        )
+    }
+
+    /// Check that the version of these keys is supported.
+    pub fn check_version(&self) -> Result<()> {
+        check_keys_file_version(self.version)
+    }
+
+    /// Upgrade these keys from `from_version` to `to_version`. Only upgrades are supported.
+    pub fn upgrade(&mut self, from_version: u8, to_version: u8) -> Result<()> {
+        check_keys_file_version(from_version)?;
+        check_keys_file_version(to_version)?;
+        if to_version <= from_version {
+            return Err(anyhow!(
+                "Cannot migrate keys file from version {from_version} to {to_version}, only upgrades are supported"
+            ));
+        }
+        if self.version != from_version {
+            return Err(anyhow!(
+                "Keys file has version {}, expected {}",
+                self.version,
+                from_version
+            ));
+        }
+        // Version 2 has the same fields as version 1, so only the version changes.
+        self.version = to_version;
+        Ok(())
+    }
+
+    /// Migrate the JSON of a keys file from `from_version` to `to_version`: parse it in the format
+    /// of `from_version`, [upgrade](Self::upgrade) it and serialize it in the format of
+    /// `to_version`. Reading and writing the file is left to the caller.
+    pub fn migrate(json: &str, from_version: u8, to_version: u8) -> Result<String> {
+        let mut keys: Self =
+            serde_json::from_str(json).map_err(|e| anyhow!("Failed to parse keys file: {e}"))?;
+        keys.upgrade(from_version, to_version)?;
+        Ok(serde_json::to_string_pretty(&keys)?)
+    }
+}
+
+fn check_keys_file_version(version: u8) -> Result<()> {
+    if !(1..=LATEST_KEYS_FILE_VERSION).contains(&version) {
+        return Err(anyhow!(
+            "Unsupported keys file version {version}, latest supported version is {LATEST_KEYS_FILE_VERSION}"
+        ));
+    }
+    Ok(())
+}
+
+/// States written before versioning have no config version and are version 1.
+fn default_config_version() -> u8 {
+    1
+}
+
+/// Initialized party configuration. The master key and partial keys are in `G`.
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[schemars(bound = "G: KeyGroup", rename = "InitializedConfig")]
+pub struct InitializedConfig<G: KeyGroup = G2Element> {
+    /// Format version, see [InitializedConfig::CURRENT_VERSION].
+    #[serde(default = "default_config_version")]
+    pub config_version: u8,
+    /// My party ID for this committee.
+    pub my_party_id: u16,
+    /// All nodes in the protocol.
+    #[schemars(schema_with = "crate::schema::fastcrypto_schema")]
+    pub nodes: Nodes<G2Element>,
+    /// This committee ID, used for random oracle.
+    #[schemars(schema_with = "crate::schema::address_schema")]
+    pub committee_id: Address,
+    /// Threshold for this committee.
+    pub threshold: u16,
+    /// Signing public keys for all parties in this committee.
+    #[schemars(schema_with = "crate::schema::fastcrypto_schema")]
+    pub signing_pks: HashMap<u16, BLS12381PublicKey>,
+    /// Threshold for old committee, for key rotation.
+    pub old_threshold: Option<u16>,
+    /// Mapping from new party ID to old party ID, for key rotation.
+    pub new_to_old_mapping: Option<HashMap<u16, u16>>,
+    /// Expected partial public keys from old committee, for key rotation.
+    #[schemars(schema_with = "crate::schema::fastcrypto_schema")]
+    pub expected_old_pks: Option<HashMap<u16, G>>,
+    /// Old partial key share for key rotation, for continuing members for key rotation.
+    #[schemars(schema_with = "crate::schema::fastcrypto_schema")]
+    pub my_old_share: Option<G2Scalar>,
+    /// Old partial public key for key rotation, for continuing members for key rotation.
+    #[schemars(schema_with = "crate::schema::fastcrypto_schema")]
+    pub my_old_pk: Option<G>,
+    /// Digest of the old committee snapshot used for key rotation.
+    #[serde(default)]
+    pub old_committee_digest: Option<String>,
+}
+
+impl InitializedConfig {
+    /// Format version of configs written by this version. Bump it when the fields change.
+    pub const CURRENT_VERSION: u8 = 1;
+}
+
+impl<G: KeyGroup> InitializedConfig<G> {
+    /// The nodes of this config with the weights given by party ID. Parties not in `weights` get
+    /// weight 1. All parties must use the same weights, as they determine the shares.
+    pub fn nodes_with_weights(&self, weights: &HashMap<u16, u16>) -> Result<Nodes<G2Element>> {
+        if let Some(unknown) = weights
+            .keys()
+            .find(|party_id| self.nodes.iter().all(|node| node.id != **party_id))
+        {
+            return Err(anyhow!(
+                "Weight given for party {unknown}, which is not in the committee"
+            ));
+        }
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|node| Node {
+                id: node.id,
+                pk: node.pk.clone(),
+                weight: weights.get(&node.id).copied().unwrap_or(1),
+            })
+            .collect();
+        Ok(Nodes::new(nodes)?)
+    }
+}
+
+/// Check the config version of a serialized state before deserializing it, so that a state of
+/// another version fails with an actionable error rather than a parse error or silent defaults.
+pub fn check_state_version(json: &str) -> Result<()> {
+    #[derive(Deserialize)]
+    struct Versioned {
+        config: VersionedConfig,
+    }
+    #[derive(Deserialize)]
+    struct VersionedConfig {
+        #[serde(default = "default_config_version")]
+        config_version: u8,
+    }
+    let version = serde_json::from_str::<Versioned>(json)?
+        .config
+        .config_version;
+    check_config_version(version)
+}
+
+/// As [check_state_version], for a state serialized with BCS. The key type tag and the config
+/// version are its first two bytes.
+pub fn check_state_version_bcs(bytes: &[u8]) -> Result<()> {
+    let version = *bytes
+        .get(1)
+        .ok_or_else(|| anyhow!("State is too short to have a version"))?;
+    check_config_version(version)
+}
+
+fn check_config_version(version: u8) -> Result<()> {
+    let current = InitializedConfig::CURRENT_VERSION;
+    if version < current {
+        return Err(anyhow!(
+            "State file version {version} is incompatible with current version {current}; it was written by an older dkg-cli, use that version to finish the ceremony or start a new ceremony with this version"
+        ));
+    }
+    if version > current {
+        return Err(anyhow!(
+            "State file version {version} is incompatible with current version {current}; it was written by a newer dkg-cli, upgrade to load it"
+        ));
+    }
+    Ok(())
+}
+
+/// Phase of a [DkgState].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DkgPhase {
+    /// The state and the own message are created, no messages are processed yet.
+    Initialized,
+    /// Messages are verified and processed, e.g. with `process-all --write-messages-only`, but not
+    /// merged yet.
+    MessagesProcessed,
+    /// The processed messages are merged, but the protocol is not completed, e.g. because of
+    /// complaints.
+    Merged,
+    /// The protocol is completed and the output is stored.
+    Completed,
+}
+
+/// Local state for DKG protocol, used for storing messages and output.
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[schemars(bound = "G: KeyGroup", rename = "DkgState")]
+pub struct DkgState<G: KeyGroup = G2Element> {
+    /// Instantiation of this state, serialized first so that loading a state of another
+    /// instantiation fails early.
+    #[serde(default)]
+    pub key_type: KeyTypeTag<G>,
+    /// Configuration
+    pub config: InitializedConfig<G>,
+    /// Messages created by this party.
+    #[schemars(schema_with = "crate::schema::fastcrypto_schema")]
+    pub my_message: Option<Message<G, G2Element>>,
+    /// Messages received from other parties.
+    #[schemars(schema_with = "crate::schema::fastcrypto_schema")]
+    pub received_messages: HashMap<u16, Message<G, G2Element>>,
+    /// Processed messages.
+    #[schemars(schema_with = "crate::schema::fastcrypto_schema")]
+    pub processed_messages: Vec<ProcessedMessage<G, G2Element>>,
+    /// Confirmation and used messages.
+    #[schemars(schema_with = "crate::schema::fastcrypto_schema")]
+    pub confirmation: Option<(
+        fastcrypto_tbls::dkg_v1::Confirmation<G2Element>,
+        UsedProcessedMessages<G, G2Element>,
+    )>,
+    /// Final output (if completed).
+    #[schemars(schema_with = "crate::schema::fastcrypto_schema")]
+    pub output: Option<Output<G, G2Element>>,
+}
+
+impl<G: KeyGroup> DkgState<G> {
+    /// New state without any messages.
+    pub fn new(config: InitializedConfig<G>, my_message: Option<Message<G, G2Element>>) -> Self {
+        Self {
+            key_type: KeyTypeTag::default(),
+            config,
+            my_message,
+            received_messages: HashMap::new(),
+            processed_messages: vec![],
+            confirmation: None,
+            output: None,
+        }
+    }
+
+    /// Deserialize a state written with `serde_json`, checking its version first.
+    pub fn from_json(json: &str) -> Result<Self> {
+        check_state_version(json)?;
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Serialize with BCS, which is much smaller than JSON for large committees.
+    pub fn to_bcs(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(self)?)
+    }
+
+    /// Deserialize a state written with [DkgState::to_bcs], checking its version first.
+    pub fn from_bcs(bytes: &[u8]) -> Result<Self> {
+        check_state_version_bcs(bytes)?;
+        Ok(bcs::from_bytes(bytes)?)
+    }
+
+    /// Current phase of this state.
+    pub fn phase(&self) -> DkgPhase {
+        if self.output.is_some() {
+            DkgPhase::Completed
+        } else if self.confirmation.is_some() {
+            DkgPhase::Merged
+        } else if !self.processed_messages.is_empty() {
+            DkgPhase::MessagesProcessed
+        } else {
+            DkgPhase::Initialized
+        }
+    }
+
+    /// Number of processed messages required to complete the protocol: a message from every
+    /// party for a fresh DKG, and from the old threshold of continuing members for a key rotation.
+    pub fn required_processed_messages(&self) -> usize {
+        self.config
+            .old_threshold
+            .map(|old_threshold| old_threshold as usize)
+            .unwrap_or_else(|| self.config.nodes.num_nodes())
+    }
+
+    /// Whether enough messages are processed to complete the protocol without further messages.
+    pub fn has_sufficient_processed_messages(&self) -> bool {
+        self.processed_messages.len() >= self.required_processed_messages()
+    }
+
+    /// Party IDs of the expected senders, split into those with a message in `messages` and those
+    /// without, both in ascending order. The expected senders are the continuing members for a key
+    /// rotation, and all parties for a fresh DKG.
+    pub fn messages_from_expected_senders(
+        &self,
+        messages: &[SignedMessage<G>],
+    ) -> (Vec<u16>, Vec<u16>) {
+        let senders: HashSet<u16> = messages.iter().map(|m| m.message.sender).collect();
+        self.expected_senders()
+            .into_iter()
+            .partition(|party_id| senders.contains(party_id))
+    }
+
+    /// Party IDs of the expected senders, see [DkgState::messages_from_expected_senders], without
+    /// a message in this state, own, received or processed, in ascending order.
+    pub fn missing_message_senders(&self) -> Vec<u16> {
+        let senders = self.message_senders();
+        self.expected_senders()
+            .into_iter()
+            .filter(|party_id| !senders.contains(party_id))
+            .collect()
+    }
+
+    /// Progress of the ceremony in this state, e.g. for a coordinator polling it.
+    pub fn progress_summary(&self) -> ProgressSummary {
+        let expected = self.expected_senders();
+        let senders = self.message_senders();
+        ProgressSummary {
+            total_expected: expected.len(),
+            messages_received: expected.iter().filter(|id| senders.contains(id)).count(),
+            messages_processed: self.processed_messages.len(),
+            has_confirmation: self.confirmation.is_some(),
+            is_complete: self.output.is_some(),
+        }
+    }
+
+    /// The continuing members for a key rotation, and all parties for a fresh DKG, in ascending
+    /// order.
+    fn expected_senders(&self) -> Vec<u16> {
+        let mut expected: Vec<u16> = match &self.config.new_to_old_mapping {
+            Some(mapping) => mapping.keys().copied().collect(),
+            None => self.config.nodes.iter().map(|node| node.id).collect(),
+        };
+        expected.sort_unstable();
+        expected
+    }
+
+    /// Senders of the own, received and processed messages.
+    fn message_senders(&self) -> HashSet<u16> {
+        self.my_message
+            .iter()
+            .chain(self.received_messages.values())
+            .chain(self.processed_messages.iter().map(|p| &p.message))
+            .map(|message| message.sender)
+            .collect()
+    }
+
+    /// Non-secret transcript of this state. Leaves out old and new shares and processed messages,
+    /// which contain decrypted shares.
+    pub fn transcript(&self) -> DkgTranscript<G> {
+        DkgTranscript {
+            key_type: KeyTypeTag::default(),
+            committee_id: self.config.committee_id,
+            my_party_id: self.config.my_party_id,
+            threshold: self.config.threshold,
+            nodes: self.config.nodes.clone(),
+            signing_pks: self.config.signing_pks.clone(),
+            old_threshold: self.config.old_threshold,
+            new_to_old_mapping: self.config.new_to_old_mapping.clone(),
+            old_committee_digest: self.config.old_committee_digest.clone(),
+            my_message: self.my_message.clone(),
+            received_messages: self.received_messages.clone(),
+            vss_pk: self.output.as_ref().map(|output| output.vss_pk.clone()),
+        }
+    }
+
+    /// Merge the received and processed messages of another partial state of the same committee,
+    /// e.g. collected by another coordinator. Processed messages are deduplicated by sender.
+    /// Returns error if the committee ID, threshold or party ID differ, since processed messages
+    /// contain the shares decrypted by this party.
+    ///
+    /// If new processed messages are merged, the confirmation no longer covers all processed
+    /// messages and is dropped, so that `process-all` merges them again. A completed state cannot
+    /// take new processed messages, since its output may already be proposed onchain. The state
+    /// is left unchanged on errors.
+    pub fn merge_with(&mut self, other: &DkgState<G>) -> Result<()> {
+        if self.config.committee_id != other.config.committee_id {
+            return Err(anyhow!(
+                "Cannot merge states of different committees: {} and {}",
+                self.config.committee_id,
+                other.config.committee_id
+            ));
+        }
+        if self.config.threshold != other.config.threshold {
+            return Err(anyhow!(
+                "Cannot merge states with different thresholds: {} and {}",
+                self.config.threshold,
+                other.config.threshold
+            ));
+        }
+
+        if self.config.my_party_id != other.config.my_party_id {
+            return Err(anyhow!(
+                "Cannot merge states of different parties: {} and {}",
+                self.config.my_party_id,
+                other.config.my_party_id
+            ));
+        }
+
+        let mut senders: HashSet<_> = self
+            .processed_messages
+            .iter()
+            .map(|processed| processed.message.sender)
+            .collect();
+        let new_processed: Vec<_> = other
+            .processed_messages
+            .iter()
+            .filter(|processed| senders.insert(processed.message.sender))
+            .cloned()
+            .collect();
+        if !new_processed.is_empty() {
+            if self.output.is_some() {
+                return Err(CeremonyError::StateConflict(anyhow!(
+                    "DKG is already completed, cannot merge processed messages from {} new sender(s)",
+                    new_processed.len()
+                ))
+                .into());
+            }
+            // Merged messages are outdated now.
+            self.confirmation = None;
+        }
+
+        for (sender, message) in &other.received_messages {
+            self.received_messages
+                .entry(*sender)
+                .or_insert_with(|| message.clone());
+        }
+        self.processed_messages.extend(new_processed);
+        Ok(())
+    }
+
+    /// BCS bytes of this party's confirmation and of the processed messages it used, as
+    /// `(confirmation_bcs, used_messages_bcs)`. Available once `process-all` merged the processed
+    /// messages. The used messages contain the shares decrypted by this party, so their bytes
+    /// must be kept secret.
+    pub fn prepare_onchain_proposal_bytes(&self) -> Result<(Vec<u8>, Vec<u8>)> {
+        let (confirmation, used_messages) = self
+            .confirmation
+            .as_ref()
+            .ok_or_else(|| anyhow!("No confirmation in the DKG state. Run process-all first."))?;
+        Ok((bcs::to_bytes(confirmation)?, bcs::to_bytes(used_messages)?))
+    }
+
+    /// Export the key server configuration for this party from the DKG output.
+    /// Returns error if the DKG is not completed.
+    pub fn export_key_server_config(&self) -> Result<KeyServerConfig> {
+        let output = self
+            .output
+            .as_ref()
+            .ok_or_else(|| anyhow!("DKG is not completed. Run process-all first."))?;
+        let share = output
+            .shares
+            .as_ref()
+            .and_then(|shares| shares.first())
+            .ok_or_else(|| {
+                anyhow!(
+                    "No key share in DKG output for party {}",
+                    self.config.my_party_id
+                )
+            })?;
+        let partial_pk = output.vss_pk.eval(share.index);
+
+        Ok(KeyServerConfig {
+            committee_id: self.config.committee_id.to_string(),
+            party_id: self.config.my_party_id,
+            threshold: self.config.threshold,
+            partial_key_share_hex: format_pk_hex(&share.value)?,
+            partial_pk_hex: format_pk_hex(&partial_pk.value)?,
+            key_server_pk_hex: format_pk_hex(output.vss_pk.c0())?,
+            vss_coefficients: (0..=output.vss_pk.degree())
+                .map(|i| format_pk_hex(output.vss_pk.coefficient(i)))
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+/// Progress of a ceremony, see [DkgState::progress_summary]. Displays as text lines.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ProgressSummary {
+    /// Number of messages expected: from the continuing members for a key rotation, and from all
+    /// parties for a fresh DKG.
+    pub total_expected: usize,
+    /// Number of expected senders with a message in the state.
+    pub messages_received: usize,
+    pub messages_processed: usize,
+    pub has_confirmation: bool,
+    pub is_complete: bool,
+}
+
+impl ProgressSummary {
+    /// Estimated completion in percent: receiving and processing the expected messages count half
+    /// each. Only 100 once complete, since a key rotation completes with fewer processed messages.
+    pub fn percent_complete(&self) -> u8 {
+        if self.is_complete {
+            return 100;
+        }
+        let done = self.messages_received.min(self.total_expected)
+            + self.messages_processed.min(self.total_expected);
+        (done * 50)
+            .checked_div(self.total_expected)
+            .map_or(0, |percent| percent.min(99) as u8)
+    }
+}
+
+impl std::fmt::Display for ProgressSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let yes_no = |value: bool| if value { "yes" } else { "no" };
+        writeln!(
+            f,
+            "Messages received:  {} of {}",
+            self.messages_received, self.total_expected
+        )?;
+        writeln!(
+            f,
+            "Messages processed: {} of {}",
+            self.messages_processed, self.total_expected
+        )?;
+        writeln!(f, "Confirmation:       {}", yes_no(self.has_confirmation))?;
+        write!(
+            f,
+            "Complete:           {} ({}%)",
+            yes_no(self.is_complete),
+            self.percent_complete()
+        )
+    }
+}
+
+/// Non-secret part of the DKG state, kept for audit after the state is pruned.
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[schemars(bound = "G: KeyGroup", rename = "DkgTranscript")]
+pub struct DkgTranscript<G: KeyGroup = G2Element> {
+    /// Instantiation of the DKG.
+    #[serde(default)]
+    pub key_type: KeyTypeTag<G>,
+    /// This committee ID.
+    #[schemars(schema_with = "crate::schema::address_schema")]
+    pub committee_id: Address,
+    /// My party ID for this committee.
+    pub my_party_id: u16,
+    /// Threshold for this committee.
+    pub threshold: u16,
+    /// All nodes in the protocol.
+    #[schemars(schema_with = "crate::schema::fastcrypto_schema")]
+    pub nodes: Nodes<G2Element>,
+    /// Signing public keys for all parties in this committee.
+    #[schemars(schema_with = "crate::schema::fastcrypto_schema")]
+    pub signing_pks: HashMap<u16, BLS12381PublicKey>,
+    /// Threshold for old committee, for key rotation.
+    pub old_threshold: Option<u16>,
+    /// Mapping from new party ID to old party ID, for key rotation.
+    pub new_to_old_mapping: Option<HashMap<u16, u16>>,
+    /// Digest of the old committee snapshot used for key rotation.
+    pub old_committee_digest: Option<String>,
+    /// Message created by this party.
+    #[schemars(schema_with = "crate::schema::fastcrypto_schema")]
+    pub my_message: Option<Message<G, G2Element>>,
+    /// Messages received from other parties.
+    #[schemars(schema_with = "crate::schema::fastcrypto_schema")]
+    pub received_messages: HashMap<u16, Message<G, G2Element>>,
+    /// Final VSS public polynomial (if completed).
+    #[schemars(schema_with = "crate::schema::fastcrypto_schema")]
+    pub vss_pk: Option<PublicPoly<G>>,
+}
+
+/// Key server configuration for a committee member, exported from a completed DKG.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+pub struct KeyServerConfig {
+    /// Committee object ID.
+    #[schemars(schema_with = "crate::schema::address_schema")]
+    pub committee_id: String,
+    /// My party ID for this committee.
+    pub party_id: u16,
+    /// Threshold for this committee.
+    pub threshold: u16,
+    /// My partial key share. Keep secret.
+    #[schemars(schema_with = "crate::schema::hex_schema")]
+    pub partial_key_share_hex: String,
+    /// My partial public key.
+    #[schemars(schema_with = "crate::schema::hex_schema")]
+    pub partial_pk_hex: String,
+    /// Key server public key.
+    #[schemars(schema_with = "crate::schema::hex_schema")]
+    pub key_server_pk_hex: String,
+    /// Coefficients of the VSS public polynomial.
+    pub vss_coefficients: Vec<String>,
+}
+
+/// Key share of a party encrypted to the encryption key of another operator, for handing over the
+/// party to them. The committee ID and party ID are bound to the encryption.
+#[derive(Serialize, Deserialize)]
+pub struct ShareHandoff {
+    /// Committee object ID.
+    pub committee_id: Address,
+    /// Party ID of the share.
+    pub party_id: u16,
+    /// Encryption public key of the recipient.
+    #[serde(with = "enc_pk_serde")]
+    pub recipient_enc_pk: PublicKey<G2Element>,
+    /// Encryption of the BCS bytes of the share.
+    #[serde(with = "encryption_serde")]
+    pub encryption: MultiRecipientEncryption<G2Element>,
+}
+
+impl ShareHandoff {
+    /// Encrypt the share of `party_id` in committee `committee_id` to `recipient_enc_pk`.
+    pub fn encrypt<R: AllowedRng>(
+        committee_id: Address,
+        party_id: u16,
+        share: &G2Scalar,
+        recipient_enc_pk: &PublicKey<G2Element>,
+        rng: &mut R,
+    ) -> Result<Self> {
+        let encryption = MultiRecipientEncryption::encrypt(
+            &[(recipient_enc_pk.clone(), bcs::to_bytes(share)?)],
+            &handoff_random_oracle(&committee_id, party_id),
+            rng,
+        );
+        Ok(Self {
+            committee_id,
+            party_id,
+            recipient_enc_pk: recipient_enc_pk.clone(),
+            encryption,
+        })
+    }
+
+    /// Decrypt the share with the recipient's keys and check it against the party's partial
+    /// public key, e.g. from the committee's key server onchain.
+    pub fn decrypt(&self, keys: &KeysFile, expected_partial_pk: &G2Element) -> Result<G2Scalar> {
+        if bcs::to_bytes(&self.recipient_enc_pk)? != bcs::to_bytes(&keys.enc_pk)? {
+            return Err(CeremonyError::InvalidInput(anyhow!(
+                "Share handoff is addressed to another encryption key"
+            ))
+            .into());
+        }
+        let random_oracle = handoff_random_oracle(&self.committee_id, self.party_id);
+        self.encryption
+            .verify(&random_oracle)
+            .map_err(|e| CeremonyError::Verification(anyhow!("Share handoff is tampered: {e}")))?;
+        let share: G2Scalar =
+            bcs::from_bytes(&self.encryption.decrypt(&keys.enc_sk, &random_oracle, 0)).map_err(
+                |e| CeremonyError::Verification(anyhow!("Failed to decrypt share handoff: {e}")),
+            )?;
+        if G2Element::generator() * share != *expected_partial_pk {
+            return Err(CeremonyError::Verification(anyhow!(
+                "Decrypted share of party {} does not match its partial public key",
+                self.party_id
+            ))
+            .into());
+        }
+        Ok(share)
+    }
+}
+
+fn handoff_random_oracle(committee_id: &Address, party_id: u16) -> RandomOracle {
+    RandomOracle::new(&format!("seal-share-handoff-{committee_id}-{party_id}"))
+}
+
+/// Contents of a message file, `message_<party ID>.json`.
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct MessageFile {
+    /// Base64 of the BCS bytes of a [SignedMessage].
+    #[schemars(schema_with = "crate::schema::base64_schema")]
+    pub message: String,
+}
+
+/// Signed message struct. Message files do not tag the instantiation, but a message of another
+/// instantiation fails to decode since G1 and G2 elements have different lengths.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedMessage<G: KeyGroup = G2Element> {
+    pub message: Message<G, G2Element>,
+    pub signature: BLS12381Signature,
+}
+
+impl<G: KeyGroup> SignedMessage<G> {
+    /// Encode as base64 of the BCS bytes, as written to message files.
+    pub fn to_base64(&self) -> Result<String> {
+        Ok(Base64::encode(bcs::to_bytes(self)?))
+    }
+
+    /// Decode from base64 of the BCS bytes, as written to message files.
+    pub fn from_base64(s: &str) -> Result<Self> {
+        if s.len() > MAX_SIGNED_MESSAGE_BASE64_LEN {
+            return Err(anyhow!(
+                "Message too large: {} bytes, at most {} allowed",
+                s.len(),
+                MAX_SIGNED_MESSAGE_BASE64_LEN
+            ));
+        }
+        Ok(bcs::from_bytes(&Base64::decode(s)?)?)
+    }
+
+    /// Contents of the message file of this message.
+    pub fn to_message_file(&self) -> Result<MessageFile> {
+        Ok(MessageFile {
+            message: self.to_base64()?,
+        })
+    }
+
+    /// Decode from the JSON envelope of a message file, whose `message` field holds the base64 of
+    /// the BCS bytes. Other fields are ignored.
+    pub fn from_envelope_json(json: &str) -> Result<Self> {
+        if json.len() > MAX_MESSAGE_ENVELOPE_LEN {
+            return Err(anyhow!(
+                "Message file too large: {} bytes, at most {} allowed",
+                json.len(),
+                MAX_MESSAGE_ENVELOPE_LEN
+            ));
+        }
+        let envelope: serde_json::Value = serde_json::from_str(json)?;
+        let message_base64 = envelope["message"]
+            .as_str()
+            .ok_or_else(|| anyhow!("Missing 'message' field"))?;
+        Self::from_base64(message_base64)
+    }
+}
+
+/// Maximum length of the base64 of a signed message. Messages grow linearly with the committee
+/// size and threshold, and are well below 1 MiB for committees of hundreds of parties.
+pub const MAX_SIGNED_MESSAGE_BASE64_LEN: usize = 16 << 20;
+
+/// Maximum length of a message file, checked before reading it since message files come from a
+/// shared directory.
+pub const MAX_MESSAGE_ENVELOPE_LEN: usize = MAX_SIGNED_MESSAGE_BASE64_LEN + (64 << 10);
+
+impl<G: KeyGroup> std::str::FromStr for SignedMessage<G> {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::from_base64(s)
+    }
+}
+
+/// Create BLS signature for signed message.
+pub fn sign_message<G: KeyGroup>(
+    message: Message<G, G2Element>,
+    sk: &BLS12381PrivateKey,
+) -> SignedMessage<G> {
+    let message_bytes = bcs::to_bytes(&message).expect("Serialization failed");
+    let signature = sk.sign(&message_bytes);
+    SignedMessage { message, signature }
+}
+
+/// Helper function to format a BCS-serializable value as hex string with 0x prefix.
+pub fn format_pk_hex<T: Serialize>(pk: &T) -> Result<String> {
+    Ok(Hex::encode_with_format(&bcs::to_bytes(pk)?))
+}
+
+/// Verify BLS signature for signed message.
+pub fn verify_signature<G: KeyGroup>(
+    signed_msg: &SignedMessage<G>,
+    pk: &BLS12381PublicKey,
+) -> Result<()> {
+    let message_bytes = bcs::to_bytes(&signed_msg.message)?;
+    pk.verify(&message_bytes, &signed_msg.signature)?;
+    Ok(())
+}
+
+/// Verify the signatures of all messages with the signing keys of their senders. Returns the
+/// senders in the order of the messages, or else every sender whose signing key is missing or
+/// whose signature is invalid, with the reason, so that they can be reported together.
+pub fn verify_all_signatures<G: KeyGroup>(
+    messages: &[SignedMessage<G>],
+    signing_pks: &HashMap<u16, BLS12381PublicKey>,
+) -> Result<Vec<u16>, Vec<(u16, anyhow::Error)>> {
+    let mut senders = Vec::with_capacity(messages.len());
+    let mut failures = Vec::new();
+    for signed_msg in messages {
+        let sender = signed_msg.message.sender;
+        let verified = signing_pks
+            .get(&sender)
+            .ok_or_else(|| anyhow!("Signing public key not found for party {sender}"))
+            .and_then(|pk| {
+                verify_signature(signed_msg, pk)
+                    .map_err(|e| anyhow!("Invalid signature on message from party {sender}: {e}"))
+            });
+        match verified {
+            Ok(()) => senders.push(sender),
+            Err(e) => failures.push((sender, e)),
+        }
+    }
+    if failures.is_empty() {
+        Ok(senders)
+    } else {
+        Err(failures)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fastcrypto::bls12381::min_sig::BLS12381KeyPair;
+    use fastcrypto::traits::KeyPair;
+    use rand::thread_rng;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_keys_file_serde() {
+        // Generate random keys
+        let mut rng = thread_rng();
+        let enc_sk = PrivateKey::<G2Element>::new(&mut rng);
+        let enc_pk = PublicKey::from_private_key(&enc_sk);
+
+        let signing_kp = BLS12381KeyPair::generate(&mut rng);
+        let signing_pk = signing_kp.public().clone();
+        let signing_sk = signing_kp.private();
+
+        let keys = KeysFile {
+            version: CURRENT_KEYS_FILE_VERSION,
+            enc_sk,
+            enc_pk,
+            signing_sk,
+            signing_pk,
+            extra_entropy: false,
+        };
+
+        // Round trip.
+        let json = serde_json::to_string_pretty(&keys).expect("Failed to serialize KeysFile");
+        let deserialized: KeysFile =
+            serde_json::from_str(&json).expect("Failed to deserialize KeysFile");
+
+        assert_eq!(
+            bcs::to_bytes(&keys.enc_sk).unwrap(),
+            bcs::to_bytes(&deserialized.enc_sk).unwrap()
+        );
+        assert_eq!(
+            bcs::to_bytes(&keys.enc_pk).unwrap(),
+            bcs::to_bytes(&deserialized.enc_pk).unwrap()
+        );
+        assert_eq!(
+            bcs::to_bytes(&keys.signing_sk).unwrap(),
+            bcs::to_bytes(&deserialized.signing_sk).unwrap()
+        );
+        assert_eq!(
+            bcs::to_bytes(&keys.signing_pk).unwrap(),
+            bcs::to_bytes(&deserialized.signing_pk).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_public_key_fingerprint() {
+        let keys = KeysFile::generate(&mut thread_rng());
+        let fingerprint = keys.public_key_fingerprint();
+        assert_eq!(fingerprint.len(), 17);
+        let (first, second) = fingerprint.split_once('-').unwrap();
+        assert_eq!((first.len(), second.len()), (8, 8));
+        assert!(Hex::decode(first).is_ok() && Hex::decode(second).is_ok());
+
+        // Only depends on the public keys.
+        let json = serde_json::to_string(&keys).unwrap();
+        let deserialized: KeysFile = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.public_key_fingerprint(), fingerprint);
+        let other = KeysFile::generate(&mut thread_rng());
+        assert_ne!(other.public_key_fingerprint(), fingerprint);
+    }
+
+    #[test]
+    fn test_keys_file_extra_entropy() {
+        let keys = KeysFile::generate_with_extra_entropy(&mut thread_rng(), b"keyboard mash");
+        let json = serde_json::to_string(&keys).unwrap();
+        let deserialized: KeysFile = serde_json::from_str(&json).unwrap();
+        assert!(deserialized.extra_entropy);
+
+        // Keys files written before the flag existed.
+        let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        value.as_object_mut().unwrap().remove("extra_entropy");
+        let deserialized: KeysFile = serde_json::from_value(value).unwrap();
+        assert!(!deserialized.extra_entropy);
+    }
+
+    #[test]
+    fn test_share_handoff() {
+        let mut rng = thread_rng();
+        let recipient = KeysFile::generate(&mut rng);
+        let share = G2Scalar::from(42u128);
+        let partial_pk = G2Element::generator() * share;
+        let handoff =
+            ShareHandoff::encrypt(Address::TWO, 3, &share, &recipient.enc_pk, &mut rng).unwrap();
+        let json = serde_json::to_string(&handoff).unwrap();
+        let handoff: ShareHandoff = serde_json::from_str(&json).unwrap();
+        assert_eq!(handoff.decrypt(&recipient, &partial_pk).unwrap(), share);
+
+        // Another partial public key.
+        let err = handoff
+            .decrypt(&recipient, &(partial_pk + partial_pk))
+            .unwrap_err();
+        assert!(err.to_string().contains("does not match"), "{err}");
+
+        // Mis-addressed.
+        let err = handoff
+            .decrypt(&KeysFile::generate(&mut rng), &partial_pk)
+            .unwrap_err();
+        assert!(err.to_string().contains("another encryption key"), "{err}");
+
+        // Tampered with the party ID, which is bound to the encryption.
+        let mut tampered: ShareHandoff = serde_json::from_str(&json).unwrap();
+        tampered.party_id = 4;
+        let err = tampered.decrypt(&recipient, &partial_pk).unwrap_err();
+        assert!(
+            matches!(
+                err.downcast_ref::<CeremonyError>(),
+                Some(CeremonyError::Verification(_))
+            ),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_keys_file_upgrade() {
+        let mut keys = KeysFile::generate(&mut thread_rng());
+        assert_eq!(keys.version, CURRENT_KEYS_FILE_VERSION);
+        keys.check_version().unwrap();
+
+        assert!(keys.upgrade(2, 3).is_err());
+        assert!(keys.upgrade(2, 1).is_err());
+        keys.upgrade(1, 2).unwrap();
+        assert_eq!(keys.version, 2);
+        // Wrong source version.
+        assert!(keys.upgrade(1, 2).is_err());
+
+        keys.version = 3;
+        assert!(keys.check_version().is_err());
+    }
+
+    #[test]
+    fn test_keys_file_migrate() {
+        let keys = KeysFile::generate(&mut thread_rng());
+
+        // Keys files written before versioning have no version field.
+        let mut json = serde_json::to_value(&keys).unwrap();
+        json.as_object_mut().unwrap().remove("version");
+        let json = json.to_string();
+
+        assert!(KeysFile::migrate(&json, 2, 3).is_err());
+        let migrated = KeysFile::migrate(&json, 1, 2).unwrap();
+        let migrated: KeysFile = serde_json::from_str(&migrated).unwrap();
+        assert_eq!(migrated.version, 2);
+        assert_eq!(migrated.enc_pk, keys.enc_pk);
+        assert_eq!(migrated.signing_pk, keys.signing_pk);
+
+        // Wrong source version and downgrades are rejected.
+        let migrated = serde_json::to_string(&migrated).unwrap();
+        assert!(KeysFile::migrate(&migrated, 1, 2).is_err());
+        assert!(KeysFile::migrate(&migrated, 2, 1).is_err());
+        assert!(KeysFile::migrate("{}", 1, 2).is_err());
+    }
+
+    #[test]
+    fn test_signed_message_malformed_input() {
+        for json in [
+            "",
+            "[]",
+            "{}",
+            r#"{"message": 1}"#,
+            r#"{"message": "not base64!"}"#,
+            r#"{"message": ""}"#,
+            // Huge ULEB128 length prefix for the first vector, with no data.
+            r#"{"message": "/////w8="}"#,
+        ] {
+            assert!(SignedMessage::<G2Element>::from_envelope_json(json).is_err());
+        }
+
+        let too_long = "A".repeat(MAX_SIGNED_MESSAGE_BASE64_LEN + 4);
+        let err = SignedMessage::<G2Element>::from_str(&too_long).unwrap_err();
+        assert!(err.to_string().contains("too large"), "{err}");
+        let envelope = format!(r#"{{"message": "{too_long}"}}"#);
+        let err = SignedMessage::<G2Element>::from_envelope_json(&envelope).unwrap_err();
+        assert!(err.to_string().contains("too large"), "{err}");
+    }
+}
@@ -0,0 +1,226 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Utility helpers for checking DKG results against committee data.
+
+use crate::errors::CeremonyError;
+use crate::groups::KeyGroup;
+use crate::types::DkgState;
+use anyhow::{anyhow, Result};
+use fastcrypto::groups::bls12381::G2Element;
+use fastcrypto_tbls::dkg_v1::{Message, Output};
+use fastcrypto_tbls::polynomial::{Eval, Poly};
+use seal_committee::{build_new_to_old_map, SealCommittee};
+use std::fmt;
+use std::num::NonZeroU16;
+
+/// A check of [CommitteeRotationVerifier] that was skipped because data is missing from the
+/// state.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerificationWarning {
+    /// The state has no old partial public keys, so neither the messages of continuing members
+    /// nor the key server public key could be checked.
+    MissingOldPartialPks,
+    /// The state has no message from this continuing member, e.g. because it was excluded.
+    MissingMessage { party_id: u16 },
+    /// The old partial public key of a continuing member is not in the state.
+    MissingOldPartialPk { party_id: u16, old_party_id: u16 },
+    /// Fewer old partial public keys than the old threshold are in the state, so the key server
+    /// public key could not be checked.
+    InsufficientOldPartialPks { found: usize, old_threshold: u16 },
+}
+
+impl fmt::Display for VerificationWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerificationWarning::MissingOldPartialPks => {
+                write!(f, "Old partial PKs not found in state")
+            }
+            VerificationWarning::MissingMessage { party_id } => {
+                write!(
+                    f,
+                    "Message from continuing party {party_id} not found in state"
+                )
+            }
+            VerificationWarning::MissingOldPartialPk {
+                party_id,
+                old_party_id,
+            } => write!(
+                f,
+                "Old partial PK of party {party_id} (old party {old_party_id}) not found in state"
+            ),
+            VerificationWarning::InsufficientOldPartialPks {
+                found,
+                old_threshold,
+            } => write!(
+                f,
+                "Only {found} old partial PKs found in state, old threshold is {old_threshold}"
+            ),
+        }
+    }
+}
+
+/// Verifies a completed key rotation against the new and old committees:
+/// 1. The new committee rotates from the old committee, and the state is for the new committee
+///    with the mapping from new to old party IDs of the continuing members.
+/// 2. The message of every continuing member commits to its partial public key in the old
+///    committee.
+/// 3. The output has the degree of the new threshold and keeps the key server public key of the
+///    old committee.
+///
+/// Checks that cannot be done since data is missing from the state are returned as warnings, and
+/// failed checks as errors.
+pub struct CommitteeRotationVerifier;
+
+impl CommitteeRotationVerifier {
+    pub fn verify<G: KeyGroup>(
+        &self,
+        new_committee: &SealCommittee,
+        old_committee: &SealCommittee,
+        state: &DkgState<G>,
+        output: &Output<G, G2Element>,
+    ) -> Result<Vec<VerificationWarning>> {
+        let mut warnings = Vec::new();
+        let config = &state.config;
+
+        // 1. Committees and mapping.
+        if new_committee.old_committee_id != Some(old_committee.id) {
+            return Err(CeremonyError::Validation(anyhow!(
+                "Committee {} does not rotate from committee {}",
+                new_committee.id,
+                old_committee.id
+            ))
+            .into());
+        }
+        if config.committee_id != new_committee.id {
+            return Err(CeremonyError::Validation(anyhow!(
+                "State is for committee {}, not {}",
+                config.committee_id,
+                new_committee.id
+            ))
+            .into());
+        }
+        if config.old_threshold != Some(old_committee.threshold) {
+            return Err(CeremonyError::Validation(anyhow!(
+                "State has old threshold {:?}, old committee has threshold {}",
+                config.old_threshold,
+                old_committee.threshold
+            ))
+            .into());
+        }
+        let mapping = build_new_to_old_map(new_committee, old_committee);
+        if config.new_to_old_mapping.as_ref() != Some(&mapping) {
+            return Err(CeremonyError::Validation(anyhow!(
+                "New-to-old mapping in state does not match the continuing members of committees {} and {}",
+                new_committee.id,
+                old_committee.id
+            ))
+            .into());
+        }
+        if mapping.len() < old_committee.threshold as usize {
+            return Err(CeremonyError::Validation(anyhow!(
+                "Found {} continuing members, need at least {} (old threshold)",
+                mapping.len(),
+                old_committee.threshold
+            ))
+            .into());
+        }
+
+        // 2. Messages of continuing members.
+        let Some(old_pks) = &config.expected_old_pks else {
+            warnings.push(VerificationWarning::MissingOldPartialPks);
+            self.verify_output(new_committee, output)?;
+            return Ok(warnings);
+        };
+        let mut party_ids: Vec<_> = mapping.keys().copied().collect();
+        party_ids.sort();
+        for party_id in party_ids {
+            let old_party_id = mapping[&party_id];
+            let Some(old_pk) = old_pks.get(&old_party_id) else {
+                warnings.push(VerificationWarning::MissingOldPartialPk {
+                    party_id,
+                    old_party_id,
+                });
+                continue;
+            };
+            let Some(message) = find_message(state, party_id) else {
+                warnings.push(VerificationWarning::MissingMessage { party_id });
+                continue;
+            };
+            if message.vss_pk.c0() != old_pk {
+                return Err(CeremonyError::Verification(anyhow!(
+                    "Message of party {party_id} does not commit to its partial PK of old party {old_party_id}"
+                ))
+                .into());
+            }
+        }
+
+        // 3. Output.
+        self.verify_output(new_committee, output)?;
+        if old_pks.len() < old_committee.threshold as usize {
+            warnings.push(VerificationWarning::InsufficientOldPartialPks {
+                found: old_pks.len(),
+                old_threshold: old_committee.threshold,
+            });
+            return Ok(warnings);
+        }
+        let mut evals = old_pks
+            .iter()
+            .map(|(old_party_id, pk)| {
+                Ok(Eval {
+                    index: share_index(*old_party_id)?,
+                    value: *pk,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        evals.sort_by_key(|eval| eval.index);
+        let old_key_server_pk = Poly::<G>::recover_c0_msm(
+            old_committee.threshold,
+            evals.iter().take(old_committee.threshold as usize),
+        )?;
+        if *output.vss_pk.c0() != old_key_server_pk {
+            return Err(CeremonyError::Verification(anyhow!(
+                "Key server PK changed in rotation from committee {}",
+                old_committee.id
+            ))
+            .into());
+        }
+        Ok(warnings)
+    }
+
+    /// Check that the output polynomial has the degree of the new threshold.
+    fn verify_output<G: KeyGroup>(
+        &self,
+        new_committee: &SealCommittee,
+        output: &Output<G, G2Element>,
+    ) -> Result<()> {
+        if output.vss_pk.degree() + 1 != new_committee.threshold as usize {
+            return Err(CeremonyError::Verification(anyhow!(
+                "Output polynomial has degree {}, expected {} for threshold {}",
+                output.vss_pk.degree(),
+                (new_committee.threshold as usize).saturating_sub(1),
+                new_committee.threshold
+            ))
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// Message of `party_id` in the state, own, received or processed.
+fn find_message<G: KeyGroup>(state: &DkgState<G>, party_id: u16) -> Option<&Message<G, G2Element>> {
+    state
+        .my_message
+        .iter()
+        .chain(state.received_messages.values())
+        .chain(state.processed_messages.iter().map(|p| &p.message))
+        .find(|message| message.sender == party_id)
+}
+
+/// Share index of a party, each party has weight 1.
+fn share_index(party_id: u16) -> Result<NonZeroU16> {
+    party_id
+        .checked_add(1)
+        .and_then(NonZeroU16::new)
+        .ok_or_else(|| anyhow!("Invalid party ID {party_id}"))
+}
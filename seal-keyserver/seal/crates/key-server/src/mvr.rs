@@ -13,15 +13,18 @@
 //! * A valid name is of the form `subname@name/mvr-app` or, equivalently, `subname.name.sui/mvr-app`. The subname is optional, but there is always an `/` in the name, meaning that it is not possible to register an object ID like `0xe8417c530cde59eddf6dfb760e8a0e3e2c6f17c69ddaab5a73dd6a6e65fc463b` as an MVR name.
 //! * The app record and package info objects point to the package address that was used when the name was registered, but there could be more recent versions of the package.
 
+use crate::cache::default_lru_cache;
 use crate::errors::InternalError;
 use crate::errors::InternalError::{Failure, InvalidMVRName, InvalidPackage};
 use crate::key_server_options::KeyServerOptions;
 use crate::sui_rpc_client::SuiRpcClient;
 use crate::types::Network;
+use moka::sync::Cache;
 use move_core_types::account_address::AccountAddress;
 use move_core_types::identifier::Identifier;
 use move_core_types::language_storage::StructTag;
 use mvr_types::name::Name;
+use once_cell::sync::Lazy;
 use serde::Deserialize;
 use serde_json::json;
 use std::collections::HashMap;
@@ -41,10 +44,23 @@ const MVR_CORE: &str = "0x62c1f5b1cb9e3bfc3dd1f73c95066487b662048a6358eabdbf67f6
 /// Testnet records are stored on mainnet on the registry defined above, but under the 'networks' section using the following ID as key
 const TESTNET_ID: &str = "4c78adac";
 
+/// Number of records of the MVR registry read per request in [reverse_resolve_mvr].
+const REGISTRY_PAGE_SIZE: usize = 50;
+
+/// Default number of records of the MVR registry scanned by [reverse_resolve_mvr].
+pub const DEFAULT_MAX_REGISTRY_RECORDS: usize = 10_000;
+
+/// Cache of parsed MVR names, see [dynamic_field_name]. MVR names come from requests, so the
+/// cache is bounded.
+static DYNAMIC_FIELD_NAME_CACHE: Lazy<Cache<String, DynamicFieldName>> =
+    Lazy::new(default_lru_cache);
+
 #[derive(Deserialize, Clone, Debug)]
+#[cfg_attr(test, derive(serde::Serialize))]
 pub struct VecMap<K, V>(sui_types::collection_types::VecMap<K, V>);
 
 #[derive(Deserialize, Clone, Debug)]
+#[cfg_attr(test, derive(serde::Serialize))]
 pub struct AppRecord {
     _app_cap_id: ObjectID,
     _ns_nft_id: ObjectID,
@@ -54,14 +70,53 @@ pub struct AppRecord {
     _storage: ObjectID,
 }
 
+impl AppRecord {
+    /// Returns true if this record has package info for testnet.
+    pub fn networks_contains_testnet(&self) -> bool {
+        self.testnet_package_info().is_some()
+    }
+
+    /// The package info for testnet, if any.
+    pub fn testnet_package_info(&self) -> Option<&AppInfo> {
+        self.networks
+            .0
+            .contents
+            .iter()
+            .find(|entry| entry.key == TESTNET_ID)
+            .map(|entry| &entry.value)
+    }
+}
+
 #[derive(Deserialize, Clone, Debug)]
+#[cfg_attr(test, derive(serde::Serialize))]
 pub struct AppInfo {
     package_info_id: Option<ObjectID>,
     package_address: Option<ObjectID>,
     _upgrade_cap_id: Option<ObjectID>,
 }
 
+impl AppInfo {
+    /// The package address, or a [Failure] naming `context`, e.g. the MVR name and network.
+    pub fn package_address_or_err(&self, context: &str) -> Result<ObjectID, InternalError> {
+        self.package_address.ok_or_else(|| {
+            Failure(format!(
+                "No package_address field on app_info for {context}"
+            ))
+        })
+    }
+
+    /// The package info ID, or a [Failure] naming `context`, e.g. the MVR name and network.
+    pub fn package_info_id_or_err(&self, context: &str) -> Result<ObjectID, InternalError> {
+        self.package_info_id.ok_or_else(|| {
+            Failure(format!(
+                "No package_info_id field on app_info for {context}"
+            ))
+        })
+    }
+}
+
 #[derive(Deserialize, Clone, Debug)]
+#[cfg_attr(test, derive(serde::Serialize))]
 pub struct PackageInfo {
     _id: ObjectID,
     _display: PackageDisplay,
@@ -71,7 +126,25 @@ pub struct PackageInfo {
     _git_versioning: Table,
 }
 
+impl PackageInfo {
+    /// The metadata value for `key`, if any.
+    pub fn get_metadata(&self, key: &str) -> Option<&str> {
+        self.metadata
+            .0
+            .contents
+            .iter()
+            .find(|entry| entry.key == key)
+            .map(|entry| entry.value.as_str())
+    }
+
+    /// The MVR name the package info was registered with.
+    pub fn default_name(&self) -> Option<&str> {
+        self.get_metadata("default")
+    }
+}
+
 #[derive(Deserialize, Clone, Debug)]
+#[cfg_attr(test, derive(serde::Serialize))]
 pub struct PackageDisplay {
     _gradient_from: String,
     _gradient_to: String,
@@ -98,49 +171,45 @@ pub(crate) async fn mvr_forward_resolution(
     key_server_options: &KeyServerOptions,
 ) -> Result<ObjectID, InternalError> {
     let network = resolve_network(&key_server_options.network)?;
+    match network {
+        Network::Mainnet => {
+            resolve_mvr_name(mvr_name, &network, sui_rpc_client, sui_rpc_client).await
+        }
+        Network::Testnet => {
+            let mainnet_client = mainnet_sui_rpc_client(sui_rpc_client, key_server_options).await?;
+            resolve_mvr_name(mvr_name, &network, &mainnet_client, sui_rpc_client).await
+        }
+        _ => Err(Failure("Invalid network for MVR resolution".to_string())),
+    }
+}
+
+/// Look up the package an MVR name points to on `network`, reading the registry with
+/// `mainnet_client` and, for testnet, the package info object with `network_client`.
+async fn resolve_mvr_name(
+    mvr_name: &str,
+    network: &Network,
+    mainnet_client: &SuiRpcClient,
+    network_client: &SuiRpcClient,
+) -> Result<ObjectID, InternalError> {
     let package_address = match network {
-        Network::Mainnet => get_from_mvr_registry(mvr_name, sui_rpc_client)
+        Network::Mainnet => get_from_mvr_registry(mvr_name, mainnet_client)
             .await?
             .value
             .app_info
             .ok_or(InvalidMVRName)?
-            .package_address
-            .ok_or(Failure(format!(
-                "No package_address field on app_info for {mvr_name} on mainnet"
-            )))?,
+            .package_address_or_err(&format!("{mvr_name} on mainnet"))?,
         Network::Testnet => {
-            let networks: HashMap<_, _> = get_from_mvr_registry(
-                mvr_name,
-                &SuiRpcClient::new(
-                    SuiClientBuilder::default()
-                        .request_timeout(key_server_options.rpc_config.timeout)
-                        .build_mainnet()
-                        .await
-                        .map_err(|_| Failure("Failed to build sui client".to_string()))?,
-                    SuiGrpcClient::new(Network::Mainnet.node_url())
-                        .expect("Failed to create SuiGrpcClient"),
-                    key_server_options.rpc_config.retry_config.clone(),
-                    sui_rpc_client.get_metrics(),
-                ),
-            )
-            .await?
-            .value
-            .networks
-            .into();
+            let app_record = get_from_mvr_registry(mvr_name, mainnet_client).await?.value;
 
             // For testnet, we need to look up the package info ID
-            let package_info_id = networks
-                .get(TESTNET_ID)
+            let package_info_id = app_record
+                .testnet_package_info()
                 .ok_or(InvalidMVRName)?
-                .package_info_id
-                .ok_or(Failure(format!(
-                    "No package info ID for MVR name {mvr_name} on testnet"
-                )))?;
-            let package_info: PackageInfo = get_object(package_info_id, sui_rpc_client).await?;
+                .package_info_id_or_err(&format!("{mvr_name} on testnet"))?;
+            let package_info: PackageInfo = get_object(package_info_id, network_client).await?;
 
             // Check that the name in the package info matches the MVR name.
-            let metadata: HashMap<_, _> = package_info.metadata.into();
-            let name_in_package_info = metadata.get("default").ok_or(Failure(
+            let name_in_package_info = package_info.default_name().ok_or(Failure(
                 "No 'default' field on package_info object".to_string(),
             ))?;
             if name_in_package_info != mvr_name {
@@ -154,6 +223,102 @@ pub(crate) async fn mvr_forward_resolution(
     Ok(package_address)
 }
 
+/// Given a package address, look up an MVR name pointing to it, e.g. to identify an unknown
+/// package. Returns `None` if no name points to the package, and fails if the registry has more
+/// than `max_records` records and none of the first `max_records` points to the package.
+///
+/// MVR has no index from packages to names, so this scans the records of the registry, which
+/// takes many requests and must not be done while handling requests. Only the package address a
+/// name was registered with is found, not later versions of the package.
+pub async fn reverse_resolve_mvr(
+    sui_rpc_client: &SuiRpcClient,
+    package_id: ObjectID,
+    options: &KeyServerOptions,
+    max_records: usize,
+) -> Result<Option<String>, InternalError> {
+    let network = resolve_network(&options.network)?;
+    let mainnet_client = match network {
+        Network::Mainnet => sui_rpc_client.clone(),
+        _ => mainnet_sui_rpc_client(sui_rpc_client, options).await?,
+    };
+    let registry_id = ObjectID::from_str(MVR_REGISTRY).unwrap();
+    let mut cursor = None;
+    let mut scanned = 0;
+    loop {
+        if scanned >= max_records {
+            return Err(Failure(format!(
+                "No MVR name found for {package_id} in the first {scanned} records of the MVR registry"
+            )));
+        }
+        let page = mainnet_client
+            .get_dynamic_fields(
+                registry_id,
+                cursor,
+                Some(REGISTRY_PAGE_SIZE.min(max_records - scanned)),
+            )
+            .await
+            .map_err(|_| Failure("Failed to list the records of the MVR registry".to_string()))?;
+        scanned += page.data.len();
+        let records = mainnet_client
+            .multi_get_object_with_options(
+                page.data.iter().map(|field| field.object_id).collect(),
+                SuiObjectDataOptions::new().with_bcs(),
+            )
+            .await
+            .map_err(|_| Failure("Failed to get records of the MVR registry".to_string()))?;
+        for record in records {
+            // Skip other dynamic fields of the registry, if any.
+            let Some(record) = record
+                .move_object_bcs()
+                .and_then(|bcs| bcs::from_bytes::<Field<Name, AppRecord>>(bcs).ok())
+            else {
+                continue;
+            };
+            if registered_package_address(&record.value, &network) != Some(package_id) {
+                continue;
+            }
+            // The testnet package address of a record is not guaranteed to be accurate, so the
+            // name must also resolve to the package.
+            let mvr_name = record.name.to_string();
+            match mvr_forward_resolution(sui_rpc_client, &mvr_name, options).await {
+                Ok(resolved) if resolved == package_id => return Ok(Some(mvr_name)),
+                Ok(_) | Err(InvalidMVRName) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        if !page.has_next_page {
+            return Ok(None);
+        }
+        cursor = page.next_cursor;
+    }
+}
+
+/// The package address an app record was registered with on `network`, if any.
+fn registered_package_address(record: &AppRecord, network: &Network) -> Option<ObjectID> {
+    match network {
+        Network::Mainnet => record.app_info.as_ref()?.package_address,
+        Network::Testnet => record.testnet_package_info()?.package_address,
+        _ => None,
+    }
+}
+
+/// Client for mainnet, where the MVR registry is, for a key server on another network.
+async fn mainnet_sui_rpc_client(
+    sui_rpc_client: &SuiRpcClient,
+    key_server_options: &KeyServerOptions,
+) -> Result<SuiRpcClient, InternalError> {
+    Ok(SuiRpcClient::new(
+        SuiClientBuilder::default()
+            .request_timeout(key_server_options.rpc_config.timeout)
+            .build_mainnet()
+            .await
+            .map_err(|_| Failure("Failed to build sui client".to_string()))?,
+        SuiGrpcClient::new(Network::Mainnet.node_url()).expect("Failed to create SuiGrpcClient"),
+        key_server_options.rpc_config.retry_config.clone(),
+        sui_rpc_client.get_metrics(),
+    ))
+}
+
 /// Resolve the network from the network configuration for Custom.
 pub(crate) fn resolve_network(network: &Network) -> Result<Network, InternalError> {
     match &network {
@@ -197,8 +362,18 @@ async fn get_from_mvr_registry(
     get_object(record_id, mainnet_sui_rpc_client).await
 }
 
-/// Construct a `DynamicFieldName` from an MVR name for use in the MVR registry.
+/// Construct a `DynamicFieldName` from an MVR name for use in the MVR registry. Names are parsed
+/// deterministically, so valid names are cached.
 fn dynamic_field_name(mvr_name: &str) -> Result<DynamicFieldName, InternalError> {
+    if let Some(name) = DYNAMIC_FIELD_NAME_CACHE.get(mvr_name) {
+        return Ok(name);
+    }
+    let name = parse_dynamic_field_name(mvr_name)?;
+    DYNAMIC_FIELD_NAME_CACHE.insert(mvr_name.to_string(), name.clone());
+    Ok(name)
+}
+
+fn parse_dynamic_field_name(mvr_name: &str) -> Result<DynamicFieldName, InternalError> {
     let parsed_name =
         mvr_types::name::VersionedName::from_str(mvr_name).map_err(|_| InvalidMVRName)?;
     if parsed_name.version.is_some() {
@@ -233,18 +408,264 @@ async fn get_object<T: for<'a> Deserialize<'a>>(
 
 #[cfg(test)]
 mod tests {
-    use crate::errors::InternalError::InvalidMVRName;
+    use crate::errors::InternalError::{Failure, InvalidMVRName};
     use crate::key_server_options::{KeyServerOptions, RetryConfig};
-    use crate::mvr::mvr_forward_resolution;
+    use crate::mvr::{
+        dynamic_field_name, mvr_forward_resolution, registered_package_address, resolve_mvr_name,
+        reverse_resolve_mvr, AppInfo, AppRecord, PackageDisplay, PackageInfo, VecMap,
+        DYNAMIC_FIELD_NAME_CACHE, MVR_REGISTRY, TESTNET_ID,
+    };
     use crate::sui_rpc_client::SuiRpcClient;
+    use crate::tests::mock_fullnode::MockFullnode;
     use crate::types::Network;
-    use mvr_types::name::VersionedName;
+    use mvr_types::name::{Name, VersionedName};
     use std::str::FromStr;
     use sui_rpc::client::v2::Client as SuiGrpcClient;
     use sui_sdk::SuiClientBuilder;
     use sui_types::base_types::ObjectID;
+    use sui_types::dynamic_field::Field;
+    use sui_types::id::UID;
+
+    fn entry<K, V>(key: K, value: V) -> sui_types::collection_types::Entry<K, V> {
+        sui_types::collection_types::Entry { key, value }
+    }
+
+    /// Package info of `package_address` with the given metadata entries.
+    fn package_info(
+        package_address: ObjectID,
+        metadata: Vec<sui_types::collection_types::Entry<String, String>>,
+    ) -> PackageInfo {
+        PackageInfo {
+            _id: ObjectID::ZERO,
+            _display: PackageDisplay {
+                _gradient_from: String::new(),
+                _gradient_to: String::new(),
+                _text_color: String::new(),
+                _name: String::new(),
+                _uri_encoded_name: String::new(),
+            },
+            _upgrade_cap_id: ObjectID::ZERO,
+            package_address,
+            metadata: VecMap(sui_types::collection_types::VecMap { contents: metadata }),
+            _git_versioning: sui_types::collection_types::Table {
+                id: ObjectID::ZERO,
+                size: 0,
+            },
+        }
+    }
+
+    /// A mainnet fullnode with an MVR registry holding a record for each of `records`, given as
+    /// the MVR name, the mainnet package address and the testnet package info ID.
+    fn mock_registry(records: &[(&str, Option<ObjectID>, Option<ObjectID>)]) -> MockFullnode {
+        let registry_id = ObjectID::from_str(MVR_REGISTRY).unwrap();
+        records.iter().enumerate().fold(
+            MockFullnode::default(),
+            |node, (i, (mvr_name, package_address, package_info_id))| {
+                let app_info = |package_info_id, package_address| AppInfo {
+                    package_info_id,
+                    package_address,
+                    _upgrade_cap_id: None,
+                };
+                let field_id = ObjectID::from_single_byte(0x80 + i as u8);
+                let record: Field<Name, AppRecord> = Field {
+                    id: UID::new(field_id),
+                    name: VersionedName::from_str(mvr_name).unwrap().name,
+                    value: AppRecord {
+                        _app_cap_id: ObjectID::ZERO,
+                        _ns_nft_id: ObjectID::ZERO,
+                        app_info: package_address.map(|address| app_info(None, Some(address))),
+                        networks: VecMap(sui_types::collection_types::VecMap {
+                            contents: package_info_id
+                                .map(|id| entry(TESTNET_ID.to_string(), app_info(Some(id), None)))
+                                .into_iter()
+                                .collect(),
+                        }),
+                        _metadata: VecMap(sui_types::collection_types::VecMap { contents: vec![] }),
+                        _storage: ObjectID::ZERO,
+                    },
+                };
+                node.with_dynamic_field(
+                    registry_id,
+                    &dynamic_field_name(mvr_name).unwrap(),
+                    field_id,
+                    &record,
+                )
+            },
+        )
+    }
+
+    #[test]
+    fn test_package_info_metadata() {
+        let package_info = package_info(
+            ObjectID::ZERO,
+            vec![
+                entry("website".to_string(), "https://example.com".to_string()),
+                entry("default".to_string(), "@mysten/kiosk".to_string()),
+            ],
+        );
+        assert_eq!(package_info.default_name(), Some("@mysten/kiosk"));
+        assert_eq!(
+            package_info.get_metadata("website"),
+            Some("https://example.com")
+        );
+        assert_eq!(package_info.get_metadata("missing"), None);
+    }
+
+    #[test]
+    fn test_app_info_accessors() {
+        let app_info = AppInfo {
+            package_info_id: None,
+            package_address: Some(ObjectID::ZERO),
+            _upgrade_cap_id: None,
+        };
+        assert_eq!(
+            app_info.package_address_or_err("@mysten/kiosk on mainnet"),
+            Ok(ObjectID::ZERO)
+        );
+        let Err(Failure(message)) = app_info.package_info_id_or_err("@mysten/kiosk on testnet")
+        else {
+            panic!("must fail");
+        };
+        assert!(message.ends_with("for @mysten/kiosk on testnet"));
+    }
+
+    #[test]
+    fn test_registered_package_address() {
+        let app_info = |package_address: u8| AppInfo {
+            package_info_id: None,
+            package_address: Some(ObjectID::from_single_byte(package_address)),
+            _upgrade_cap_id: None,
+        };
+        let record = AppRecord {
+            _app_cap_id: ObjectID::ZERO,
+            _ns_nft_id: ObjectID::ZERO,
+            app_info: Some(app_info(1)),
+            networks: VecMap(sui_types::collection_types::VecMap {
+                contents: vec![sui_types::collection_types::Entry {
+                    key: TESTNET_ID.to_string(),
+                    value: app_info(2),
+                }],
+            }),
+            _metadata: VecMap(sui_types::collection_types::VecMap { contents: vec![] }),
+            _storage: ObjectID::ZERO,
+        };
+        assert_eq!(
+            registered_package_address(&record, &Network::Mainnet),
+            Some(ObjectID::from_single_byte(1))
+        );
+        assert_eq!(
+            registered_package_address(&record, &Network::Testnet),
+            Some(ObjectID::from_single_byte(2))
+        );
+        assert_eq!(registered_package_address(&record, &Network::Devnet), None);
+
+        let mainnet_only = AppRecord {
+            networks: VecMap(sui_types::collection_types::VecMap { contents: vec![] }),
+            ..record
+        };
+        assert_eq!(
+            registered_package_address(&mainnet_only, &Network::Testnet),
+            None
+        );
+    }
+
+    #[test]
+    fn test_dynamic_field_name_cache() {
+        let name = dynamic_field_name("@mysten/cache-test").unwrap();
+        assert_eq!(
+            DYNAMIC_FIELD_NAME_CACHE.get("@mysten/cache-test"),
+            Some(name.clone())
+        );
+        assert_eq!(dynamic_field_name("@mysten/cache-test").unwrap(), name);
+
+        // Invalid names are not cached.
+        assert_eq!(
+            dynamic_field_name("invalid_name").err(),
+            Some(InvalidMVRName)
+        );
+        assert!(DYNAMIC_FIELD_NAME_CACHE.get("invalid_name").is_none());
+    }
+
     #[tokio::test]
     async fn test_forward_resolution() {
+        let kiosk = ObjectID::from_single_byte(1);
+        let demo_info_id = ObjectID::from_single_byte(2);
+        let demo = ObjectID::from_single_byte(3);
+        let mainnet = mock_registry(&[
+            ("@mysten/mock-kiosk", Some(kiosk), None),
+            ("@pkg/seal-demo", None, Some(demo_info_id)),
+            ("@pkg/renamed-demo", None, Some(demo_info_id)),
+        ])
+        .serve()
+        .await;
+        let testnet = MockFullnode::default()
+            .with_object(
+                demo_info_id,
+                &package_info(
+                    demo,
+                    vec![entry("default".to_string(), "@pkg/seal-demo".to_string())],
+                ),
+            )
+            .serve()
+            .await;
+
+        // Resolved through the cache of the key server, with the registry on the same network.
+        let options = KeyServerOptions::new_for_testing(Network::Custom {
+            node_url: None,
+            use_default_mainnet_for_mvr: Some(true),
+        });
+        assert!(crate::externals::check_mvr_package_id(
+            &Some("@mysten/mock-kiosk".to_string()),
+            &mainnet,
+            &options,
+            kiosk,
+            None
+        )
+        .await
+        .is_ok());
+        assert_eq!(
+            crate::externals::get_mvr_cache("@mysten/mock-kiosk"),
+            Some(kiosk)
+        );
+
+        assert_eq!(
+            resolve_mvr_name("@pkg/seal-demo", &Network::Testnet, &mainnet, &testnet).await,
+            Ok(demo)
+        );
+        // Not registered on mainnet.
+        assert_eq!(
+            resolve_mvr_name("@pkg/seal-demo", &Network::Mainnet, &mainnet, &mainnet).await,
+            Err(InvalidMVRName)
+        );
+        // Not registered on testnet.
+        assert_eq!(
+            resolve_mvr_name("@mysten/mock-kiosk", &Network::Testnet, &mainnet, &testnet).await,
+            Err(InvalidMVRName)
+        );
+        // The package info on testnet was registered with another name.
+        assert_eq!(
+            resolve_mvr_name("@pkg/renamed-demo", &Network::Testnet, &mainnet, &testnet).await,
+            Err(InvalidMVRName)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalid_name() {
+        let mainnet = mock_registry(&[("@mysten/mock-kiosk", Some(ObjectID::ZERO), None)])
+            .serve()
+            .await;
+        let options = KeyServerOptions::new_for_testing(Network::Mainnet);
+        for mvr_name in ["@saemundur/seal", "invalid_name"] {
+            assert_eq!(
+                mvr_forward_resolution(&mainnet, mvr_name, &options).await,
+                Err(InvalidMVRName)
+            );
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "requires mainnet and testnet access"]
+    async fn test_forward_resolution_live() {
         assert!(crate::externals::check_mvr_package_id(
             &Some("@mysten/kiosk".to_string()),
             &SuiRpcClient::new(
@@ -334,7 +755,70 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_invalid_name() {
+    #[ignore = "requires mainnet and testnet access, and scans the whole MVR registry"]
+    async fn test_reverse_resolution() {
+        let mainnet_client = SuiRpcClient::new(
+            SuiClientBuilder::default().build_mainnet().await.unwrap(),
+            SuiGrpcClient::new(Network::Mainnet.node_url()).unwrap(),
+            RetryConfig::default(),
+            None,
+        );
+        let mainnet_options = KeyServerOptions::new_for_testing(Network::Mainnet);
+        assert_eq!(
+            reverse_resolve_mvr(
+                &mainnet_client,
+                ObjectID::from_str(
+                    "0xdfb4f1d4e43e0c3ad834dcd369f0d39005c872e118c9dc1c5da9765bb93ee5f3"
+                )
+                .unwrap(),
+                &mainnet_options,
+                usize::MAX,
+            )
+            .await
+            .unwrap(),
+            Some("@mysten/kiosk".to_string())
+        );
+        assert_eq!(
+            reverse_resolve_mvr(
+                &mainnet_client,
+                ObjectID::ZERO,
+                &mainnet_options,
+                usize::MAX
+            )
+            .await
+            .unwrap(),
+            None
+        );
+        // The registry has more records than scanned.
+        assert!(matches!(
+            reverse_resolve_mvr(&mainnet_client, ObjectID::ZERO, &mainnet_options, 1).await,
+            Err(Failure(_))
+        ));
+
+        assert_eq!(
+            reverse_resolve_mvr(
+                &SuiRpcClient::new(
+                    SuiClientBuilder::default().build_testnet().await.unwrap(),
+                    SuiGrpcClient::new(Network::Testnet.node_url()).unwrap(),
+                    RetryConfig::default(),
+                    None,
+                ),
+                ObjectID::from_str(
+                    "0xc5ce2742cac46421b62028557f1d7aea8a4c50f651379a79afdf12cd88628807"
+                )
+                .unwrap(),
+                &KeyServerOptions::new_for_testing(Network::Testnet),
+                usize::MAX,
+            )
+            .await
+            .unwrap(),
+            Some("@pkg/seal-demo-1234".to_string())
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires mainnet and testnet access"]
+    async fn test_invalid_name_live() {
         assert_eq!(
             mvr_forward_resolution(
                 &SuiRpcClient::new(
@@ -7,14 +7,14 @@ use crate::externals::get_reference_gas_price;
 use crate::key_server_options::ServerMode;
 use crate::metrics::{call_with_duration, observation_callback, status_callback, Metrics};
 use crate::metrics_push::create_push_client;
-use crate::mvr::mvr_forward_resolution;
+use crate::mvr::{mvr_forward_resolution, reverse_resolve_mvr, DEFAULT_MAX_REGISTRY_RECORDS};
 use crate::periodic_updater::spawn_periodic_updater;
 use crate::signed_message::signed_request;
 use crate::time::checked_duration_since;
 use crate::time::from_mins;
 use crate::time::{duration_since_as_f64, saturating_duration_since};
 use crate::types::{MasterKeyPOP, Network};
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use axum::extract::{Query, Request};
 use axum::http::{HeaderMap, HeaderValue};
 use axum::middleware::{from_fn_with_state, map_response, Next};
@@ -138,18 +138,7 @@ struct Server {
 
 impl Server {
     async fn new(options: KeyServerOptions, metrics: Option<Arc<Metrics>>) -> Self {
-        let sui_rpc_client = SuiRpcClient::new(
-            SuiClientBuilder::default()
-                .request_timeout(options.rpc_config.timeout)
-                .build(&options.network.node_url())
-                .await
-                .expect(
-                    "SuiClientBuilder should not failed unless provided with invalid network url",
-                ),
-            SuiGrpcClient::new(options.network.node_url()).expect("Failed to create SuiGrpcClient"),
-            options.rpc_config.retry_config.clone(),
-            metrics,
-        );
+        let sui_rpc_client = create_sui_rpc_client(&options, metrics).await;
         info!("Server started with network: {:?}", options.network);
         let master_keys = MasterKeys::load(&options).unwrap_or_else(|e| {
             panic!("Failed to load master keys: {e}");
@@ -771,6 +760,13 @@ async fn start_server_background_tasks(
 #[tokio::main]
 async fn main() -> Result<()> {
     let _guard = mysten_service::logging::init();
+
+    // Administration commands run once with the configuration of the key server instead of serving.
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    if let [command, args @ ..] = args.as_slice() {
+        return run_admin_command(command, args).await;
+    }
+
     let (monitor_handle, app) = app().await?;
 
     tokio::select! {
@@ -785,10 +781,56 @@ async fn main() -> Result<()> {
     }
 }
 
-pub(crate) async fn app() -> Result<(JoinHandle<Result<()>>, Router)> {
+const ADMIN_USAGE: &str = "Usage: key-server reverse-resolve-mvr <PACKAGE_ID> [MAX_RECORDS]";
+
+/// Run an administration command given on the command line and print its result.
+async fn run_admin_command(command: &str, args: &[String]) -> Result<()> {
+    match (command, args) {
+        ("reverse-resolve-mvr", [package_id, max_records @ ..]) if max_records.len() <= 1 => {
+            let package_id =
+                ObjectID::from_hex_literal(package_id).context("Invalid package ID")?;
+            let max_records = match max_records.first() {
+                Some(max_records) => max_records
+                    .parse()
+                    .context("Invalid maximum number of records")?,
+                None => DEFAULT_MAX_REGISTRY_RECORDS,
+            };
+            let options = load_options()?;
+            let sui_rpc_client = create_sui_rpc_client(&options, None).await;
+            match reverse_resolve_mvr(&sui_rpc_client, package_id, &options, max_records)
+                .await
+                .map_err(|e| anyhow!("Reverse MVR resolution failed: {e:?}"))?
+            {
+                Some(mvr_name) => println!("{mvr_name}"),
+                None => println!("No MVR name points to package {package_id}"),
+            }
+            Ok(())
+        }
+        _ => Err(anyhow!(ADMIN_USAGE)),
+    }
+}
+
+async fn create_sui_rpc_client(
+    options: &KeyServerOptions,
+    metrics: Option<Arc<Metrics>>,
+) -> SuiRpcClient {
+    SuiRpcClient::new(
+        SuiClientBuilder::default()
+            .request_timeout(options.rpc_config.timeout)
+            .build(&options.network.node_url())
+            .await
+            .expect("SuiClientBuilder should not failed unless provided with invalid network url"),
+        SuiGrpcClient::new(options.network.node_url()).expect("Failed to create SuiGrpcClient"),
+        options.rpc_config.retry_config.clone(),
+        metrics,
+    )
+}
+
+/// Load the configuration of the key server.
+fn load_options() -> Result<KeyServerOptions> {
     // If CONFIG_PATH is set, read the configuration from the file.
     // Otherwise, use the local environment variables.
-    let options = match env::var("CONFIG_PATH") {
+    Ok(match env::var("CONFIG_PATH") {
         Ok(config_path) => {
             info!("Loading config file: {}", config_path);
             let mut opts: KeyServerOptions = serde_yaml::from_reader(
@@ -827,7 +869,10 @@ pub(crate) async fn app() -> Result<(JoinHandle<Result<()>>, Router)> {
             } = opts.server_mode
             {
                 if let Some(pkg_id_str) = env::var("NEXT_PUBLIC_PACKAGE_ID").ok() {
-                    info!("Found NEXT_PUBLIC_PACKAGE_ID environment variable, parsing: {}", pkg_id_str);
+                    info!(
+                        "Found NEXT_PUBLIC_PACKAGE_ID environment variable, parsing: {}",
+                        pkg_id_str
+                    );
 
                     match ObjectID::from_hex_literal(&pkg_id_str) {
                         Ok(package_id) => {
@@ -865,7 +910,11 @@ pub(crate) async fn app() -> Result<(JoinHandle<Result<()>>, Router)> {
                 utils::decode_object_id("KEY_SERVER_OBJECT_ID")?,
             )
         }
-    };
+    })
+}
+
+pub(crate) async fn app() -> Result<(JoinHandle<Result<()>>, Router)> {
+    let options = load_options()?;
 
     info!("Setting up metrics");
     let registry = start_prometheus_server(SocketAddr::new(
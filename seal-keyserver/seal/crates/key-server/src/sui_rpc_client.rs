@@ -8,7 +8,9 @@ use sui_rpc::client::v2::Client as SuiGrpcClient;
 use sui_rpc::proto::proto_to_timestamp_ms;
 use sui_sdk::{
     error::SuiRpcResult,
-    rpc_types::{DryRunTransactionBlockResponse, SuiObjectDataOptions, SuiObjectResponse},
+    rpc_types::{
+        DryRunTransactionBlockResponse, DynamicFieldPage, SuiObjectDataOptions, SuiObjectResponse,
+    },
     SuiClient,
 };
 use sui_types::base_types::ObjectID;
@@ -229,6 +231,26 @@ impl SuiRpcClient {
         .await
     }
 
+    /// Returns objects with the given options, in the order of `object_ids`.
+    pub async fn multi_get_object_with_options(
+        &self,
+        object_ids: Vec<ObjectID>,
+        options: SuiObjectDataOptions,
+    ) -> SuiRpcResult<Vec<SuiObjectResponse>> {
+        sui_rpc_with_retries(
+            &self.rpc_retry_config,
+            "multi_get_object_with_options",
+            self.metrics.clone(),
+            || async {
+                self.sui_client
+                    .read_api()
+                    .multi_get_object_with_options(object_ids.clone(), options.clone())
+                    .await
+            },
+        )
+        .await
+    }
+
     /// Returns the latest checkpoint sequence number.
     pub async fn get_latest_checkpoint_sequence_number(&self) -> RpcResult<u64> {
         sui_rpc_with_retries(
@@ -317,6 +339,27 @@ impl SuiRpcClient {
         .await
     }
 
+    /// Returns a page of the dynamic fields of an object, starting after `cursor`.
+    pub async fn get_dynamic_fields(
+        &self,
+        object_id: ObjectID,
+        cursor: Option<ObjectID>,
+        limit: Option<usize>,
+    ) -> SuiRpcResult<DynamicFieldPage> {
+        sui_rpc_with_retries(
+            &self.rpc_retry_config,
+            "get_dynamic_fields",
+            self.metrics.clone(),
+            || async {
+                self.sui_client
+                    .read_api()
+                    .get_dynamic_fields(object_id, cursor, limit)
+                    .await
+            },
+        )
+        .await
+    }
+
     /// Returns an object with the given dynamic field name.
     pub async fn get_dynamic_field_object(
         &self,
@@ -0,0 +1,141 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A fullnode serving canned objects over JSON-RPC, so that code reading objects through
+//! [SuiRpcClient] can be tested without network access.
+
+use crate::key_server_options::RetryConfig;
+use crate::sui_rpc_client::SuiRpcClient;
+use axum::extract::State;
+use axum::routing::post;
+use axum::{Json, Router};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use sui_rpc::client::v2::Client as SuiGrpcClient;
+use sui_sdk::rpc_types::{
+    SuiObjectData, SuiObjectResponse, SuiObjectResponseError, SuiRawData, SuiRawMoveObject,
+};
+use sui_sdk::SuiClientBuilder;
+use sui_types::base_types::{ObjectDigest, ObjectID, SequenceNumber};
+use sui_types::dynamic_field::DynamicFieldName;
+use sui_types::parse_sui_struct_tag;
+
+/// Objects served by a [MockFullnode], as BCS bytes of their Move structs.
+#[derive(Default)]
+pub(crate) struct MockFullnode {
+    objects: HashMap<ObjectID, Vec<u8>>,
+    /// Dynamic field object IDs by parent object ID and JSON of the field name.
+    dynamic_fields: HashMap<(ObjectID, Value), ObjectID>,
+}
+
+impl MockFullnode {
+    /// Serve `value` as the Move object `object_id`.
+    pub(crate) fn with_object(mut self, object_id: ObjectID, value: &impl Serialize) -> Self {
+        self.objects
+            .insert(object_id, bcs::to_bytes(value).expect("serializable"));
+        self
+    }
+
+    /// Serve `value` as the dynamic field `name` of `parent`, stored in object `field_id`.
+    pub(crate) fn with_dynamic_field(
+        self,
+        parent: ObjectID,
+        name: &DynamicFieldName,
+        field_id: ObjectID,
+        value: &impl Serialize,
+    ) -> Self {
+        let mut node = self.with_object(field_id, value);
+        node.dynamic_fields.insert(
+            (parent, serde_json::to_value(name).expect("serializable")),
+            field_id,
+        );
+        node
+    }
+
+    /// Start serving on a local port and return a client for it.
+    pub(crate) async fn serve(self) -> SuiRpcClient {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let app = Router::new()
+            .route("/", post(handle_request))
+            .with_state(Arc::new(self));
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        SuiRpcClient::new(
+            SuiClientBuilder::default().build(&url).await.unwrap(),
+            SuiGrpcClient::new(url).unwrap(),
+            RetryConfig::default(),
+            None,
+        )
+    }
+
+    fn object_response(&self, object_id: ObjectID) -> SuiObjectResponse {
+        let Some(bcs_bytes) = self.objects.get(&object_id) else {
+            return SuiObjectResponse::new_with_error(SuiObjectResponseError::NotExists {
+                object_id,
+            });
+        };
+        SuiObjectResponse::new_with_data(SuiObjectData {
+            object_id,
+            version: SequenceNumber::from_u64(1),
+            digest: ObjectDigest::random(),
+            type_: None,
+            owner: None,
+            previous_transaction: None,
+            storage_rebate: None,
+            display: None,
+            content: None,
+            bcs: Some(SuiRawData::MoveObject(SuiRawMoveObject {
+                type_: parse_sui_struct_tag("0x2::mock::Object").unwrap(),
+                has_public_transfer: false,
+                version: SequenceNumber::from_u64(1),
+                bcs_bytes: bcs_bytes.clone(),
+            })),
+        })
+    }
+
+    fn dynamic_field_response(&self, parent: ObjectID, name: &Value) -> SuiObjectResponse {
+        match self.dynamic_fields.get(&(parent, name.clone())) {
+            Some(field_id) => self.object_response(*field_id),
+            None => {
+                SuiObjectResponse::new_with_error(SuiObjectResponseError::DynamicFieldNotFound {
+                    parent_object_id: parent,
+                })
+            }
+        }
+    }
+}
+
+/// Answer a JSON-RPC request with the served objects. Only the methods needed by the tests are
+/// supported.
+async fn handle_request(
+    State(node): State<Arc<MockFullnode>>,
+    Json(request): Json<Value>,
+) -> Json<Value> {
+    let params = &request["params"];
+    let object_id = |param: &Value| -> ObjectID {
+        serde_json::from_value(param.clone()).expect("valid object ID")
+    };
+    let result = match request["method"].as_str().unwrap_or_default() {
+        "rpc.discover" => json!({ "info": { "version": "1.0.0" }, "methods": [] }),
+        "sui_getObject" => json!(node.object_response(object_id(&params[0]))),
+        "sui_multiGetObjects" => json!(params[0]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|id| node.object_response(object_id(id)))
+            .collect::<Vec<_>>()),
+        "suix_getDynamicFieldObject" => {
+            json!(node.dynamic_field_response(object_id(&params[0]), &params[1]))
+        }
+        method => {
+            return Json(json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "error": { "code": -32601, "message": format!("Method {method} not supported") },
+            }))
+        }
+    };
+    Json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }))
+}
@@ -33,6 +33,7 @@ use test_cluster::{TestCluster, TestClusterBuilder};
 
 mod e2e;
 mod externals;
+pub(crate) mod mock_fullnode;
 mod pd;
 mod tle;
 pub(crate) mod whitelist;
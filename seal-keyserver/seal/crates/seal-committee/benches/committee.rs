@@ -0,0 +1,138 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Benchmarks for processing committees with many members.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use fastcrypto::bls12381::min_sig::BLS12381KeyPair;
+use fastcrypto::encoding::{Encoding, Hex};
+use fastcrypto::groups::bls12381::G2Element;
+use fastcrypto::traits::KeyPair;
+use fastcrypto_tbls::ecies_v1::{PrivateKey, PublicKey};
+use rand::thread_rng;
+use seal_committee::grpc_helper::deserialize_move_object;
+use seal_committee::{build_new_to_old_map, SealCommittee};
+use serde::Serialize;
+use sui_sdk_types::{
+    Address, Digest, Identifier, MoveStruct, Object, ObjectData, Owner, StructTag,
+};
+
+const COMMITTEE_SIZES: [usize; 4] = [10, 50, 100, 200];
+
+/// Same BCS layout as the onchain committee object, see [SealCommittee].
+#[derive(Serialize)]
+struct CommitteeBcs {
+    id: Address,
+    threshold: u16,
+    members: Vec<Address>,
+    state: CommitteeStateBcs,
+    old_committee_id: Option<Address>,
+}
+
+#[derive(Serialize)]
+enum CommitteeStateBcs {
+    Init {
+        members_info: Vec<(Address, MemberInfoBcs)>,
+    },
+}
+
+/// Public keys are Move byte literals of the BCS bytes, as registered onchain.
+#[derive(Serialize)]
+struct MemberInfoBcs {
+    enc_pk: Vec<u8>,
+    signing_pk: Vec<u8>,
+    url: String,
+}
+
+fn move_byte_literal<T: Serialize>(value: &T) -> Vec<u8> {
+    Hex::encode_with_format(bcs::to_bytes(value).unwrap()).into_bytes()
+}
+
+/// Address of member `i`, unique for up to 2^16 members.
+fn member_address(i: usize) -> Address {
+    let mut bytes = [0u8; 32];
+    bytes[30..].copy_from_slice(&(i as u16).to_be_bytes());
+    Address::new(bytes)
+}
+
+/// BCS bytes of a committee object in Init state where `members` are all registered.
+fn committee_object_bcs(id: Address, members: Vec<Address>) -> Vec<u8> {
+    let members_info = members
+        .iter()
+        .map(|address| {
+            let enc_sk = PrivateKey::<G2Element>::new(&mut thread_rng());
+            let signing_kp = BLS12381KeyPair::generate(&mut thread_rng());
+            let info = MemberInfoBcs {
+                enc_pk: move_byte_literal(&PublicKey::from_private_key(&enc_sk)),
+                signing_pk: move_byte_literal(signing_kp.public()),
+                url: format!("https://{address}.example.com"),
+            };
+            (*address, info)
+        })
+        .collect();
+    let committee = CommitteeBcs {
+        id,
+        threshold: (members.len() / 2 + 1) as u16,
+        members,
+        state: CommitteeStateBcs::Init { members_info },
+        old_committee_id: None,
+    };
+
+    let type_ = StructTag {
+        address: Address::ZERO,
+        module: Identifier::new("seal_committee").unwrap(),
+        name: Identifier::new("Committee").unwrap(),
+        type_params: vec![],
+    };
+    let contents = bcs::to_bytes(&committee).unwrap();
+    let object = Object::new(
+        ObjectData::Struct(MoveStruct::new(type_, false, 1, contents).unwrap()),
+        Owner::Shared(1),
+        Digest::ZERO,
+        0,
+    );
+    bcs::to_bytes(&object).unwrap()
+}
+
+fn deserialize_committee(bytes: &[u8]) -> SealCommittee {
+    deserialize_move_object(bytes, "Committee object").unwrap()
+}
+
+fn bench_committee(c: &mut Criterion) {
+    let mut group = c.benchmark_group("committee");
+    for size in COMMITTEE_SIZES {
+        let members: Vec<Address> = (0..size).map(member_address).collect();
+        let bytes = committee_object_bcs(Address::new([1; 32]), members.clone());
+        let committee = deserialize_committee(&bytes);
+
+        // Rotation where half of the old members continue, at reversed positions.
+        let mut new_members: Vec<Address> = members[..size / 2].to_vec();
+        new_members.extend((size..size + size / 2).map(member_address));
+        new_members.reverse();
+        let new_committee =
+            deserialize_committee(&committee_object_bcs(Address::new([2; 32]), new_members));
+
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(
+            BenchmarkId::new("deserialize_move_object", size),
+            &bytes,
+            |b, bytes| b.iter(|| deserialize_committee(bytes)),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("get_members_info", size),
+            &committee,
+            |b, committee| b.iter(|| committee.get_members_info().unwrap()),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("build_new_to_old_map", size),
+            &(new_committee, committee),
+            |b, (new_committee, old_committee)| {
+                b.iter(|| build_new_to_old_map(new_committee, old_committee))
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_committee);
+criterion_main!(benches);
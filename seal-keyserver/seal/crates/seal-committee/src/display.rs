@@ -0,0 +1,257 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Human-readable summaries of committees and key servers via [fmt::Display]. Object IDs,
+//! addresses and keys are abbreviated to their first 6 and last 4 hex digits, e.g.
+//! `0x1d8e07..e903`. The alternate flag (`{:#}`) prints them in full.
+//!
+//! The format is locked by the tests below, so that scripts parsing it notice changes.
+
+use std::fmt;
+
+use fastcrypto::encoding::{Encoding, Hex};
+use serde::Serialize;
+
+use crate::move_types::{
+    CommitteeState, KeyServerV2, PartialKeyServerInfo, SealCommittee, ServerType,
+};
+
+/// `hex` with 0x prefix, abbreviated unless `wide`.
+fn abbreviate(hex: &str, wide: bool) -> String {
+    let digits = hex.strip_prefix("0x").unwrap_or(hex);
+    if wide || digits.len() <= 10 {
+        return format!("0x{digits}");
+    }
+    format!("0x{}..{}", &digits[..6], &digits[digits.len() - 4..])
+}
+
+/// Hex of the BCS bytes of `value`, abbreviated unless `wide`.
+fn bcs_hex<T: Serialize>(value: &T, wide: bool) -> Result<String, fmt::Error> {
+    let bytes = bcs::to_bytes(value).map_err(|_| fmt::Error)?;
+    Ok(abbreviate(&Hex::encode(bytes), wide))
+}
+
+impl fmt::Display for CommitteeState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Init { members_info } => {
+                write!(f, "Init ({} registered)", members_info.0.contents.len())
+            }
+            Self::PostDKG { approvals, .. } => {
+                write!(f, "PostDKG ({} approved)", approvals.contents.len())
+            }
+            Self::Finalized => write!(f, "Finalized"),
+        }
+    }
+}
+
+impl fmt::Display for SealCommittee {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let wide = f.alternate();
+        writeln!(f, "Committee {}", abbreviate(&self.id.to_string(), wide))?;
+        writeln!(
+            f,
+            "  Threshold:     {} of {}",
+            self.threshold,
+            self.members.len()
+        )?;
+        writeln!(f, "  State:         {}", self.state)?;
+        if let Some(old_committee_id) = &self.old_committee_id {
+            writeln!(
+                f,
+                "  Old committee: {}",
+                abbreviate(&old_committee_id.to_string(), wide)
+            )?;
+        }
+        let members_info = match &self.state {
+            CommitteeState::Init { members_info }
+            | CommitteeState::PostDKG { members_info, .. } => Some(members_info),
+            CommitteeState::Finalized => None,
+        };
+        write!(f, "  Members:")?;
+        for (party_id, address) in self.members.iter().enumerate() {
+            write!(
+                f,
+                "\n    {party_id}: {}",
+                abbreviate(&address.to_string(), wide)
+            )?;
+            match members_info.map(|info| info.get(address)) {
+                Some(Some(info)) => write!(
+                    f,
+                    " enc_pk {} signing_pk {}",
+                    bcs_hex(&info.enc_pk, wide)?,
+                    bcs_hex(&info.signing_pk, wide)?
+                )?,
+                Some(None) => write!(f, " not registered")?,
+                None => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for KeyServerV2 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let wide = f.alternate();
+        writeln!(f, "Key server {}", self.name)?;
+        writeln!(f, "  Key type: {}", self.key_type)?;
+        writeln!(
+            f,
+            "  PK:       {}",
+            abbreviate(&Hex::encode(&self.pk), wide)
+        )?;
+        match &self.server_type {
+            ServerType::Independent { url } => write!(f, "  Type:     Independent, URL {url}"),
+            ServerType::Committee {
+                version,
+                threshold,
+                partial_key_servers,
+            } => {
+                let mut entries: Vec<_> = partial_key_servers.0.contents.iter().collect();
+                entries.sort_by_key(|entry| entry.value.party_id);
+                write!(
+                    f,
+                    "  Type:     Committee v{version}, threshold {threshold} of {}",
+                    entries.len()
+                )?;
+                for entry in entries {
+                    write!(
+                        f,
+                        "\n    {}: {} {} partial_pk {}",
+                        entry.value.party_id,
+                        abbreviate(&entry.key.to_string(), wide),
+                        entry.value.url,
+                        abbreviate(&Hex::encode(&entry.value.partial_pk), wide)
+                    )?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for PartialKeyServerInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let wide = f.alternate();
+        write!(
+            f,
+            "Party {} of key server {}, partial_pk {}",
+            self.party_id,
+            abbreviate(&self.ks_obj_id.to_string(), wide),
+            bcs_hex(&self.partial_pk, wide)?
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::move_types::Field;
+    use std::str::FromStr;
+    use sui_sdk_types::Address;
+
+    fn fixture<T: serde::de::DeserializeOwned>(hex: &str) -> T {
+        bcs::from_bytes(&Hex::decode(hex.trim()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_committee_display() {
+        let init: SealCommittee = fixture(include_str!("../tests/fixtures/committee_init.hex"));
+        assert_eq!(
+            init.to_string(),
+            "Committee 0x1d8e07..e903
+  Threshold:     2 of 3
+  State:         Init (3 registered)
+  Members:
+    0: 0x063615..ca9d enc_pk 0xaf2ca4..fa58 signing_pk 0x89dcee..0b46
+    1: 0xe6a37f..1ed6 enc_pk 0xaf2ca4..fa58 signing_pk 0x89dcee..0b46
+    2: 0x223762..61b9 enc_pk 0xaf2ca4..fa58 signing_pk 0x89dcee..0b46"
+        );
+
+        let post_dkg: SealCommittee =
+            fixture(include_str!("../tests/fixtures/committee_post_dkg.hex"));
+        assert_eq!(post_dkg.state.to_string(), "PostDKG (2 approved)");
+
+        let finalized: SealCommittee =
+            fixture(include_str!("../tests/fixtures/committee_finalized.hex"));
+        assert_eq!(
+            finalized.to_string(),
+            "Committee 0x82283c..a867
+  Threshold:     2 of 3
+  State:         Finalized
+  Old committee: 0xaf2962..af80
+  Members:
+    0: 0x063615..ca9d
+    1: 0xe6a37f..1ed6
+    2: 0x223762..61b9"
+        );
+        assert_eq!(
+            format!("{finalized:#}"),
+            "Committee 0x82283c1056bb18832428034d20e0af5ed098bc58f8815363c33eb3a9b3fba867
+  Threshold:     2 of 3
+  State:         Finalized
+  Old committee: 0xaf2962d702d718f7b968eddc262da28418a33c296786cd356a43728a858faf80
+  Members:
+    0: 0x0636157e9d013585ff473b3b378499ac2f1d207ed07d70e2cd815711725bca9d
+    1: 0xe6a37ff5cd968b6a666fb033d85eabc674449f44f9fc2b600e55e27354211ed6
+    2: 0x223762117ab21a439f0f3f3b0577e838b8b26a37d9a1723a4be311243f4461b9"
+        );
+
+        // Members that did not register yet.
+        let partial = SealCommittee::new_for_testing(Address::new([1; 32]), 1, vec![Address::ZERO]);
+        assert!(partial.to_string().ends_with(
+            "  State:         Init (0 registered)
+  Members:
+    0: 0x000000..0000 not registered"
+        ));
+    }
+
+    #[test]
+    fn test_key_server_display() {
+        let committee: Field<u64, KeyServerV2> = fixture(include_str!(
+            "../tests/fixtures/key_server_v2_committee.hex"
+        ));
+        assert_eq!(
+            committee.value.to_string(),
+            "Key server seal-committee
+  Key type: 0
+  PK:       0x94eba0..c069
+  Type:     Committee v1, threshold 2 of 3
+    0: 0x063615..ca9d https://seal-0.example.com partial_pk 0xaa56bd..2e57
+    1: 0xe6a37f..1ed6 https://seal-1.example.com partial_pk 0x8f1902..6bc3
+    2: 0x223762..61b9 https://seal-2.example.com partial_pk 0x8d942a..6848"
+        );
+
+        let independent: Field<u64, KeyServerV2> = fixture(include_str!(
+            "../tests/fixtures/key_server_v2_independent.hex"
+        ));
+        assert_eq!(
+            independent.value.to_string(),
+            "Key server seal-committee
+  Key type: 0
+  PK:       0x94eba0..c069
+  Type:     Independent, URL https://seal.example.com"
+        );
+
+        let ServerType::Committee {
+            partial_key_servers,
+            ..
+        } = &committee.value.server_type
+        else {
+            panic!("Expected a committee key server");
+        };
+        let info = PartialKeyServerInfo {
+            ks_obj_id: Address::from_str(
+                "0x5b4b868b22f4e1e87d3938f29aefc71a1e1ddf7352e214088c9eaf37e31efd31",
+            )
+            .unwrap(),
+            party_id: 0,
+            partial_pk: bcs::from_bytes(&partial_key_servers.0.contents[0].value.partial_pk)
+                .unwrap(),
+        };
+        assert_eq!(
+            info.to_string(),
+            "Party 0 of key server 0x5b4b86..fd31, partial_pk 0xaa56bd..2e57"
+        );
+    }
+}
@@ -0,0 +1,203 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Errors returned when fetching and parsing onchain committee objects, and when checking the
+//! membership, state and keys of a committee.
+
+use std::time::Duration;
+
+use sui_sdk_types::Address;
+use thiserror::Error;
+
+/// Error from fetching or interpreting onchain objects, or from checking the membership, state or
+/// keys of a committee, e.g. [crate::SealCommittee::get_party_id]. Converts into [anyhow::Error]
+/// with `?`, so callers using anyhow can propagate it unchanged and downcast it to inspect the
+/// variant.
+#[derive(Debug, Error)]
+pub enum SealCommitteeError {
+    /// The fullnode has no object with this ID, e.g. a mistyped committee ID.
+    #[error("Object {id} not found")]
+    ObjectNotFound { id: Address },
+    /// A specific version of an object was requested and the fullnode does not have it, e.g. a
+    /// version that was never written or was pruned.
+    #[error("Object {id} not found at version {version}")]
+    VersionNotFound { id: Address, version: u64 },
+    /// The gRPC request failed for a reason other than a missing object.
+    #[error("gRPC request failed: {}", .0.message())]
+    Rpc(#[source] tonic::Status),
+    /// The fullnode did not answer a request for the object within its deadline, see
+    /// [crate::FetchOptions::request_timeout].
+    #[error("Timed out after {elapsed:?} fetching object {id}")]
+    Timeout { id: Address, elapsed: Duration },
+    /// The BCS data of an object or field could not be parsed into the expected type.
+    #[error("Failed to deserialize {context}: {source}")]
+    Deserialization {
+        context: String,
+        #[source]
+        source: bcs::Error,
+    },
+    /// The object is not a Move struct, e.g. a package ID was given instead of an object ID.
+    #[error("Object is not a Move struct in {context}")]
+    WrongObjectType { context: String },
+    /// A value could not be serialized to BCS, e.g. for a digest.
+    #[error("Failed to serialize {context}: {source}")]
+    Serialization {
+        context: String,
+        #[source]
+        source: bcs::Error,
+    },
+    /// The address is not a member of the committee.
+    #[error("Address {address} is not a member of committee {committee_id}")]
+    NotAMember {
+        committee_id: Address,
+        address: Address,
+    },
+    /// The committee is not in the state required for the operation.
+    #[error("Committee {committee_id} is in {actual} state, expected {expected}")]
+    InvalidState {
+        committee_id: Address,
+        expected: &'static str,
+        actual: &'static str,
+    },
+    /// A member has not registered its public keys yet.
+    #[error(
+        "Member {address} is not registered in committee {committee_id}. Do not init DKG before all members register."
+    )]
+    MemberNotRegistered {
+        committee_id: Address,
+        address: Address,
+    },
+    /// The threshold is larger than the number of members.
+    #[error("Threshold {threshold} exceeds the number of members {members}")]
+    ThresholdExceedsMembers { threshold: u16, members: usize },
+    /// The partial public key proposed for a party is missing or malformed.
+    #[error("Missing or invalid partial PK for party {party_id}: {source}")]
+    InvalidPartialKey {
+        party_id: u16,
+        #[source]
+        source: anyhow::Error,
+    },
+    /// The aggregated public key of the committee, as proposed or on its key server, is malformed.
+    #[error("Invalid PK of committee {committee_id}: {source}")]
+    InvalidKey {
+        committee_id: Address,
+        #[source]
+        source: bcs::Error,
+    },
+    /// Any other failure, e.g. reading recorded fixtures.
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl SealCommitteeError {
+    /// Whether the error is a transient gRPC failure or timeout that may succeed on retry.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Rpc(status) => matches!(
+                status.code(),
+                tonic::Code::Unavailable
+                    | tonic::Code::DeadlineExceeded
+                    | tonic::Code::ResourceExhausted
+            ),
+            Self::Timeout { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the object may only be missing because the fullnode has not indexed it yet, e.g.
+    /// right after the transaction creating it. See [crate::wait_for_object].
+    pub fn is_not_indexed(&self) -> bool {
+        match self {
+            Self::ObjectNotFound { .. } => true,
+            Self::Rpc(status) => status.code() == tonic::Code::DataLoss,
+            _ => false,
+        }
+    }
+
+    pub(crate) fn deserialization(context: impl Into<String>, source: bcs::Error) -> Self {
+        Self::Deserialization {
+            context: context.into(),
+            source,
+        }
+    }
+
+    pub(crate) fn serialization(context: impl Into<String>, source: bcs::Error) -> Self {
+        Self::Serialization {
+            context: context.into(),
+            source,
+        }
+    }
+}
+
+/// A structural invariant broken by a committee, as reported by
+/// [crate::SealCommittee::validate]. Such a committee was not created by the Seal package, e.g.
+/// a tampered snapshot or an object of another type.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum ValidationIssue {
+    /// The threshold is 0, so no key could be derived.
+    #[error("Threshold is 0")]
+    ZeroThreshold,
+    /// The threshold is larger than the number of members.
+    #[error("Threshold {threshold} exceeds the number of members {num_members}")]
+    ThresholdExceedsMembers { threshold: u16, num_members: usize },
+    /// The same address is a member more than once.
+    #[error("Member {address} is both party {first} and party {second}")]
+    DuplicateMember {
+        address: Address,
+        first: usize,
+        second: usize,
+    },
+    /// Party IDs are u16, so they cannot cover all members.
+    #[error("{num_members} members exceed the maximum of {}", u16::MAX)]
+    TooManyMembers { num_members: usize },
+    /// A member is missing from the members info of a committee in Init or PostDKG state.
+    #[error("Member {address} (party {party_id}) has no registered keys")]
+    MemberNotRegistered { address: Address, party_id: usize },
+    /// The committee rotates the key of itself.
+    #[error("Committee {id} is its own old committee")]
+    SelfRotation { id: Address },
+}
+
+/// Why the keys of an address do not match its onchain registration, as reported by
+/// [crate::SealCommittee::verify_member_registration].
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum RegistrationMismatch {
+    /// The address is not a member of the committee.
+    #[error("Address {0} is not a member of the committee")]
+    NotAMember(Address),
+    /// The committee no longer stores the registered keys, i.e. it is finalized.
+    #[error("Committee is in {0} state, which has no registered keys")]
+    NoRegistrations(&'static str),
+    /// The member has not registered its public keys yet.
+    #[error("Member {0} is not registered")]
+    NotRegistered(Address),
+    /// Only the ECIES encryption PK differs from the registration.
+    #[error("ECIES PK of {address} does not match its registration: {enc_pk}")]
+    EncPk {
+        address: Address,
+        enc_pk: KeyMismatch,
+    },
+    /// Only the signing PK differs from the registration.
+    #[error("Signing PK of {address} does not match its registration: {signing_pk}")]
+    SigningPk {
+        address: Address,
+        signing_pk: KeyMismatch,
+    },
+    /// Both PKs differ from the registration, e.g. the keys file of another member.
+    #[error(
+        "ECIES and signing PKs of {address} do not match its registration: ECIES PK {enc_pk}, signing PK {signing_pk}"
+    )]
+    BothPks {
+        address: Address,
+        enc_pk: KeyMismatch,
+        signing_pk: KeyMismatch,
+    },
+}
+
+/// A key that differs from its registration, both as hex of the BCS bytes.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[error("given {given}, registered onchain {registered}")]
+pub struct KeyMismatch {
+    pub given: String,
+    pub registered: String,
+}
@@ -0,0 +1,201 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Source of onchain objects for the fetch helpers in [crate::grpc_helper], implemented by the gRPC
+//! client and by [StaticCommitteeFetcher], which serves objects from memory, e.g. in tests or to
+//! work offline from previously fetched objects.
+
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use sui_sdk_types::{
+    Address, Digest, Identifier, MoveStruct, Object, ObjectData, Owner, StructTag,
+};
+
+use crate::errors::SealCommitteeError;
+use crate::grpc_helper::{
+    fetch_committee_data, fetch_key_server_at_version, fetch_key_server_latest, fetch_objects_bcs,
+    fetch_partial_key_server_info,
+};
+use crate::move_types::{KeyServerV2, PartialKeyServerInfoResult, SealCommittee};
+
+/// Fetches committee objects and related onchain data. Only [Self::fetch_object_bcs] must be
+/// implemented, the other methods parse the objects it returns. Methods take `&self`, so one
+/// fetcher can serve concurrent fetches, e.g. from several tasks.
+#[tonic::async_trait]
+pub trait CommitteeFetcher: Send + Sync {
+    /// BCS of the full object, at `version` if given or else at its latest version. Fails with
+    /// [SealCommitteeError::VersionNotFound] if the object has no such version.
+    async fn fetch_object_bcs(
+        &self,
+        object_id: &Address,
+        version: Option<u64>,
+    ) -> Result<Vec<u8>, SealCommitteeError>;
+
+    /// BCS of the latest versions of several objects, one result per ID in the same order, so
+    /// that a missing object only fails its own result. Defaults to [fetch_objects_bcs], the gRPC
+    /// fetchers use a single batch request instead.
+    async fn fetch_objects_bcs(
+        &self,
+        object_ids: &[Address],
+    ) -> Vec<Result<Vec<u8>, SealCommitteeError>> {
+        fetch_objects_bcs(self, object_ids).await
+    }
+
+    /// Latest version of an object, or `None` if it does not exist. Defaults to fetching the full
+    /// object, the gRPC fetchers only request the version.
+    async fn fetch_object_version(
+        &self,
+        object_id: &Address,
+    ) -> Result<Option<u64>, SealCommitteeError> {
+        version_of_fetched(self.fetch_object_bcs(object_id, None).await)
+    }
+
+    /// See [fetch_committee_data].
+    async fn fetch_committee(
+        &self,
+        committee_id: &Address,
+    ) -> Result<SealCommittee, SealCommitteeError> {
+        fetch_committee_data(self, committee_id).await
+    }
+
+    /// Key server object ID and data of a committee, at the given version of the key server or
+    /// else at its latest version.
+    async fn fetch_key_server(
+        &self,
+        committee_id: &Address,
+        version: Option<u64>,
+    ) -> Result<(Address, KeyServerV2), SealCommitteeError> {
+        match version {
+            Some(version) => fetch_key_server_at_version(self, committee_id, version).await,
+            None => fetch_key_server_latest(self, committee_id).await,
+        }
+    }
+
+    /// See [fetch_partial_key_server_info].
+    async fn fetch_partial_key_server_info(
+        &self,
+        committee_id: &Address,
+    ) -> Result<PartialKeyServerInfoResult, SealCommitteeError> {
+        fetch_partial_key_server_info(self, committee_id).await
+    }
+}
+
+/// [CommitteeFetcher] serving the objects it was given from memory. All versions of an object are
+/// kept, and the highest one is returned if no version is requested.
+#[derive(Clone, Debug, Default)]
+pub struct StaticCommitteeFetcher {
+    objects: HashMap<Address, BTreeMap<u64, Vec<u8>>>,
+}
+
+impl StaticCommitteeFetcher {
+    /// Add an object with the given BCS bytes at `version`.
+    pub fn with_object_bcs(mut self, object_id: Address, version: u64, bcs: Vec<u8>) -> Self {
+        self.objects
+            .entry(object_id)
+            .or_default()
+            .insert(version, bcs);
+        self
+    }
+
+    /// Add a Move object with the given struct contents at version 1, e.g. a recorded fixture.
+    /// The contents must start with the object ID.
+    pub fn with_move_object(self, object_id: Address, contents: Vec<u8>) -> Result<Self> {
+        let bcs = move_object_bcs(object_id, 1, contents)?;
+        Ok(self.with_object_bcs(object_id, 1, bcs))
+    }
+}
+
+#[tonic::async_trait]
+impl CommitteeFetcher for StaticCommitteeFetcher {
+    async fn fetch_object_bcs(
+        &self,
+        object_id: &Address,
+        version: Option<u64>,
+    ) -> Result<Vec<u8>, SealCommitteeError> {
+        let versions = self
+            .objects
+            .get(object_id)
+            .ok_or(SealCommitteeError::ObjectNotFound { id: *object_id })?;
+        let bcs = match version {
+            Some(version) => versions
+                .get(&version)
+                .ok_or(SealCommitteeError::VersionNotFound {
+                    id: *object_id,
+                    version,
+                })?,
+            // Objects are only added with a version.
+            None => versions.values().next_back().unwrap(),
+        };
+        Ok(bcs.clone())
+    }
+}
+
+/// Version of a fetched object given its BCS, or `None` if it was not found.
+pub(crate) fn version_of_fetched(
+    fetched: Result<Vec<u8>, SealCommitteeError>,
+) -> Result<Option<u64>, SealCommitteeError> {
+    match fetched {
+        Ok(bcs_bytes) => {
+            let object: Object = bcs::from_bytes(&bcs_bytes)
+                .map_err(|e| SealCommitteeError::deserialization("Object", e))?;
+            Ok(Some(object.version()))
+        }
+        Err(SealCommitteeError::ObjectNotFound { .. }) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// BCS of a Move object with the given struct contents. The object type is not read by the fetch
+/// helpers, so all such objects share one.
+pub(crate) fn move_object_bcs(
+    object_id: Address,
+    version: u64,
+    contents: Vec<u8>,
+) -> Result<Vec<u8>> {
+    let type_tag = StructTag {
+        address: Address::ZERO,
+        module: Identifier::from_str("mock").unwrap(),
+        name: Identifier::from_str("Object").unwrap(),
+        type_params: vec![],
+    };
+    let move_struct = MoveStruct::new(type_tag, false, version, contents)
+        .ok_or_else(|| anyhow!("Contents of object {object_id} are too short"))?;
+    let object = Object::new(
+        ObjectData::Struct(move_struct),
+        Owner::Immutable,
+        Digest::ZERO,
+        0,
+    );
+    Ok(bcs::to_bytes(&object)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_static_fetcher_versions() {
+        let object_id = Address::new([1; 32]);
+        let fetcher = StaticCommitteeFetcher::default()
+            .with_object_bcs(object_id, 1, vec![1])
+            .with_object_bcs(object_id, 3, vec![3]);
+        assert_eq!(
+            fetcher.fetch_object_bcs(&object_id, None).await.unwrap(),
+            [3]
+        );
+        assert_eq!(
+            fetcher.fetch_object_bcs(&object_id, Some(1)).await.unwrap(),
+            [1]
+        );
+        assert!(matches!(
+            fetcher.fetch_object_bcs(&object_id, Some(2)).await,
+            Err(SealCommitteeError::VersionNotFound { id, version: 2 }) if id == object_id
+        ));
+        assert!(matches!(
+            fetcher.fetch_object_bcs(&Address::ZERO, None).await,
+            Err(SealCommitteeError::ObjectNotFound { .. })
+        ));
+    }
+}
@@ -4,74 +4,1067 @@
 //! gRPC utilities for interacting with Sui blockchain.
 
 use std::collections::HashMap;
+use std::future::Future;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
 
 use crate::{
-    move_types::{Field, KeyServerV2, PartialKeyServerInfo, SealCommittee, ServerType, Wrapper},
-    Network,
+    errors::SealCommitteeError,
+    fetcher::{version_of_fetched, CommitteeFetcher},
+    move_types::{
+        CommitteeState, Field, KeyServer, KeyServerV2, MemberUrl, PartialKeyServerInfoResult,
+        SealCommittee, Wrapper,
+    },
+    recording::{FixtureMode, RecordedObject},
+    Network, NetworkConfig,
 };
 use anyhow::{anyhow, Result};
+use fastcrypto::groups::bls12381::G2Element;
+use fastcrypto::hash::{Blake2b256, HashFunction};
+use futures::future::join_all;
+use futures::stream::{self, StreamExt};
 use sui_rpc::client::v2::Client;
-use sui_sdk_types::{Address, Object, StructTag, TypeTag};
+use sui_rpc::client::HeadersInterceptor;
+use sui_rpc::proto::sui::rpc::v2::{
+    self as proto, get_object_result, BatchGetObjectsRequest, GetObjectRequest,
+};
+use sui_sdk_types::{Address, Digest, Object, StructTag, TypeTag};
+use tonic::metadata::MetadataKey;
+use tonic::transport::{Certificate, ClientTlsConfig, Endpoint};
 
 pub(crate) const EXPECTED_KEY_SERVER_VERSION: u64 = 2;
 
-/// Create gRPC client for a given network.
+/// Sui Clock object, exists on every network.
+
+/// Default deadline of a single gRPC request, see [GrpcClientConfig::request_timeout].
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Maximum number of requests in flight in [fetch_objects_bcs].
+const MAX_CONCURRENT_FETCHES: usize = 8;
+
+/// Timeouts, keep-alive, TLS and header settings of the channel to the fullnode.
+#[derive(Clone, PartialEq)]
+pub struct GrpcClientConfig {
+    /// Maximum time to establish the connection.
+    pub connect_timeout: Duration,
+    /// Deadline of every request on the channel. Object fetches also set their own deadline,
+    /// see [FetchOptions::request_timeout].
+    pub request_timeout: Duration,
+    /// Interval of HTTP/2 pings, so that a dead connection is detected while waiting.
+    pub keep_alive_interval: Duration,
+    /// Time to wait for a ping to be acknowledged before the connection is closed.
+    pub keep_alive_timeout: Duration,
+    /// Value of the `authorization` header sent with every request, e.g. `Bearer <token>`.
+    pub auth_header: Option<String>,
+    /// Other headers sent with every request, as name and value.
+    pub extra_headers: Vec<(String, String)>,
+    /// PEM of a CA certificate trusted in addition to the public roots, e.g. of a provider with
+    /// a private CA. Requires an https URL.
+    pub tls_ca_cert: Option<Vec<u8>>,
+    /// Name to verify the server certificate against instead of the host of the URL.
+    pub domain_override: Option<String>,
+}
+
+impl Default for GrpcClientConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(10),
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            keep_alive_interval: Duration::from_secs(30),
+            keep_alive_timeout: Duration::from_secs(10),
+            auth_header: None,
+            extra_headers: vec![],
+            tls_ca_cert: None,
+            domain_override: None,
+        }
+    }
+}
+
+/// Leaves out header values, which usually contain credentials.
+impl std::fmt::Debug for GrpcClientConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GrpcClientConfig")
+            .field("connect_timeout", &self.connect_timeout)
+            .field("request_timeout", &self.request_timeout)
+            .field("keep_alive_interval", &self.keep_alive_interval)
+            .field("keep_alive_timeout", &self.keep_alive_timeout)
+            .field(
+                "auth_header",
+                &self.auth_header.as_ref().map(|_| "<redacted>"),
+            )
+            .field(
+                "extra_headers",
+                &self
+                    .extra_headers
+                    .iter()
+                    .map(|(name, _)| name)
+                    .collect::<Vec<_>>(),
+            )
+            .field("tls_ca_cert", &self.tls_ca_cert.is_some())
+            .field("domain_override", &self.domain_override)
+            .finish()
+    }
+}
+
+impl GrpcClientConfig {
+    /// Trust the CA certificate in the given PEM file, see [Self::tls_ca_cert].
+    pub fn with_ca_cert_file(mut self, path: &Path) -> Result<Self> {
+        let pem = std::fs::read(path)
+            .map_err(|e| anyhow!("Failed to read CA certificate {}: {}", path.display(), e))?;
+        if !String::from_utf8_lossy(&pem).contains("-----BEGIN CERTIFICATE-----") {
+            return Err(anyhow!("No PEM certificate found in {}", path.display()));
+        }
+        self.tls_ca_cert = Some(pem);
+        Ok(self)
+    }
+
+    /// Headers sent with every request through the header interceptor of the client.
+    fn headers(&self) -> Result<HeadersInterceptor> {
+        let mut interceptor = HeadersInterceptor::new();
+        let headers = interceptor.headers_mut();
+        if let Some(auth_header) = &self.auth_header {
+            headers.insert(
+                "authorization",
+                auth_header
+                    .parse()
+                    .map_err(|_| anyhow!("Invalid authorization header value"))?,
+            );
+        }
+        for (name, value) in &self.extra_headers {
+            let key =
+                MetadataKey::from_str(name).map_err(|_| anyhow!("Invalid header name {name:?}"))?;
+            let value = value
+                .parse()
+                .map_err(|_| anyhow!("Invalid value of header {name:?}"))?;
+            headers.insert(key, value);
+        }
+        Ok(interceptor)
+    }
+}
+
+/// Create gRPC client for a given network with the default [GrpcClientConfig].
 pub fn create_grpc_client(network: &Network) -> Result<Client> {
-    let rpc_url = match network {
-        Network::Mainnet => Client::MAINNET_FULLNODE,
-        Network::Testnet => Client::TESTNET_FULLNODE,
-    };
-    Ok(Client::new(rpc_url)?)
+    create_grpc_client_with_url(&network.node_url())
+}
+
+/// Create gRPC client for the fullnode of a [NetworkConfig], with its request timeout.
+pub fn create_grpc_client_for_network(config: &NetworkConfig) -> Result<Client> {
+    create_grpc_client_with_config(&config.grpc_url, &config.grpc_client_config())
+}
+
+/// Create gRPC client for a fullnode at the given URL, e.g. a local mock in tests.
+pub fn create_grpc_client_with_url(url: &str) -> Result<Client> {
+    create_grpc_client_with_config(url, &GrpcClientConfig::default())
+}
+
+/// Create gRPC client for a fullnode at the given URL with the given settings. The connection is
+/// established on the first request.
+pub fn create_grpc_client_with_config(url: &str, config: &GrpcClientConfig) -> Result<Client> {
+    let mut endpoint = Endpoint::from_shared(url.to_string())?
+        .connect_timeout(config.connect_timeout)
+        .timeout(config.request_timeout)
+        .http2_keep_alive_interval(config.keep_alive_interval)
+        .keep_alive_timeout(config.keep_alive_timeout)
+        .keep_alive_while_idle(true);
+    if endpoint.uri().scheme_str() == Some("https") {
+        let mut tls = ClientTlsConfig::new().with_enabled_roots();
+        if let Some(pem) = &config.tls_ca_cert {
+            tls = tls.ca_certificate(Certificate::from_pem(pem));
+        }
+        if let Some(domain) = &config.domain_override {
+            tls = tls.domain_name(domain);
+        }
+        endpoint = endpoint.tls_config(tls)?;
+    } else if config.tls_ca_cert.is_some() || config.domain_override.is_some() {
+        return Err(anyhow!("TLS settings require an https URL, got {url}"));
+    }
+    Ok(Client::from_endpoint(&endpoint).with_headers(config.headers()?))
+}
+
+/// Check connectivity to a fullnode by fetching the committee object through `fetcher`, e.g. a
+/// client with the auth header and CA certificate of the ceremony, so that these are checked too.
+/// Returns the committee and the round-trip time, including connection setup.
+pub async fn ping_committee_node<F: CommitteeFetcher + ?Sized>(
+    fetcher: &F,
+    committee_id: &Address,
+    timeout: Duration,
+) -> Result<(SealCommittee, Duration)> {
+    let start = Instant::now();
+    let committee = tokio::time::timeout(timeout, fetch_committee_data(fetcher, committee_id))
+        .await
+        .map_err(|_| anyhow!("Timed out after {timeout:?} fetching committee {committee_id}"))??;
+    Ok((committee, start.elapsed()))
+}
+
+/// Health of a committee member node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HealthStatus {
+    Healthy { latency_ms: u64 },
+    Unreachable { error: String },
+}
+
+/// Checks availability of the key servers of committee members at their registered URLs.
+pub struct GrpcHealthChecker;
+
+impl GrpcHealthChecker {
+    /// Probe the health endpoint of every committee member's key server concurrently. The URLs
+    /// are those registered in the committee before DKG, and those of the partial key servers
+    /// fetched with `fetcher` once the committee is finalized. Members without a URL are reported
+    /// as unreachable. Fails if the URLs cannot be read, e.g. a URL is not an http(s) URL.
+    pub async fn check_all<F: CommitteeFetcher + ?Sized>(
+        fetcher: &F,
+        committee: &SealCommittee,
+        timeout: Duration,
+    ) -> Result<HashMap<Address, HealthStatus>, SealCommitteeError> {
+        let urls = match &committee.state {
+            CommitteeState::Finalized => get_key_server_urls(fetcher, &committee.id).await?,
+            CommitteeState::Init { .. } | CommitteeState::PostDKG { .. } => {
+                committee.member_urls()?
+            }
+        };
+        let http_client = reqwest::Client::new();
+        let checks = committee.members.iter().map(|member| {
+            let url = urls.get(member).map(|member_url| member_url.url.as_str());
+            let http_client = &http_client;
+            async move {
+                let status = match url {
+                    Some(url) => Self::check(http_client, url, timeout).await,
+                    None => HealthStatus::Unreachable {
+                        error: "No registered URL".to_string(),
+                    },
+                };
+                (*member, status)
+            }
+        });
+        Ok(join_all(checks).await.into_iter().collect())
+    }
+
+    /// Only a successful response of the key server's `/health` endpoint means the node is up,
+    /// not any response at the URL, e.g. of a proxy in front of a key server that is down.
+    async fn check(http_client: &reqwest::Client, url: &str, timeout: Duration) -> HealthStatus {
+        let start = Instant::now();
+        let request = async {
+            http_client
+                .get(format!("{}/health", url.trim_end_matches('/')))
+                .send()
+                .await?
+                .error_for_status()?;
+            Ok::<_, anyhow::Error>(())
+        };
+        match tokio::time::timeout(timeout, request).await {
+            Ok(Ok(())) => HealthStatus::Healthy {
+                latency_ms: start.elapsed().as_millis() as u64,
+            },
+            Ok(Err(e)) => HealthStatus::Unreachable {
+                error: e.to_string(),
+            },
+            Err(_) => HealthStatus::Unreachable {
+                error: format!("Timed out after {timeout:?}"),
+            },
+        }
+    }
 }
 
 /// Fetch an object's BCS data and deserialize as type T.
-async fn fetch_and_deserialize_move_object<T: serde::de::DeserializeOwned>(
-    grpc_client: &mut Client,
+async fn fetch_and_deserialize_move_object<T, F>(
+    fetcher: &F,
     object_id: &Address,
     error_context: &str,
-) -> Result<T> {
+) -> Result<T, SealCommitteeError>
+where
+    T: serde::de::DeserializeOwned,
+    F: CommitteeFetcher + ?Sized,
+{
+    fetch_and_deserialize_move_object_at_version(fetcher, object_id, None, error_context).await
+}
+
+/// Fetch an object's BCS data at `version`, or the latest version if `None`, and deserialize as
+/// type T, e.g. to audit a historical state of a committee. A missing version is
+/// [SealCommitteeError::VersionNotFound]. See [fetch_move_object_at_version] to also get the
+/// version and digest read.
+pub async fn fetch_and_deserialize_move_object_at_version<T, F>(
+    fetcher: &F,
+    object_id: &Address,
+    version: Option<u64>,
+    error_context: &str,
+) -> Result<T, SealCommitteeError>
+where
+    T: serde::de::DeserializeOwned,
+    F: CommitteeFetcher + ?Sized,
+{
+    let bcs_bytes = fetcher.fetch_object_bcs(object_id, version).await?;
+    deserialize_move_object(&bcs_bytes, error_context)
+}
+
+/// Retry policy for transient gRPC failures when fetching objects, see
+/// [SealCommitteeError::is_retryable]. Missing objects and invalid data are never retried.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FetchOptions {
+    /// Total number of attempts, including the first one.
+    pub max_attempts: u32,
+    /// Delay before the first retry.
+    pub initial_delay: Duration,
+    /// Factor by which the delay grows after each retry.
+    pub multiplier: f64,
+    /// Up to this fraction of the delay is added at random, e.g. 0.2 for up to 20%, so that
+    /// concurrent clients do not retry in lockstep.
+    pub jitter: f64,
+    /// Deadline of each attempt, sent to the fullnode with the request. An attempt exceeding it
+    /// fails with [SealCommitteeError::Timeout]. No deadline if `None`.
+    pub request_timeout: Option<Duration>,
+}
+
+impl Default for FetchOptions {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            initial_delay: Duration::from_millis(250),
+            multiplier: 2.0,
+            jitter: 0.2,
+            request_timeout: Some(DEFAULT_REQUEST_TIMEOUT),
+        }
+    }
+}
+
+impl FetchOptions {
+    /// Make a single attempt.
+    pub fn no_retry() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Delay before the given retry, starting from 1, without jitter.
+    fn base_delay(&self, retry: u32) -> Duration {
+        self.initial_delay
+            .mul_f64(self.multiplier.powi(retry.saturating_sub(1) as i32))
+    }
+
+    fn delay(&self, retry: u32) -> Duration {
+        let base = self.base_delay(retry);
+        base + base.mul_f64(self.jitter * rand::random::<f64>())
+    }
+}
+
+/// gRPC client fetching with the given [FetchOptions]. The client itself implements
+/// [CommitteeFetcher] with the default options.
+pub struct GrpcFetcher {
+    client: Client,
+    options: FetchOptions,
+}
+
+impl GrpcFetcher {
+    pub fn new(client: Client, options: FetchOptions) -> Self {
+        Self { client, options }
+    }
+}
+
+#[tonic::async_trait]
+impl CommitteeFetcher for GrpcFetcher {
+    async fn fetch_object_bcs(
+        &self,
+        object_id: &Address,
+        version: Option<u64>,
+    ) -> Result<Vec<u8>, SealCommitteeError> {
+        fetch_from_fullnode(&self.client, object_id, version, &self.options).await
+    }
+
+    async fn fetch_object_version(
+        &self,
+        object_id: &Address,
+    ) -> Result<Option<u64>, SealCommitteeError> {
+        version_from_fullnode(&self.client, object_id, &self.options).await
+    }
+
+    async fn fetch_objects_bcs(
+        &self,
+        object_ids: &[Address],
+    ) -> Vec<Result<Vec<u8>, SealCommitteeError>> {
+        match batch_fetch_from_fullnode(&self.client, object_ids, &self.options).await {
+            Some(results) => results,
+            None => fetch_objects_bcs(self, object_ids).await,
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl CommitteeFetcher for Client {
+    async fn fetch_object_bcs(
+        &self,
+        object_id: &Address,
+        version: Option<u64>,
+    ) -> Result<Vec<u8>, SealCommitteeError> {
+        fetch_from_fullnode(self, object_id, version, &FetchOptions::default()).await
+    }
+
+    async fn fetch_object_version(
+        &self,
+        object_id: &Address,
+    ) -> Result<Option<u64>, SealCommitteeError> {
+        fetch_object_version(self, object_id).await
+    }
+
+    async fn fetch_objects_bcs(
+        &self,
+        object_ids: &[Address],
+    ) -> Vec<Result<Vec<u8>, SealCommitteeError>> {
+        match batch_fetch_from_fullnode(self, object_ids, &FetchOptions::default()).await {
+            Some(results) => results,
+            None => fetch_objects_bcs(self, object_ids).await,
+        }
+    }
+}
+
+/// Fetch from the fullnode, recorded or replayed as set by [FixtureMode::from_env]. Only latest
+/// versions are recorded, and a replayed object must match the requested version.
+async fn fetch_from_fullnode(
+    grpc_client: &Client,
+    object_id: &Address,
+    version: Option<u64>,
+    options: &FetchOptions,
+) -> Result<Vec<u8>, SealCommitteeError> {
+    let mode = FixtureMode::from_env()?;
+    if let FixtureMode::Replay(dir) = &mode {
+        let recorded = RecordedObject::read(dir, object_id)?;
+        if let Some(version) = version.filter(|version| recorded.version != Some(*version)) {
+            return Err(SealCommitteeError::VersionNotFound {
+                id: *object_id,
+                version,
+            });
+        }
+        return Ok(recorded.bcs_bytes()?);
+    }
+
+    let (bcs_bytes, object_version) = retry_transient(options, &object_id.to_string(), || {
+        get_object_bcs(grpc_client, object_id, version, options.request_timeout)
+    })
+    .await?;
+    if let (FixtureMode::Record(dir), None) = (&mode, version) {
+        RecordedObject::new(*object_id, object_version, &bcs_bytes).write(dir)?;
+    }
+    Ok(bcs_bytes)
+}
+
+/// Run `attempt` until it succeeds, fails with an error that is not retryable or
+/// `options.max_attempts` is reached, waiting between attempts as given by `options`.
+async fn retry_transient<T, Fut>(
+    options: &FetchOptions,
+    objects: &str,
+    mut attempt: impl FnMut() -> Fut,
+) -> Result<T, SealCommitteeError>
+where
+    Fut: Future<Output = Result<T, SealCommitteeError>>,
+{
+    let mut attempts = 1;
+    loop {
+        match attempt().await {
+            Err(e) if e.is_retryable() && attempts < options.max_attempts => {
+                let delay = options.delay(attempts);
+                tracing::warn!(
+                    objects,
+                    attempt = attempts,
+                    max_attempts = options.max_attempts,
+                    ?delay,
+                    "Fetching object failed, retrying: {e}"
+                );
+                tokio::time::sleep(delay).await;
+                attempts += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Fetch the latest versions of several objects from the fullnode with one `BatchGetObjects`
+/// request, retried as a whole on transient failures. Failures of single objects are returned
+/// in their results and not retried. Returns `None` if the fullnode does not support batching,
+/// and when recording or replaying fixtures, which is done per object.
+async fn batch_fetch_from_fullnode(
+    grpc_client: &Client,
+    object_ids: &[Address],
+    options: &FetchOptions,
+) -> Option<Vec<Result<Vec<u8>, SealCommitteeError>>> {
+    if !matches!(FixtureMode::from_env(), Ok(FixtureMode::Live)) {
+        return None;
+    }
+    if object_ids.is_empty() {
+        return Some(vec![]);
+    }
+    let result = retry_transient(options, &format!("{} objects", object_ids.len()), || {
+        batch_get_objects_bcs(grpc_client, object_ids, options.request_timeout)
+    })
+    .await;
+    match result {
+        Ok(results) => Some(results),
+        Err(SealCommitteeError::Rpc(status)) if status.code() == tonic::Code::Unimplemented => None,
+        // The whole batch failed, so every object failed the same way.
+        Err(e) => Some(
+            object_ids
+                .iter()
+                .map(|object_id| {
+                    Err(match &e {
+                        SealCommitteeError::Rpc(status) => SealCommitteeError::Rpc(status.clone()),
+                        SealCommitteeError::Timeout { elapsed, .. } => {
+                            SealCommitteeError::Timeout {
+                                id: *object_id,
+                                elapsed: *elapsed,
+                            }
+                        }
+                        e => SealCommitteeError::Other(anyhow!("{e}")),
+                    })
+                })
+                .collect(),
+        ),
+    }
+}
+
+/// One `BatchGetObjects` request for the BCS data of the given objects, failing with
+/// [SealCommitteeError::Timeout] for the first object if it takes longer than `timeout`.
+async fn batch_get_objects_bcs(
+    grpc_client: &Client,
+    object_ids: &[Address],
+    timeout: Option<Duration>,
+) -> Result<Vec<Result<Vec<u8>, SealCommitteeError>>, SealCommitteeError> {
+    let mut grpc_client = grpc_client.clone();
     let mut ledger_client = grpc_client.ledger_client();
-    let mut request = sui_rpc::proto::sui::rpc::v2::GetObjectRequest::default();
-    request.object_id = Some(object_id.to_string());
+    let mut request = BatchGetObjectsRequest::default();
+    request.requests = object_ids
+        .iter()
+        .map(|object_id| {
+            let mut request = GetObjectRequest::default();
+            request.object_id = Some(object_id.to_string());
+            request
+        })
+        .collect();
     request.read_mask = Some(prost_types::FieldMask {
         paths: vec!["bcs".to_string()],
     });
+    let mut request = tonic::Request::new(request);
+    if let Some(timeout) = timeout {
+        request.set_timeout(timeout);
+    }
 
-    let response = ledger_client
-        .get_object(request)
+    let start = Instant::now();
+    let response = match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, ledger_client.batch_get_objects(request))
+            .await
+            .map_err(|_| SealCommitteeError::Timeout {
+                id: object_ids[0],
+                elapsed: start.elapsed(),
+            })?,
+        None => ledger_client.batch_get_objects(request).await,
+    };
+    let results = response
+        .map_err(SealCommitteeError::Rpc)?
+        .into_inner()
+        .objects;
+    if results.len() != object_ids.len() {
+        return Err(SealCommitteeError::Rpc(tonic::Status::data_loss(format!(
+            "Expected {} objects in batch response, got {}",
+            object_ids.len(),
+            results.len()
+        ))));
+    }
+    Ok(object_ids
+        .iter()
+        .zip(results)
+        .map(|(object_id, result)| match result.result {
+            Some(get_object_result::Result::Object(object)) => object
+                .bcs
+                .and_then(|bcs| bcs.value)
+                .map(|bytes| bytes.to_vec())
+                .ok_or_else(|| {
+                    SealCommitteeError::Rpc(tonic::Status::data_loss(format!(
+                        "No BCS data in object {object_id}"
+                    )))
+                }),
+            Some(get_object_result::Result::Error(status)) => {
+                Err(match tonic::Code::from_i32(status.code) {
+                    tonic::Code::NotFound => SealCommitteeError::ObjectNotFound { id: *object_id },
+                    code => SealCommitteeError::Rpc(tonic::Status::new(code, status.message)),
+                })
+            }
+            None => Err(SealCommitteeError::Rpc(tonic::Status::data_loss(format!(
+                "No result for object {object_id}"
+            )))),
+        })
+        .collect())
+}
+
+/// Fetch the latest versions of several objects with [CommitteeFetcher::fetch_object_bcs], at most
+/// [MAX_CONCURRENT_FETCHES] at a time, one result per ID in the same order. This is the default of
+/// [CommitteeFetcher::fetch_objects_bcs], also used by the gRPC fetchers if the fullnode does not
+/// support batching.
+pub async fn fetch_objects_bcs<F: CommitteeFetcher + ?Sized>(
+    fetcher: &F,
+    object_ids: &[Address],
+) -> Vec<Result<Vec<u8>, SealCommitteeError>> {
+    stream::iter(object_ids)
+        .map(|object_id| fetcher.fetch_object_bcs(object_id, None))
+        .buffered(MAX_CONCURRENT_FETCHES)
+        .collect()
         .await
-        .map(|r| r.into_inner())?;
+}
 
-    let bcs_bytes = response
-        .object
-        .and_then(|obj| obj.bcs)
+/// Fetch an object's BCS data and version from the fullnode, failing with
+/// [SealCommitteeError::Timeout] if it takes longer than `timeout`.
+async fn get_object_bcs(
+    grpc_client: &Client,
+    object_id: &Address,
+    version: Option<u64>,
+    timeout: Option<Duration>,
+) -> Result<(Vec<u8>, Option<u64>), SealCommitteeError> {
+    let object = get_object(
+        grpc_client,
+        object_id,
+        version,
+        &["bcs", "version"],
+        timeout,
+    )
+    .await?;
+    let object_version = object.version;
+    let bcs_bytes = object
+        .bcs
         .and_then(|bcs| bcs.value)
         .map(|bytes| bytes.to_vec())
-        .ok_or_else(|| anyhow!("No BCS data in {}", error_context))?;
+        .ok_or_else(|| {
+            SealCommitteeError::Rpc(tonic::Status::data_loss(format!(
+                "No BCS data in object {object_id}"
+            )))
+        })?;
+    Ok((bcs_bytes, object_version))
+}
+
+/// Fetch only the latest version of an object from the fullnode.
+async fn get_object_version(
+    grpc_client: &Client,
+    object_id: &Address,
+    timeout: Option<Duration>,
+) -> Result<u64, SealCommitteeError> {
+    get_object(grpc_client, object_id, None, &["version"], timeout)
+        .await?
+        .version
+        .ok_or_else(|| {
+            SealCommitteeError::Rpc(tonic::Status::data_loss(format!(
+                "No version in object {object_id}"
+            )))
+        })
+}
+
+/// Fetch the fields of an object given by `read_mask` from the fullnode, failing with
+/// [SealCommitteeError::Timeout] if it takes longer than `timeout`. If a version is given, the
+/// fullnode does not tell whether the object or only the version is missing, so both fail with
+/// [SealCommitteeError::VersionNotFound].
+async fn get_object(
+    grpc_client: &Client,
+    object_id: &Address,
+    version: Option<u64>,
+    read_mask: &[&str],
+    timeout: Option<Duration>,
+) -> Result<proto::Object, SealCommitteeError> {
+    // Clones share the underlying channel, so concurrent fetches do not need exclusive access.
+    let mut grpc_client = grpc_client.clone();
+    let mut ledger_client = grpc_client.ledger_client();
+    let mut request = GetObjectRequest::default();
+    request.object_id = Some(object_id.to_string());
+    request.version = version;
+    request.read_mask = Some(prost_types::FieldMask {
+        paths: read_mask.iter().map(|path| path.to_string()).collect(),
+    });
+    let mut request = tonic::Request::new(request);
+    if let Some(timeout) = timeout {
+        request.set_timeout(timeout);
+    }
+
+    // The deadline is also enforced locally, as the fullnode may not honor it.
+    let start = Instant::now();
+    let response = match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, ledger_client.get_object(request))
+            .await
+            .map_err(|_| SealCommitteeError::Timeout {
+                id: *object_id,
+                elapsed: start.elapsed(),
+            })?,
+        None => ledger_client.get_object(request).await,
+    };
+    response
+        .map_err(|status| match (status.code(), version) {
+            (tonic::Code::NotFound, None) => SealCommitteeError::ObjectNotFound { id: *object_id },
+            (tonic::Code::NotFound, Some(version)) => SealCommitteeError::VersionNotFound {
+                id: *object_id,
+                version,
+            },
+            _ => SealCommitteeError::Rpc(status),
+        })?
+        .into_inner()
+        .object
+        .ok_or_else(|| {
+            SealCommitteeError::Rpc(tonic::Status::data_loss(format!(
+                "No object {object_id} in response"
+            )))
+        })
+}
+
+/// Latest version of an object, or `None` if it does not exist. Only the version is requested
+/// from the fullnode, so this is much cheaper than fetching the object, e.g. to detect changes.
+pub async fn fetch_object_version(
+    grpc_client: &Client,
+    object_id: &Address,
+) -> Result<Option<u64>, SealCommitteeError> {
+    version_from_fullnode(grpc_client, object_id, &FetchOptions::default()).await
+}
+
+/// See [fetch_object_version]. When recording or replaying fixtures, the full object is fetched
+/// instead.
+async fn version_from_fullnode(
+    grpc_client: &Client,
+    object_id: &Address,
+    options: &FetchOptions,
+) -> Result<Option<u64>, SealCommitteeError> {
+    if !matches!(FixtureMode::from_env()?, FixtureMode::Live) {
+        return version_of_fetched(
+            fetch_from_fullnode(grpc_client, object_id, None, options).await,
+        );
+    }
+    let result = retry_transient(options, &object_id.to_string(), || {
+        get_object_version(grpc_client, object_id, options.request_timeout)
+    })
+    .await;
+    match result {
+        Ok(version) => Ok(Some(version)),
+        Err(SealCommitteeError::ObjectNotFound { .. }) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Fetch the BCS bytes of an object's Move struct contents, as deserialized into the types in
+/// [crate::move_types]. Used to capture test fixtures.
+pub async fn fetch_move_object_contents<F: CommitteeFetcher + ?Sized>(
+    fetcher: &F,
+    object_id: &Address,
+) -> Result<Vec<u8>, SealCommitteeError> {
+    let context = format!("object {object_id}");
+    let bcs_bytes = fetcher.fetch_object_bcs(object_id, None).await?;
+    let obj: Object = bcs::from_bytes(&bcs_bytes)
+        .map_err(|e| SealCommitteeError::deserialization(context.clone(), e))?;
+    let move_object = obj
+        .as_struct()
+        .ok_or(SealCommitteeError::WrongObjectType { context })?;
+    Ok(move_object.contents().to_vec())
+}
+
+/// Deserialize an object's BCS data and its Move struct contents as type T.
+pub fn deserialize_move_object<T: serde::de::DeserializeOwned>(
+    bcs_bytes: &[u8],
+    error_context: &str,
+) -> Result<T, SealCommitteeError> {
+    Ok(parse_move_object(bcs_bytes, error_context)?.1)
+}
 
-    let obj: Object = bcs::from_bytes(&bcs_bytes)?;
+/// The object of the given BCS data and its Move struct contents as type T.
+pub(crate) fn parse_move_object<T: serde::de::DeserializeOwned>(
+    bcs_bytes: &[u8],
+    error_context: &str,
+) -> Result<(Object, T), SealCommitteeError> {
+    let obj: Object = bcs::from_bytes(bcs_bytes)
+        .map_err(|e| SealCommitteeError::deserialization(error_context, e))?;
     let move_object = obj
         .as_struct()
-        .ok_or_else(|| anyhow!("Object is not a Move struct in {}", error_context))?;
-    bcs::from_bytes(move_object.contents())
-        .map_err(|e| anyhow!("Failed to deserialize {}: {}", error_context, e))
+        .ok_or_else(|| SealCommitteeError::WrongObjectType {
+            context: error_context.to_string(),
+        })?;
+    let value = bcs::from_bytes(move_object.contents())
+        .map_err(|e| SealCommitteeError::deserialization(error_context, e))?;
+    Ok((obj, value))
 }
 
 /// Fetch seal Committee object onchain.
-pub async fn fetch_committee_data(
-    grpc_client: &mut Client,
+pub async fn fetch_committee_data<F: CommitteeFetcher + ?Sized>(
+    fetcher: &F,
     committee_id: &Address,
-) -> Result<SealCommittee> {
-    fetch_and_deserialize_move_object(grpc_client, committee_id, "Committee object").await
+) -> Result<SealCommittee, SealCommitteeError> {
+    fetch_and_deserialize_move_object(fetcher, committee_id, "Committee object").await
+}
+
+/// Contents of an object as read at a specific version, with the version and digest of the
+/// object to record where the contents came from, e.g. in an audit or to pin a ceremony.
+#[derive(Clone, Debug)]
+pub struct VersionedObject<T> {
+    pub value: T,
+    pub version: u64,
+    pub digest: Digest,
+}
+
+/// Fetch an object at the given version and deserialize its Move struct contents as type T.
+/// Fails with [SealCommitteeError::VersionNotFound] if the object does not exist at that version.
+pub async fn fetch_move_object_at_version<T, F>(
+    fetcher: &F,
+    object_id: &Address,
+    version: u64,
+    error_context: &str,
+) -> Result<VersionedObject<T>, SealCommitteeError>
+where
+    T: serde::de::DeserializeOwned,
+    F: CommitteeFetcher + ?Sized,
+{
+    let bcs_bytes = fetcher.fetch_object_bcs(object_id, Some(version)).await?;
+    let (object, value) = parse_move_object(&bcs_bytes, error_context)?;
+    Ok(VersionedObject {
+        value,
+        version: object.version(),
+        digest: object_digest(&bcs_bytes),
+    })
+}
+
+/// Fetch a Committee object as it was at the given object version, e.g. its PostDKG state after
+/// it was finalized.
+pub async fn fetch_committee_data_at_version<F: CommitteeFetcher + ?Sized>(
+    fetcher: &F,
+    committee_id: &Address,
+    version: u64,
+) -> Result<VersionedObject<SealCommittee>, SealCommitteeError> {
+    fetch_move_object_at_version(fetcher, committee_id, version, "Committee object").await
+}
+
+/// Fetch the KeyServerV2 data of a KeyServer object as it was at the given version of its data
+/// field object. This is the version of the onchain object, not the key server version of
+/// [fetch_key_server_at_version].
+pub async fn fetch_key_server_v2_at_version<F: CommitteeFetcher + ?Sized>(
+    fetcher: &F,
+    ks_obj_id: &Address,
+    version: u64,
+) -> Result<VersionedObject<KeyServerV2>, SealCommitteeError> {
+    let field_id = key_server_v2_field_id(ks_obj_id)?;
+    let field: VersionedObject<Field<u64, KeyServerV2>> =
+        fetch_move_object_at_version(fetcher, &field_id, version, "KeyServerV2 Field object")
+            .await?;
+    Ok(VersionedObject {
+        value: field.value.value,
+        version: field.version,
+        digest: field.digest,
+    })
+}
+
+/// Digest of an object given its BCS data, as computed onchain.
+pub(crate) fn object_digest(bcs_bytes: &[u8]) -> Digest {
+    let mut hasher = Blake2b256::default();
+    hasher.update(b"Object::");
+    hasher.update(bcs_bytes);
+    Digest::new(hasher.finalize().digest)
+}
+
+/// Fetch an object and deserialize its Move struct contents as type T, waiting for the fullnode
+/// to index it, e.g. right after the transaction creating it. The object is fetched every
+/// `poll_interval` while it is missing or has no data, see [SealCommitteeError::is_not_indexed].
+/// Any other failure, including data that cannot be deserialized as T, fails immediately. Fails
+/// if `timeout` elapses.
+pub async fn wait_for_object<T, F>(
+    fetcher: &F,
+    object_id: &Address,
+    timeout: Duration,
+    poll_interval: Duration,
+) -> Result<T, SealCommitteeError>
+where
+    T: serde::de::DeserializeOwned,
+    F: CommitteeFetcher + ?Sized,
+{
+    let context = format!("object {object_id}");
+    let poll = async {
+        loop {
+            match fetcher.fetch_object_bcs(object_id, None).await {
+                Ok(bcs_bytes) => return deserialize_move_object(&bcs_bytes, &context),
+                Err(e) if e.is_not_indexed() => tokio::time::sleep(poll_interval).await,
+                Err(e) => return Err(e),
+            }
+        }
+    };
+    tokio::time::timeout(timeout, poll).await.map_err(|_| {
+        SealCommitteeError::Other(anyhow!(
+            "Timed out after {:?} waiting for the fullnode to index object {}",
+            timeout,
+            object_id
+        ))
+    })?
+}
+
+/// Polls a committee object until it reaches a target state.
+pub struct CommitteeStatePoller<F = Client> {
+    fetcher: F,
+    committee_id: Address,
+    interval: Duration,
+}
+
+impl<F: CommitteeFetcher> CommitteeStatePoller<F> {
+    pub fn new(fetcher: F, committee_id: Address, interval: Duration) -> Self {
+        Self {
+            fetcher,
+            committee_id,
+            interval,
+        }
+    }
+
+    /// Wait until the committee is finalized and return it.
+    pub async fn until_finalized(
+        fetcher: F,
+        committee_id: Address,
+        interval: Duration,
+        timeout: Duration,
+    ) -> Result<SealCommittee, SealCommitteeError> {
+        Self::new(fetcher, committee_id, interval)
+            .wait_until(
+                |committee| matches!(committee.state, CommitteeState::Finalized),
+                timeout,
+            )
+            .await
+    }
+
+    /// Fetch the committee, starting immediately, until `predicate` returns true for it, and
+    /// return it. The version of the committee object is checked every `interval`, and the
+    /// committee is only fetched again once it changed. Fails on the first failed fetch, or if
+    /// `timeout` elapses.
+    pub async fn wait_until<P>(
+        self,
+        mut predicate: P,
+        timeout: Duration,
+    ) -> Result<SealCommittee, SealCommitteeError>
+    where
+        P: FnMut(&SealCommittee) -> bool,
+    {
+        let poll = async {
+            let mut checked_version = None;
+            loop {
+                let version = self
+                    .fetcher
+                    .fetch_object_version(&self.committee_id)
+                    .await?
+                    .ok_or(SealCommitteeError::ObjectNotFound {
+                        id: self.committee_id,
+                    })?;
+                if checked_version != Some(version) {
+                    let committee = fetch_committee_data(&self.fetcher, &self.committee_id).await?;
+                    if predicate(&committee) {
+                        return Ok(committee);
+                    }
+                    checked_version = Some(version);
+                }
+                tokio::time::sleep(self.interval).await;
+            }
+        };
+        tokio::time::timeout(timeout, poll).await.map_err(|_| {
+            SealCommitteeError::Other(anyhow!(
+                "Timed out after {:?} waiting for committee {}",
+                timeout,
+                self.committee_id
+            ))
+        })?
+    }
+}
+
+/// Poll a committee every `poll_interval` until `predicate` holds for it and return it, e.g. to
+/// wait until [SealCommittee::is_finalized] succeeds. See [CommitteeStatePoller::wait_until],
+/// `predicate` is only called again once the committee changed. Fails on the first failed fetch,
+/// or if `timeout` elapses. Dropping the returned future stops watching, e.g. to cancel it with
+/// [tokio::select!].
+pub async fn watch_committee_state<F, P>(
+    fetcher: F,
+    committee_id: Address,
+    predicate: P,
+    poll_interval: Duration,
+    timeout: Duration,
+) -> Result<SealCommittee, SealCommitteeError>
+where
+    F: CommitteeFetcher,
+    P: FnMut(&SealCommittee) -> bool,
+{
+    CommitteeStatePoller::new(fetcher, committee_id, poll_interval)
+        .wait_until(predicate, timeout)
+        .await
 }
 
 /// Fetch the KeyServer object and KeyServerV2 data for a given committee.
 /// Returns the KeyServer object ID and the KeyServerV2 data.
-pub async fn fetch_key_server(
-    grpc_client: &mut Client,
+pub async fn fetch_key_server<F: CommitteeFetcher + ?Sized>(
+    fetcher: &F,
+    committee_id: &Address,
+) -> Result<(Address, KeyServerV2), SealCommitteeError> {
+    fetch_key_server_at_version(fetcher, committee_id, EXPECTED_KEY_SERVER_VERSION).await
+}
+
+/// Like [fetch_key_server], but reads the data field of the latest version of the KeyServer
+/// object as given by its `last_version`, instead of [EXPECTED_KEY_SERVER_VERSION].
+pub async fn fetch_key_server_latest<F: CommitteeFetcher + ?Sized>(
+    fetcher: &F,
+    committee_id: &Address,
+) -> Result<(Address, KeyServerV2), SealCommitteeError> {
+    let ks_obj_id = fetch_key_server_id(fetcher, committee_id).await?;
+
+    // The data field of the expected version is fetched along with the KeyServer object, which
+    // saves a round trip unless the key server was upgraded.
+    let expected_field_id = key_server_v2_field_id(&ks_obj_id)?;
+    let [key_server_bcs, expected_field_bcs]: [_; 2] = fetcher
+        .fetch_objects_bcs(&[ks_obj_id, expected_field_id])
+        .await
+        .try_into()
+        .map_err(|_| anyhow!("Expected 2 objects from batch fetch"))?;
+    let version =
+        deserialize_move_object::<KeyServer>(&key_server_bcs?, "KeyServer object")?.last_version;
+    let key_server = match expected_field_bcs {
+        Ok(bcs) if version == EXPECTED_KEY_SERVER_VERSION => {
+            deserialize_move_object::<Field<u64, KeyServerV2>>(&bcs, "KeyServerV2 Field object")?
+                .value
+        }
+        _ => fetch_key_server_data(fetcher, &ks_obj_id, version).await?,
+    };
+    Ok((ks_obj_id, key_server))
+}
+
+/// Like [fetch_key_server], but reads the data field of the given version, e.g. to debug an
+/// upgrade of the key server object.
+pub async fn fetch_key_server_at_version<F: CommitteeFetcher + ?Sized>(
+    fetcher: &F,
+    committee_id: &Address,
+    version: u64,
+) -> Result<(Address, KeyServerV2), SealCommitteeError> {
+    let ks_obj_id = fetch_key_server_id(fetcher, committee_id).await?;
+    let key_server = fetch_key_server_data(fetcher, &ks_obj_id, version).await?;
+    Ok((ks_obj_id, key_server))
+}
+
+/// Latest version of the KeyServer object owned by a committee, i.e. the version of its current
+/// data field.
+pub async fn fetch_key_server_version<F: CommitteeFetcher + ?Sized>(
+    fetcher: &F,
+    committee_id: &Address,
+) -> Result<u64, SealCommitteeError> {
+    let ks_obj_id = fetch_key_server_id(fetcher, committee_id).await?;
+    Ok(fetch_key_server_object(fetcher, &ks_obj_id)
+        .await?
+        .last_version)
+}
+
+/// ID of the KeyServer object owned by a committee.
+pub(crate) async fn fetch_key_server_id<F: CommitteeFetcher + ?Sized>(
+    fetcher: &F,
     committee_id: &Address,
-) -> Result<(Address, KeyServerV2)> {
-    // Derive dynamic object field wrapper id.
+) -> Result<Address, SealCommitteeError> {
+    let field_wrapper_id = key_server_field_wrapper_id(committee_id)?;
+    let field_wrapper: Field<Wrapper<Address>, Address> =
+        fetch_and_deserialize_move_object(fetcher, &field_wrapper_id, "Field wrapper object")
+            .await?;
+    Ok(field_wrapper.value)
+}
+
+async fn fetch_key_server_object<F: CommitteeFetcher + ?Sized>(
+    fetcher: &F,
+    ks_obj_id: &Address,
+) -> Result<KeyServer, SealCommitteeError> {
+    fetch_and_deserialize_move_object(fetcher, ks_obj_id, "KeyServer object").await
+}
+
+/// ID of the dynamic object field wrapper under which a committee owns its KeyServer object.
+pub fn key_server_field_wrapper_id(committee_id: &Address) -> Result<Address> {
     let wrapper_key = Wrapper {
         name: *committee_id,
     };
@@ -89,102 +1082,237 @@ pub async fn fetch_key_server(
         }))],
     }));
 
-    let field_wrapper_id =
-        committee_id.derive_dynamic_child_id(&wrapper_type_tag, &wrapper_key_bcs);
+    Ok(committee_id.derive_dynamic_child_id(&wrapper_type_tag, &wrapper_key_bcs))
+}
 
-    let field_wrapper: Field<Wrapper<Address>, Address> =
-        fetch_and_deserialize_move_object(grpc_client, &field_wrapper_id, "Field wrapper object")
-            .await?;
-    let ks_obj_id = field_wrapper.value;
+/// ID of the dynamic field holding the KeyServerV2 data of a KeyServer object.
+pub fn key_server_v2_field_id(ks_obj_id: &Address) -> Result<Address> {
+    key_server_version_field_id(ks_obj_id, EXPECTED_KEY_SERVER_VERSION)
+}
 
-    // Derive KeyServerV2 dynamic field ID on KeyServer object.
+/// ID of the dynamic field holding the data of the given version of a KeyServer object.
+pub fn key_server_version_field_id(ks_obj_id: &Address, version: u64) -> Result<Address> {
     // This is a regular dynamic_field, not dynamic_object_field.
-    // Key type: u64, Key value: EXPECTED_KEY_SERVER_VERSION
-    let v2_field_name_bcs = bcs::to_bytes(&EXPECTED_KEY_SERVER_VERSION)?;
-    let key_server_v2_field_id =
-        ks_obj_id.derive_dynamic_child_id(&sui_sdk_types::TypeTag::U64, &v2_field_name_bcs);
+    // Key type: u64, Key value: the version.
+    let field_name_bcs = bcs::to_bytes(&version)?;
+    Ok(ks_obj_id.derive_dynamic_child_id(&sui_sdk_types::TypeTag::U64, &field_name_bcs))
+}
+
+/// Fetch the KeyServerV2 data of a KeyServer object, for a committee or an independent key server.
+pub async fn fetch_key_server_v2<F: CommitteeFetcher + ?Sized>(
+    fetcher: &F,
+    ks_obj_id: &Address,
+) -> Result<KeyServerV2, SealCommitteeError> {
+    fetch_key_server_data(fetcher, ks_obj_id, EXPECTED_KEY_SERVER_VERSION).await
+}
+
+/// Fetch the data field of the given version of a KeyServer object.
+async fn fetch_key_server_data<F: CommitteeFetcher + ?Sized>(
+    fetcher: &F,
+    ks_obj_id: &Address,
+    version: u64,
+) -> Result<KeyServerV2, SealCommitteeError> {
+    // Derive the versioned dynamic field ID on KeyServer object.
+    let field_id = key_server_version_field_id(ks_obj_id, version)?;
 
     // Fetch and deserialize the Field<u64, KeyServerV2> object.
-    let field: Field<u64, KeyServerV2> = fetch_and_deserialize_move_object(
-        grpc_client,
-        &key_server_v2_field_id,
-        "KeyServerV2 Field object",
-    )
-    .await?;
+    let field: Field<u64, KeyServerV2> =
+        fetch_and_deserialize_move_object(fetcher, &field_id, "KeyServerV2 Field object").await?;
+
+    Ok(field.value)
+}
 
-    Ok((ks_obj_id, field.value))
+/// Aggregated public key of a committee in PostDKG or Finalized state. The key server is only
+/// fetched once the committee is finalized.
+pub async fn fetch_committee_pk<F: CommitteeFetcher + ?Sized>(
+    fetcher: &F,
+    committee: &SealCommittee,
+) -> Result<G2Element, SealCommitteeError> {
+    let key_server = match committee.state {
+        CommitteeState::Finalized => Some(fetch_key_server(fetcher, &committee.id).await?.1),
+        _ => None,
+    };
+    Ok(committee.get_committee_pk(key_server.as_ref())?)
 }
 
-/// Fetch partial key server info for all committee members.
-/// Returns a HashMap mapping member addresses to their partial key server info.
-pub async fn fetch_partial_key_server_info(
-    grpc_client: &mut Client,
+/// Fetch partial key server info for all committee members, if the key server of `committee_id`
+/// is of type Committee. For an independent key server, `infos` is `None`.
+pub async fn fetch_partial_key_server_info<F: CommitteeFetcher + ?Sized>(
+    fetcher: &F,
     committee_id: &Address,
-) -> Result<HashMap<Address, PartialKeyServerInfo>> {
-    let (ks_obj_id, key_server_v2) = fetch_key_server(grpc_client, committee_id).await?;
-
-    // Extract partial key servers from ServerType::Committee.
-    match key_server_v2.server_type {
-        ServerType::Committee {
-            partial_key_servers,
-            ..
-        } => partial_key_servers
-            .0
-            .contents
-            .into_iter()
-            .map(|entry| {
-                let partial_pk = bcs::from_bytes(&entry.value.partial_pk)
-                    .map_err(|e| anyhow!("Failed to deserialize partial PK: {}", e))?;
-                Ok((
-                    entry.key,
-                    PartialKeyServerInfo {
-                        ks_obj_id,
-                        party_id: entry.value.party_id,
-                        partial_pk,
-                    },
-                ))
-            })
-            .collect(),
-        _ => Err(anyhow!("KeyServer is not of type Committee")),
-    }
+) -> Result<PartialKeyServerInfoResult, SealCommitteeError> {
+    let (ks_obj_id, key_server_v2) = fetch_key_server(fetcher, committee_id).await?;
+    PartialKeyServerInfoResult::new(ks_obj_id, key_server_v2)
+}
+
+/// Serving URLs of the partial key servers of the finalized committee `committee_id`, by member
+/// address, with the party ID of each member for ordering. Fails if the key server is not of type
+/// Committee or a URL is not an http(s) URL. Before DKG, use [SealCommittee::member_urls].
+pub async fn get_key_server_urls<F: CommitteeFetcher + ?Sized>(
+    fetcher: &F,
+    committee_id: &Address,
+) -> Result<HashMap<Address, MemberUrl>, SealCommitteeError> {
+    let (_, key_server_v2) = fetch_key_server(fetcher, committee_id).await?;
+    Ok(key_server_v2.member_urls()?)
+}
+
+/// Onchain data for rotating the key of `old_committee` to `committee`, see
+/// [fetch_rotation_context].
+pub struct RotationContext {
+    pub committee: SealCommittee,
+    pub old_committee: SealCommittee,
+    /// ID and data of the KeyServer object of the old committee.
+    pub old_key_server_id: Address,
+    pub old_key_server: KeyServerV2,
+    /// Partial key servers of the old committee members, by address.
+    pub old_partial_key_servers: PartialKeyServerInfoResult,
+}
+
+/// Fetch a committee, the old committee it rotates from and the key server of the old committee
+/// concurrently. The key server is read at `key_server_version` if given, or else at its latest
+/// version. Errors name the object that failed. It is not checked that `committee` rotates from
+/// `old_committee_id`.
+pub async fn fetch_rotation_context<F: CommitteeFetcher + ?Sized>(
+    fetcher: &F,
+    committee_id: &Address,
+    old_committee_id: &Address,
+    key_server_version: Option<u64>,
+) -> Result<RotationContext, SealCommitteeError> {
+    let in_context = |what: String| {
+        move |e: SealCommitteeError| SealCommitteeError::Other(anyhow::Error::new(e).context(what))
+    };
+    let (committee, old_committee, (old_key_server_id, old_key_server)) = futures::try_join!(
+        async {
+            fetch_committee_data(fetcher, committee_id)
+                .await
+                .map_err(in_context(format!(
+                    "Failed to fetch committee {committee_id}"
+                )))
+        },
+        async {
+            fetch_committee_data(fetcher, old_committee_id)
+                .await
+                .map_err(in_context(format!(
+                    "Failed to fetch old committee {old_committee_id}"
+                )))
+        },
+        async {
+            fetcher
+                .fetch_key_server(old_committee_id, key_server_version)
+                .await
+                .map_err(in_context(format!(
+                    "Failed to fetch key server of old committee {old_committee_id}"
+                )))
+        },
+    )?;
+    let old_partial_key_servers =
+        PartialKeyServerInfoResult::new(old_key_server_id, old_key_server.clone())?;
+    Ok(RotationContext {
+        committee,
+        old_committee,
+        old_key_server_id,
+        old_key_server,
+        old_partial_key_servers,
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::fetcher::StaticCommitteeFetcher;
+    use crate::mock_grpc::{MockGrpcServer, MockLedgerService};
+    use crate::move_types::{ServerType, ServerTypeKind};
+    use crate::test_utils::{addresses, init_committee};
     use crate::ParsedMemberInfo;
     use fastcrypto::bls12381::min_sig::BLS12381PublicKey;
     use fastcrypto::encoding::{Encoding, Hex};
-    use fastcrypto::groups::bls12381::G2Element;
     use fastcrypto_tbls::ecies_v1::PublicKey;
     use std::str::FromStr;
 
+    /// Test committee object on testnet set up with 3 members.
+    const INIT_COMMITTEE_ID: &str =
+        "0x1d8e07b865da82d86c71bb0ac8adf174996fd780ccae8237dd5f6ea38d9fe903";
+    /// Test rotated finalized committee from testnet.
+    const ROTATED_COMMITTEE_ID: &str =
+        "0x82283c1056bb18832428034d20e0af5ed098bc58f8815363c33eb3a9b3fba867";
+    /// Old committee ID (before rotation).
+    const OLD_COMMITTEE_ID: &str =
+        "0xaf2962d702d718f7b968eddc262da28418a33c296786cd356a43728a858faf80";
+    const KEY_SERVER_ID: &str =
+        "0x5b4b868b22f4e1e87d3938f29aefc71a1e1ddf7352e214088c9eaf37e31efd31";
+
     /// Helper to deserialize from hex string.
     fn from_hex_bcs<T: serde::de::DeserializeOwned>(hex_str: &str) -> T {
         let bytes = Hex::decode(hex_str).unwrap();
         bcs::from_bytes(&bytes).unwrap()
     }
 
-    #[tokio::test]
-    async fn test_fetch_committee_members() {
-        // Test committee object on testnet set up with 3 members.
-        let committee_id =
-            Address::from_str("0x1d8e07b865da82d86c71bb0ac8adf174996fd780ccae8237dd5f6ea38d9fe903")
-                .unwrap();
+    fn address(s: &str) -> Address {
+        Address::from_str(s).unwrap()
+    }
+
+    /// The recorded fixtures in tests/fixtures as Move struct contents, by the IDs of the
+    /// testnet objects.
+    fn testnet_objects() -> Vec<(Address, Vec<u8>)> {
+        let fixture = |hex: &str| Hex::decode(hex.trim()).unwrap();
+        vec![
+            (
+                address(INIT_COMMITTEE_ID),
+                fixture(include_str!("../tests/fixtures/committee_init.hex")),
+            ),
+            (
+                address(ROTATED_COMMITTEE_ID),
+                fixture(include_str!("../tests/fixtures/committee_finalized.hex")),
+            ),
+            (
+                key_server_field_wrapper_id(&address(ROTATED_COMMITTEE_ID)).unwrap(),
+                fixture(include_str!("../tests/fixtures/field_wrapper.hex")),
+            ),
+            (
+                address(KEY_SERVER_ID),
+                fixture(include_str!("../tests/fixtures/key_server.hex")),
+            ),
+            (
+                key_server_v2_field_id(&address(KEY_SERVER_ID)).unwrap(),
+                fixture(include_str!(
+                    "../tests/fixtures/key_server_v2_committee.hex"
+                )),
+            ),
+        ]
+    }
+
+    /// Mock fullnode with the testnet fixtures.
+    fn testnet_service() -> MockLedgerService {
+        testnet_objects()
+            .into_iter()
+            .fold(MockLedgerService::default(), |service, (id, contents)| {
+                service.with_move_object(id, contents).unwrap()
+            })
+    }
+
+    /// Serve the testnet fixtures from a mock fullnode.
+    async fn mock_testnet() -> MockGrpcServer {
+        testnet_service().serve().await.unwrap()
+    }
 
-        let mut grpc_client = create_grpc_client(&Network::Testnet).unwrap();
-        let committee = fetch_committee_data(&mut grpc_client, &committee_id)
+    /// Serve the testnet fixtures from memory.
+    fn static_testnet() -> StaticCommitteeFetcher {
+        testnet_objects().into_iter().fold(
+            StaticCommitteeFetcher::default(),
+            |fetcher, (id, contents)| fetcher.with_move_object(id, contents).unwrap(),
+        )
+    }
+
+    async fn check_committee_members(grpc_client: &impl CommitteeFetcher) {
+        let committee = fetch_committee_data(grpc_client, &address(INIT_COMMITTEE_ID))
             .await
             .unwrap();
         let members_info = committee.get_members_info().unwrap();
 
         let addresses = [
-            Address::from_str("0x0636157e9d013585ff473b3b378499ac2f1d207ed07d70e2cd815711725bca9d")
-                .unwrap(),
-            Address::from_str("0xe6a37ff5cd968b6a666fb033d85eabc674449f44f9fc2b600e55e27354211ed6")
-                .unwrap(),
-            Address::from_str("0x223762117ab21a439f0f3f3b0577e838b8b26a37d9a1723a4be311243f4461b9")
-                .unwrap(),
+            address("0x0636157e9d013585ff473b3b378499ac2f1d207ed07d70e2cd815711725bca9d"),
+            address("0xe6a37ff5cd968b6a666fb033d85eabc674449f44f9fc2b600e55e27354211ed6"),
+            address("0x223762117ab21a439f0f3f3b0577e838b8b26a37d9a1723a4be311243f4461b9"),
         ];
 
         let expected_enc_pk: PublicKey<G2Element> = from_hex_bcs("0xaf2ca44fd70f4e72d5ef6ad1bc8f5ab42850a36f75e1562f4f33ca2d25c5fee5fe780e164f17e0591a46a44d545e71f21447d316563899b77f34ee34d84ee70c70505f98dc4e7f5914b347cec49ef3a510efa9568416413cacd5361f42c8fa58");
@@ -206,51 +1334,38 @@ mod tests {
         assert!(committee.is_finalized().is_err());
     }
 
-    #[tokio::test]
-    async fn test_fetch_partial_key_servers() {
-        // Test rotated finalized committee from testnet.
-        let committee_id =
-            Address::from_str("0x82283c1056bb18832428034d20e0af5ed098bc58f8815363c33eb3a9b3fba867")
-                .unwrap();
-
-        // Old committee ID (before rotation).
-        let old_committee_id =
-            Address::from_str("0xaf2962d702d718f7b968eddc262da28418a33c296786cd356a43728a858faf80")
-                .unwrap();
-
-        // Create gRPC client.
-        let mut grpc_client = Client::new(Client::TESTNET_FULLNODE).unwrap();
+    async fn check_partial_key_servers(grpc_client: &impl CommitteeFetcher) {
+        let committee_id = address(ROTATED_COMMITTEE_ID);
 
         // Assert that the old committee has no key server object (should fail).
-        let old_result = fetch_partial_key_server_info(&mut grpc_client, &old_committee_id).await;
+        let old_result =
+            fetch_partial_key_server_info(grpc_client, &address(OLD_COMMITTEE_ID)).await;
         assert!(
             old_result.is_err(),
             "Old committee should not have a key server object after rotation"
         );
 
         // Fetch committee data to get member addresses.
-        let committee = fetch_committee_data(&mut grpc_client, &committee_id)
+        let committee = fetch_committee_data(grpc_client, &committee_id)
             .await
             .unwrap();
 
-        // Expected values for rotated committee (4 parties).
-        let expected_key_server =
-            Address::from_str("0x5b4b868b22f4e1e87d3938f29aefc71a1e1ddf7352e214088c9eaf37e31efd31")
-                .unwrap();
+        // Expected partial PKs by party ID.
         let expected_partial_pks = [
             "0xaa56bd6b3af3eb4c92b75c1cbe7c4fff64563966b81f812008b4edcf1dac9cf42df1695be94f850f01dcfa813add29630810a51dc5bb558f67da1f3182e5e1ff064555e3c3cf83e295899677873c10c284ace2526dd7f5f7b898a7d323622e57",
             "0x8f1902dbf32c7c2dd7a6eefa97b1e6833bccd859c35c4ec6124dde5f267d260fcd16240cf0c5ecadfa5202563f97035e05ba0cd246ceaca8abb930505cf2752b00e14565af0ffe02a437de0b5c799c1e84314297b7fdc7e9fdd322a9c77c6bc3",
             "0x8d942a02eb6a3bf78d27ec8ee27b9a8721b07fe22866bb4f6614f78978e394c9ddc8b87712ddbc3fa2f0386bc3b68ccc18dd0f05f2ca5345bf19433933a5d77bf56cd2563a2e872f82b16495529b47086212466f903f84949b15153d7eab6848",
             "0x94eba091a424bed60ad920855706ee476d23c2d9d4763ab5a4f832b3e57c38eb7d81013ea8f5b4790b4db6cd1ad2fd051633e6c8e9a25f302b5b4382724c5e83c40e487dba39910df2829c09f7d38ee2d37e0a8a1bdc2a71486c5fb6e508c069",
         ];
-        let partial_key_servers = fetch_partial_key_server_info(&mut grpc_client, &committee_id)
+        let result = fetch_partial_key_server_info(grpc_client, &committee_id)
             .await
             .unwrap();
+        assert_eq!(result.server_type, ServerTypeKind::Committee);
+        let partial_key_servers = result.infos.unwrap();
 
         // Fetch KeyServerV2 to check the version field.
-        let (_ks_obj_id, key_server_v2) = fetch_key_server(&mut grpc_client, &committee_id)
-            .await
-            .unwrap();
+        let (_ks_obj_id, key_server_v2) =
+            fetch_key_server(grpc_client, &committee_id).await.unwrap();
 
         // Assert that the version field is 1.
         match key_server_v2.server_type {
@@ -260,19 +1375,28 @@ mod tests {
             _ => panic!("KeyServer should be of type Committee"),
         }
 
+        let urls = get_key_server_urls(grpc_client, &committee_id)
+            .await
+            .unwrap();
+        assert_eq!(urls.len(), committee.members.len());
+
         for member in &committee.members {
             let partial_key_server_info = partial_key_servers.get(member).unwrap();
+            let url = &urls[member];
+            assert_eq!(url.party_id, partial_key_server_info.party_id);
+            assert_eq!(
+                url.url,
+                format!("https://seal-{}.example.com", url.party_id)
+            );
 
             assert_eq!(
-                partial_key_server_info.ks_obj_id, expected_key_server,
+                partial_key_server_info.ks_obj_id,
+                address(KEY_SERVER_ID),
                 "Key server address should match for member {member}"
             );
 
-            let expected_pk_bytes =
-                Hex::decode(expected_partial_pks[partial_key_server_info.party_id as usize])
-                    .unwrap();
-            let expected_pk: fastcrypto::groups::bls12381::G2Element =
-                bcs::from_bytes(&expected_pk_bytes).unwrap();
+            let expected_pk: G2Element =
+                from_hex_bcs(expected_partial_pks[partial_key_server_info.party_id as usize]);
             assert_eq!(
                 partial_key_server_info.partial_pk, expected_pk,
                 "Partial PK for party {} (member {}) should match",
@@ -280,4 +1404,816 @@ mod tests {
             );
         }
     }
+
+    #[tokio::test]
+    async fn test_fetch_committee_members() {
+        let server = mock_testnet().await;
+        check_committee_members(&server.client().unwrap()).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "requires testnet access"]
+    async fn test_fetch_committee_members_testnet() {
+        check_committee_members(&create_grpc_client(&Network::Testnet).unwrap()).await;
+    }
+
+    #[tokio::test]
+    async fn test_fetch_partial_key_servers() {
+        let server = mock_testnet().await;
+        check_partial_key_servers(&server.client().unwrap()).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "requires testnet access"]
+    async fn test_fetch_partial_key_servers_testnet() {
+        check_partial_key_servers(&create_grpc_client(&Network::Testnet).unwrap()).await;
+    }
+
+    #[tokio::test]
+    async fn test_static_fetcher() {
+        check_committee_members(&static_testnet()).await;
+        check_partial_key_servers(&static_testnet()).await;
+
+        // Through the trait object, as used by dkg-cli.
+        let fetcher: &dyn CommitteeFetcher = &static_testnet();
+        let committee_id = address(ROTATED_COMMITTEE_ID);
+        let committee = fetcher.fetch_committee(&committee_id).await.unwrap();
+        assert!(committee.is_finalized().is_ok());
+        let (ks_obj_id, _) = fetcher.fetch_key_server(&committee_id, None).await.unwrap();
+        assert_eq!(ks_obj_id, address(KEY_SERVER_ID));
+        let infos = fetcher
+            .fetch_partial_key_server_info(&committee_id)
+            .await
+            .unwrap()
+            .into_committee_infos()
+            .unwrap();
+        assert_eq!(infos.len(), committee.members.len());
+    }
+
+    #[tokio::test]
+    async fn test_replay_recorded_objects() {
+        // Record the committee object as fetched from the mock, then serve it from disk.
+        let server = mock_testnet().await;
+        let committee_id = address(INIT_COMMITTEE_ID);
+        let bcs_bytes = server
+            .client()
+            .unwrap()
+            .fetch_object_bcs(&committee_id, None)
+            .await
+            .unwrap();
+        let dir = std::env::temp_dir().join(format!("seal-replay-{}", std::process::id()));
+        RecordedObject::new(committee_id, Some(1), &bcs_bytes)
+            .write(&dir)
+            .unwrap();
+
+        let replay = MockLedgerService::default()
+            .with_recorded_dir(&dir)
+            .unwrap()
+            .serve()
+            .await
+            .unwrap();
+        let committee = fetch_committee_data(&replay.client().unwrap(), &committee_id)
+            .await
+            .unwrap();
+        assert_eq!(committee.id, committee_id);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_fetch_committee_pk() {
+        let server = mock_testnet().await;
+        let grpc_client = server.client().unwrap();
+        let committee = fetch_committee_data(&grpc_client, &address(ROTATED_COMMITTEE_ID))
+            .await
+            .unwrap();
+        let (_, key_server) = fetch_key_server(&grpc_client, &committee.id).await.unwrap();
+        assert_eq!(
+            fetch_committee_pk(&grpc_client, &committee).await.unwrap(),
+            bcs::from_bytes::<G2Element>(&key_server.pk).unwrap()
+        );
+
+        // DKG is not completed in Init state.
+        let committee = fetch_committee_data(&grpc_client, &address(INIT_COMMITTEE_ID))
+            .await
+            .unwrap();
+        assert!(fetch_committee_pk(&grpc_client, &committee).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_key_server_latest() {
+        let server = mock_testnet().await;
+        let grpc_client = server.client().unwrap();
+        let committee_id = address(ROTATED_COMMITTEE_ID);
+        assert_eq!(
+            fetch_key_server_version(&grpc_client, &committee_id)
+                .await
+                .unwrap(),
+            EXPECTED_KEY_SERVER_VERSION
+        );
+
+        let (ks_obj_id, latest) = fetch_key_server_latest(&grpc_client, &committee_id)
+            .await
+            .unwrap();
+        let (_, expected) = fetch_key_server(&grpc_client, &committee_id).await.unwrap();
+        assert_eq!(ks_obj_id, address(KEY_SERVER_ID));
+        assert_eq!(latest.pk, expected.pk);
+
+        // No data field for a future version.
+        let err = fetch_key_server_at_version(&grpc_client, &committee_id, 3)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, SealCommitteeError::ObjectNotFound { id }
+                if id == key_server_version_field_id(&ks_obj_id, 3).unwrap()),
+            "{err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_objects_bcs() {
+        let missing = Address::new([7; 32]);
+        let ids = [address(INIT_COMMITTEE_ID), missing, address(KEY_SERVER_ID)];
+        let check = |results: Vec<Result<Vec<u8>, SealCommitteeError>>| {
+            assert_eq!(results.len(), 3);
+            let committee: SealCommittee =
+                deserialize_move_object(results[0].as_ref().unwrap(), "committee").unwrap();
+            assert_eq!(committee.id, ids[0]);
+            assert!(matches!(
+                &results[1],
+                Err(SealCommitteeError::ObjectNotFound { id }) if *id == missing
+            ));
+            assert!(results[2].is_ok());
+        };
+
+        // One batch request.
+        let server = mock_testnet().await;
+        let client = server.client().unwrap();
+        check(client.fetch_objects_bcs(&ids).await);
+        assert_eq!(server.batch_get_objects_calls(), 1);
+        assert_eq!(server.get_object_calls(), 0);
+
+        // One request per object without batching.
+        let server = testnet_service().without_batch().serve().await.unwrap();
+        let client = server.client().unwrap();
+        check(client.fetch_objects_bcs(&ids).await);
+        assert_eq!(server.get_object_calls(), 3);
+
+        // Also with fetchers not overriding the default.
+        let fetcher = testnet_objects().into_iter().fold(
+            StaticCommitteeFetcher::default(),
+            |fetcher, (id, contents)| fetcher.with_move_object(id, contents).unwrap(),
+        );
+        check(fetch_objects_bcs(&fetcher, &ids).await);
+
+        // A failed batch fails every object, after retrying transient failures.
+        let server = testnet_service()
+            .with_failures([tonic::Code::Unavailable, tonic::Code::PermissionDenied])
+            .serve()
+            .await
+            .unwrap();
+        let fetcher = GrpcFetcher::new(
+            server.client().unwrap(),
+            FetchOptions {
+                initial_delay: Duration::from_millis(1),
+                ..FetchOptions::default()
+            },
+        );
+        let results = fetcher.fetch_objects_bcs(&ids).await;
+        assert!(results.iter().all(|result| matches!(
+            result,
+            Err(SealCommitteeError::Rpc(status)) if status.code() == tonic::Code::PermissionDenied
+        )));
+        assert_eq!(server.batch_get_objects_calls(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_key_server_latest_round_trips() {
+        let server = mock_testnet().await;
+        let (ks_obj_id, _) =
+            fetch_key_server_latest(&server.client().unwrap(), &address(ROTATED_COMMITTEE_ID))
+                .await
+                .unwrap();
+        assert_eq!(ks_obj_id, address(KEY_SERVER_ID));
+        // The field wrapper, then the KeyServer object and its data field in one batch.
+        assert_eq!(server.get_object_calls(), 1);
+        assert_eq!(server.batch_get_objects_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_rotation_context() {
+        let server = testnet_service()
+            .with_delay(Duration::from_millis(300))
+            .serve()
+            .await
+            .unwrap();
+        let client = server.client().unwrap();
+        let start = Instant::now();
+        let context = fetch_rotation_context(
+            &client,
+            &address(INIT_COMMITTEE_ID),
+            &address(ROTATED_COMMITTEE_ID),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(context.committee.id, address(INIT_COMMITTEE_ID));
+        assert_eq!(context.old_committee.id, address(ROTATED_COMMITTEE_ID));
+        assert_eq!(context.old_key_server_id, address(KEY_SERVER_ID));
+        assert_eq!(
+            context
+                .old_partial_key_servers
+                .into_committee_infos()
+                .unwrap()
+                .len(),
+            context.old_committee.members.len()
+        );
+        // The committees are fetched while the key server takes two round trips.
+        assert!(start.elapsed() < Duration::from_millis(900));
+
+        // The error names the object that failed.
+        let err = fetch_rotation_context(
+            &client,
+            &address(INIT_COMMITTEE_ID),
+            &address(OLD_COMMITTEE_ID),
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains(&format!("old committee {OLD_COMMITTEE_ID}")),
+            "{err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_at_version() {
+        let committee_id = address(INIT_COMMITTEE_ID);
+        let server = mock_testnet().await;
+        let client = server.client().unwrap();
+        let committee = fetch_committee_data_at_version(&client, &committee_id, 1)
+            .await
+            .unwrap();
+        assert_eq!(committee.value.id, committee_id);
+        assert_eq!(committee.version, 1);
+        let bcs_bytes = client.fetch_object_bcs(&committee_id, None).await.unwrap();
+        assert_eq!(committee.digest, object_digest(&bcs_bytes));
+        for version in [Some(1), None] {
+            let fetched: SealCommittee = fetch_and_deserialize_move_object_at_version(
+                &client,
+                &committee_id,
+                version,
+                "Committee object",
+            )
+            .await
+            .unwrap();
+            assert_eq!(fetched, committee.value);
+        }
+
+        let key_server = fetch_key_server_v2_at_version(&client, &address(KEY_SERVER_ID), 1)
+            .await
+            .unwrap();
+        assert_eq!(key_server.version, 1);
+        assert_eq!(
+            ServerTypeKind::from(&key_server.value.server_type),
+            ServerTypeKind::Committee
+        );
+
+        // A missing version is distinct from a missing object.
+        let err = fetch_committee_data_at_version(&client, &committee_id, 2)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, SealCommitteeError::VersionNotFound { id, version: 2 }
+                if id == committee_id),
+            "{err}"
+        );
+        let err = fetch_and_deserialize_move_object_at_version::<SealCommittee, _>(
+            &client,
+            &committee_id,
+            Some(2),
+            "Committee object",
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            matches!(err, SealCommitteeError::VersionNotFound { version: 2, .. }),
+            "{err}"
+        );
+        assert!(matches!(
+            fetch_committee_data(&client, &Address::ZERO).await,
+            Err(SealCommitteeError::ObjectNotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_custom_network() {
+        let server = mock_testnet().await;
+        let network = Network::Custom {
+            grpc_url: server.url(),
+        };
+        check_committee_members(&create_grpc_client(&network).unwrap()).await;
+    }
+
+    #[tokio::test]
+    async fn test_fetch_missing_object() {
+        let server = MockLedgerService::default().serve().await.unwrap();
+        let err = fetch_committee_data(&server.client().unwrap(), &Address::ZERO)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, SealCommitteeError::ObjectNotFound { id } if id == Address::ZERO),
+            "{err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_malformed_object() {
+        let object_id = address(INIT_COMMITTEE_ID);
+        let server = MockLedgerService::default()
+            .with_object_bcs(object_id, vec![0xff; 16])
+            .serve()
+            .await
+            .unwrap();
+        let err = fetch_committee_data(&server.client().unwrap(), &object_id)
+            .await
+            .unwrap_err();
+        let SealCommitteeError::Deserialization { context, .. } = &err else {
+            panic!("Unexpected error: {err}");
+        };
+        assert_eq!(context, "Committee object");
+
+        // A valid Move object whose contents are only the object ID, not a committee.
+        let server = MockLedgerService::default()
+            .with_move_object(object_id, object_id.into_inner().to_vec())
+            .unwrap()
+            .serve()
+            .await
+            .unwrap();
+        let err = fetch_committee_data(&server.client().unwrap(), &object_id)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, SealCommitteeError::Deserialization { .. }),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_retry_classification() {
+        for code in [
+            tonic::Code::Unavailable,
+            tonic::Code::DeadlineExceeded,
+            tonic::Code::ResourceExhausted,
+        ] {
+            assert!(SealCommitteeError::Rpc(tonic::Status::new(code, "")).is_retryable());
+        }
+        assert!(!SealCommitteeError::Rpc(tonic::Status::permission_denied("")).is_retryable());
+        assert!(!SealCommitteeError::ObjectNotFound { id: Address::ZERO }.is_retryable());
+        let malformed = bcs::from_bytes::<Object>(&[0xff]).unwrap_err();
+        assert!(!SealCommitteeError::deserialization("object", malformed).is_retryable());
+
+        let options = FetchOptions {
+            initial_delay: Duration::from_millis(100),
+            jitter: 0.5,
+            ..FetchOptions::default()
+        };
+        assert_eq!(options.base_delay(1), Duration::from_millis(100));
+        assert_eq!(options.base_delay(3), Duration::from_millis(400));
+        let delay = options.delay(2);
+        assert!(delay >= Duration::from_millis(200) && delay <= Duration::from_millis(300));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_retries_transient_failures() {
+        let options = FetchOptions {
+            initial_delay: Duration::from_millis(1),
+            ..FetchOptions::default()
+        };
+        let committee_id = address(INIT_COMMITTEE_ID);
+
+        // Fails twice, then succeeds.
+        let server = testnet_service()
+            .with_failures([tonic::Code::Unavailable, tonic::Code::ResourceExhausted])
+            .serve()
+            .await
+            .unwrap();
+        let fetcher = GrpcFetcher::new(server.client().unwrap(), options.clone());
+        let committee = fetch_committee_data(&fetcher, &committee_id).await.unwrap();
+        assert_eq!(committee.id, committee_id);
+        assert_eq!(server.get_object_calls(), 3);
+
+        // Gives up after max_attempts.
+        let server = testnet_service()
+            .with_failures([tonic::Code::Unavailable, tonic::Code::DeadlineExceeded])
+            .serve()
+            .await
+            .unwrap();
+        let fetcher = GrpcFetcher::new(
+            server.client().unwrap(),
+            FetchOptions {
+                max_attempts: 2,
+                ..options.clone()
+            },
+        );
+        let err = fetch_committee_data(&fetcher, &committee_id)
+            .await
+            .unwrap_err();
+        let SealCommitteeError::Rpc(status) = &err else {
+            panic!("Unexpected error: {err}");
+        };
+        assert_eq!(status.code(), tonic::Code::DeadlineExceeded);
+        assert_eq!(server.get_object_calls(), 2);
+
+        // Not retried: a non-transient failure, a missing object and a malformed object.
+        let server = testnet_service()
+            .with_failures([tonic::Code::PermissionDenied])
+            .with_object_bcs(Address::ZERO, vec![0xff; 16])
+            .serve()
+            .await
+            .unwrap();
+        let fetcher = GrpcFetcher::new(server.client().unwrap(), options);
+        assert!(fetch_committee_data(&fetcher, &committee_id).await.is_err());
+        assert_eq!(server.get_object_calls(), 1);
+        assert!(matches!(
+            fetch_committee_data(&fetcher, &address(OLD_COMMITTEE_ID)).await,
+            Err(SealCommitteeError::ObjectNotFound { .. })
+        ));
+        assert_eq!(server.get_object_calls(), 2);
+        assert!(matches!(
+            fetch_committee_data(&fetcher, &Address::ZERO).await,
+            Err(SealCommitteeError::Deserialization { .. })
+        ));
+        assert_eq!(server.get_object_calls(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_grpc_client_tls_and_headers() {
+        let server = testnet_service()
+            .serve_tls(
+                include_bytes!("../tests/fixtures/tls/server.pem"),
+                include_bytes!("../tests/fixtures/tls/server.key"),
+            )
+            .await
+            .unwrap();
+        let ca_cert = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tls/ca.pem");
+        let config = GrpcClientConfig {
+            auth_header: Some("Bearer test-token".to_string()),
+            extra_headers: vec![("x-api-key".to_string(), "key".to_string())],
+            domain_override: Some("localhost".to_string()),
+            ..GrpcClientConfig::default()
+        }
+        .with_ca_cert_file(&ca_cert)
+        .unwrap();
+        assert!(!format!("{config:?}").contains("test-token"));
+
+        let client = create_grpc_client_with_config(&server.url(), &config).unwrap();
+        check_committee_members(&client).await;
+        fetch_committee_data(&client, &address(ROTATED_COMMITTEE_ID))
+            .await
+            .unwrap();
+        let metadata = server.request_metadata();
+        assert!(metadata.len() >= 2);
+        for request in metadata {
+            assert_eq!(request.get("authorization").unwrap(), "Bearer test-token");
+            assert_eq!(request.get("x-api-key").unwrap(), "key");
+        }
+
+        // The server certificate is not trusted without the test CA.
+        let untrusted = GrpcClientConfig {
+            tls_ca_cert: None,
+            ..config.clone()
+        };
+        let fetcher = GrpcFetcher::new(
+            create_grpc_client_with_config(&server.url(), &untrusted).unwrap(),
+            FetchOptions::no_retry(),
+        );
+        assert!(fetch_committee_data(&fetcher, &address(INIT_COMMITTEE_ID))
+            .await
+            .is_err());
+
+        // TLS settings are rejected for plain connections.
+        assert!(create_grpc_client_with_config("http://127.0.0.1:1", &config).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_fetches() {
+        let server = testnet_service()
+            .with_delay(Duration::from_millis(500))
+            .serve()
+            .await
+            .unwrap();
+        let client = server.client().unwrap();
+        let start = Instant::now();
+        let (init, rotated) = tokio::join!(
+            fetch_committee_data(&client, &address(INIT_COMMITTEE_ID)),
+            fetch_committee_data(&client, &address(ROTATED_COMMITTEE_ID)),
+        );
+        assert_eq!(init.unwrap().id, address(INIT_COMMITTEE_ID));
+        assert_eq!(rotated.unwrap().id, address(ROTATED_COMMITTEE_ID));
+        assert_eq!(server.get_object_calls(), 2);
+        // Both requests were in flight at the same time.
+        assert!(start.elapsed() < Duration::from_millis(1000));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_timeout() {
+        let committee_id = address(INIT_COMMITTEE_ID);
+        let server = testnet_service()
+            .with_delay(Duration::from_secs(5))
+            .serve()
+            .await
+            .unwrap();
+        let fetcher = GrpcFetcher::new(
+            server.client().unwrap(),
+            FetchOptions {
+                request_timeout: Some(Duration::from_millis(100)),
+                ..FetchOptions::no_retry()
+            },
+        );
+        let start = Instant::now();
+        let err = fetch_committee_data(&fetcher, &committee_id)
+            .await
+            .unwrap_err();
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(err.is_retryable());
+        let SealCommitteeError::Timeout { id, elapsed } = err else {
+            panic!("Unexpected error: {err}");
+        };
+        assert_eq!(id, committee_id);
+        assert!(elapsed >= Duration::from_millis(100));
+        assert_eq!(server.get_object_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_committee_state_poller() {
+        let server = mock_testnet().await;
+        let interval = Duration::from_millis(10);
+        let committee = CommitteeStatePoller::until_finalized(
+            server.client().unwrap(),
+            address(ROTATED_COMMITTEE_ID),
+            interval,
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+        assert!(matches!(committee.state, CommitteeState::Finalized));
+
+        let err = CommitteeStatePoller::until_finalized(
+            server.client().unwrap(),
+            address(INIT_COMMITTEE_ID),
+            interval,
+            Duration::from_millis(100),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("Timed out"), "{err}");
+
+        let committee = CommitteeStatePoller::new(
+            server.client().unwrap(),
+            address(INIT_COMMITTEE_ID),
+            interval,
+        )
+        .wait_until(
+            |committee| committee.is_init().is_ok(),
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+        assert_eq!(committee.members.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_watch_committee_state() {
+        let server = mock_testnet().await;
+        let interval = Duration::from_millis(10);
+        let mut checked = 0;
+        let committee = watch_committee_state(
+            server.client().unwrap(),
+            address(ROTATED_COMMITTEE_ID),
+            |committee| {
+                checked += 1;
+                committee.is_finalized().is_ok()
+            },
+            interval,
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+        assert!(committee.is_finalized().is_ok());
+        assert_eq!(checked, 1);
+
+        // The committee never changes, so the predicate is only checked once before the timeout.
+        let mut checked = 0;
+        let err = watch_committee_state(
+            server.client().unwrap(),
+            address(INIT_COMMITTEE_ID),
+            |committee| {
+                checked += 1;
+                committee.is_finalized().is_ok()
+            },
+            interval,
+            Duration::from_millis(100),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("Timed out"), "{err}");
+        assert_eq!(checked, 1);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_object() {
+        let object_id = address(INIT_COMMITTEE_ID);
+        let interval = Duration::from_millis(10);
+        let server = testnet_service()
+            .with_failures([tonic::Code::NotFound, tonic::Code::DataLoss])
+            .serve()
+            .await
+            .unwrap();
+        let committee: SealCommittee = wait_for_object(
+            &server.client().unwrap(),
+            &object_id,
+            Duration::from_secs(5),
+            interval,
+        )
+        .await
+        .unwrap();
+        assert_eq!(committee.members.len(), 3);
+        assert_eq!(server.get_object_calls(), 3);
+
+        // Data of the wrong type fails without polling.
+        let server = MockLedgerService::default()
+            .with_object_bcs(object_id, vec![0xff; 16])
+            .serve()
+            .await
+            .unwrap();
+        let err = wait_for_object::<SealCommittee, _>(
+            &server.client().unwrap(),
+            &object_id,
+            Duration::from_secs(5),
+            interval,
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            matches!(err, SealCommitteeError::Deserialization { .. }),
+            "{err}"
+        );
+        assert_eq!(server.get_object_calls(), 1);
+
+        let err = wait_for_object::<SealCommittee, _>(
+            &StaticCommitteeFetcher::default(),
+            &object_id,
+            Duration::from_millis(100),
+            interval,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("Timed out"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_object_version() {
+        let server = mock_testnet().await;
+        let client = server.client().unwrap();
+        assert_eq!(
+            fetch_object_version(&client, &address(INIT_COMMITTEE_ID))
+                .await
+                .unwrap(),
+            Some(1)
+        );
+        assert_eq!(
+            fetch_object_version(&client, &Address::ZERO).await.unwrap(),
+            None
+        );
+
+        // The poller only fetches the committee again once its version changed.
+        let mut checks = 0;
+        let err = CommitteeStatePoller::new(client, address(INIT_COMMITTEE_ID), Duration::ZERO)
+            .wait_until(
+                |_| {
+                    checks += 1;
+                    false
+                },
+                Duration::from_millis(100),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Timed out"), "{err}");
+        assert_eq!(checks, 1);
+    }
+
+    #[tokio::test]
+    async fn test_ping_committee_node() {
+        let server = mock_testnet().await;
+        let grpc_client = server.client().unwrap();
+        let (committee, latency) = ping_committee_node(
+            &grpc_client,
+            &address(INIT_COMMITTEE_ID),
+            Duration::from_secs(10),
+        )
+        .await
+        .unwrap();
+        assert_eq!(committee.id, address(INIT_COMMITTEE_ID));
+        assert!(latency < Duration::from_secs(10));
+
+        // The committee must exist, not only the fullnode be reachable.
+        assert!(
+            ping_committee_node(&grpc_client, &Address::ZERO, Duration::from_secs(10))
+                .await
+                .is_err()
+        );
+        assert!(
+            ping_committee_node(&grpc_client, &address(INIT_COMMITTEE_ID), Duration::ZERO)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_health_checker_unreachable() {
+        let members = addresses(2);
+        let mut committee = init_committee(members.clone(), 2);
+        if let CommitteeState::Init { members_info } = &mut committee.state {
+            // Nothing listens on port 1.
+            members_info.0.contents[0].value.url = "http://127.0.0.1:1".to_string();
+            members_info.0.contents.truncate(1);
+        }
+        let statuses =
+            GrpcHealthChecker::check_all(&static_testnet(), &committee, Duration::from_secs(5))
+                .await
+                .unwrap();
+        assert_eq!(statuses.len(), 2);
+        assert!(matches!(
+            statuses[&members[0]],
+            HealthStatus::Unreachable { .. }
+        ));
+        assert_eq!(
+            statuses[&members[1]],
+            HealthStatus::Unreachable {
+                error: "No registered URL".to_string()
+            }
+        );
+    }
+
+    /// Serve an empty HTTP response with `status` to every request on a local port, returns the
+    /// URL.
+    async fn serve_http(status: &'static str) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = [0u8; 1024];
+                let _ = stream.read(&mut request).await;
+                let response =
+                    format!("HTTP/1.1 {status}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n");
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_health_checker_requires_success() {
+        let members = addresses(2);
+        let mut committee = init_committee(members.clone(), 2);
+        let healthy = serve_http("200 OK").await;
+        let failing = serve_http("503 Service Unavailable").await;
+        if let CommitteeState::Init { members_info } = &mut committee.state {
+            members_info.0.contents[0].value.url = healthy;
+            members_info.0.contents[1].value.url = format!("{failing}/");
+        }
+        let statuses =
+            GrpcHealthChecker::check_all(&static_testnet(), &committee, Duration::from_secs(5))
+                .await
+                .unwrap();
+        assert!(matches!(
+            statuses[&members[0]],
+            HealthStatus::Healthy { .. }
+        ));
+        // A response with an error status is not healthy.
+        assert!(
+            matches!(&statuses[&members[1]], HealthStatus::Unreachable { error } if error.contains("503")),
+            "{:?}",
+            statuses[&members[1]]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_health_checker_finalized() {
+        let fetcher = static_testnet();
+        let committee = fetch_committee_data(&fetcher, &address(ROTATED_COMMITTEE_ID))
+            .await
+            .unwrap();
+        let statuses = GrpcHealthChecker::check_all(&fetcher, &committee, Duration::from_secs(1))
+            .await
+            .unwrap();
+        // Every member is probed at the URL of its partial key server.
+        assert_eq!(statuses.len(), committee.members.len());
+        assert!(!statuses.values().any(|status| *status
+            == HealthStatus::Unreachable {
+                error: "No registered URL".to_string()
+            }));
+    }
 }
@@ -1,17 +1,49 @@
 // Copyright (c), Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+mod display;
+pub mod errors;
+pub mod fetcher;
 pub mod grpc_helper;
 pub mod move_types;
+pub mod recording;
+pub mod snapshot;
 pub mod types;
 pub mod utils;
+pub mod watcher;
 
+#[cfg(any(test, feature = "test-utils"))]
+pub mod mock_grpc;
+#[cfg(test)]
+pub(crate) mod test_utils;
+
+pub use errors::{KeyMismatch, RegistrationMismatch, SealCommitteeError, ValidationIssue};
+pub use fetcher::{CommitteeFetcher, StaticCommitteeFetcher};
 pub use grpc_helper::{
-    create_grpc_client, fetch_committee_data, fetch_key_server, fetch_partial_key_server_info,
+    create_grpc_client, create_grpc_client_for_network, create_grpc_client_with_config,
+    create_grpc_client_with_url, fetch_and_deserialize_move_object_at_version,
+    fetch_committee_data, fetch_committee_data_at_version, fetch_committee_pk, fetch_key_server,
+    fetch_key_server_at_version, fetch_key_server_latest, fetch_key_server_v2,
+    fetch_key_server_v2_at_version, fetch_key_server_version, fetch_move_object_at_version,
+    fetch_move_object_contents, fetch_object_version, fetch_objects_bcs,
+    fetch_partial_key_server_info, fetch_rotation_context, get_key_server_urls,
+    key_server_field_wrapper_id, key_server_v2_field_id, key_server_version_field_id,
+    ping_committee_node, wait_for_object, watch_committee_state, CommitteeStatePoller,
+    FetchOptions, GrpcClientConfig, GrpcFetcher, GrpcHealthChecker, HealthStatus, RotationContext,
+    VersionedObject,
 };
 pub use move_types::{
-    CommitteeState, KeyServerV2, MemberInfo, ParsedMemberInfo, PartialKeyServerInfo, SealCommittee,
-    ServerType, VecMap,
+    CommitteeMembership, CommitteeState, KeyServerV2, MemberInfo, MemberUrl, ParsedMemberInfo,
+    PartialKeyServerInfo, PartialKeyServerInfoResult, PostDkgInfo, PostDkgPartialPk, SealCommittee,
+    ServerType, ServerTypeKind, VecMap,
+};
+pub use snapshot::{
+    export_committee_snapshot, CommitteeSnapshot, KeyServerSnapshot, MemberSnapshot,
+    PartialKeySnapshot,
+};
+pub use types::{Network, NetworkConfig, LOCALNET_URL_ENV};
+pub use utils::{
+    build_new_to_old_map, build_old_to_new_map, verify_partial_pk_consistency, CommitteeRule,
+    CommitteeValidator, ContinuingMember, RotationMember, RotationPlan,
 };
-pub use types::Network;
-pub use utils::build_new_to_old_map;
+pub use watcher::{CommitteeEventListener, CommitteeStateWatcher, LoggingEventListener};
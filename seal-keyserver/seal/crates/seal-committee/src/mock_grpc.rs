@@ -0,0 +1,306 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Mock of the Sui gRPC ledger service for tests. Serves canned objects by ID from a local port,
+//! so that code using [crate::create_grpc_client_with_url] can run without network access.
+
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Result;
+use sui_rpc::client::v2::Client;
+use sui_rpc::proto::google::rpc::Status as RpcStatus;
+use sui_rpc::proto::sui::rpc::v2::ledger_service_server::{LedgerService, LedgerServiceServer};
+use sui_rpc::proto::sui::rpc::v2::{
+    get_object_result, BatchGetObjectsRequest, BatchGetObjectsResponse,
+    BatchGetTransactionsRequest, BatchGetTransactionsResponse, Bcs, GetCheckpointRequest,
+    GetCheckpointResponse, GetEpochRequest, GetEpochResponse, GetObjectRequest, GetObjectResponse,
+    GetObjectResult, GetServiceInfoRequest, GetServiceInfoResponse, GetTransactionRequest,
+    GetTransactionResponse, Object,
+};
+use sui_sdk_types::Address;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::metadata::MetadataMap;
+use tonic::transport::{Identity, ServerTlsConfig};
+use tonic::{Code, Request, Response, Status};
+
+use crate::create_grpc_client_with_url;
+use crate::fetcher::move_object_bcs;
+use crate::recording::RecordedObject;
+
+/// Ledger service answering `GetObject` and `BatchGetObjects` with the BCS of the objects it was
+/// given. Every other method returns `Unimplemented`.
+#[derive(Clone, Default)]
+pub struct MockLedgerService {
+    objects: Arc<HashMap<Address, Vec<u8>>>,
+    /// Codes to fail the next `GetObject` or `BatchGetObjects` requests with, in order.
+    failures: Arc<Mutex<VecDeque<Code>>>,
+    /// Time to wait before answering each request.
+    delay: Duration,
+    /// Whether `BatchGetObjects` returns `Unimplemented`, as on fullnodes without batching.
+    batch_disabled: bool,
+    get_object_calls: Arc<AtomicUsize>,
+    batch_get_objects_calls: Arc<AtomicUsize>,
+    /// Metadata of all requests received, e.g. to check auth headers.
+    request_metadata: Arc<Mutex<Vec<MetadataMap>>>,
+}
+
+impl MockLedgerService {
+    /// Add an object with the given BCS bytes, as returned in the `bcs` field of `GetObject`.
+    pub fn with_object_bcs(mut self, object_id: Address, bcs: Vec<u8>) -> Self {
+        Arc::make_mut(&mut self.objects).insert(object_id, bcs);
+        self
+    }
+
+    /// Add a Move object with the given struct contents, e.g. a recorded fixture. The contents
+    /// must start with the object ID.
+    pub fn with_move_object(self, object_id: Address, contents: Vec<u8>) -> Result<Self> {
+        let bcs = move_object_bcs(object_id, 1, contents)?;
+        Ok(self.with_object_bcs(object_id, bcs))
+    }
+
+    /// Fail the next `GetObject` or `BatchGetObjects` requests with the given codes, one request
+    /// per code, e.g. to test retries.
+    pub fn with_failures(self, codes: impl IntoIterator<Item = Code>) -> Self {
+        self.failures.lock().unwrap().extend(codes);
+        self
+    }
+
+    /// Answer each request only after `delay`, e.g. to test deadlines.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Answer `BatchGetObjects` with `Unimplemented`, e.g. to test the fallback to one request
+    /// per object.
+    pub fn without_batch(mut self) -> Self {
+        self.batch_disabled = true;
+        self
+    }
+
+    /// Add all objects recorded in `dir` with `SEAL_RECORD_FIXTURES`.
+    pub fn with_recorded_dir(mut self, dir: &Path) -> Result<Self> {
+        for recorded in RecordedObject::read_all(dir)? {
+            let bcs = recorded.bcs_bytes()?;
+            self = self.with_object_bcs(recorded.object_id, bcs);
+        }
+        Ok(self)
+    }
+
+    /// Serve on a free local port until the returned server is dropped.
+    pub async fn serve(self) -> Result<MockGrpcServer> {
+        self.serve_with_tls(None).await
+    }
+
+    /// Serve over TLS with the given PEM certificate chain and key, e.g. to test client TLS
+    /// settings.
+    pub async fn serve_tls(self, cert_pem: &[u8], key_pem: &[u8]) -> Result<MockGrpcServer> {
+        let tls = ServerTlsConfig::new().identity(Identity::from_pem(cert_pem, key_pem));
+        self.serve_with_tls(Some(tls)).await
+    }
+
+    async fn serve_with_tls(self, tls: Option<ServerTlsConfig>) -> Result<MockGrpcServer> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let get_object_calls = self.get_object_calls.clone();
+        let batch_get_objects_calls = self.batch_get_objects_calls.clone();
+        let request_metadata = self.request_metadata.clone();
+        let scheme = if tls.is_some() { "https" } else { "http" };
+        let mut builder = tonic::transport::Server::builder();
+        if let Some(tls) = tls {
+            builder = builder.tls_config(tls)?;
+        }
+        let handle = tokio::spawn(async move {
+            let _ = builder
+                .add_service(LedgerServiceServer::new(self))
+                .serve_with_incoming(TcpListenerStream::new(listener))
+                .await;
+        });
+        Ok(MockGrpcServer {
+            addr,
+            scheme,
+            handle,
+            get_object_calls,
+            batch_get_objects_calls,
+            request_metadata,
+        })
+    }
+}
+
+/// Running [MockLedgerService], stopped on drop.
+pub struct MockGrpcServer {
+    addr: SocketAddr,
+    scheme: &'static str,
+    handle: JoinHandle<()>,
+    get_object_calls: Arc<AtomicUsize>,
+    batch_get_objects_calls: Arc<AtomicUsize>,
+    request_metadata: Arc<Mutex<Vec<MetadataMap>>>,
+}
+
+impl MockGrpcServer {
+    /// URL of the server, with https if served over TLS.
+    pub fn url(&self) -> String {
+        format!("{}://{}", self.scheme, self.addr)
+    }
+
+    /// gRPC client connected to this server, with the default settings. A server started with
+    /// [MockLedgerService::serve_tls] needs a client trusting its CA instead.
+    pub fn client(&self) -> Result<Client> {
+        create_grpc_client_with_url(&self.url())
+    }
+
+    /// Number of `GetObject` requests received so far, including failed ones.
+    pub fn get_object_calls(&self) -> usize {
+        self.get_object_calls.load(Ordering::SeqCst)
+    }
+
+    /// Number of `BatchGetObjects` requests received so far, including failed ones.
+    pub fn batch_get_objects_calls(&self) -> usize {
+        self.batch_get_objects_calls.load(Ordering::SeqCst)
+    }
+
+    /// Metadata of the `GetObject` and `BatchGetObjects` requests received so far, in order.
+    pub fn request_metadata(&self) -> Vec<MetadataMap> {
+        self.request_metadata.lock().unwrap().clone()
+    }
+}
+
+impl Drop for MockGrpcServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+impl MockLedgerService {
+    /// Record a request and wait or fail as configured.
+    async fn receive<T>(&self, request: &Request<T>, calls: &AtomicUsize) -> Result<(), Status> {
+        calls.fetch_add(1, Ordering::SeqCst);
+        self.request_metadata
+            .lock()
+            .unwrap()
+            .push(request.metadata().clone());
+        tokio::time::sleep(self.delay).await;
+        match self.failures.lock().unwrap().pop_front() {
+            Some(code) => Err(Status::new(code, "Injected failure")),
+            None => Ok(()),
+        }
+    }
+
+    /// The object with the given ID, with only its ID, version and BCS set. Each object has only
+    /// the version in its BCS, so requests for any other version fail.
+    fn object(&self, object_id: Option<String>, version: Option<u64>) -> Result<Object, Status> {
+        let object_id = object_id.ok_or_else(|| Status::invalid_argument("Missing object_id"))?;
+        let object_id = Address::from_str(&object_id)
+            .map_err(|e| Status::invalid_argument(format!("Invalid object_id: {e}")))?;
+        let bcs = self
+            .objects
+            .get(&object_id)
+            .ok_or_else(|| Status::not_found(format!("Object {object_id} not found")))?;
+
+        let object_version = bcs::from_bytes::<sui_sdk_types::Object>(bcs)
+            .ok()
+            .map(|object| object.version());
+        if let Some(version) = version.filter(|version| object_version != Some(*version)) {
+            return Err(Status::not_found(format!(
+                "Object {object_id} not found at version {version}"
+            )));
+        }
+
+        let mut object_bcs = Bcs::default();
+        object_bcs.value = Some(bcs.clone().into());
+        let mut object = Object::default();
+        object.object_id = Some(object_id.to_string());
+        object.version = object_version;
+        object.bcs = Some(object_bcs);
+        Ok(object)
+    }
+}
+
+#[tonic::async_trait]
+impl LedgerService for MockLedgerService {
+    async fn get_service_info(
+        &self,
+        _request: Request<GetServiceInfoRequest>,
+    ) -> Result<Response<GetServiceInfoResponse>, Status> {
+        Err(Status::unimplemented("get_service_info"))
+    }
+
+    async fn get_object(
+        &self,
+        request: Request<GetObjectRequest>,
+    ) -> Result<Response<GetObjectResponse>, Status> {
+        self.receive(&request, &self.get_object_calls).await?;
+        let mut response = GetObjectResponse::default();
+        let request = request.into_inner();
+        response.object = Some(self.object(request.object_id, request.version)?);
+        Ok(Response::new(response))
+    }
+
+    /// Answers with one result per requested object, with an error status for missing objects.
+    async fn batch_get_objects(
+        &self,
+        request: Request<BatchGetObjectsRequest>,
+    ) -> Result<Response<BatchGetObjectsResponse>, Status> {
+        if self.batch_disabled {
+            return Err(Status::unimplemented("batch_get_objects"));
+        }
+        self.receive(&request, &self.batch_get_objects_calls)
+            .await?;
+        let mut response = BatchGetObjectsResponse::default();
+        response.objects = request
+            .into_inner()
+            .requests
+            .into_iter()
+            .map(|request| {
+                let mut result = GetObjectResult::default();
+                result.result = Some(match self.object(request.object_id, request.version) {
+                    Ok(object) => get_object_result::Result::Object(object),
+                    Err(status) => {
+                        let mut error = RpcStatus::default();
+                        error.code = status.code() as i32;
+                        error.message = status.message().to_string();
+                        get_object_result::Result::Error(error)
+                    }
+                });
+                result
+            })
+            .collect();
+        Ok(Response::new(response))
+    }
+
+    async fn get_transaction(
+        &self,
+        _request: Request<GetTransactionRequest>,
+    ) -> Result<Response<GetTransactionResponse>, Status> {
+        Err(Status::unimplemented("get_transaction"))
+    }
+
+    async fn batch_get_transactions(
+        &self,
+        _request: Request<BatchGetTransactionsRequest>,
+    ) -> Result<Response<BatchGetTransactionsResponse>, Status> {
+        Err(Status::unimplemented("batch_get_transactions"))
+    }
+
+    async fn get_checkpoint(
+        &self,
+        _request: Request<GetCheckpointRequest>,
+    ) -> Result<Response<GetCheckpointResponse>, Status> {
+        Err(Status::unimplemented("get_checkpoint"))
+    }
+
+    async fn get_epoch(
+        &self,
+        _request: Request<GetEpochRequest>,
+    ) -> Result<Response<GetEpochResponse>, Status> {
+        Err(Status::unimplemented("get_epoch"))
+    }
+}
@@ -3,20 +3,41 @@
 
 //! Move struct definitions and parsers.
 
+use crate::errors::{KeyMismatch, RegistrationMismatch, SealCommitteeError, ValidationIssue};
 use anyhow::{anyhow, Result};
 use fastcrypto::bls12381::min_sig::BLS12381PublicKey;
 use fastcrypto::encoding::{Encoding, Hex};
 use fastcrypto::groups::bls12381::G2Element;
+use fastcrypto::hash::{Blake2b256, HashFunction};
 use fastcrypto_tbls::ecies_v1::PublicKey;
+use indexmap::IndexMap;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::OnceLock;
 use sui_sdk_types::Address;
-use sui_types::collection_types::VecSet;
+use sui_types::collection_types::{Entry, VecSet};
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
 pub struct VecMap<K, V>(pub sui_types::collection_types::VecMap<K, V>);
 
-#[derive(Deserialize, Debug)]
+impl<K: PartialEq, V> VecMap<K, V> {
+    /// Value of `key`, found by a linear scan, which is fine for the sizes of committees.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.0
+            .contents
+            .iter()
+            .find(|entry| entry.key == *key)
+            .map(|entry| &entry.value)
+    }
+
+    /// Whether the map has an entry for `key`, see [Self::get].
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
 pub struct KeyServerV2 {
     pub name: String,
     pub key_type: u8,
@@ -24,14 +45,14 @@ pub struct KeyServerV2 {
     pub server_type: ServerType,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
 pub struct KeyServer {
     pub id: Address,
     pub first_version: u64,
     pub last_version: u64,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
 pub enum ServerType {
     Independent {
         url: String,
@@ -43,42 +64,168 @@ pub enum ServerType {
     },
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
 pub struct PartialKeyServer {
-    #[serde(deserialize_with = "deserialize_move_bytes")]
+    #[serde(
+        deserialize_with = "deserialize_move_bytes",
+        serialize_with = "serialize_move_bytes"
+    )]
     pub partial_pk: Vec<u8>,
     pub url: String,
     pub party_id: u16,
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
 pub struct Wrapper<T> {
     pub name: T,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
 pub struct Field<K, V> {
     pub id: Address,
     pub name: K,
     pub value: V,
 }
 
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
 pub struct PartialKeyServerInfo {
     pub ks_obj_id: Address,
     pub party_id: u16,
     pub partial_pk: G2Element,
 }
 
-#[derive(Deserialize, Debug)]
+/// Serving URL of a committee member, with its party ID for ordering. See
+/// [KeyServerV2::member_urls] and [SealCommittee::member_urls].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemberUrl {
+    pub party_id: u16,
+    pub url: String,
+}
+
+impl KeyServerV2 {
+    /// URLs of the partial key servers by member address, or an error if the key server is not of
+    /// type Committee or a URL is not an http(s) URL.
+    pub fn member_urls(&self) -> Result<HashMap<Address, MemberUrl>, SealCommitteeError> {
+        let ServerType::Committee {
+            partial_key_servers,
+            ..
+        } = &self.server_type
+        else {
+            return Err(anyhow!("KeyServer is not of type Committee").into());
+        };
+        partial_key_servers
+            .0
+            .contents
+            .iter()
+            .map(|entry| {
+                check_http_url(&entry.key, &entry.value.url)?;
+                Ok((
+                    entry.key,
+                    MemberUrl {
+                        party_id: entry.value.party_id,
+                        url: entry.value.url.clone(),
+                    },
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Check that the URL registered by `member` is an http(s) URL with a host.
+fn check_http_url(member: &Address, url: &str) -> Result<(), SealCommitteeError> {
+    let host = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .and_then(|rest| rest.split(['/', '?', '#']).next())
+        .unwrap_or_default();
+    if host.is_empty() || url.chars().any(char::is_whitespace) {
+        return Err(anyhow!("Invalid URL of member {member}: {url:?}").into());
+    }
+    Ok(())
+}
+
+/// Kind of a [ServerType], without its data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ServerTypeKind {
+    Independent,
+    Committee,
+}
+
+impl From<&ServerType> for ServerTypeKind {
+    fn from(server_type: &ServerType) -> Self {
+        match server_type {
+            ServerType::Independent { .. } => ServerTypeKind::Independent,
+            ServerType::Committee { .. } => ServerTypeKind::Committee,
+        }
+    }
+}
+
+/// Result of [crate::fetch_partial_key_server_info]. `infos` is only set for committee key
+/// servers, and maps member addresses to their partial key server info.
+pub struct PartialKeyServerInfoResult {
+    pub server_type: ServerTypeKind,
+    pub infos: Option<HashMap<Address, PartialKeyServerInfo>>,
+}
+
+impl PartialKeyServerInfoResult {
+    /// Extract the partial key server infos from the data of the KeyServer object `ks_obj_id`.
+    pub fn new(ks_obj_id: Address, key_server_v2: KeyServerV2) -> Result<Self, SealCommitteeError> {
+        let server_type = ServerTypeKind::from(&key_server_v2.server_type);
+
+        // Extract partial key servers from ServerType::Committee.
+        let infos = match key_server_v2.server_type {
+            ServerType::Committee {
+                partial_key_servers,
+                ..
+            } => Some(
+                partial_key_servers
+                    .0
+                    .contents
+                    .into_iter()
+                    .map(|entry| {
+                        let partial_pk = bcs::from_bytes(&entry.value.partial_pk)
+                            .map_err(|e| SealCommitteeError::deserialization("partial PK", e))?;
+                        Ok((
+                            entry.key,
+                            PartialKeyServerInfo {
+                                ks_obj_id,
+                                party_id: entry.value.party_id,
+                                partial_pk,
+                            },
+                        ))
+                    })
+                    .collect::<Result<HashMap<_, _>, SealCommitteeError>>()?,
+            ),
+            ServerType::Independent { .. } => None,
+        };
+        Ok(Self { server_type, infos })
+    }
+
+    /// The partial key server infos, or an error if the key server is not of type Committee.
+    pub fn into_committee_infos(
+        self,
+    ) -> Result<HashMap<Address, PartialKeyServerInfo>, SealCommitteeError> {
+        self.infos
+            .ok_or_else(|| anyhow!("KeyServer is not of type Committee").into())
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
 pub struct MemberInfo {
-    #[serde(deserialize_with = "deserialize_enc_pk")]
+    #[serde(
+        deserialize_with = "deserialize_enc_pk",
+        serialize_with = "serialize_move_bcs"
+    )]
     pub enc_pk: PublicKey<G2Element>,
-    #[serde(deserialize_with = "deserialize_signing_pk")]
+    #[serde(
+        deserialize_with = "deserialize_signing_pk",
+        serialize_with = "serialize_move_bcs"
+    )]
     pub signing_pk: BLS12381PublicKey,
     pub url: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
 pub enum CommitteeState {
     Init {
         members_info: VecMap<Address, MemberInfo>,
@@ -86,101 +233,425 @@ pub enum CommitteeState {
     PostDKG {
         members_info: VecMap<Address, MemberInfo>,
         partial_pks: Vec<Vec<u8>>,
-        #[serde(deserialize_with = "deserialize_move_bytes")]
+        #[serde(
+            deserialize_with = "deserialize_move_bytes",
+            serialize_with = "serialize_move_bytes"
+        )]
         pk: Vec<u8>,
         approvals: VecSet<Address>,
     },
     Finalized,
 }
 
-#[derive(Deserialize, Debug)]
+impl MemberInfo {
+    pub fn new(
+        enc_pk: PublicKey<G2Element>,
+        signing_pk: BLS12381PublicKey,
+        url: impl Into<String>,
+    ) -> Self {
+        Self {
+            enc_pk,
+            signing_pk,
+            url: url.into(),
+        }
+    }
+}
+
+impl CommitteeState {
+    /// Name of the state, as in [SealCommitteeError::InvalidState].
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Init { .. } => "Init",
+            Self::PostDKG { .. } => "PostDKG",
+            Self::Finalized => "Finalized",
+        }
+    }
+}
+
+/// Committee object of the Seal package. Create it with [SealCommittee::new] or by deserializing
+/// the object. The members are indexed by address on the first lookup, see
+/// [SealCommittee::membership], so they must not change afterwards.
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
 pub struct SealCommittee {
     pub id: Address,
     pub threshold: u16,
     pub members: Vec<Address>,
     pub state: CommitteeState,
     pub old_committee_id: Option<Address>,
+    #[serde(skip)]
+    membership: MembershipCache,
 }
 
+/// Index of the members of a [SealCommittee], built once. Not part of the Move struct, so it is
+/// skipped by serde and equal for all committees.
+#[derive(Debug, Default)]
+struct MembershipCache(OnceLock<CommitteeMembership>);
+
+impl PartialEq for MembershipCache {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+/// Members of a committee indexed by address, in party ID order. A member listed more than once
+/// has the party ID of its first occurrence, as with [SealCommittee::get_party_id].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CommitteeMembership {
+    party_ids: IndexMap<Address, u16>,
+    members: Vec<Address>,
+}
+
+impl CommitteeMembership {
+    /// Check if `addr` is a member.
+    pub fn contains(&self, addr: &Address) -> bool {
+        self.party_ids.contains_key(addr)
+    }
+
+    /// Party ID of the member `addr`, if it is a member.
+    pub fn party_id(&self, addr: &Address) -> Option<u16> {
+        self.party_ids.get(addr).copied()
+    }
+
+    /// Address of the member with the given party ID.
+    pub fn address(&self, party_id: u16) -> Option<&Address> {
+        self.members.get(party_id as usize)
+    }
+
+    /// Number of members.
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Check if there are no members.
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// Members of both `self` and `other`, ordered by party ID in `self`.
+    pub fn intersection(&self, other: &Self) -> Vec<Address> {
+        self.party_ids
+            .keys()
+            .filter(|addr| other.contains(addr))
+            .copied()
+            .collect()
+    }
+}
+
+/// Domain separator of [SealCommittee::digest].
+const COMMITTEE_DIGEST_DOMAIN: &[u8] = b"SealCommittee::";
+
+/// Encoding of a committee hashed by [SealCommittee::digest].
+#[derive(Serialize)]
+struct CanonicalCommittee<'a> {
+    id: &'a Address,
+    threshold: u16,
+    members: Vec<CanonicalMember<'a>>,
+    old_committee_id: &'a Option<Address>,
+}
+
+#[derive(Serialize)]
+struct CanonicalMember<'a> {
+    address: &'a Address,
+    enc_pk: Vec<u8>,
+    signing_pk: Vec<u8>,
+}
+
+#[cfg(any(test, feature = "test-utils"))]
 impl SealCommittee {
-    /// Get party ID (index in the members list) for a given member address.
-    pub fn get_party_id(&self, member_addr: &Address) -> Result<u16> {
-        self.members
+    /// Committee in Init state without registered members, for tests. Add members info with
+    /// [Self::with_member_info].
+    pub fn new_for_testing(id: Address, threshold: u16, members: Vec<Address>) -> Self {
+        Self::new(
+            id,
+            threshold,
+            members,
+            CommitteeState::Init {
+                members_info: VecMap(sui_types::collection_types::VecMap { contents: vec![] }),
+            },
+            None,
+        )
+    }
+
+    /// Register `info` for `member`, replacing an earlier registration. Panics unless the
+    /// committee is in Init or PostDKG state.
+    pub fn with_member_info(mut self, member: Address, info: MemberInfo) -> Self {
+        let (CommitteeState::Init { members_info } | CommitteeState::PostDKG { members_info, .. }) =
+            &mut self.state
+        else {
+            panic!("Committee {} has no members info", self.id);
+        };
+        let contents = &mut members_info.0.contents;
+        contents.retain(|entry| entry.key != member);
+        contents.push(Entry {
+            key: member,
+            value: info,
+        });
+        self
+    }
+
+    pub fn with_state(mut self, state: CommitteeState) -> Self {
+        self.state = state;
+        self
+    }
+
+    pub fn with_old_committee_id(mut self, old_committee_id: Address) -> Self {
+        self.old_committee_id = Some(old_committee_id);
+        self
+    }
+}
+
+impl SealCommittee {
+    pub fn new(
+        id: Address,
+        threshold: u16,
+        members: Vec<Address>,
+        state: CommitteeState,
+        old_committee_id: Option<Address>,
+    ) -> Self {
+        Self {
+            id,
+            threshold,
+            members,
+            state,
+            old_committee_id,
+            membership: MembershipCache::default(),
+        }
+    }
+
+    /// Digest of the committee as all parties should see it, e.g. to check that they work with
+    /// the same committee. It is the Blake2b256 hash of `b"SealCommittee::"` followed by the BCS
+    /// bytes of the Move struct
+    ///
+    /// ```move
+    /// struct CanonicalCommittee {
+    ///     id: address,
+    ///     threshold: u16,
+    ///     members: vector<CanonicalMember>,
+    ///     old_committee_id: Option<address>,
+    /// }
+    ///
+    /// struct CanonicalMember {
+    ///     address: address,
+    ///     enc_pk: vector<u8>,
+    ///     signing_pk: vector<u8>,
+    /// }
+    /// ```
+    ///
+    /// with the members in party ID order. `enc_pk` and `signing_pk` are the registered keys, i.e.
+    /// the decoded byte literals of the member's `MemberInfo`, and empty if the member has not
+    /// registered. The state, URLs, approvals and the proposed keys are not covered, so the digest
+    /// is the same in Init and PostDKG state. A finalized committee has no members info onchain,
+    /// so its digest covers no keys.
+    pub fn digest(&self) -> Result<[u8; 32], SealCommitteeError> {
+        let members_info = match &self.state {
+            CommitteeState::Init { members_info }
+            | CommitteeState::PostDKG { members_info, .. } => Some(members_info),
+            CommitteeState::Finalized => None,
+        };
+        let members = self
+            .members
             .iter()
-            .position(|addr| addr == member_addr)
-            .map(|idx| idx as u16) // safe because length is limited by u16.
-            .ok_or_else(|| {
-                anyhow!(
-                    "Member address {} not found in committee {}",
-                    member_addr,
-                    self.id
-                )
+            .map(|address| {
+                let info = members_info.and_then(|info| info.get(address));
+                Ok(CanonicalMember {
+                    address,
+                    enc_pk: info
+                        .map(|info| bcs::to_bytes(&info.enc_pk))
+                        .transpose()
+                        .map_err(|e| SealCommitteeError::serialization("ECIES PK", e))?
+                        .unwrap_or_default(),
+                    signing_pk: info
+                        .map(|info| bcs::to_bytes(&info.signing_pk))
+                        .transpose()
+                        .map_err(|e| SealCommitteeError::serialization("signing PK", e))?
+                        .unwrap_or_default(),
+                })
             })
+            .collect::<Result<_, SealCommitteeError>>()?;
+        let canonical = CanonicalCommittee {
+            id: &self.id,
+            threshold: self.threshold,
+            members,
+            old_committee_id: &self.old_committee_id,
+        };
+        let mut hasher = Blake2b256::default();
+        hasher.update(COMMITTEE_DIGEST_DOMAIN);
+        hasher.update(
+            bcs::to_bytes(&canonical)
+                .map_err(|e| SealCommitteeError::serialization("committee", e))?,
+        );
+        Ok(hasher.finalize().digest)
+    }
+
+    /// Members indexed by address, built on the first call and reused by [Self::get_party_id]
+    /// and [Self::contains].
+    pub fn membership(&self) -> &CommitteeMembership {
+        self.membership.0.get_or_init(|| {
+            let mut party_ids = IndexMap::with_capacity(self.members.len());
+            for (party_id, addr) in self.members.iter().enumerate() {
+                // Safe because length is limited by u16.
+                party_ids.entry(*addr).or_insert(party_id as u16);
+            }
+            CommitteeMembership {
+                party_ids,
+                members: self.members.clone(),
+            }
+        })
+    }
+
+    /// Get party ID (index in the members list) for a given member address, or
+    /// [SealCommitteeError::NotAMember] if it is not a member, e.g. to check that the local address
+    /// can take part in a ceremony.
+    pub fn get_party_id(&self, member_addr: &Address) -> Result<u16, SealCommitteeError> {
+        self.membership()
+            .party_id(member_addr)
+            .ok_or(SealCommitteeError::NotAMember {
+                committee_id: self.id,
+                address: *member_addr,
+            })
+    }
+
+    /// Check the structural invariants of the committee, independent of its state: a threshold
+    /// between 1 and the number of members, distinct members that fit in u16 party IDs, keys of
+    /// every member while they are onchain (Init and PostDKG state), and no rotation from itself.
+    /// Returns every broken invariant, not only the first.
+    ///
+    /// Members registering over time is normal in Init state, so call this once all members are
+    /// expected to be registered, e.g. before creating DKG messages.
+    pub fn validate(&self) -> Result<(), Vec<ValidationIssue>> {
+        let mut issues = vec![];
+        let num_members = self.members.len();
+        if self.threshold == 0 {
+            issues.push(ValidationIssue::ZeroThreshold);
+        }
+        if self.threshold as usize > num_members {
+            issues.push(ValidationIssue::ThresholdExceedsMembers {
+                threshold: self.threshold,
+                num_members,
+            });
+        }
+        if num_members > u16::MAX as usize {
+            issues.push(ValidationIssue::TooManyMembers { num_members });
+        }
+
+        let mut first_party_ids = HashMap::new();
+        for (party_id, address) in self.members.iter().enumerate() {
+            let first = *first_party_ids.entry(address).or_insert(party_id);
+            if first != party_id {
+                issues.push(ValidationIssue::DuplicateMember {
+                    address: *address,
+                    first,
+                    second: party_id,
+                });
+            }
+        }
+
+        if let CommitteeState::Init { members_info }
+        | CommitteeState::PostDKG { members_info, .. } = &self.state
+        {
+            issues.extend(
+                self.members
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, address)| !members_info.contains_key(address))
+                    .map(|(party_id, address)| ValidationIssue::MemberNotRegistered {
+                        address: *address,
+                        party_id,
+                    }),
+            );
+        }
+
+        if self.old_committee_id == Some(self.id) {
+            issues.push(ValidationIssue::SelfRotation { id: self.id });
+        }
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(issues)
+        }
     }
 
     /// Check if committee is in Init state, returns error if not.
-    pub fn is_init(&self) -> Result<()> {
+    pub fn is_init(&self) -> Result<(), SealCommitteeError> {
         if !matches!(self.state, CommitteeState::Init { .. }) {
-            return Err(anyhow!(
-                "Committee {} is not in Init state. Current state: {:?}",
-                self.id,
-                self.state
-            ));
+            return Err(self.invalid_state("Init"));
         }
         Ok(())
     }
 
     /// Check if committee is in Finalized state, returns error if not.
-    pub fn is_finalized(&self) -> Result<()> {
+    pub fn is_finalized(&self) -> Result<(), SealCommitteeError> {
         if !matches!(self.state, CommitteeState::Finalized) {
+            return Err(self.invalid_state("Finalized"));
+        }
+        Ok(())
+    }
+
+    /// Select exactly `threshold` party IDs, e.g. for choosing which partial signatures to
+    /// aggregate. Parties in `prefer_parties` are taken first, in the given order, and the rest is
+    /// filled with the lowest remaining party IDs. Returns the party IDs in ascending order.
+    pub fn subset_for_threshold(
+        &self,
+        prefer_parties: &[u16],
+    ) -> Result<Vec<u16>, SealCommitteeError> {
+        let num_members = self.members.len();
+        if self.threshold as usize > num_members {
+            return Err(SealCommitteeError::ThresholdExceedsMembers {
+                threshold: self.threshold,
+                members: num_members,
+            });
+        }
+        if let Some(invalid) = prefer_parties
+            .iter()
+            .find(|party_id| **party_id as usize >= num_members)
+        {
             return Err(anyhow!(
-                "Committee {} is not in Finalized state. Current state: {:?}",
+                "Invalid party ID {} for committee {} with {} members",
+                invalid,
                 self.id,
-                self.state
-            ));
+                num_members
+            )
+            .into());
         }
-        Ok(())
+
+        let mut subset = BTreeSet::new();
+        for party_id in prefer_parties.iter().copied().chain(0..num_members as u16) {
+            if subset.len() == self.threshold as usize {
+                break;
+            }
+            subset.insert(party_id);
+        }
+        Ok(subset.into_iter().collect())
     }
 
     /// Check if the committee contains a specific member.
     pub fn contains(&self, member_addr: &Address) -> bool {
-        self.members.contains(member_addr)
+        self.membership().contains(member_addr)
     }
 
     /// Extract members' info and return a HashMap mapping address to ParsedMemberInfo.
-    pub fn get_members_info(&self) -> Result<HashMap<Address, ParsedMemberInfo>> {
+    pub fn get_members_info(
+        &self,
+    ) -> Result<HashMap<Address, ParsedMemberInfo>, SealCommitteeError> {
         // Extract candidate data from Init state
         let members_info = match &self.state {
             CommitteeState::Init { members_info } => members_info,
             CommitteeState::PostDKG { members_info, .. } => members_info,
-            _ => {
-                return Err(anyhow!(
-                    "Invalid committee state {}: {:?}",
-                    self.id,
-                    self.state
-                ));
-            }
+            _ => return Err(self.invalid_state("Init or PostDKG")),
         };
 
-        let info_map: HashMap<_, _> = members_info
-            .0
-            .contents
-            .iter()
-            .map(|entry| (&entry.key, &entry.value))
-            .collect();
-
         // Party ID is the index in self.members.
         self.members
             .iter()
             .enumerate()
             .map(|(party_id, member_addr)| {
-                let info = info_map.get(member_addr).ok_or_else(|| {
-                    anyhow!(
-                        "Member {} not registered in committee {}. Do not init DKG before all members register.",
-                        member_addr,
-                        self.id
-                    )
-                })?;
+                let Some(info) = members_info.get(member_addr) else {
+                    return Err(SealCommitteeError::MemberNotRegistered {
+                        committee_id: self.id,
+                        address: *member_addr,
+                    });
+                };
 
                 Ok((
                     *member_addr,
@@ -194,6 +665,312 @@ impl SealCommittee {
             })
             .collect()
     }
+
+    /// Extract members' info along with their proposed partial public keys. Only valid in
+    /// PostDKG state, returns a HashMap mapping address to (ParsedMemberInfo, partial PK).
+    pub fn get_members_info_for_postdkg(
+        &self,
+    ) -> Result<HashMap<Address, (ParsedMemberInfo, G2Element)>, SealCommitteeError> {
+        let CommitteeState::PostDKG { partial_pks, .. } = &self.state else {
+            return Err(self.invalid_state("PostDKG"));
+        };
+
+        self.get_members_info()?
+            .into_iter()
+            .map(|(addr, info)| {
+                let party_id = info.party_id;
+                let partial_pk_bytes = partial_pks.get(party_id as usize).ok_or_else(|| {
+                    SealCommitteeError::InvalidPartialKey {
+                        party_id,
+                        source: anyhow!("No partial PK proposed"),
+                    }
+                })?;
+                let partial_pk = parse_partial_pk::<G2Element>(party_id, partial_pk_bytes)?;
+                Ok((addr, (info, partial_pk)))
+            })
+            .collect()
+    }
+
+    /// The parsed data proposed by DKG: the aggregated public key, the partial public keys of all
+    /// members and the members that approved. Only valid in PostDKG state.
+    pub fn get_postdkg_info(&self) -> Result<PostDkgInfo, SealCommitteeError> {
+        let CommitteeState::PostDKG {
+            partial_pks,
+            approvals,
+            ..
+        } = &self.state
+        else {
+            return Err(self.invalid_state("PostDKG"));
+        };
+        if partial_pks.len() != self.members.len() {
+            return Err(anyhow!(
+                "Committee {} has {} partial PKs for {} members",
+                self.id,
+                partial_pks.len(),
+                self.members.len()
+            )
+            .into());
+        }
+
+        let partial_pks = self
+            .members
+            .iter()
+            .zip(partial_pks)
+            .enumerate()
+            .map(|(party_id, (address, partial_pk_bytes))| {
+                let party_id = party_id as u16;
+                let partial_pk = parse_partial_pk::<G2Element>(party_id, partial_pk_bytes)?;
+                Ok(PostDkgPartialPk {
+                    party_id,
+                    address: *address,
+                    partial_pk,
+                })
+            })
+            .collect::<Result<_, SealCommitteeError>>()?;
+        Ok(PostDkgInfo {
+            pk: self.get_postdkg_pk()?,
+            partial_pks,
+            approvals: approvals.contents.iter().copied().collect(),
+        })
+    }
+
+    /// Partial public keys proposed by DKG by party ID, in the key group `K` of the DKG, e.g. to
+    /// verify a DKG whose key server is not of key type G2. Only valid in PostDKG state.
+    pub fn get_postdkg_partial_pks<K: DeserializeOwned>(
+        &self,
+    ) -> Result<HashMap<u16, K>, SealCommitteeError> {
+        let CommitteeState::PostDKG { partial_pks, .. } = &self.state else {
+            return Err(self.invalid_state("PostDKG"));
+        };
+        if partial_pks.len() != self.members.len() {
+            return Err(anyhow!(
+                "Committee {} has {} partial PKs for {} members",
+                self.id,
+                partial_pks.len(),
+                self.members.len()
+            )
+            .into());
+        }
+        partial_pks
+            .iter()
+            .enumerate()
+            .map(|(party_id, partial_pk_bytes)| {
+                let party_id = party_id as u16;
+                Ok((party_id, parse_partial_pk(party_id, partial_pk_bytes)?))
+            })
+            .collect()
+    }
+
+    /// Raw bytes of the aggregated public key proposed by DKG, e.g. for hashing. Only valid in
+    /// PostDKG state.
+    pub fn get_postdkg_pk_bytes(&self) -> Result<&[u8], SealCommitteeError> {
+        let CommitteeState::PostDKG { pk, .. } = &self.state else {
+            return Err(self.invalid_state("PostDKG"));
+        };
+        Ok(pk)
+    }
+
+    /// Aggregated public key proposed by DKG. Only valid in PostDKG state.
+    pub fn get_postdkg_pk(&self) -> Result<G2Element, SealCommitteeError> {
+        bcs::from_bytes(self.get_postdkg_pk_bytes()?).map_err(|source| self.invalid_key(source))
+    }
+
+    /// Aggregated public key of the committee, from the committee object in PostDKG state or from
+    /// its key server once Finalized. `key_server` is the KeyServerV2 of this committee and is
+    /// only required in Finalized state, see [crate::grpc_helper::fetch_committee_pk].
+    pub fn get_committee_pk(
+        &self,
+        key_server: Option<&KeyServerV2>,
+    ) -> Result<G2Element, SealCommitteeError> {
+        match &self.state {
+            CommitteeState::Init { .. } => Err(self.invalid_state("PostDKG or Finalized")),
+            CommitteeState::PostDKG { .. } => self.get_postdkg_pk(),
+            CommitteeState::Finalized => {
+                let key_server = key_server.ok_or_else(|| {
+                    anyhow!(
+                        "Committee {} is finalized, its PK is on the key server",
+                        self.id
+                    )
+                })?;
+                bcs::from_bytes(&key_server.pk).map_err(|source| self.invalid_key(source))
+            }
+        }
+    }
+
+    /// Approval status of each member in PostDKG state: `true` if the member has submitted its
+    /// approval onchain. Returns error for other states.
+    pub fn get_approval_status(&self) -> Result<HashMap<Address, bool>, SealCommitteeError> {
+        let CommitteeState::PostDKG { approvals, .. } = &self.state else {
+            return Err(self.invalid_state("PostDKG"));
+        };
+        Ok(self
+            .members
+            .iter()
+            .map(|addr| (*addr, approvals.contents.contains(addr)))
+            .collect())
+    }
+
+    /// Check that `address` is a member that registered exactly `enc_pk` and `signing_pk`, e.g.
+    /// the keys derived from its keys file. Only possible in Init or PostDKG state.
+    pub fn verify_member_registration(
+        &self,
+        address: &Address,
+        enc_pk: &PublicKey<G2Element>,
+        signing_pk: &BLS12381PublicKey,
+    ) -> Result<(), RegistrationMismatch> {
+        let members_info = match &self.state {
+            CommitteeState::Init { members_info }
+            | CommitteeState::PostDKG { members_info, .. } => members_info,
+            CommitteeState::Finalized => {
+                return Err(RegistrationMismatch::NoRegistrations(self.state.name()));
+            }
+        };
+        if !self.contains(address) {
+            return Err(RegistrationMismatch::NotAMember(*address));
+        }
+        let info = members_info
+            .get(address)
+            .ok_or(RegistrationMismatch::NotRegistered(*address))?;
+        let enc_pk = (*enc_pk != info.enc_pk).then(|| key_mismatch(enc_pk, &info.enc_pk));
+        let signing_pk =
+            (*signing_pk != info.signing_pk).then(|| key_mismatch(signing_pk, &info.signing_pk));
+        let address = *address;
+        match (enc_pk, signing_pk) {
+            (None, None) => Ok(()),
+            (Some(enc_pk), None) => Err(RegistrationMismatch::EncPk { address, enc_pk }),
+            (None, Some(signing_pk)) => Err(RegistrationMismatch::SigningPk {
+                address,
+                signing_pk,
+            }),
+            (Some(enc_pk), Some(signing_pk)) => Err(RegistrationMismatch::BothPks {
+                address,
+                enc_pk,
+                signing_pk,
+            }),
+        }
+    }
+
+    /// URLs registered by the members by address, e.g. to check the endpoints before DKG. Only
+    /// possible in Init or PostDKG state, see [KeyServerV2::member_urls] once finalized. Members
+    /// that did not register yet are left out. Fails if a URL is not an http(s) URL.
+    pub fn member_urls(&self) -> Result<HashMap<Address, MemberUrl>, SealCommitteeError> {
+        let members_info = match &self.state {
+            CommitteeState::Init { members_info }
+            | CommitteeState::PostDKG { members_info, .. } => members_info,
+            CommitteeState::Finalized => return Err(self.invalid_state("Init or PostDKG")),
+        };
+        self.members
+            .iter()
+            .enumerate()
+            .filter_map(|(party_id, address)| {
+                members_info
+                    .get(address)
+                    .map(|info| (party_id, address, info))
+            })
+            .map(|(party_id, address, info)| {
+                check_http_url(address, &info.url)?;
+                Ok((
+                    *address,
+                    MemberUrl {
+                        party_id: party_id as u16,
+                        url: info.url.clone(),
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    /// Member information as CSV with a header row and one row per member, ordered by party ID:
+    /// `party_id,address,enc_pk_hex,signing_pk_hex,url,registered`. Keys are hex-encoded BCS.
+    /// A finalized committee no longer stores member info, so its rows only have the party ID and
+    /// the address, and are marked registered as every member registered before DKG.
+    pub fn to_csv(&self) -> Result<String, SealCommitteeError> {
+        let members_info = match &self.state {
+            CommitteeState::Init { members_info }
+            | CommitteeState::PostDKG { members_info, .. } => Some(members_info),
+            CommitteeState::Finalized => None,
+        };
+        let csv_error = |e: csv::Error| SealCommitteeError::Other(e.into());
+        let mut writer = csv::Writer::from_writer(vec![]);
+        writer
+            .write_record([
+                "party_id",
+                "address",
+                "enc_pk_hex",
+                "signing_pk_hex",
+                "url",
+                "registered",
+            ])
+            .map_err(csv_error)?;
+        for (party_id, address) in self.members.iter().enumerate() {
+            let (enc_pk, signing_pk, url, registered) =
+                match members_info.map(|info| info.get(address)) {
+                    Some(Some(info)) => (
+                        Hex::encode_with_format(
+                            bcs::to_bytes(&info.enc_pk)
+                                .map_err(|e| SealCommitteeError::serialization("ECIES PK", e))?,
+                        ),
+                        Hex::encode_with_format(
+                            bcs::to_bytes(&info.signing_pk)
+                                .map_err(|e| SealCommitteeError::serialization("signing PK", e))?,
+                        ),
+                        info.url.clone(),
+                        true,
+                    ),
+                    Some(None) => Default::default(),
+                    None => (String::new(), String::new(), String::new(), true),
+                };
+            writer
+                .write_record([
+                    party_id.to_string(),
+                    address.to_string(),
+                    enc_pk,
+                    signing_pk,
+                    url,
+                    registered.to_string(),
+                ])
+                .map_err(csv_error)?;
+        }
+        let bytes = writer
+            .into_inner()
+            .map_err(|e| SealCommitteeError::Other(e.into_error().into()))?;
+        Ok(String::from_utf8(bytes).map_err(anyhow::Error::from)?)
+    }
+
+    fn invalid_state(&self, expected: &'static str) -> SealCommitteeError {
+        SealCommitteeError::InvalidState {
+            committee_id: self.id,
+            expected,
+            actual: self.state.name(),
+        }
+    }
+
+    fn invalid_key(&self, source: bcs::Error) -> SealCommitteeError {
+        SealCommitteeError::InvalidKey {
+            committee_id: self.id,
+            source,
+        }
+    }
+}
+
+/// Parse the partial PK proposed for `party_id`, a Move byte literal of its BCS bytes, in the key
+/// group `K`.
+fn parse_partial_pk<K: DeserializeOwned>(
+    party_id: u16,
+    bytes: &[u8],
+) -> Result<K, SealCommitteeError> {
+    parse_move_byte_literal(bytes)
+        .and_then(|bytes| Ok(bcs::from_bytes(&bytes)?))
+        .map_err(|source| SealCommitteeError::InvalidPartialKey { party_id, source })
+}
+
+/// Hex of the BCS bytes of a key given for a member and of the key it registered.
+fn key_mismatch<T: Serialize>(given: &T, registered: &T) -> KeyMismatch {
+    let hex = |key: &T| Hex::encode_with_format(bcs::to_bytes(key).expect("Keys serialize to BCS"));
+    KeyMismatch {
+        given: hex(given),
+        registered: hex(registered),
+    }
 }
 
 /// Helper struct storing member info with deserialized public keys.
@@ -204,6 +981,25 @@ pub struct ParsedMemberInfo {
     pub signing_pk: BLS12381PublicKey,
 }
 
+/// Data proposed by DKG for a committee in PostDKG state, see [SealCommittee::get_postdkg_info].
+#[derive(Clone, Debug, PartialEq)]
+pub struct PostDkgInfo {
+    /// Aggregated public key.
+    pub pk: G2Element,
+    /// Partial public keys of all members, ordered by party ID.
+    pub partial_pks: Vec<PostDkgPartialPk>,
+    /// Members that have submitted their approval onchain.
+    pub approvals: BTreeSet<Address>,
+}
+
+/// Partial public key proposed for a member.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PostDkgPartialPk {
+    pub party_id: u16,
+    pub address: Address,
+    pub partial_pk: G2Element,
+}
+
 /// Helper function to parse Move byte literal (x"0x..." or x"...") to decoded bytes.
 fn parse_move_byte_literal(bytes: &[u8]) -> Result<Vec<u8>> {
     let str = String::from_utf8(bytes.to_vec())
@@ -250,3 +1046,731 @@ macro_rules! move_bytes_deserializer {
 move_bytes_deserializer!(deserialize_move_bytes, Vec<u8>);
 move_bytes_deserializer!(deserialize_enc_pk, PublicKey<G2Element>);
 move_bytes_deserializer!(deserialize_signing_pk, BLS12381PublicKey);
+
+/// Encode bytes as a Move byte literal in the format used onchain, e.g. `x0x0102`. The inverse of
+/// [parse_move_byte_literal].
+fn move_byte_literal(bytes: &[u8]) -> Vec<u8> {
+    format!("x{}", Hex::encode_with_format(bytes)).into_bytes()
+}
+
+/// Serialize bytes as a Move byte literal, for the fields read with `deserialize_move_bytes`.
+fn serialize_move_bytes<S: serde::Serializer>(
+    bytes: &[u8],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    move_byte_literal(bytes).serialize(serializer)
+}
+
+/// Serialize a value as a Move byte literal of its BCS bytes, for the fields read with the other
+/// deserializers generated by [move_bytes_deserializer].
+fn serialize_move_bcs<T: Serialize, S: serde::Serializer>(
+    value: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let bytes = bcs::to_bytes(value).map_err(serde::ser::Error::custom)?;
+    serialize_move_bytes(&bytes, serializer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{addresses, committee, init_committee, members_info};
+    use fastcrypto::groups::bls12381::{G1Element, Scalar};
+    use fastcrypto::groups::GroupElement;
+
+    #[test]
+    fn test_verify_member_registration() {
+        let members = addresses(3);
+        let committee = init_committee(members.clone(), 2);
+        let CommitteeState::Init { members_info } = &committee.state else {
+            unreachable!()
+        };
+        let mine = members_info.get(&members[0]).unwrap();
+        let other = members_info.get(&members[1]).unwrap();
+        let verify = |enc_pk, signing_pk| {
+            committee.verify_member_registration(&members[0], enc_pk, signing_pk)
+        };
+
+        assert_eq!(verify(&mine.enc_pk, &mine.signing_pk), Ok(()));
+
+        let enc_pk = key_mismatch(&other.enc_pk, &mine.enc_pk);
+        assert_eq!(
+            verify(&other.enc_pk, &mine.signing_pk),
+            Err(RegistrationMismatch::EncPk {
+                address: members[0],
+                enc_pk: enc_pk.clone(),
+            })
+        );
+        // Both hex encodings are in the message.
+        let err = verify(&other.enc_pk, &mine.signing_pk)
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("ECIES PK of"), "{err}");
+        assert!(err.contains(&enc_pk.given) && err.contains(&enc_pk.registered));
+        assert_ne!(enc_pk.given, enc_pk.registered);
+
+        let signing_pk = key_mismatch(&other.signing_pk, &mine.signing_pk);
+        assert_eq!(
+            verify(&mine.enc_pk, &other.signing_pk),
+            Err(RegistrationMismatch::SigningPk {
+                address: members[0],
+                signing_pk: signing_pk.clone(),
+            })
+        );
+        assert_eq!(
+            verify(&other.enc_pk, &other.signing_pk),
+            Err(RegistrationMismatch::BothPks {
+                address: members[0],
+                enc_pk,
+                signing_pk,
+            })
+        );
+
+        let outsider = Address::new([9; 32]);
+        assert_eq!(
+            committee.verify_member_registration(&outsider, &mine.enc_pk, &mine.signing_pk),
+            Err(RegistrationMismatch::NotAMember(outsider))
+        );
+        let unregistered = SealCommittee::new_for_testing(Address::ZERO, 2, members.clone());
+        assert_eq!(
+            unregistered.verify_member_registration(&members[0], &mine.enc_pk, &mine.signing_pk),
+            Err(RegistrationMismatch::NotRegistered(members[0]))
+        );
+        let finalized = init_committee(members.clone(), 2).with_state(CommitteeState::Finalized);
+        assert_eq!(
+            finalized.verify_member_registration(&members[0], &mine.enc_pk, &mine.signing_pk),
+            Err(RegistrationMismatch::NoRegistrations("Finalized"))
+        );
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let members = addresses(3);
+        let info = members_info(&members[..1]).0.contents.remove(0).value;
+        let committee = SealCommittee::new_for_testing(Address::ZERO, 2, members.clone())
+            .with_member_info(
+                members[0],
+                MemberInfo::new(
+                    info.enc_pk.clone(),
+                    info.signing_pk.clone(),
+                    "https://seal.example.com/a,b",
+                ),
+            )
+            .with_member_info(
+                members[1],
+                MemberInfo::new(info.enc_pk.clone(), info.signing_pk.clone(), &info.url),
+            );
+
+        let csv = committee.to_csv().unwrap();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(
+            lines[0],
+            "party_id,address,enc_pk_hex,signing_pk_hex,url,registered"
+        );
+        let enc_pk = Hex::encode_with_format(bcs::to_bytes(&info.enc_pk).unwrap());
+        let signing_pk = Hex::encode_with_format(bcs::to_bytes(&info.signing_pk).unwrap());
+        // The URL with a comma is quoted.
+        assert_eq!(
+            lines[1],
+            format!(
+                "0,{},{enc_pk},{signing_pk},\"https://seal.example.com/a,b\",true",
+                members[0]
+            )
+        );
+        assert_eq!(
+            lines[2],
+            format!("1,{},{enc_pk},{signing_pk},{},true", members[1], info.url)
+        );
+        assert_eq!(lines[3], format!("2,{},,,,false", members[2]));
+
+        let finalized = committee.with_state(CommitteeState::Finalized);
+        let csv = finalized.to_csv().unwrap();
+        assert_eq!(
+            csv.lines().nth(3).unwrap(),
+            format!("2,{},,,,true", members[2])
+        );
+    }
+
+    #[test]
+    fn test_validate_valid_committees() {
+        assert_eq!(init_committee(addresses(3), 2).validate(), Ok(()));
+        assert_eq!(init_committee(addresses(1), 1).validate(), Ok(()));
+        let mut rotation = committee(addresses(3), 3, CommitteeState::Finalized);
+        rotation.old_committee_id = Some(Address::new([9; 32]));
+        assert_eq!(rotation.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_threshold() {
+        assert_eq!(
+            init_committee(addresses(3), 0).validate(),
+            Err(vec![ValidationIssue::ZeroThreshold])
+        );
+        assert_eq!(
+            init_committee(addresses(3), 4).validate(),
+            Err(vec![ValidationIssue::ThresholdExceedsMembers {
+                threshold: 4,
+                num_members: 3
+            }])
+        );
+        // No threshold is valid for an empty committee.
+        assert_eq!(
+            committee(vec![], 0, CommitteeState::Finalized).validate(),
+            Err(vec![ValidationIssue::ZeroThreshold])
+        );
+        assert_eq!(
+            committee(vec![], 1, CommitteeState::Finalized).validate(),
+            Err(vec![ValidationIssue::ThresholdExceedsMembers {
+                threshold: 1,
+                num_members: 0
+            }])
+        );
+    }
+
+    #[test]
+    fn test_validate_duplicate_members() {
+        let members = addresses(2);
+        let duplicated = vec![members[0], members[1], members[0], members[0]];
+        assert_eq!(
+            committee(duplicated, 2, CommitteeState::Finalized).validate(),
+            Err(vec![
+                ValidationIssue::DuplicateMember {
+                    address: members[0],
+                    first: 0,
+                    second: 2
+                },
+                ValidationIssue::DuplicateMember {
+                    address: members[0],
+                    first: 0,
+                    second: 3
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_validate_too_many_members() {
+        let members = (0..=u16::MAX as u32)
+            .map(|i| {
+                let mut bytes = [0; 32];
+                bytes[..4].copy_from_slice(&i.to_be_bytes());
+                Address::new(bytes)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            committee(members, 2, CommitteeState::Finalized).validate(),
+            Err(vec![ValidationIssue::TooManyMembers {
+                num_members: u16::MAX as usize + 1
+            }])
+        );
+    }
+
+    #[test]
+    fn test_validate_unregistered_members() {
+        let members = addresses(4);
+        let partial = committee(
+            members.clone(),
+            2,
+            CommitteeState::Init {
+                members_info: members_info(&members[1..3]),
+            },
+        );
+        assert_eq!(
+            partial.validate(),
+            Err(vec![
+                ValidationIssue::MemberNotRegistered {
+                    address: members[0],
+                    party_id: 0
+                },
+                ValidationIssue::MemberNotRegistered {
+                    address: members[3],
+                    party_id: 3
+                },
+            ])
+        );
+
+        let post_dkg = committee(
+            members.clone(),
+            2,
+            CommitteeState::PostDKG {
+                members_info: members_info(&members[..3]),
+                partial_pks: vec![],
+                pk: vec![],
+                approvals: VecSet { contents: vec![] },
+            },
+        );
+        assert_eq!(
+            post_dkg.validate(),
+            Err(vec![ValidationIssue::MemberNotRegistered {
+                address: members[3],
+                party_id: 3
+            }])
+        );
+    }
+
+    #[test]
+    fn test_validate_self_rotation() {
+        let mut rotation = init_committee(addresses(3), 0);
+        rotation.old_committee_id = Some(rotation.id);
+        let issues = rotation.validate().unwrap_err();
+        assert_eq!(
+            issues,
+            vec![
+                ValidationIssue::ZeroThreshold,
+                ValidationIssue::SelfRotation { id: rotation.id }
+            ]
+        );
+        assert_eq!(
+            issues[1].to_string(),
+            format!("Committee {} is its own old committee", Address::ZERO)
+        );
+    }
+
+    fn bcs_round_trip<T: Serialize + serde::de::DeserializeOwned>(value: &T) -> T {
+        bcs::from_bytes(&bcs::to_bytes(value).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_serialize_round_trip() {
+        let members = addresses(3);
+        let committee = members_info(&members).0.contents.into_iter().fold(
+            SealCommittee::new_for_testing(Address::new([9; 32]), 2, members.clone())
+                .with_old_committee_id(Address::new([8; 32])),
+            |committee, entry| committee.with_member_info(entry.key, entry.value),
+        );
+        assert_eq!(committee.get_members_info().unwrap().len(), 3);
+        assert_eq!(bcs_round_trip(&committee), committee);
+
+        let partial_pk = bcs::to_bytes(&G2Element::generator()).unwrap();
+        let post_dkg = committee.with_state(CommitteeState::PostDKG {
+            members_info: members_info(&members),
+            partial_pks: vec![move_byte_literal(&partial_pk)],
+            pk: partial_pk.clone(),
+            approvals: VecSet {
+                contents: vec![members[1]],
+            },
+        });
+        assert_eq!(bcs_round_trip(&post_dkg), post_dkg);
+        let finalized = post_dkg.with_state(CommitteeState::Finalized);
+        assert_eq!(bcs_round_trip(&finalized), finalized);
+
+        let independent = KeyServerV2 {
+            name: "independent".to_string(),
+            key_type: 0,
+            pk: partial_pk.clone(),
+            server_type: ServerType::Independent {
+                url: "https://example.com".to_string(),
+            },
+        };
+        assert_eq!(bcs_round_trip(&independent), independent);
+        let committee_key_server = KeyServerV2 {
+            name: "committee".to_string(),
+            key_type: 1,
+            pk: partial_pk.clone(),
+            server_type: ServerType::Committee {
+                version: 1,
+                threshold: 2,
+                partial_key_servers: VecMap(sui_types::collection_types::VecMap {
+                    contents: vec![Entry {
+                        key: members[0],
+                        value: PartialKeyServer {
+                            partial_pk,
+                            url: "https://party-0.example.com".to_string(),
+                            party_id: 0,
+                        },
+                    }],
+                }),
+            },
+        };
+        assert_eq!(bcs_round_trip(&committee_key_server), committee_key_server);
+    }
+
+    #[test]
+    fn test_member_urls() {
+        let members = addresses(3);
+        let committee = SealCommittee::new_for_testing(Address::ZERO, 2, members.clone());
+        let committee = members_info(&members[1..])
+            .0
+            .contents
+            .into_iter()
+            .fold(committee, |committee, entry| {
+                committee.with_member_info(entry.key, entry.value)
+            });
+        // Member 0 did not register.
+        assert_eq!(
+            committee.member_urls().unwrap(),
+            HashMap::from([
+                (
+                    members[1],
+                    MemberUrl {
+                        party_id: 1,
+                        url: format!("https://{}.example.com", members[1]),
+                    }
+                ),
+                (
+                    members[2],
+                    MemberUrl {
+                        party_id: 2,
+                        url: format!("https://{}.example.com", members[2]),
+                    }
+                ),
+            ])
+        );
+
+        let info = members_info(&members[..1]).0.contents.remove(0).value;
+        for url in [
+            "seal.example.com",
+            "ftp://seal.example.com",
+            "https://",
+            "https://a b",
+        ] {
+            let invalid = SealCommittee::new_for_testing(Address::ZERO, 1, vec![members[0]])
+                .with_member_info(
+                    members[0],
+                    MemberInfo::new(info.enc_pk.clone(), info.signing_pk.clone(), url),
+                );
+            let err = invalid.member_urls().unwrap_err().to_string();
+            assert!(err.contains("Invalid URL of member"), "{err}");
+        }
+        assert!(committee
+            .with_state(CommitteeState::Finalized)
+            .member_urls()
+            .is_err());
+    }
+
+    #[test]
+    fn test_key_server_member_urls() {
+        let bytes =
+            Hex::decode(include_str!("../tests/fixtures/key_server_v2_committee.hex").trim())
+                .unwrap();
+        let key_server = bcs::from_bytes::<Field<u64, KeyServerV2>>(&bytes)
+            .unwrap()
+            .value;
+        let mut urls: Vec<_> = key_server.member_urls().unwrap().into_values().collect();
+        urls.sort_by_key(|member| member.party_id);
+        assert_eq!(
+            urls,
+            (0..3)
+                .map(|party_id| MemberUrl {
+                    party_id,
+                    url: format!("https://seal-{party_id}.example.com"),
+                })
+                .collect::<Vec<_>>()
+        );
+
+        let independent = KeyServerV2 {
+            server_type: ServerType::Independent {
+                url: "https://seal.example.com".to_string(),
+            },
+            ..key_server
+        };
+        assert!(independent.member_urls().is_err());
+    }
+
+    #[test]
+    fn test_vec_map_lookup() {
+        let members = addresses(3);
+        let info = members_info(&members[..2]);
+        assert!(info.contains_key(&members[0]));
+        assert!(!info.contains_key(&members[2]));
+        assert_eq!(
+            info.get(&members[1]).unwrap().url,
+            format!("https://{}.example.com", members[1])
+        );
+        assert!(info.get(&members[2]).is_none());
+    }
+
+    #[test]
+    fn test_subset_for_threshold() {
+        let committee = init_committee(addresses(5), 3);
+        assert_eq!(committee.subset_for_threshold(&[]).unwrap(), vec![0, 1, 2]);
+        assert_eq!(
+            committee.subset_for_threshold(&[4, 2]).unwrap(),
+            vec![0, 2, 4]
+        );
+        // Duplicates are ignored and at most `threshold` preferred parties are taken.
+        assert_eq!(
+            committee.subset_for_threshold(&[3, 3, 4]).unwrap(),
+            vec![0, 3, 4]
+        );
+        assert_eq!(
+            committee.subset_for_threshold(&[4, 3, 2, 1]).unwrap(),
+            vec![2, 3, 4]
+        );
+
+        assert!(committee.subset_for_threshold(&[5]).is_err());
+        assert!(matches!(
+            init_committee(addresses(2), 3).subset_for_threshold(&[]),
+            Err(SealCommitteeError::ThresholdExceedsMembers {
+                threshold: 3,
+                members: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn test_membership() {
+        let members = addresses(4);
+        let committee = init_committee(members.clone(), 3);
+        let membership = committee.membership();
+        assert_eq!(membership.len(), 4);
+        assert!(membership.contains(&members[2]));
+        assert!(!membership.contains(&Address::ZERO));
+        assert_eq!(membership.party_id(&members[3]), Some(3));
+        assert_eq!(membership.party_id(&Address::ZERO), None);
+        assert_eq!(membership.address(1), Some(&members[1]));
+        assert_eq!(membership.address(4), None);
+
+        assert_eq!(committee.get_party_id(&members[2]).unwrap(), 2);
+        let err = committee.get_party_id(&Address::ZERO).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Address {} is not a member of committee {}",
+                Address::ZERO,
+                committee.id
+            )
+        );
+
+        // A duplicate member keeps its first party ID, and later party IDs are not shifted.
+        let duplicate = init_committee(vec![members[0], members[1], members[0], members[2]], 2);
+        let duplicate_membership = duplicate.membership();
+        assert_eq!(duplicate_membership.len(), 4);
+        assert_eq!(duplicate_membership.party_id(&members[0]), Some(0));
+        assert_eq!(duplicate.get_party_id(&members[0]).unwrap(), 0);
+        assert_eq!(duplicate_membership.party_id(&members[2]), Some(3));
+        assert_eq!(duplicate_membership.address(2), Some(&members[0]));
+        assert_eq!(duplicate_membership.address(3), Some(&members[2]));
+        assert_eq!(
+            duplicate_membership.intersection(membership),
+            vec![members[0], members[1], members[2]]
+        );
+
+        let other_committee = init_committee(vec![members[3], Address::ZERO, members[1]], 2);
+        let other = other_committee.membership();
+        assert_eq!(membership.intersection(other), vec![members[1], members[3]]);
+        assert_eq!(other.intersection(membership), vec![members[3], members[1]]);
+    }
+
+    #[test]
+    fn test_get_members_info_for_postdkg() {
+        let members = addresses(3);
+        let partial_pks: Vec<G2Element> = (1..=3u128)
+            .map(|i| G2Element::generator() * Scalar::from(i))
+            .collect();
+
+        let post_dkg = committee(
+            members.clone(),
+            2,
+            CommitteeState::PostDKG {
+                members_info: members_info(&members),
+                partial_pks: partial_pks
+                    .iter()
+                    .map(|pk| Hex::encode_with_format(bcs::to_bytes(pk).unwrap()).into_bytes())
+                    .collect(),
+                pk: vec![],
+                approvals: VecSet { contents: vec![] },
+            },
+        );
+        let info = post_dkg.get_members_info_for_postdkg().unwrap();
+        assert_eq!(info.len(), 3);
+        for (party_id, addr) in members.iter().enumerate() {
+            let (member_info, partial_pk) = &info[addr];
+            assert_eq!(member_info.party_id as usize, party_id);
+            assert_eq!(partial_pk, &partial_pks[party_id]);
+        }
+
+        let init = init_committee(members, 2);
+        assert!(init.get_members_info_for_postdkg().is_err());
+    }
+
+    #[test]
+    fn test_get_postdkg_info() {
+        let members = addresses(3);
+        let pk = G2Element::generator() * Scalar::from(5u128);
+        let partial_pks: Vec<G2Element> = (1..=3u128)
+            .map(|i| G2Element::generator() * Scalar::from(i))
+            .collect();
+        let post_dkg = |partial_pks: &[G2Element]| {
+            committee(
+                members.clone(),
+                2,
+                CommitteeState::PostDKG {
+                    members_info: members_info(&members),
+                    partial_pks: partial_pks
+                        .iter()
+                        .map(|pk| Hex::encode_with_format(bcs::to_bytes(pk).unwrap()).into_bytes())
+                        .collect(),
+                    pk: bcs::to_bytes(&pk).unwrap(),
+                    approvals: VecSet {
+                        contents: vec![members[2]],
+                    },
+                },
+            )
+        };
+
+        let info = post_dkg(&partial_pks).get_postdkg_info().unwrap();
+        assert_eq!(info.pk, pk);
+        assert_eq!(info.approvals, BTreeSet::from([members[2]]));
+        for (party_id, partial) in info.partial_pks.iter().enumerate() {
+            assert_eq!(partial.party_id as usize, party_id);
+            assert_eq!(partial.address, members[party_id]);
+            assert_eq!(partial.partial_pk, partial_pks[party_id]);
+        }
+        let by_party_id: HashMap<u16, G2Element> =
+            post_dkg(&partial_pks).get_postdkg_partial_pks().unwrap();
+        assert_eq!(by_party_id.len(), 3);
+        assert_eq!(by_party_id[&1], partial_pks[1]);
+        // G2 partial PKs are not valid in G1.
+        assert!(post_dkg(&partial_pks)
+            .get_postdkg_partial_pks::<G1Element>()
+            .is_err());
+
+        assert!(post_dkg(&partial_pks[..2]).get_postdkg_info().is_err());
+        assert!(post_dkg(&partial_pks[..2])
+            .get_postdkg_partial_pks::<G2Element>()
+            .is_err());
+        let mut malformed = post_dkg(&partial_pks);
+        if let CommitteeState::PostDKG { partial_pks, .. } = &mut malformed.state {
+            partial_pks[1] = b"0x00".to_vec();
+        }
+        let err = malformed.get_postdkg_info().unwrap_err();
+        assert!(
+            matches!(
+                err,
+                SealCommitteeError::InvalidPartialKey { party_id: 1, .. }
+            ),
+            "{err}"
+        );
+        assert!(std::error::Error::source(&err).is_some());
+        assert!(init_committee(members.clone(), 2)
+            .get_postdkg_info()
+            .is_err());
+    }
+
+    #[test]
+    fn test_get_approval_status() {
+        let members = addresses(3);
+        let post_dkg = committee(
+            members.clone(),
+            2,
+            CommitteeState::PostDKG {
+                members_info: members_info(&members),
+                partial_pks: vec![],
+                pk: vec![],
+                approvals: VecSet {
+                    contents: vec![members[2], members[0]],
+                },
+            },
+        );
+        let status = post_dkg.get_approval_status().unwrap();
+        assert_eq!(
+            status,
+            HashMap::from([(members[0], true), (members[1], false), (members[2], true)])
+        );
+
+        let init = init_committee(members, 2);
+        assert!(matches!(
+            init.get_approval_status(),
+            Err(SealCommitteeError::InvalidState {
+                committee_id,
+                expected: "PostDKG",
+                actual: "Init"
+            }) if committee_id == init.id
+        ));
+    }
+
+    #[test]
+    fn test_partial_key_server_info_result() {
+        let independent = ServerType::Independent {
+            url: "https://example.com".to_string(),
+        };
+        assert_eq!(
+            ServerTypeKind::from(&independent),
+            ServerTypeKind::Independent
+        );
+        let result = PartialKeyServerInfoResult {
+            server_type: ServerTypeKind::Independent,
+            infos: None,
+        };
+        assert!(result.into_committee_infos().is_err());
+
+        let committee = ServerType::Committee {
+            version: 0,
+            threshold: 2,
+            partial_key_servers: VecMap(sui_types::collection_types::VecMap { contents: vec![] }),
+        };
+        assert_eq!(ServerTypeKind::from(&committee), ServerTypeKind::Committee);
+        let result = PartialKeyServerInfoResult {
+            server_type: ServerTypeKind::Committee,
+            infos: Some(HashMap::new()),
+        };
+        assert!(result.into_committee_infos().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let field = Field {
+            id: Address::new([1; 32]),
+            name: Wrapper {
+                name: Address::new([2; 32]),
+            },
+            value: Address::new([3; 32]),
+        };
+        let json = serde_json::to_string(&field).unwrap();
+        let parsed: Field<Wrapper<Address>, Address> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, field);
+        let parsed: Field<Wrapper<Address>, Address> =
+            bcs::from_bytes(&bcs::to_bytes(&field).unwrap()).unwrap();
+        assert_eq!(parsed, field);
+
+        let info = PartialKeyServerInfo {
+            ks_obj_id: Address::new([4; 32]),
+            party_id: 3,
+            partial_pk: G2Element::generator() * Scalar::from(7u128),
+        };
+        let json = serde_json::to_string(&info).unwrap();
+        let parsed: PartialKeyServerInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, info);
+    }
+
+    #[test]
+    fn test_get_committee_pk() {
+        let members = addresses(3);
+        let pk = G2Element::generator() * Scalar::from(5u128);
+        let post_dkg = committee(
+            members.clone(),
+            2,
+            CommitteeState::PostDKG {
+                members_info: members_info(&members),
+                partial_pks: vec![],
+                pk: bcs::to_bytes(&pk).unwrap(),
+                approvals: VecSet { contents: vec![] },
+            },
+        );
+        assert_eq!(
+            post_dkg.get_postdkg_pk_bytes().unwrap(),
+            bcs::to_bytes(&pk).unwrap()
+        );
+        assert_eq!(post_dkg.get_postdkg_pk().unwrap(), pk);
+        assert_eq!(post_dkg.get_committee_pk(None).unwrap(), pk);
+
+        let init = init_committee(members.clone(), 2);
+        assert!(init.get_postdkg_pk_bytes().is_err());
+        assert!(init.get_committee_pk(None).is_err());
+
+        let finalized = committee(members, 2, CommitteeState::Finalized);
+        assert!(finalized.get_postdkg_pk().is_err());
+        assert!(finalized.get_committee_pk(None).is_err());
+        let key_server = KeyServerV2 {
+            name: "seal".to_string(),
+            key_type: 0,
+            pk: bcs::to_bytes(&pk).unwrap(),
+            server_type: ServerType::Independent {
+                url: "https://example.com".to_string(),
+            },
+        };
+        assert_eq!(finalized.get_committee_pk(Some(&key_server)).unwrap(), pk);
+    }
+}
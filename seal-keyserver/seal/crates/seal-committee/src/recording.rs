@@ -0,0 +1,135 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Record and replay of gRPC object fetches, to capture fixtures from a live network and serve
+//! them deterministically in tests.
+//!
+//! With `SEAL_RECORD_FIXTURES=<dir>`, every object fetched by [crate::grpc_helper] is also written
+//! to `<dir>/<object id>.json`. With `SEAL_REPLAY_FIXTURES=<dir>`, objects are read from there
+//! instead of the network, and a missing object is an error.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+use fastcrypto::encoding::{Encoding, Hex};
+use serde::{Deserialize, Serialize};
+use sui_sdk_types::Address;
+
+pub const RECORD_FIXTURES_ENV: &str = "SEAL_RECORD_FIXTURES";
+pub const REPLAY_FIXTURES_ENV: &str = "SEAL_REPLAY_FIXTURES";
+
+/// Where object fetches go, set from the environment.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FixtureMode {
+    Live,
+    Record(PathBuf),
+    Replay(PathBuf),
+}
+
+impl FixtureMode {
+    pub fn from_env() -> Result<Self> {
+        let record = std::env::var_os(RECORD_FIXTURES_ENV);
+        let replay = std::env::var_os(REPLAY_FIXTURES_ENV);
+        match (record, replay) {
+            (None, None) => Ok(FixtureMode::Live),
+            (Some(dir), None) => Ok(FixtureMode::Record(dir.into())),
+            (None, Some(dir)) => Ok(FixtureMode::Replay(dir.into())),
+            (Some(_), Some(_)) => bail!(
+                "Only one of {} and {} can be set",
+                RECORD_FIXTURES_ENV,
+                REPLAY_FIXTURES_ENV
+            ),
+        }
+    }
+}
+
+/// A `GetObject` response as stored on disk.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedObject {
+    pub object_id: Address,
+    pub version: Option<u64>,
+    /// Hex encoded BCS of the full object.
+    pub bcs: String,
+}
+
+impl RecordedObject {
+    pub fn new(object_id: Address, version: Option<u64>, bcs: &[u8]) -> Self {
+        Self {
+            object_id,
+            version,
+            bcs: Hex::encode_with_format(bcs),
+        }
+    }
+
+    pub fn bcs_bytes(&self) -> Result<Vec<u8>> {
+        Hex::decode(&self.bcs).map_err(|e| anyhow!("Invalid BCS of recorded object: {}", e))
+    }
+
+    fn path(dir: &Path, object_id: &Address) -> PathBuf {
+        dir.join(format!("{object_id}.json"))
+    }
+
+    pub fn write(&self, dir: &Path) -> Result<()> {
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(
+            Self::path(dir, &self.object_id),
+            serde_json::to_string_pretty(self)?,
+        )?;
+        Ok(())
+    }
+
+    pub fn read(dir: &Path, object_id: &Address) -> Result<Self> {
+        let path = Self::path(dir, object_id);
+        let json = std::fs::read_to_string(&path).map_err(|e| {
+            anyhow!(
+                "No recorded object {} in {}: {}",
+                object_id,
+                dir.display(),
+                e
+            )
+        })?;
+        let recorded: Self = serde_json::from_str(&json)
+            .map_err(|e| anyhow!("Invalid recorded object {}: {}", path.display(), e))?;
+        if recorded.object_id != *object_id {
+            bail!(
+                "Recorded object {} has ID {}",
+                path.display(),
+                recorded.object_id
+            );
+        }
+        Ok(recorded)
+    }
+
+    /// All recorded objects in a directory.
+    pub fn read_all(dir: &Path) -> Result<Vec<Self>> {
+        let mut objects = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                objects.push(serde_json::from_str(&std::fs::read_to_string(&path)?)?);
+            }
+        }
+        Ok(objects)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recorded_object_round_trip() {
+        let dir = std::env::temp_dir().join(format!("seal-recording-{}", std::process::id()));
+        let object = RecordedObject::new(Address::new([1; 32]), Some(7), &[1, 2, 3]);
+        object.write(&dir).unwrap();
+
+        let read = RecordedObject::read(&dir, &object.object_id).unwrap();
+        assert_eq!(read, object);
+        assert_eq!(read.bcs_bytes().unwrap(), vec![1, 2, 3]);
+        assert_eq!(RecordedObject::read_all(&dir).unwrap(), vec![object]);
+
+        // A miss is an error.
+        assert!(RecordedObject::read(&dir, &Address::new([2; 32])).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
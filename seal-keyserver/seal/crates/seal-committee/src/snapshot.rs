@@ -0,0 +1,372 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Snapshots of a committee and its key server as one JSON document, to hand the onchain state of
+//! a committee to machines without network access.
+//!
+//! A snapshot keeps the BCS of the fetched objects, so the committee and key server it yields are
+//! exactly what was onchain, along with their object digests. The members info and partial key
+//! servers are included in readable form for reviewing the snapshot. A content digest over all
+//! fields detects edits to the JSON.
+
+use anyhow::{anyhow, Result};
+use fastcrypto::encoding::{Encoding, Hex};
+use fastcrypto::hash::{Blake2b256, HashFunction};
+use serde::{Deserialize, Serialize};
+use sui_sdk_types::{Address, Digest};
+
+use crate::errors::SealCommitteeError;
+use crate::fetcher::CommitteeFetcher;
+use crate::grpc_helper::{
+    fetch_key_server_id, key_server_v2_field_id, object_digest, parse_move_object,
+};
+use crate::move_types::{CommitteeState, Field, KeyServerV2, SealCommittee, ServerType};
+
+/// A committee object as fetched, with the members info it resolves to and, once the committee is
+/// finalized, its key server.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CommitteeSnapshot {
+    /// Committee object ID.
+    pub committee_id: Address,
+    /// Version of the committee object.
+    pub version: u64,
+    /// Digest of the committee object, as computed onchain.
+    pub object_digest: Digest,
+    /// Hex encoded BCS of the full committee object.
+    pub committee_bcs: String,
+    /// Registered members with their party IDs, for a committee in Init or PostDKG state.
+    pub members_info: Vec<MemberSnapshot>,
+    /// Key server of a finalized committee.
+    pub key_server: Option<KeyServerSnapshot>,
+    /// Digest of all fields above, see [CommitteeSnapshot::compute_digest].
+    pub content_digest: String,
+}
+
+/// Keys and URL registered by a member.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MemberSnapshot {
+    pub address: Address,
+    pub party_id: u16,
+    /// Hex encoded BCS encryption public key.
+    pub enc_pk: String,
+    /// Hex encoded BCS signing public key.
+    pub signing_pk: String,
+    pub url: String,
+}
+
+/// The KeyServerV2 data field of a committee's key server.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct KeyServerSnapshot {
+    /// KeyServer object ID.
+    pub key_server_id: Address,
+    /// Version of the data field object.
+    pub version: u64,
+    /// Digest of the data field object, as computed onchain.
+    pub object_digest: Digest,
+    /// Hex encoded BCS of the full data field object.
+    pub field_bcs: String,
+    /// Partial key servers, ordered by party ID.
+    pub partial_key_infos: Vec<PartialKeySnapshot>,
+}
+
+/// Partial public key and URL of a member in the key server.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PartialKeySnapshot {
+    pub address: Address,
+    pub party_id: u16,
+    /// Hex encoded BCS partial public key, of the group of the key server's key type.
+    pub partial_pk: String,
+    pub url: String,
+}
+
+/// Fetch a committee and, if it is finalized, its key server, and bundle them into a snapshot.
+pub async fn export_committee_snapshot<F: CommitteeFetcher + ?Sized>(
+    fetcher: &F,
+    committee_id: &Address,
+) -> Result<CommitteeSnapshot, SealCommitteeError> {
+    let committee_bcs = fetcher.fetch_object_bcs(committee_id, None).await?;
+    let (object, committee) =
+        parse_move_object::<SealCommittee>(&committee_bcs, "Committee object")?;
+    let key_server = match committee.state {
+        CommitteeState::Finalized => {
+            let key_server_id = fetch_key_server_id(fetcher, committee_id).await?;
+            let field_bcs = fetcher
+                .fetch_object_bcs(&key_server_v2_field_id(&key_server_id)?, None)
+                .await?;
+            Some(KeyServerSnapshot::new(key_server_id, &field_bcs)?)
+        }
+        _ => None,
+    };
+    let mut snapshot = CommitteeSnapshot {
+        committee_id: *committee_id,
+        version: object.version(),
+        object_digest: object_digest(&committee_bcs),
+        committee_bcs: Hex::encode_with_format(&committee_bcs),
+        members_info: members_snapshot(&committee)?,
+        key_server,
+        content_digest: String::new(),
+    };
+    snapshot.content_digest = snapshot.compute_digest()?;
+    Ok(snapshot)
+}
+
+impl CommitteeSnapshot {
+    /// Pretty-printed JSON of the snapshot.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Parse a snapshot from JSON. Fails if the content digest or the object digests do not
+    /// match, i.e. if the snapshot was edited.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let snapshot: Self =
+            serde_json::from_str(json).map_err(|e| anyhow!("Failed to parse snapshot: {e}"))?;
+        let digest = snapshot.compute_digest()?;
+        if digest != snapshot.content_digest {
+            return Err(anyhow!(
+                "Snapshot digest mismatch: expected {}, computed {}",
+                snapshot.content_digest,
+                digest
+            ));
+        }
+        let committee = snapshot.committee()?;
+        if committee.id != snapshot.committee_id {
+            return Err(anyhow!(
+                "Snapshot of committee {} holds committee object {}",
+                snapshot.committee_id,
+                committee.id
+            ));
+        }
+        if let Some(key_server) = &snapshot.key_server {
+            key_server.key_server()?;
+        }
+        Ok(snapshot)
+    }
+
+    /// The committee object, after checking it against its object digest.
+    pub fn committee(&self) -> Result<SealCommittee> {
+        let bcs = decode_object(&self.committee_bcs, &self.object_digest, "Committee object")?;
+        Ok(parse_move_object(&bcs, "Committee object")?.1)
+    }
+
+    /// Blake2b256 digest of the BCS bytes of all fields except the content digest itself.
+    fn compute_digest(&self) -> Result<String> {
+        let bytes = bcs::to_bytes(&(
+            &self.committee_id,
+            self.version,
+            &self.object_digest,
+            &self.committee_bcs,
+            &self.members_info,
+            &self.key_server,
+        ))?;
+        Ok(Hex::encode_with_format(Blake2b256::digest(&bytes).digest))
+    }
+}
+
+impl KeyServerSnapshot {
+    fn new(key_server_id: Address, field_bcs: &[u8]) -> Result<Self, SealCommitteeError> {
+        let (object, field) =
+            parse_move_object::<Field<u64, KeyServerV2>>(field_bcs, "KeyServerV2 Field object")?;
+        let mut partial_key_infos = match &field.value.server_type {
+            ServerType::Committee {
+                partial_key_servers,
+                ..
+            } => partial_key_servers
+                .0
+                .contents
+                .iter()
+                .map(|entry| PartialKeySnapshot {
+                    address: entry.key,
+                    party_id: entry.value.party_id,
+                    partial_pk: Hex::encode_with_format(&entry.value.partial_pk),
+                    url: entry.value.url.clone(),
+                })
+                .collect(),
+            _ => vec![],
+        };
+        partial_key_infos.sort_by_key(|info: &PartialKeySnapshot| info.party_id);
+        Ok(Self {
+            key_server_id,
+            version: object.version(),
+            object_digest: object_digest(field_bcs),
+            field_bcs: Hex::encode_with_format(field_bcs),
+            partial_key_infos,
+        })
+    }
+
+    /// The key server data, after checking it against its object digest.
+    pub fn key_server(&self) -> Result<KeyServerV2> {
+        let context = "KeyServerV2 Field object";
+        let bcs = decode_object(&self.field_bcs, &self.object_digest, context)?;
+        Ok(parse_move_object::<Field<u64, KeyServerV2>>(&bcs, context)?
+            .1
+            .value)
+    }
+}
+
+/// Members info of a committee in Init or PostDKG state, ordered by party ID.
+fn members_snapshot(committee: &SealCommittee) -> Result<Vec<MemberSnapshot>> {
+    let members_info = match &committee.state {
+        CommitteeState::Init { members_info } | CommitteeState::PostDKG { members_info, .. } => {
+            members_info
+        }
+        CommitteeState::Finalized => return Ok(vec![]),
+    };
+    committee
+        .members
+        .iter()
+        .enumerate()
+        .filter_map(|(party_id, address)| {
+            let info = members_info.get(address)?;
+            Some((party_id as u16, address, info))
+        })
+        .map(|(party_id, address, info)| {
+            Ok(MemberSnapshot {
+                address: *address,
+                party_id,
+                enc_pk: bcs_hex(&info.enc_pk)?,
+                signing_pk: bcs_hex(&info.signing_pk)?,
+                url: info.url.clone(),
+            })
+        })
+        .collect()
+}
+
+fn bcs_hex<T: Serialize>(value: &T) -> Result<String> {
+    Ok(Hex::encode_with_format(&bcs::to_bytes(value)?))
+}
+
+/// Decode hex encoded object BCS and check it against the object digest.
+fn decode_object(hex: &str, digest: &Digest, context: &str) -> Result<Vec<u8>> {
+    let bcs = Hex::decode(hex).map_err(|e| anyhow!("Invalid {context} BCS: {e}"))?;
+    let computed = object_digest(&bcs);
+    if computed != *digest {
+        return Err(anyhow!(
+            "{context} digest mismatch: expected {digest}, computed {computed}"
+        ));
+    }
+    Ok(bcs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fetcher::StaticCommitteeFetcher;
+    use crate::grpc_helper::key_server_field_wrapper_id;
+    use crate::move_types::Wrapper;
+
+    fn fixture(hex: &str) -> Vec<u8> {
+        Hex::decode(hex.trim()).unwrap()
+    }
+
+    /// Serve a committee fixture under its object ID, returned along with the fetcher.
+    fn serve_committee(contents: Vec<u8>) -> (Address, StaticCommitteeFetcher) {
+        let committee: SealCommittee = bcs::from_bytes(&contents).unwrap();
+        let fetcher = StaticCommitteeFetcher::default()
+            .with_move_object(committee.id, contents)
+            .unwrap();
+        (committee.id, fetcher)
+    }
+
+    /// Export a snapshot and check that it survives a JSON round trip unchanged.
+    async fn round_trip(
+        fetcher: &StaticCommitteeFetcher,
+        committee_id: &Address,
+    ) -> CommitteeSnapshot {
+        let snapshot = export_committee_snapshot(fetcher, committee_id)
+            .await
+            .unwrap();
+        let loaded = CommitteeSnapshot::from_json(&snapshot.to_json().unwrap()).unwrap();
+        assert_eq!(loaded, snapshot);
+        assert_eq!(loaded.committee().unwrap().id, *committee_id);
+        loaded
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_round_trip_init() {
+        let (committee_id, fetcher) = serve_committee(fixture(include_str!(
+            "../tests/fixtures/committee_init.hex"
+        )));
+        let snapshot = round_trip(&fetcher, &committee_id).await;
+        assert!(snapshot.committee().unwrap().is_init().is_ok());
+        assert!(snapshot.key_server.is_none());
+        assert!(!snapshot.members_info.is_empty());
+        assert!(snapshot
+            .members_info
+            .windows(2)
+            .all(|pair| pair[0].party_id < pair[1].party_id));
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_round_trip_post_dkg() {
+        let (committee_id, fetcher) = serve_committee(fixture(include_str!(
+            "../tests/fixtures/committee_post_dkg.hex"
+        )));
+        let snapshot = round_trip(&fetcher, &committee_id).await;
+        let committee = snapshot.committee().unwrap();
+        assert!(matches!(committee.state, CommitteeState::PostDKG { .. }));
+        assert_eq!(snapshot.members_info.len(), committee.members.len());
+        assert!(snapshot.key_server.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_round_trip_finalized() {
+        let (committee_id, fetcher) = serve_committee(fixture(include_str!(
+            "../tests/fixtures/committee_finalized.hex"
+        )));
+        let wrapper = fixture(include_str!("../tests/fixtures/field_wrapper.hex"));
+        let key_server_id = bcs::from_bytes::<Field<Wrapper<Address>, Address>>(&wrapper)
+            .unwrap()
+            .value;
+        let fetcher = fetcher
+            .with_move_object(key_server_field_wrapper_id(&committee_id).unwrap(), wrapper)
+            .unwrap()
+            .with_move_object(
+                key_server_v2_field_id(&key_server_id).unwrap(),
+                fixture(include_str!(
+                    "../tests/fixtures/key_server_v2_committee.hex"
+                )),
+            )
+            .unwrap();
+
+        let snapshot = round_trip(&fetcher, &committee_id).await;
+        assert!(snapshot.committee().unwrap().is_finalized().is_ok());
+        assert!(snapshot.members_info.is_empty());
+        let key_server = snapshot.key_server.as_ref().unwrap();
+        assert_eq!(key_server.key_server_id, key_server_id);
+        let ServerType::Committee {
+            partial_key_servers,
+            ..
+        } = key_server.key_server().unwrap().server_type
+        else {
+            panic!("Expected a committee key server");
+        };
+        assert_eq!(
+            key_server.partial_key_infos.len(),
+            partial_key_servers.0.contents.len()
+        );
+
+        // Editing a readable field is detected by the content digest.
+        let mut tampered = snapshot.clone();
+        tampered.key_server.as_mut().unwrap().partial_key_infos[0].url =
+            "https://attacker.example.com".to_string();
+        let err = CommitteeSnapshot::from_json(&tampered.to_json().unwrap()).unwrap_err();
+        assert!(
+            err.to_string().contains("Snapshot digest mismatch"),
+            "{err}"
+        );
+
+        // Editing the committee BCS along with the content digest is detected by the object
+        // digest.
+        let mut bcs = Hex::decode(&snapshot.committee_bcs).unwrap();
+        *bcs.last_mut().unwrap() ^= 1;
+        let mut tampered = snapshot;
+        tampered.committee_bcs = Hex::encode_with_format(&bcs);
+        tampered.content_digest = tampered.compute_digest().unwrap();
+        let err = CommitteeSnapshot::from_json(&tampered.to_json().unwrap()).unwrap_err();
+        assert!(
+            err.to_string().contains("Committee object digest mismatch"),
+            "{err}"
+        );
+    }
+}
@@ -0,0 +1,53 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Helpers for building committee objects in tests.
+
+use crate::move_types::{CommitteeState, MemberInfo, SealCommittee, VecMap};
+use fastcrypto::bls12381::min_sig::BLS12381KeyPair;
+use fastcrypto::groups::bls12381::G2Element;
+use fastcrypto::traits::KeyPair;
+use fastcrypto_tbls::ecies_v1::{PrivateKey, PublicKey};
+use rand::thread_rng;
+use sui_sdk_types::Address;
+use sui_types::collection_types::Entry;
+
+/// Deterministic test addresses 0x01..01, 0x02..02, etc.
+pub(crate) fn addresses(n: u8) -> Vec<Address> {
+    (1..=n).map(|i| Address::new([i; 32])).collect()
+}
+
+/// Registered member info with random keys for each of the given addresses.
+pub(crate) fn members_info(members: &[Address]) -> VecMap<Address, MemberInfo> {
+    VecMap(sui_types::collection_types::VecMap {
+        contents: members
+            .iter()
+            .map(|addr| Entry {
+                key: *addr,
+                value: MemberInfo {
+                    enc_pk: PublicKey::<G2Element>::from_private_key(&PrivateKey::new(
+                        &mut thread_rng(),
+                    )),
+                    signing_pk: BLS12381KeyPair::generate(&mut thread_rng())
+                        .public()
+                        .clone(),
+                    url: format!("https://{addr}.example.com"),
+                },
+            })
+            .collect(),
+    })
+}
+
+pub(crate) fn committee(
+    members: Vec<Address>,
+    threshold: u16,
+    state: CommitteeState,
+) -> SealCommittee {
+    SealCommittee::new(Address::ZERO, threshold, members, state, None)
+}
+
+/// Committee in Init state with all members registered.
+pub(crate) fn init_committee(members: Vec<Address>, threshold: u16) -> SealCommittee {
+    let members_info = members_info(&members);
+    committee(members, threshold, CommitteeState::Init { members_info })
+}
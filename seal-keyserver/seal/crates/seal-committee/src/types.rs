@@ -1,15 +1,118 @@
 // Copyright (c), Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::str::FromStr;
+use std::time::Duration;
+
+use crate::grpc_helper::{FetchOptions, GrpcClientConfig};
+
+/// Overrides the gRPC URL of [Network::Localnet], e.g. for a local network on another port.
+pub const LOCALNET_URL_ENV: &str = "SEAL_LOCALNET_URL";
+
+/// gRPC URL of a local network started with `sui start`.
+pub const DEFAULT_LOCALNET_URL: &str = "http://127.0.0.1:9000";
 
 /// Network enum for DKG and Seal CLI operations.
-/// Only supports mainnet and testnet.
+/// Supports mainnet, testnet, devnet and localnet, and any other deployment by its gRPC URL.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub enum Network {
     Testnet,
     Mainnet,
+    Devnet,
+    /// A local network, at [DEFAULT_LOCALNET_URL] unless overridden by [LOCALNET_URL_ENV].
+    Localnet,
+    /// A private deployment or a dedicated RPC provider.
+    Custom {
+        grpc_url: String,
+    },
+}
+
+impl Network {
+    /// gRPC URL of the public fullnode of this network, or the given URL of a custom network.
+    pub fn node_url(&self) -> String {
+        match self {
+            Network::Mainnet => "https://fullnode.mainnet.sui.io:443".to_string(),
+            Network::Testnet => "https://fullnode.testnet.sui.io:443".to_string(),
+            Network::Devnet => "https://fullnode.devnet.sui.io:443".to_string(),
+            Network::Localnet => {
+                std::env::var(LOCALNET_URL_ENV).unwrap_or_else(|_| DEFAULT_LOCALNET_URL.to_string())
+            }
+            Network::Custom { grpc_url } => grpc_url.clone(),
+        }
+    }
+
+    /// Whether a ceremony on this network can secure real keys. Devnet and localnet are for
+    /// development only. A custom network may be a dedicated RPC provider of mainnet.
+    pub fn is_production(&self) -> bool {
+        !matches!(self, Network::Devnet | Network::Localnet)
+    }
+}
+
+/// A [Network] with the endpoints and request settings used to talk to it, so that they can be
+/// passed around together, see [crate::create_grpc_client_for_network].
+#[derive(Clone, Debug, PartialEq)]
+pub struct NetworkConfig {
+    pub network: Network,
+    /// gRPC URL of the fullnode.
+    pub grpc_url: String,
+    /// JSON-RPC URL of the fullnode.
+    pub rpc_url: String,
+    /// Deadline of each request.
+    pub request_timeout: Duration,
+    /// Number of attempts of each fetch, including the first one.
+    pub retry_attempts: u32,
+}
+
+impl NetworkConfig {
+    pub fn for_mainnet() -> Self {
+        Self::with_defaults(Network::Mainnet, Network::Mainnet.node_url())
+    }
+
+    pub fn for_testnet() -> Self {
+        Self::with_defaults(Network::Testnet, Network::Testnet.node_url())
+    }
+
+    /// Config of any network with the default request settings. Fails if the URL of a
+    /// [Network::Custom] or [LOCALNET_URL_ENV] is not an http(s) URL.
+    pub fn from_network(network: &Network) -> Result<Self> {
+        let url = network.node_url();
+        if !url.starts_with("https://") && !url.starts_with("http://") {
+            return Err(anyhow!("Invalid URL of {network} network: {url}"));
+        }
+        Ok(Self::with_defaults(network.clone(), url))
+    }
+
+    /// Fullnodes serve gRPC and JSON-RPC on the same URL.
+    fn with_defaults(network: Network, url: String) -> Self {
+        let client_config = GrpcClientConfig::default();
+        Self {
+            network,
+            rpc_url: url.clone(),
+            grpc_url: url,
+            request_timeout: client_config.request_timeout,
+            retry_attempts: FetchOptions::default().max_attempts,
+        }
+    }
+
+    /// Default [GrpcClientConfig] with the request timeout of this config.
+    pub fn grpc_client_config(&self) -> GrpcClientConfig {
+        GrpcClientConfig {
+            request_timeout: self.request_timeout,
+            ..Default::default()
+        }
+    }
+
+    /// Default [FetchOptions] with the request timeout and attempts of this config.
+    pub fn fetch_options(&self) -> FetchOptions {
+        FetchOptions {
+            max_attempts: self.retry_attempts,
+            request_timeout: Some(self.request_timeout),
+            ..Default::default()
+        }
+    }
 }
 
 impl FromStr for Network {
@@ -19,9 +122,115 @@ impl FromStr for Network {
         match s {
             "mainnet" => Ok(Network::Mainnet),
             "testnet" => Ok(Network::Testnet),
-            _ => Err(format!(
-                "Unknown network: {s}. Only 'mainnet' and 'testnet' are supported"
-            )),
+            "devnet" => Ok(Network::Devnet),
+            "localnet" => Ok(Network::Localnet),
+            _ => match s.strip_prefix("custom:") {
+                Some("") => Err("Missing gRPC URL in custom:<url>".to_string()),
+                Some(grpc_url) => Ok(Network::Custom {
+                    grpc_url: grpc_url.to_string(),
+                }),
+                None => Err(format!(
+                    "Unknown network: {s}. Only 'mainnet', 'testnet', 'devnet', 'localnet' and 'custom:<url>' are supported"
+                )),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Network::Mainnet => f.write_str("mainnet"),
+            Network::Testnet => f.write_str("testnet"),
+            Network::Devnet => f.write_str("devnet"),
+            Network::Localnet => f.write_str("localnet"),
+            Network::Custom { grpc_url } => write!(f, "custom:{grpc_url}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_network_round_trip() {
+        let cases = [
+            (
+                Network::Mainnet,
+                "mainnet",
+                "https://fullnode.mainnet.sui.io:443",
+            ),
+            (
+                Network::Testnet,
+                "testnet",
+                "https://fullnode.testnet.sui.io:443",
+            ),
+            (
+                Network::Devnet,
+                "devnet",
+                "https://fullnode.devnet.sui.io:443",
+            ),
+            (Network::Localnet, "localnet", DEFAULT_LOCALNET_URL),
+        ];
+        for (network, name, url) in cases {
+            assert_eq!(network.to_string(), name);
+            assert_eq!(Network::from_str(name).unwrap(), network);
+            if network != Network::Localnet || std::env::var_os(LOCALNET_URL_ENV).is_none() {
+                assert_eq!(network.node_url(), url);
+            }
+            let json = serde_json::to_string(&network).unwrap();
+            assert_eq!(serde_json::from_str::<Network>(&json).unwrap(), network);
+        }
+        assert!(Network::Mainnet.is_production());
+        assert!(!Network::Devnet.is_production());
+        assert!(!Network::Localnet.is_production());
+        assert!(Network::from_str("regtest").is_err());
+    }
+
+    #[test]
+    fn test_network_config() {
+        let mainnet = NetworkConfig::for_mainnet();
+        assert_eq!(mainnet.network, Network::Mainnet);
+        assert_eq!(mainnet.grpc_url, "https://fullnode.mainnet.sui.io:443");
+        assert_eq!(mainnet.rpc_url, mainnet.grpc_url);
+        assert_eq!(
+            NetworkConfig::from_network(&Network::Mainnet).unwrap(),
+            mainnet
+        );
+        assert_eq!(
+            NetworkConfig::for_testnet().grpc_url,
+            "https://fullnode.testnet.sui.io:443"
+        );
+
+        let custom = Network::from_str("custom:http://127.0.0.1:9100").unwrap();
+        let config = NetworkConfig::from_network(&custom).unwrap();
+        assert_eq!(config.grpc_url, "http://127.0.0.1:9100");
+        assert_eq!(config.fetch_options().max_attempts, config.retry_attempts);
+        assert_eq!(
+            config.grpc_client_config().request_timeout,
+            config.request_timeout
+        );
+        assert!(NetworkConfig::from_network(&Network::from_str("custom:foo").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_custom_network() {
+        let network = Network::from_str("custom:https://rpc.example.com:443").unwrap();
+        assert_eq!(
+            network,
+            Network::Custom {
+                grpc_url: "https://rpc.example.com:443".to_string()
+            }
+        );
+        assert_eq!(network.node_url(), "https://rpc.example.com:443");
+        assert_eq!(network.to_string(), "custom:https://rpc.example.com:443");
+        assert_eq!(Network::from_str(&network.to_string()).unwrap(), network);
+        assert!(Network::from_str("custom:").is_err());
+
+        for network in [Network::Testnet, network] {
+            let json = serde_json::to_string(&network).unwrap();
+            assert_eq!(serde_json::from_str::<Network>(&json).unwrap(), network);
         }
     }
 }
@@ -4,22 +4,670 @@
 //! Utility helper functions for working with Seal protocol types.
 
 use crate::move_types::SealCommittee;
+use anyhow::{anyhow, Result};
+use fastcrypto::groups::bls12381::G2Element;
+use fastcrypto_tbls::polynomial::{Eval, Poly};
+use std::collections::{HashMap, HashSet};
+use std::num::NonZeroU16;
+use sui_sdk_types::Address;
 
 /// Build a mapping from new committee party IDs to old committee party IDs.
 /// This is used for key rotation to identify which members are continuing from the old committee.
+/// Prefer [RotationPlan::build], which also checks the committees and lists the members.
 pub fn build_new_to_old_map(
     new_committee: &SealCommittee,
     old_committee: &SealCommittee,
-) -> std::collections::HashMap<u16, u16> {
-    let mut new_to_old_map = std::collections::HashMap::new();
-    new_committee
-        .members
+) -> HashMap<u16, u16> {
+    RotationPlan::new(new_committee, old_committee).new_to_old
+}
+
+/// Build a mapping from old committee party IDs to new committee party IDs of the continuing
+/// members, the inverse of [build_new_to_old_map].
+pub fn build_old_to_new_map(
+    new_committee: &SealCommittee,
+    old_committee: &SealCommittee,
+) -> HashMap<u16, u16> {
+    build_new_to_old_map(new_committee, old_committee)
+        .into_iter()
+        .map(|(new_party_id, old_party_id)| (old_party_id, new_party_id))
+        .collect()
+}
+
+/// A member of one committee in a key rotation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RotationMember {
+    pub address: Address,
+    pub party_id: u16,
+}
+
+/// A member of both committees in a key rotation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContinuingMember {
+    pub address: Address,
+    pub new_party_id: u16,
+    pub old_party_id: u16,
+}
+
+/// How the members of an old committee carry over to a new committee in a key rotation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RotationPlan {
+    pub new_committee_id: Address,
+    pub old_committee_id: Address,
+    pub new_threshold: u16,
+    pub old_threshold: u16,
+    /// New party ID to old party ID of the continuing members.
+    pub new_to_old: HashMap<u16, u16>,
+    /// Members in both committees, ordered by new party ID.
+    pub continuing: Vec<ContinuingMember>,
+    /// Members only in the new committee, with their new party ID.
+    pub joining: Vec<RotationMember>,
+    /// Members only in the old committee, with their old party ID.
+    pub departing: Vec<RotationMember>,
+}
+
+impl RotationPlan {
+    /// Build the plan of the rotation from `old_committee` to `new_committee`. Fails if the new
+    /// committee names another old committee, or if either committee lists a member twice, as the
+    /// mapping of party IDs would then be ambiguous. Whether the rotation can complete is checked
+    /// separately by [RotationPlan::feasible].
+    pub fn build(new_committee: &SealCommittee, old_committee: &SealCommittee) -> Result<Self> {
+        if let Some(old_committee_id) = new_committee
+            .old_committee_id
+            .filter(|id| *id != old_committee.id)
+        {
+            return Err(anyhow!(
+                "Committee {} rotates from committee {}, not {}",
+                new_committee.id,
+                old_committee_id,
+                old_committee.id
+            ));
+        }
+        for committee in [new_committee, old_committee] {
+            let mut seen = HashSet::new();
+            if let Some(address) = committee.members.iter().find(|a| !seen.insert(*a)) {
+                return Err(anyhow!(
+                    "Duplicate member {} in committee {}",
+                    address,
+                    committee.id
+                ));
+            }
+        }
+        Ok(Self::new(new_committee, old_committee))
+    }
+
+    fn new(new_committee: &SealCommittee, old_committee: &SealCommittee) -> Self {
+        let new_membership = new_committee.membership();
+        let old_membership = old_committee.membership();
+        let mut continuing = Vec::new();
+        let mut joining = Vec::new();
+        for (party_id, address) in new_committee.members.iter().enumerate() {
+            // Safe because length is limited by u16.
+            let party_id = party_id as u16;
+            match old_membership.party_id(address) {
+                Some(old_party_id) => continuing.push(ContinuingMember {
+                    address: *address,
+                    new_party_id: party_id,
+                    old_party_id,
+                }),
+                None => joining.push(RotationMember {
+                    address: *address,
+                    party_id,
+                }),
+            }
+        }
+        let departing = old_committee
+            .members
+            .iter()
+            .enumerate()
+            .filter(|(_, address)| !new_membership.contains(address))
+            .map(|(party_id, address)| RotationMember {
+                address: *address,
+                party_id: party_id as u16,
+            })
+            .collect();
+        Self {
+            new_committee_id: new_committee.id,
+            old_committee_id: old_committee.id,
+            new_threshold: new_committee.threshold,
+            old_threshold: old_committee.threshold,
+            new_to_old: continuing
+                .iter()
+                .map(|member| (member.new_party_id, member.old_party_id))
+                .collect(),
+            continuing,
+            joining,
+            departing,
+        }
+    }
+
+    /// Check that at least the old threshold of members continue, as their old shares are needed
+    /// to reshare the key.
+    pub fn feasible(&self) -> Result<()> {
+        if self.continuing.len() < self.old_threshold as usize {
+            return Err(anyhow!(
+                "Invalid rotation from committee {} to {}: found {} continuing members, need at least {} (old threshold)",
+                self.old_committee_id,
+                self.new_committee_id,
+                self.continuing.len(),
+                self.old_threshold
+            ));
+        }
+        Ok(())
+    }
+
+    /// Number of continuing members that may fail to submit a message while the rotation can
+    /// still complete, or `None` if the rotation is not [feasible](RotationPlan::feasible).
+    pub fn slack(&self) -> Option<usize> {
+        self.continuing
+            .len()
+            .checked_sub(self.old_threshold as usize)
+    }
+
+    /// Whether the member with this new party ID continues from the old committee.
+    pub fn is_continuing(&self, new_party_id: u16) -> bool {
+        self.new_to_old.contains_key(&new_party_id)
+    }
+
+    /// Number of members of the new committee.
+    pub fn new_size(&self) -> usize {
+        self.continuing.len() + self.joining.len()
+    }
+
+    /// Number of members of the old committee.
+    pub fn old_size(&self) -> usize {
+        self.continuing.len() + self.departing.len()
+    }
+}
+
+/// Check that the partial public keys proposed by DKG, as `(party_id, partial_pk)`, are the
+/// evaluations of a polynomial of degree `threshold - 1` with constant term `pk`, where party `i`
+/// evaluates it at `i + 1`. Otherwise some members would hold shares that do not combine into the
+/// key of the committee.
+///
+/// Interpolating the first `threshold` partial PKs at zero must give `pk`. Each other partial PK
+/// is cross-checked by replacing the last of them, which fixes the polynomial together with `pk`.
+pub fn verify_partial_pk_consistency(
+    pk: &G2Element,
+    partial_pks: &[(u16, G2Element)],
+    threshold: u16,
+) -> Result<()> {
+    if threshold == 0 {
+        return Err(anyhow!("Threshold must be at least 1"));
+    }
+    if partial_pks.len() < threshold as usize {
+        return Err(anyhow!(
+            "Found {} partial PKs, need at least {} (threshold)",
+            partial_pks.len(),
+            threshold
+        ));
+    }
+    let mut evals = partial_pks
         .iter()
-        .enumerate()
-        .for_each(|(party_id, address)| {
-            if let Ok(old_party_id) = old_committee.get_party_id(address) {
-                new_to_old_map.insert(party_id as u16, old_party_id);
+        .map(|(party_id, partial_pk)| {
+            let index = party_id
+                .checked_add(1)
+                .and_then(NonZeroU16::new)
+                .ok_or_else(|| anyhow!("Invalid party ID {party_id}"))?;
+            Ok(Eval {
+                index,
+                value: *partial_pk,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    evals.sort_by_key(|eval| eval.index);
+    if let Some(pair) = evals.windows(2).find(|pair| pair[0].index == pair[1].index) {
+        return Err(anyhow!(
+            "Duplicate partial PK of party {}",
+            pair[0].index.get() - 1
+        ));
+    }
+
+    let (basis, rest) = evals.split_at(threshold as usize);
+    if Poly::<G2Element>::recover_c0_msm(threshold, basis.iter())? != *pk {
+        return Err(anyhow!(
+            "Interpolation of the partial PKs of parties {:?} does not match the aggregate PK",
+            basis
+                .iter()
+                .map(|eval| eval.index.get() - 1)
+                .collect::<Vec<_>>()
+        ));
+    }
+    let fixed = &basis[..threshold as usize - 1];
+    for eval in rest {
+        if Poly::<G2Element>::recover_c0_msm(threshold, fixed.iter().chain([eval]))? != *pk {
+            return Err(anyhow!(
+                "Partial PK of party {} is not consistent with the aggregate PK",
+                eval.index.get() - 1
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// A single validation rule for a committee.
+pub trait CommitteeRule {
+    /// Returns an error describing the violation if the committee does not satisfy the rule.
+    fn check(&self, committee: &SealCommittee) -> Result<()>;
+}
+
+/// Committee must be in Init state.
+pub struct RequireInitState;
+
+impl CommitteeRule for RequireInitState {
+    fn check(&self, committee: &SealCommittee) -> Result<()> {
+        Ok(committee.is_init()?)
+    }
+}
+
+/// All members must have registered their public keys.
+pub struct RequireAllMembersRegistered;
+
+impl CommitteeRule for RequireAllMembersRegistered {
+    fn check(&self, committee: &SealCommittee) -> Result<()> {
+        committee.get_members_info()?;
+        Ok(())
+    }
+}
+
+/// Threshold must be at least half of the committee size.
+pub struct RequireThresholdAtLeastHalf;
+
+impl CommitteeRule for RequireThresholdAtLeastHalf {
+    fn check(&self, committee: &SealCommittee) -> Result<()> {
+        if (committee.threshold as usize) * 2 < committee.members.len() {
+            return Err(anyhow!(
+                "Threshold {} is less than half of the committee size {}",
+                committee.threshold,
+                committee.members.len()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Members list must not contain duplicate addresses.
+pub struct RequireNoDuplicateMembers;
+
+impl CommitteeRule for RequireNoDuplicateMembers {
+    fn check(&self, committee: &SealCommittee) -> Result<()> {
+        let mut seen = HashSet::new();
+        for member in &committee.members {
+            if !seen.insert(member) {
+                return Err(anyhow!("Duplicate member {} in committee", member));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Runs a set of [CommitteeRule]s against a committee and reports all failures at once.
+pub struct CommitteeValidator {
+    rules: Vec<Box<dyn CommitteeRule>>,
+}
+
+impl Default for CommitteeValidator {
+    /// All built-in rules.
+    fn default() -> Self {
+        Self {
+            rules: vec![
+                Box::new(RequireInitState),
+                Box::new(RequireAllMembersRegistered),
+                Box::new(RequireThresholdAtLeastHalf),
+                Box::new(RequireNoDuplicateMembers),
+            ],
+        }
+    }
+}
+
+impl CommitteeValidator {
+    /// Create a validator with the given rules only.
+    pub fn new(rules: Vec<Box<dyn CommitteeRule>>) -> Self {
+        Self { rules }
+    }
+
+    /// Add a rule to run after the existing ones.
+    pub fn add_rule(&mut self, rule: impl CommitteeRule + 'static) -> &mut Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    /// Run all rules and return the failures, empty if the committee is valid.
+    pub fn check_all(&self, committee: &SealCommittee) -> Vec<anyhow::Error> {
+        self.rules
+            .iter()
+            .filter_map(|rule| rule.check(committee).err())
+            .collect()
+    }
+
+    /// Run all rules and return an error listing every failure.
+    pub fn validate(&self, committee: &SealCommittee) -> Result<()> {
+        let failures = self.check_all(committee);
+        if failures.is_empty() {
+            return Ok(());
+        }
+        Err(anyhow!(
+            "Committee {} failed {} validation check(s):\n{}",
+            committee.id,
+            failures.len(),
+            failures
+                .iter()
+                .map(|e| format!("- {e}"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::move_types::CommitteeState;
+    use crate::test_utils::{addresses, committee, init_committee, members_info};
+    use fastcrypto::groups::bls12381::Scalar;
+    use fastcrypto::groups::GroupElement;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_default_validator() {
+        let validator = CommitteeValidator::default();
+        assert!(validator.validate(&init_committee(addresses(3), 2)).is_ok());
+
+        // Low threshold and unregistered member are both reported.
+        let members = addresses(4);
+        let unregistered = committee(
+            members.clone(),
+            1,
+            CommitteeState::Init {
+                members_info: members_info(&members[..3]),
+            },
+        );
+        assert_eq!(validator.check_all(&unregistered).len(), 2);
+
+        let mut duplicated = addresses(3);
+        duplicated.push(duplicated[0]);
+        assert_eq!(validator.check_all(&init_committee(duplicated, 2)).len(), 1);
+
+        let finalized = committee(addresses(3), 2, CommitteeState::Finalized);
+        let err = validator.validate(&finalized).unwrap_err().to_string();
+        assert!(err.contains("failed 2 validation check(s)"), "{err}");
+    }
+
+    #[test]
+    fn test_custom_rule() {
+        struct RequireAtLeastMembers(usize);
+        impl CommitteeRule for RequireAtLeastMembers {
+            fn check(&self, committee: &SealCommittee) -> Result<()> {
+                if committee.members.len() < self.0 {
+                    return Err(anyhow!("Not enough members"));
+                }
+                Ok(())
+            }
+        }
+
+        let mut validator = CommitteeValidator::new(vec![]);
+        validator.add_rule(RequireAtLeastMembers(5));
+        assert!(validator
+            .validate(&init_committee(addresses(3), 2))
+            .is_err());
+        assert!(validator.validate(&init_committee(addresses(5), 3)).is_ok());
+    }
+
+    /// Partial PKs of `n` parties of a random polynomial of degree `threshold - 1`, and its
+    /// constant term.
+    fn random_partial_pks(n: u16, threshold: u16) -> (G2Element, Vec<(u16, G2Element)>) {
+        let poly = Poly::<Scalar>::rand(threshold - 1, &mut rand::thread_rng());
+        let public_poly: Poly<G2Element> = poly.commit();
+        let partial_pks = (0..n)
+            .map(|party_id| {
+                let index = NonZeroU16::new(party_id + 1).unwrap();
+                (party_id, public_poly.eval(index).value)
+            })
+            .collect();
+        (*public_poly.c0(), partial_pks)
+    }
+
+    #[test]
+    fn test_verify_partial_pk_consistency() {
+        for (n, threshold) in [(1, 1), (3, 1), (3, 2), (5, 3), (4, 4)] {
+            let (pk, partial_pks) = random_partial_pks(n, threshold);
+            verify_partial_pk_consistency(&pk, &partial_pks, threshold).unwrap();
+
+            // Any subset of at least threshold partial PKs, in any order.
+            let mut reversed = partial_pks[n as usize - threshold as usize..].to_vec();
+            reversed.reverse();
+            verify_partial_pk_consistency(&pk, &reversed, threshold).unwrap();
+
+            // A corrupted partial PK is found wherever it is.
+            for corrupted in 0..n as usize {
+                let mut partial_pks = partial_pks.clone();
+                partial_pks[corrupted].1 += G2Element::generator();
+                assert!(
+                    verify_partial_pk_consistency(&pk, &partial_pks, threshold).is_err(),
+                    "n={n}, threshold={threshold}, corrupted={corrupted}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_verify_partial_pk_consistency_errors() {
+        let (pk, partial_pks) = random_partial_pks(4, 3);
+        let err = verify_partial_pk_consistency(&G2Element::generator(), &partial_pks, 3)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("does not match the aggregate PK"), "{err}");
+
+        // Partial PKs of a polynomial of higher degree.
+        let err = verify_partial_pk_consistency(&pk, &partial_pks, 2)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("does not match the aggregate PK"), "{err}");
+
+        let err = verify_partial_pk_consistency(&pk, &partial_pks[..2], 3)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("need at least 3"), "{err}");
+
+        let mut duplicated = partial_pks.clone();
+        duplicated[1].0 = 0;
+        let err = verify_partial_pk_consistency(&pk, &duplicated, 3)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Duplicate partial PK of party 0"), "{err}");
+
+        assert!(verify_partial_pk_consistency(&pk, &partial_pks, 0).is_err());
+    }
+
+    #[test]
+    fn test_rotation_plan() {
+        let old_members = addresses(4);
+        let old_committee = init_committee(old_members.clone(), 2);
+        let joining = addresses(6)[5];
+        let new_committee = init_committee(vec![old_members[2], joining, old_members[1]], 2);
+
+        let plan = RotationPlan::build(&new_committee, &old_committee).unwrap();
+        assert_eq!(plan.new_to_old, HashMap::from([(0, 2), (2, 1)]));
+        assert_eq!(
+            plan.continuing,
+            vec![
+                ContinuingMember {
+                    address: old_members[2],
+                    new_party_id: 0,
+                    old_party_id: 2,
+                },
+                ContinuingMember {
+                    address: old_members[1],
+                    new_party_id: 2,
+                    old_party_id: 1,
+                },
+            ]
+        );
+        assert_eq!(
+            plan.joining,
+            vec![RotationMember {
+                address: joining,
+                party_id: 1,
+            }]
+        );
+        assert_eq!(
+            plan.departing,
+            vec![
+                RotationMember {
+                    address: old_members[0],
+                    party_id: 0,
+                },
+                RotationMember {
+                    address: old_members[3],
+                    party_id: 3,
+                },
+            ]
+        );
+        assert!(plan.is_continuing(2) && !plan.is_continuing(1));
+        plan.feasible().unwrap();
+
+        // Identical committees: everyone continues at the same party ID.
+        let plan = RotationPlan::build(&old_committee, &old_committee).unwrap();
+        assert_eq!(plan.continuing.len(), 4);
+        assert!(plan.joining.is_empty() && plan.departing.is_empty());
+        assert!(plan.new_to_old.iter().all(|(new, old)| new == old));
+        plan.feasible().unwrap();
+
+        // Disjoint committees: nobody can reshare the old key.
+        let disjoint = init_committee(addresses(8)[4..].to_vec(), 2);
+        let plan = RotationPlan::build(&disjoint, &old_committee).unwrap();
+        assert!(plan.new_to_old.is_empty() && plan.continuing.is_empty());
+        assert_eq!((plan.joining.len(), plan.departing.len()), (4, 4));
+        let err = plan.feasible().unwrap_err().to_string();
+        assert!(
+            err.contains("found 0 continuing members, need at least 2"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_rotation_plan_errors() {
+        let members = addresses(3);
+        let old_committee = init_committee(members.clone(), 2);
+
+        let mut new_committee = init_committee(members.clone(), 2);
+        new_committee.old_committee_id = Some(Address::new([7; 32]));
+        let err = RotationPlan::build(&new_committee, &old_committee)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("rotates from committee"), "{err}");
+
+        let duplicate = init_committee(vec![members[0], members[1], members[0]], 2);
+        let err = RotationPlan::build(&duplicate, &old_committee)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Duplicate member"), "{err}");
+        assert!(RotationPlan::build(&old_committee, &duplicate).is_err());
+    }
+
+    #[test]
+    fn test_rotation_plan_slack() {
+        let old_members = addresses(4);
+        let old_committee = init_committee(old_members.clone(), 3);
+        let joining = addresses(6)[5];
+        let new_committee = init_committee(vec![old_members[2], joining, old_members[1]], 2);
+
+        let plan = RotationPlan::build(&new_committee, &old_committee).unwrap();
+        assert_eq!((plan.old_threshold, plan.old_size()), (3, 4));
+        assert_eq!((plan.new_threshold, plan.new_size()), (2, 3));
+        assert_eq!(plan.slack(), None);
+        assert!(plan.feasible().is_err());
+
+        let old_committee = init_committee(old_members, 2);
+        let plan = RotationPlan::build(&new_committee, &old_committee).unwrap();
+        assert_eq!(plan.slack(), Some(0));
+        plan.feasible().unwrap();
+    }
+
+    /// Distinct party seeds drawn from `0..pool`, in random order.
+    fn arb_seeds(pool: u8, min_size: usize, max_size: usize) -> impl Strategy<Value = Vec<u8>> {
+        proptest::collection::hash_set(0..pool, min_size..=max_size)
+            .prop_map(|seeds| seeds.into_iter().collect::<Vec<_>>())
+            .prop_shuffle()
+    }
+
+    /// A new and an old committee with random overlap, including complete replacement and all
+    /// members continuing. Sizes are at most 100.
+    fn arb_committee_pair(
+        min_size: usize,
+        max_size: usize,
+    ) -> impl Strategy<Value = (SealCommittee, SealCommittee)> {
+        assert!(max_size <= 100);
+        // Seeds of old members are shifted by `pool` for complete replacement.
+        let pool = (max_size * 5 / 4).max(1) as u8;
+        let seeds = move || arb_seeds(pool, min_size, max_size);
+        prop_oneof![
+            (seeds(), seeds()),
+            // All members continue, in another order.
+            seeds().prop_flat_map(|old| (Just(old.clone()).prop_shuffle(), Just(old))),
+            // All members are replaced.
+            (seeds(), seeds()).prop_map(move |(new, old)| {
+                (
+                    new,
+                    old.into_iter().map(|seed| seed + pool).collect::<Vec<_>>(),
+                )
+            }),
+        ]
+        .prop_map(|(new_seeds, old_seeds)| {
+            let members = |seeds: Vec<u8>| -> Vec<Address> {
+                seeds.into_iter().map(|i| Address::new([i; 32])).collect()
+            };
+            let old_committee = committee(members(old_seeds), 1, CommitteeState::Finalized);
+            let mut new_committee = committee(members(new_seeds), 1, CommitteeState::Finalized);
+            new_committee.old_committee_id = Some(old_committee.id);
+            (new_committee, old_committee)
+        })
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(1000))]
+
+        #[test]
+        fn prop_new_to_old_map_in_range((new, old) in arb_committee_pair(0, 100)) {
+            let mapping = build_new_to_old_map(&new, &old);
+            for (new_party_id, old_party_id) in &mapping {
+                prop_assert!((*new_party_id as usize) < new.members.len());
+                prop_assert!((*old_party_id as usize) < old.members.len());
+                prop_assert_eq!(
+                    new.members[*new_party_id as usize],
+                    old.members[*old_party_id as usize]
+                );
+            }
+        }
+
+        #[test]
+        fn prop_old_to_new_map_is_inverse((new, old) in arb_committee_pair(0, 100)) {
+            let new_to_old = build_new_to_old_map(&new, &old);
+            let old_to_new = build_old_to_new_map(&new, &old);
+            prop_assert_eq!(new_to_old.len(), old_to_new.len());
+            for (new_party_id, old_party_id) in &new_to_old {
+                prop_assert_eq!(old_to_new.get(old_party_id), Some(new_party_id));
             }
-        });
-    new_to_old_map
+        }
+
+        #[test]
+        fn prop_continuing_members_bounded((new, old) in arb_committee_pair(0, 100)) {
+            let continuing = build_new_to_old_map(&new, &old).len();
+            prop_assert!(continuing <= new.members.len().min(old.members.len()));
+            let expected = new.members.iter().filter(|m| old.members.contains(m)).count();
+            prop_assert_eq!(continuing, expected);
+        }
+
+        #[test]
+        fn prop_rotation_plan_feasible(
+            (new, mut old) in arb_committee_pair(0, 100),
+            old_threshold in 0u16..=101,
+        ) {
+            old.threshold = old_threshold;
+            let continuing = build_new_to_old_map(&new, &old).len();
+            let plan = RotationPlan::build(&new, &old).unwrap();
+            prop_assert_eq!(plan.feasible().is_ok(), continuing >= old_threshold as usize);
+            prop_assert_eq!(plan.slack().is_some(), plan.feasible().is_ok());
+            prop_assert_eq!(plan.new_to_old, build_new_to_old_map(&new, &old));
+        }
+    }
 }
@@ -0,0 +1,310 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Callbacks on committee state transitions during a ceremony, e.g. for coordinator scripts that
+//! notify members or advance to the next step.
+
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use sui_rpc::client::v2::Client;
+use sui_sdk_types::Address;
+
+use crate::errors::SealCommitteeError;
+use crate::fetcher::CommitteeFetcher;
+use crate::grpc_helper::CommitteeStatePoller;
+use crate::move_types::{CommitteeState, SealCommittee};
+
+/// Receives the changes of a committee observed by [CommitteeStateWatcher]. All methods do
+/// nothing by default, so a listener only implements the events it needs.
+pub trait CommitteeEventListener: Send {
+    /// A member registered its public keys and URL.
+    fn on_member_registered(&mut self, _committee: &SealCommittee, _member: &Address) {}
+
+    /// The DKG output was proposed onchain and the committee is in PostDKG state.
+    fn on_postdkg_announced(&mut self, _committee: &SealCommittee) {}
+
+    /// A member approved the proposed DKG output.
+    fn on_approval_received(&mut self, _committee: &SealCommittee, _member: &Address) {}
+
+    /// The committee is finalized. This is the last event.
+    fn on_finalized(&mut self, _committee: &SealCommittee) {}
+}
+
+/// Listener logging every event with [tracing], used by [CommitteeStateWatcher::watch].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LoggingEventListener;
+
+impl CommitteeEventListener for LoggingEventListener {
+    fn on_member_registered(&mut self, committee: &SealCommittee, member: &Address) {
+        tracing::info!(committee_id = %committee.id, %member, "Member registered");
+    }
+
+    fn on_postdkg_announced(&mut self, committee: &SealCommittee) {
+        tracing::info!(committee_id = %committee.id, "DKG output proposed");
+    }
+
+    fn on_approval_received(&mut self, committee: &SealCommittee, member: &Address) {
+        tracing::info!(committee_id = %committee.id, %member, "Approval received");
+    }
+
+    fn on_finalized(&mut self, committee: &SealCommittee) {
+        tracing::info!(committee_id = %committee.id, "Committee finalized");
+    }
+}
+
+/// Polls a committee until it is finalized and reports each change to a listener by comparing
+/// successive states. The first state fetched is compared to an empty committee, so everything
+/// that already happened is reported once when watching starts. Transitions between two polls
+/// are reported as far as the later state shows them, e.g. no approvals are reported for a
+/// committee that went from Init to Finalized in between.
+pub struct CommitteeStateWatcher<F = Client> {
+    poller: CommitteeStatePoller<F>,
+}
+
+impl<F: CommitteeFetcher> CommitteeStateWatcher<F> {
+    pub fn new(fetcher: F, committee_id: Address, interval: Duration) -> Self {
+        Self {
+            poller: CommitteeStatePoller::new(fetcher, committee_id, interval),
+        }
+    }
+
+    /// Watch with [LoggingEventListener].
+    pub async fn watch(self, timeout: Duration) -> Result<SealCommittee, SealCommitteeError> {
+        self.watch_with(&mut LoggingEventListener, timeout).await
+    }
+
+    /// Watch until the committee is finalized and return it. Fails on the first failed fetch, or
+    /// if `timeout` elapses.
+    pub async fn watch_with(
+        self,
+        listener: &mut dyn CommitteeEventListener,
+        timeout: Duration,
+    ) -> Result<SealCommittee, SealCommitteeError> {
+        let mut seen = CommitteeProgress::default();
+        self.poller
+            .wait_until(
+                |committee| {
+                    let current = CommitteeProgress::of(committee);
+                    current.notify_changes(&seen, committee, listener);
+                    seen = current;
+                    seen.finalized
+                },
+                timeout,
+            )
+            .await
+    }
+}
+
+/// What a committee state shows of the ceremony progress.
+#[derive(Debug, Default)]
+struct CommitteeProgress {
+    registered: BTreeSet<Address>,
+    post_dkg: bool,
+    approvals: BTreeSet<Address>,
+    finalized: bool,
+}
+
+impl CommitteeProgress {
+    fn of(committee: &SealCommittee) -> Self {
+        match &committee.state {
+            CommitteeState::Init { members_info } => Self {
+                registered: members_info.0.contents.iter().map(|e| e.key).collect(),
+                ..Self::default()
+            },
+            CommitteeState::PostDKG {
+                members_info,
+                approvals,
+                ..
+            } => Self {
+                registered: members_info.0.contents.iter().map(|e| e.key).collect(),
+                post_dkg: true,
+                approvals: approvals.contents.iter().copied().collect(),
+                finalized: false,
+            },
+            CommitteeState::Finalized => Self {
+                finalized: true,
+                ..Self::default()
+            },
+        }
+    }
+
+    /// Report what is new compared to `previous`, members in party ID order.
+    fn notify_changes(
+        &self,
+        previous: &Self,
+        committee: &SealCommittee,
+        listener: &mut dyn CommitteeEventListener,
+    ) {
+        let is_new = |set: &BTreeSet<Address>, seen: &BTreeSet<Address>, member: &Address| {
+            set.contains(member) && !seen.contains(member)
+        };
+        for member in &committee.members {
+            if is_new(&self.registered, &previous.registered, member) {
+                listener.on_member_registered(committee, member);
+            }
+        }
+        if self.post_dkg && !previous.post_dkg {
+            listener.on_postdkg_announced(committee);
+        }
+        for member in &committee.members {
+            if is_new(&self.approvals, &previous.approvals, member) {
+                listener.on_approval_received(committee, member);
+            }
+        }
+        if self.finalized && !previous.finalized {
+            listener.on_finalized(committee);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fetcher::{move_object_bcs, version_of_fetched};
+    use crate::test_utils::{addresses, committee, init_committee, members_info};
+    use fastcrypto::encoding::{Encoding, Hex};
+    use std::sync::Mutex;
+    use sui_types::collection_types::VecSet;
+
+    /// Records the events as strings.
+    #[derive(Default)]
+    struct RecordingListener(Vec<String>);
+
+    impl CommitteeEventListener for RecordingListener {
+        fn on_member_registered(&mut self, _committee: &SealCommittee, member: &Address) {
+            self.0.push(format!("registered {member}"));
+        }
+
+        fn on_postdkg_announced(&mut self, _committee: &SealCommittee) {
+            self.0.push("postdkg".to_string());
+        }
+
+        fn on_approval_received(&mut self, _committee: &SealCommittee, member: &Address) {
+            self.0.push(format!("approved {member}"));
+        }
+
+        fn on_finalized(&mut self, _committee: &SealCommittee) {
+            self.0.push("finalized".to_string());
+        }
+    }
+
+    /// Returns the given objects in order on successive fetches, then the last one. Version
+    /// checks return the version of the next object.
+    struct SequenceFetcher(Mutex<Vec<Vec<u8>>>);
+
+    #[tonic::async_trait]
+    impl CommitteeFetcher for SequenceFetcher {
+        async fn fetch_object_bcs(
+            &self,
+            _object_id: &Address,
+            _version: Option<u64>,
+        ) -> Result<Vec<u8>, SealCommitteeError> {
+            let mut objects = self.0.lock().unwrap();
+            Ok(if objects.len() > 1 {
+                objects.remove(0)
+            } else {
+                objects[0].clone()
+            })
+        }
+
+        async fn fetch_object_version(
+            &self,
+            _object_id: &Address,
+        ) -> Result<Option<u64>, SealCommitteeError> {
+            version_of_fetched(Ok(self.0.lock().unwrap()[0].clone()))
+        }
+    }
+
+    #[test]
+    fn test_notify_changes() {
+        let members = addresses(3);
+        let mut listener = RecordingListener::default();
+
+        // Only the first member registered.
+        let mut partial = init_committee(members.clone(), 2);
+        if let CommitteeState::Init { members_info } = &mut partial.state {
+            members_info.0.contents.truncate(1);
+        }
+        let first = CommitteeProgress::of(&partial);
+        first.notify_changes(&CommitteeProgress::default(), &partial, &mut listener);
+        assert_eq!(listener.0, [format!("registered {}", members[0])]);
+
+        // Unchanged state reports nothing, the others registering reports them.
+        first.notify_changes(&first, &partial, &mut listener);
+        assert_eq!(listener.0.len(), 1);
+        let init = init_committee(members.clone(), 2);
+        let second = CommitteeProgress::of(&init);
+        second.notify_changes(&first, &init, &mut listener);
+        assert_eq!(
+            listener.0[1..],
+            [
+                format!("registered {}", members[1]),
+                format!("registered {}", members[2])
+            ]
+        );
+
+        listener.0.clear();
+        let post_dkg = committee(
+            members.clone(),
+            2,
+            CommitteeState::PostDKG {
+                members_info: members_info(&members),
+                partial_pks: vec![],
+                pk: vec![],
+                approvals: VecSet {
+                    contents: vec![members[2], members[0]],
+                },
+            },
+        );
+        let third = CommitteeProgress::of(&post_dkg);
+        third.notify_changes(&second, &post_dkg, &mut listener);
+        assert_eq!(
+            listener.0,
+            [
+                "postdkg".to_string(),
+                format!("approved {}", members[0]),
+                format!("approved {}", members[2])
+            ]
+        );
+
+        listener.0.clear();
+        let finalized = committee(members, 2, CommitteeState::Finalized);
+        CommitteeProgress::of(&finalized).notify_changes(&third, &finalized, &mut listener);
+        assert_eq!(listener.0, ["finalized"]);
+    }
+
+    #[tokio::test]
+    async fn test_watch_committee() {
+        let fixture = |hex: &str, version| {
+            move_object_bcs(Address::ZERO, version, Hex::decode(hex.trim()).unwrap()).unwrap()
+        };
+        let init = include_str!("../tests/fixtures/committee_init.hex");
+        let finalized = include_str!("../tests/fixtures/committee_finalized.hex");
+        let watcher = CommitteeStateWatcher::new(
+            SequenceFetcher(Mutex::new(vec![
+                fixture(init, 1),
+                fixture(init, 2),
+                fixture(finalized, 3),
+            ])),
+            Address::ZERO,
+            Duration::from_millis(1),
+        );
+        let mut listener = RecordingListener::default();
+        let committee = watcher
+            .watch_with(&mut listener, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(committee.is_finalized().is_ok());
+
+        // Registrations are reported once although the Init state was fetched twice.
+        let (finalized, registered) = listener.0.split_last().unwrap();
+        assert_eq!(finalized, "finalized");
+        assert!(!registered.is_empty());
+        assert!(registered
+            .iter()
+            .all(|event| event.starts_with("registered")));
+        let unique: BTreeSet<_> = registered.iter().collect();
+        assert_eq!(unique.len(), registered.len());
+    }
+}
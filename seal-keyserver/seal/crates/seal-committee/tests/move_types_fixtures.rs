@@ -0,0 +1,325 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Golden BCS fixtures for the Move struct layouts in `move_types`, so that a layout change in
+//! the Move package fails here rather than with "Failed to deserialize" during a ceremony.
+//!
+//! The fixtures in `tests/fixtures` hold the BCS bytes of the Move struct contents, hex encoded.
+//! To refresh them after an intentional Move package upgrade, run
+//! `cargo test -p seal-committee --features regenerate-fixtures --test move_types_fixtures`
+//! with network access, and update the expected values below.
+
+use fastcrypto::bls12381::min_sig::BLS12381PublicKey;
+use fastcrypto::encoding::{Encoding, Hex};
+use fastcrypto::groups::bls12381::G2Element;
+use fastcrypto_tbls::ecies_v1::PublicKey;
+use seal_committee::move_types::{Field, KeyServer, Wrapper};
+use seal_committee::{CommitteeState, KeyServerV2, SealCommittee, ServerType};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::str::FromStr;
+use sui_sdk_types::Address;
+
+const INIT_COMMITTEE_ID: &str =
+    "0x1d8e07b865da82d86c71bb0ac8adf174996fd780ccae8237dd5f6ea38d9fe903";
+const ROTATED_COMMITTEE_ID: &str =
+    "0x82283c1056bb18832428034d20e0af5ed098bc58f8815363c33eb3a9b3fba867";
+const OLD_COMMITTEE_ID: &str = "0xaf2962d702d718f7b968eddc262da28418a33c296786cd356a43728a858faf80";
+const KEY_SERVER_ID: &str = "0x5b4b868b22f4e1e87d3938f29aefc71a1e1ddf7352e214088c9eaf37e31efd31";
+
+const MEMBERS: [&str; 3] = [
+    "0x0636157e9d013585ff473b3b378499ac2f1d207ed07d70e2cd815711725bca9d",
+    "0xe6a37ff5cd968b6a666fb033d85eabc674449f44f9fc2b600e55e27354211ed6",
+    "0x223762117ab21a439f0f3f3b0577e838b8b26a37d9a1723a4be311243f4461b9",
+];
+const ENC_PK: &str = "0xaf2ca44fd70f4e72d5ef6ad1bc8f5ab42850a36f75e1562f4f33ca2d25c5fee5fe780e164f17e0591a46a44d545e71f21447d316563899b77f34ee34d84ee70c70505f98dc4e7f5914b347cec49ef3a510efa9568416413cacd5361f42c8fa58";
+const SIGNING_PK: &str = "0x89dcee7b2f5b6256eafe4eabcac4a2fa348ce52d10b6a994da6f2969eb76d87e54f0298d446ab72f0094dae0f0fb5e2018e1d2957cb1514837d0bdb6edab1f549638bdbdca7542f81b62d426a898c9efff50cdaa1958b8ed06cbc72208570b46";
+const PARTIAL_PKS: [&str; 4] = [
+    "0xaa56bd6b3af3eb4c92b75c1cbe7c4fff64563966b81f812008b4edcf1dac9cf42df1695be94f850f01dcfa813add29630810a51dc5bb558f67da1f3182e5e1ff064555e3c3cf83e295899677873c10c284ace2526dd7f5f7b898a7d323622e57",
+    "0x8f1902dbf32c7c2dd7a6eefa97b1e6833bccd859c35c4ec6124dde5f267d260fcd16240cf0c5ecadfa5202563f97035e05ba0cd246ceaca8abb930505cf2752b00e14565af0ffe02a437de0b5c799c1e84314297b7fdc7e9fdd322a9c77c6bc3",
+    "0x8d942a02eb6a3bf78d27ec8ee27b9a8721b07fe22866bb4f6614f78978e394c9ddc8b87712ddbc3fa2f0386bc3b68ccc18dd0f05f2ca5345bf19433933a5d77bf56cd2563a2e872f82b16495529b47086212466f903f84949b15153d7eab6848",
+    "0x94eba091a424bed60ad920855706ee476d23c2d9d4763ab5a4f832b3e57c38eb7d81013ea8f5b4790b4db6cd1ad2fd051633e6c8e9a25f302b5b4382724c5e83c40e487dba39910df2829c09f7d38ee2d37e0a8a1bdc2a71486c5fb6e508c069",
+];
+
+fn fixture_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(format!("{name}.hex"))
+}
+
+fn load_fixture<T: serde::de::DeserializeOwned>(name: &str) -> T {
+    let hex = std::fs::read_to_string(fixture_path(name)).unwrap();
+    let bytes = Hex::decode(hex.trim()).unwrap();
+    bcs::from_bytes(&bytes).unwrap_or_else(|e| panic!("Failed to deserialize fixture {name}: {e}"))
+}
+
+/// Check that a fixture serializes back to its bytes, i.e. that serializing round-trips through
+/// the Move byte literal encodings read by the deserializers.
+fn check_round_trip<T>(name: &str)
+where
+    T: serde::de::DeserializeOwned + serde::Serialize + PartialEq + std::fmt::Debug,
+{
+    let hex = std::fs::read_to_string(fixture_path(name)).unwrap();
+    let bytes = Hex::decode(hex.trim()).unwrap();
+    let value: T = load_fixture(name);
+    let serialized = bcs::to_bytes(&value).unwrap();
+    assert_eq!(serialized, bytes, "{name}");
+    assert_eq!(bcs::from_bytes::<T>(&serialized).unwrap(), value, "{name}");
+}
+
+fn address(s: &str) -> Address {
+    Address::from_str(s).unwrap()
+}
+
+fn from_hex_bcs<T: serde::de::DeserializeOwned>(hex: &str) -> T {
+    bcs::from_bytes(&Hex::decode(hex).unwrap()).unwrap()
+}
+
+fn members() -> Vec<Address> {
+    MEMBERS.iter().map(|member| address(member)).collect()
+}
+
+fn check_committee_header(committee: &SealCommittee, id: &str, old_committee_id: Option<&str>) {
+    assert_eq!(committee.id, address(id));
+    assert_eq!(committee.threshold, 2);
+    assert_eq!(committee.members, members());
+    assert_eq!(committee.old_committee_id, old_committee_id.map(address));
+}
+
+fn check_members_info(committee: &SealCommittee) {
+    let (CommitteeState::Init { members_info } | CommitteeState::PostDKG { members_info, .. }) =
+        &committee.state
+    else {
+        panic!("Committee has no members info");
+    };
+    let expected_enc_pk: PublicKey<G2Element> = from_hex_bcs(ENC_PK);
+    let expected_signing_pk: BLS12381PublicKey = from_hex_bcs(SIGNING_PK);
+    assert_eq!(members_info.0.contents.len(), MEMBERS.len());
+    for (i, entry) in members_info.0.contents.iter().enumerate() {
+        assert_eq!(entry.key, address(MEMBERS[i]));
+        assert_eq!(entry.value.enc_pk, expected_enc_pk);
+        assert_eq!(entry.value.signing_pk, expected_signing_pk);
+        assert_eq!(entry.value.url, format!("https://seal-{i}.example.com"));
+    }
+}
+
+#[test]
+fn test_committee_init_fixture() {
+    let committee: SealCommittee = load_fixture("committee_init");
+    check_committee_header(&committee, INIT_COMMITTEE_ID, None);
+    assert!(matches!(committee.state, CommitteeState::Init { .. }));
+    check_members_info(&committee);
+}
+
+#[test]
+fn test_committee_post_dkg_fixture() {
+    let committee: SealCommittee = load_fixture("committee_post_dkg");
+    check_committee_header(&committee, INIT_COMMITTEE_ID, None);
+    check_members_info(&committee);
+    let CommitteeState::PostDKG {
+        partial_pks,
+        pk,
+        approvals,
+        ..
+    } = &committee.state
+    else {
+        panic!("Committee is not in PostDKG state");
+    };
+    assert_eq!(partial_pks.len(), 3);
+    assert_eq!(pk, &Hex::decode(PARTIAL_PKS[3]).unwrap());
+    assert_eq!(
+        approvals.contents,
+        vec![address(MEMBERS[0]), address(MEMBERS[2])]
+    );
+
+    // Partial PKs are stored as Move byte literals.
+    let members_info = committee.get_members_info_for_postdkg().unwrap();
+    for (i, member) in MEMBERS.iter().enumerate() {
+        let (info, partial_pk) = &members_info[&address(member)];
+        assert_eq!(info.party_id as usize, i);
+        assert_eq!(*partial_pk, from_hex_bcs::<G2Element>(PARTIAL_PKS[i]));
+    }
+}
+
+#[test]
+fn test_committee_post_dkg_info() {
+    let committee: SealCommittee = load_fixture("committee_post_dkg");
+    let info = committee.get_postdkg_info().unwrap();
+    assert_eq!(info.pk, from_hex_bcs::<G2Element>(PARTIAL_PKS[3]));
+    assert_eq!(info.partial_pks.len(), MEMBERS.len());
+    for (i, partial) in info.partial_pks.iter().enumerate() {
+        assert_eq!(partial.party_id as usize, i);
+        assert_eq!(partial.address, address(MEMBERS[i]));
+        assert_eq!(
+            partial.partial_pk,
+            from_hex_bcs::<G2Element>(PARTIAL_PKS[i])
+        );
+    }
+    assert_eq!(
+        info.approvals,
+        BTreeSet::from([address(MEMBERS[0]), address(MEMBERS[2])])
+    );
+
+    let init: SealCommittee = load_fixture("committee_init");
+    assert!(init.get_postdkg_info().is_err());
+}
+
+#[test]
+fn test_committee_finalized_fixture() {
+    let committee: SealCommittee = load_fixture("committee_finalized");
+    check_committee_header(&committee, ROTATED_COMMITTEE_ID, Some(OLD_COMMITTEE_ID));
+    assert!(matches!(committee.state, CommitteeState::Finalized));
+}
+
+#[test]
+fn test_fixtures_round_trip() {
+    check_round_trip::<SealCommittee>("committee_init");
+    check_round_trip::<SealCommittee>("committee_post_dkg");
+    check_round_trip::<SealCommittee>("committee_finalized");
+    check_round_trip::<Field<u64, KeyServerV2>>("key_server_v2_committee");
+    check_round_trip::<Field<u64, KeyServerV2>>("key_server_v2_independent");
+    check_round_trip::<Field<Wrapper<Address>, Address>>("field_wrapper");
+    check_round_trip::<KeyServer>("key_server");
+}
+
+/// Pinned values of [SealCommittee::digest], which must not change across versions.
+#[test]
+fn test_committee_digest_vectors() {
+    let digest = |name: &str| Hex::encode(load_fixture::<SealCommittee>(name).digest().unwrap());
+    // Init and PostDKG states of the same committee have the same digest.
+    assert_eq!(
+        digest("committee_init"),
+        "23dab53cf09e533ca8ae872781fe3bff400350d89b268beb7ff4832c8cba31a5"
+    );
+    assert_eq!(
+        digest("committee_post_dkg"),
+        "23dab53cf09e533ca8ae872781fe3bff400350d89b268beb7ff4832c8cba31a5"
+    );
+    assert_eq!(
+        digest("committee_finalized"),
+        "1a5d2754ce84502c65757000c75bc08629d2338de5ac48903eec803d1cfe91d2"
+    );
+
+    let mut committee = SealCommittee::new(
+        Address::new([1; 32]),
+        1,
+        vec![Address::new([2; 32])],
+        CommitteeState::Finalized,
+        None,
+    );
+    assert_eq!(
+        Hex::encode(committee.digest().unwrap()),
+        "33aecbcbf56d9966512b2388b0547aa36ff06780469d7674cd158ddfa1f5496d"
+    );
+    committee.old_committee_id = Some(Address::new([3; 32]));
+    assert_eq!(
+        Hex::encode(committee.digest().unwrap()),
+        "e7be4bdd86f6095c2f50c7329fec628d17e66596a8070309f7a01a5c75ac4adc"
+    );
+}
+
+fn check_key_server_v2_field(field: &Field<u64, KeyServerV2>) {
+    assert_eq!(field.id, Address::new([0x11; 32]));
+    assert_eq!(field.name, 2);
+    assert_eq!(field.value.name, "seal-committee");
+    assert_eq!(field.value.key_type, 0);
+    assert_eq!(field.value.pk, Hex::decode(PARTIAL_PKS[3]).unwrap());
+}
+
+#[test]
+fn test_key_server_v2_committee_fixture() {
+    let field: Field<u64, KeyServerV2> = load_fixture("key_server_v2_committee");
+    check_key_server_v2_field(&field);
+    let ServerType::Committee {
+        version,
+        threshold,
+        partial_key_servers,
+    } = &field.value.server_type
+    else {
+        panic!("KeyServer is not of type Committee");
+    };
+    assert_eq!(*version, 1);
+    assert_eq!(*threshold, 2);
+    assert_eq!(partial_key_servers.0.contents.len(), MEMBERS.len());
+    for (i, entry) in partial_key_servers.0.contents.iter().enumerate() {
+        assert_eq!(entry.key, address(MEMBERS[i]));
+        // Partial PKs are stored as Move byte literals.
+        assert_eq!(entry.value.partial_pk, Hex::decode(PARTIAL_PKS[i]).unwrap());
+        assert_eq!(entry.value.url, format!("https://seal-{i}.example.com"));
+        assert_eq!(entry.value.party_id as usize, i);
+    }
+}
+
+#[test]
+fn test_key_server_v2_independent_fixture() {
+    let field: Field<u64, KeyServerV2> = load_fixture("key_server_v2_independent");
+    check_key_server_v2_field(&field);
+    let ServerType::Independent { url } = &field.value.server_type else {
+        panic!("KeyServer is not of type Independent");
+    };
+    assert_eq!(url, "https://seal.example.com");
+}
+
+#[test]
+fn test_field_wrapper_fixture() {
+    let field: Field<Wrapper<Address>, Address> = load_fixture("field_wrapper");
+    assert_eq!(field.id, Address::new([0x22; 32]));
+    assert_eq!(field.name.name, address(ROTATED_COMMITTEE_ID));
+    assert_eq!(field.value, address(KEY_SERVER_ID));
+}
+
+#[test]
+fn test_key_server_fixture() {
+    let key_server: KeyServer = load_fixture("key_server");
+    assert_eq!(key_server.id, address(KEY_SERVER_ID));
+    assert_eq!(key_server.first_version, 1);
+    assert_eq!(key_server.last_version, 2);
+}
+
+/// Capture the fixtures from testnet. Fixtures without a known testnet object take its ID from an
+/// environment variable and are skipped if it is not set.
+#[cfg(feature = "regenerate-fixtures")]
+#[tokio::test]
+async fn regenerate_fixtures() {
+    use seal_committee::{
+        create_grpc_client, fetch_move_object_contents, key_server_field_wrapper_id,
+        key_server_v2_field_id, Network,
+    };
+
+    let env_address = |name: &str| std::env::var(name).ok().map(|id| address(&id));
+    let fixtures = [
+        ("committee_init", Some(address(INIT_COMMITTEE_ID))),
+        (
+            "committee_post_dkg",
+            env_address("SEAL_FIXTURE_POST_DKG_COMMITTEE"),
+        ),
+        ("committee_finalized", Some(address(ROTATED_COMMITTEE_ID))),
+        (
+            "key_server_v2_committee",
+            Some(key_server_v2_field_id(&address(KEY_SERVER_ID)).unwrap()),
+        ),
+        (
+            "key_server_v2_independent",
+            env_address("SEAL_FIXTURE_INDEPENDENT_KEY_SERVER")
+                .map(|id| key_server_v2_field_id(&id).unwrap()),
+        ),
+        (
+            "field_wrapper",
+            Some(key_server_field_wrapper_id(&address(ROTATED_COMMITTEE_ID)).unwrap()),
+        ),
+        ("key_server", Some(address(KEY_SERVER_ID))),
+    ];
+
+    let grpc_client = create_grpc_client(&Network::Testnet).unwrap();
+    for (name, object_id) in fixtures {
+        let Some(object_id) = object_id else {
+            eprintln!("Skipping fixture {name}, no object ID set");
+            continue;
+        };
+        let contents = fetch_move_object_contents(&grpc_client, &object_id)
+            .await
+            .unwrap();
+        std::fs::write(fixture_path(name), format!("{}\n", Hex::encode(contents))).unwrap();
+        eprintln!("Wrote fixture {name} from object {object_id}");
+    }
+}